@@ -1,39 +1,256 @@
 use anyhow::Result;
 use chrono::{Local, TimeZone};
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Padding, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, Padding, Paragraph, Wrap},
     Frame,
 };
+use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
+use tracing::{debug, warn};
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::commands::CommandHandler;
-use crate::config::Config;
-use crate::formatting::{format_message_text, slack_emoji_to_unicode};
-use crate::persistence::{Aliases, AppState, LayoutData};
-use crate::slack::{SlackAttachment, SlackClient, SlackUpdate};
+use crate::config::{Config, HookEvent};
+use crate::formatting::{
+    contains_ignore_case, emoji_names_with_prefix, find_keyword, format_file_size,
+    convert_slack_emojis, format_message_text, format_timestamp, jumbo_emoji, valid_time_format, HighlightRule, link_labels, mention_labels, slack_emoji_to_unicode, split_labels,
+    split_matches, split_message, TimestampStyle,
+};
+use crate::ipc::{error_reply, IpcCommand, IpcRequest};
+use crate::logging::LogHandle;
+use crate::notifications::{Notification, NotificationKind, Notifier};
+use crate::persistence::{
+    Aliases, AppState, FollowedThread, FollowedThreads, LayoutData, Nicknames, OutboxMessage,
+    Drafts, PaneState, RecentChats, SavedSearches, SessionCache, SidebarOrder, Snippets, StarredChannels,
+};
+use crate::scripting::ScriptAction;
+use crate::search::SearchQuery;
+use crate::slack::{
+    is_network_error, is_system_subtype, text_mentions_me, ConnectionState, RotatedToken, SlackAttachment, SlackClient,
+    SlackError, SlackMessage, SlackUpdate, UserSummary, MAX_MESSAGE_CHARS,
+};
 use crate::split_view::{PaneNode, SplitDirection};
-use crate::utils::send_desktop_notification;
-use crate::widgets::ChatPane;
+use crate::utils::{
+    append_tee_line, key_matches, run_hook, send_desktop_notification, send_terminal_notification,
+    set_terminal_title,
+};
+use crate::widgets::{
+    AutoScroll, CachedMessage, ChatPane, FileAttachment, FileBrowser, FileEntry, FilterType, InfoPopup, MessageData, PaneSearch,
+    ClickAction, ClickTarget, CopyMode, MentionPicker, UserEntry, UserPicker, WorkspacePrompt,
+    WorkspaceStep,
+};
 
 const REALTIME_STALE_SECS: u64 = 30;
 const FALLBACK_REFRESH_SECS: u64 = 15;
 
+/// Messages per open pane kept in the session cache for offline mode
+const CACHED_MESSAGES: usize = 200;
+/// How often offline mode tries to reach Slack again
+const OFFLINE_RETRY_SECS: u64 = 30;
+/// Wait before trying a failed token refresh again
+const TOKEN_REFRESH_RETRY_SECS: u64 = 300;
+/// The composer shows a character counter from this length on
+const MESSAGE_COUNTER_FROM: usize = MAX_MESSAGE_CHARS * 9 / 10;
+
+/// Outcome of a background workspace connection: (client, my_user_id, my_usergroups)
+type WorkspaceSwitchResult = Result<(SlackClient, String, Vec<String>), ConnectError>;
+
+/// Why connecting to a workspace in the background failed
+pub enum ConnectError {
+    Unreachable(String),
+    Rejected(String), // The token was refused (invalid_auth, token_expired, ...)
+    Failed(String),
+}
+
+impl ConnectError {
+    fn from_error(e: anyhow::Error) -> Self {
+        if is_network_error(&e) {
+            ConnectError::Unreachable(format!("{:#}", e))
+        } else if let Some(SlackError::InvalidAuth(code)) = e.downcast_ref::<SlackError>() {
+            ConnectError::Rejected(code.clone())
+        } else {
+            ConnectError::Failed(e.to_string())
+        }
+    }
+}
+
+impl std::fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectError::Unreachable(e) | ConnectError::Failed(e) => f.write_str(e),
+            ConnectError::Rejected(code) => write!(f, "Slack rejected the token ({})", code),
+        }
+    }
+}
+
+/// Unread conversations per workspace: (workspace_name, unread chats or error)
+type UnreadsResult = Vec<(String, Result<Vec<ChatInfo>, String>)>;
+
+/// Outcome of `/markallread`: (conversations marked, failures)
+type MarkAllReadResult = (usize, usize);
+
+/// /finduser looks up presence (one API call each) for this many matches
+const FIND_USER_PRESENCE_LOOKUPS: usize = 10;
+
+/// Rows in the `@` mention picker
+const MENTION_PICKER_MAX: usize = 8;
+
+/// Title of the /downloads popup, which is refreshed while it is open
+const DOWNLOADS_TITLE: &str = "Downloads";
+
+/// Border being dragged with the mouse
+enum DragTarget {
+    Sidebar,
+    Divider { path: Vec<usize>, divider: usize },
+}
+
+/// Narrowest sidebar that can be dragged to
+const MIN_SIDEBAR_WIDTH: u16 = 10;
+
+/// A file transfer started with /media or /files
+pub struct Download {
+    pub name: String,
+    pub received: u64,
+    pub total: Option<u64>,
+    pub state: DownloadState,
+}
+
+pub enum DownloadState {
+    InProgress,
+    Done(std::path::PathBuf),
+    Failed(String),
+}
+
+impl Download {
+    fn progress_text(&self) -> String {
+        match self.total {
+            Some(total) if total > 0 => format!(
+                "{}% of {}",
+                self.received.saturating_mul(100) / total,
+                format_file_size(total)
+            ),
+            _ => format_file_size(self.received),
+        }
+    }
+}
+
+/// A draft longer than `MAX_MESSAGE_CHARS`, held until the user picks how to send it
+pub struct LongMessage {
+    pane_idx: usize,
+    input: String, // As typed, for the input history
+    text: String,  // Mentions converted
+    broadcast: bool,
+}
+
+/// Messages plus (id, name) of senders that weren't in the name cache yet
+type LoadedHistory = (Vec<SlackMessage>, Vec<(String, String)>);
+
+/// Results of Slack calls made from spawned tasks, so a slow network never blocks the UI loop
+pub enum ApiEvent {
+    /// chat.postMessage finished for `message`
+    Sent {
+        message: OutboxMessage,
+        result: Result<String, String>,
+        restricted: bool, // A top-level post refused by the channel's posting permissions
+        retry: bool,      // Not sent because Slack was unreachable or the token rejected
+    },
+    /// conversations.list, to update the sidebar shown from the cache
+    Chats(Result<Vec<ChatInfo>, String>),
+    /// users.list prefetch: members for the mention picker and everyone's names
+    Users(Result<(Vec<UserSummary>, std::collections::HashMap<String, String>), String>),
+    /// A group DM's members changed
+    GroupRenamed { channel_id: String, name: String },
+    /// oauth.v2.access renewed (or failed to renew) a workspace's rotating token
+    TokenRefreshed { workspace: String, result: Result<RotatedToken, String> },
+    /// Messages of a channel (no `thread_ts`) or thread, plus names looked up for unknown senders
+    History {
+        channel_id: String,
+        thread_ts: Option<String>,
+        unread: Option<u32>, // Set when the chat was just opened: mark the unread ones, jump to the end
+        result: Result<LoadedHistory, String>,
+    },
+}
+
+/// Sent by download tasks to the UI loop; `id` indexes `App::downloads`
+pub enum DownloadEvent {
+    Progress {
+        id: usize,
+        received: u64,
+        total: Option<u64>,
+    },
+    Finished {
+        id: usize,
+        result: Result<std::path::PathBuf, String>,
+    },
+}
+
+/// Composer text in channels where only some members may post
+const POSTING_RESTRICTED_HINT: &str =
+    "Only some members can post here. Reply in a thread: r in copy mode, or /thread N";
+
+/// Title of the cross-workspace unreads pane
+const UNREADS_PANE_NAME: &str = "All unreads";
+/// Search results panes are titled "Search: <query>"
+const SEARCH_PANE_PREFIX: &str = "Search: ";
+/// Results asked from search.messages (its maximum per page)
+const SEARCH_RESULTS: usize = 100;
+
+/// A line in a pane that lists things (unreads, search results) rather than a conversation
+fn list_row(sender: &str, text: String, ts: String) -> MessageData {
+    MessageData {
+        sender_name: sender.to_string(),
+        text,
+        is_outgoing: false,
+        ts,
+        reactions: Vec::new(),
+        reply_count: 0,
+        forwarded_text: None,
+        mentions_me: false,
+        matches_keyword: false,
+        local_echo_id: None,
+        is_edited: false,
+        is_deleted: false,
+        is_system: false,
+        is_bot: false,
+        media_type: None,
+        file_ids: Vec::new(),
+        file_urls: Vec::new(),
+        file_names: Vec::new(),
+        files: Vec::new(),
+    }
+}
+
 pub struct App {
     pub config: Config,
     pub slack: SlackClient,
     pub my_user_id: String, // Current user's ID
+    pub my_usergroups: Vec<String>, // User groups the current user belongs to
     pub chats: Vec<ChatInfo>,
     pub selected_chat_idx: usize,
     pub panes: Vec<ChatPane>,
     pub focused_pane_idx: usize,
     pub pane_tree: PaneNode,
+    /// Render only the focused pane full-screen; the tree is left untouched
+    pub pane_zoomed: bool,
     pub input_history: Vec<String>,
     pub aliases: Aliases,
+    pub snippets: Snippets,
+    pub saved_searches: SavedSearches,
+    pub nicknames: Nicknames,
+    pub followed_threads: FollowedThreads,
+    pub starred: StarredChannels,
+    pub recent: RecentChats,
+    pub sidebar_order: SidebarOrder, // This workspace's /move order
+    pub drafts: Drafts, // Composer text of conversations no pane shows
+    pub huddles: std::collections::HashMap<String, usize>, // Channel ID -> people in its live huddle
+    pub sync_stars: bool, // /fav also stars the channel on Slack
+    pub sidebar_width: u16, // 0 = fit the longest chat name
+    pane_tree_area: Option<Rect>, // Where the pane tree was last drawn (for divider drags)
+    drag: Option<DragTarget>,
     pub focus_on_chat_list: bool,
     pub status_message: Option<String>,
     pub status_expire: Option<std::time::Instant>,
@@ -42,8 +259,33 @@ pub struct App {
     pub chat_list_scroll_offset: usize,
     pub pending_open_chat: bool,
     pub pending_refresh_chats: bool,
+    pub pending_open_thread: Option<usize>, // Message index whose thread was clicked
     pub pending_reload_panes: bool,
-    pub pending_workspace_switch: Option<tokio::sync::oneshot::Receiver<Result<(SlackClient, String), String>>>,
+    pub pending_workspace_switch: Option<tokio::sync::oneshot::Receiver<WorkspaceSwitchResult>>,
+    reauth_asked: bool, // The rejected token was already prompted for; reset by the next client
+    reauthenticating: bool, // pending_workspace_switch is a sign-in with a new token
+    starting: bool, // pending_workspace_switch is the first connect, started by App::new
+    token_refreshes: std::collections::HashMap<String, std::time::Instant>, // Workspace -> last refresh started
+    token_refresh_failed: std::collections::HashSet<String>, // Workspaces whose last refresh failed
+    pub pending_unreads: Option<tokio::sync::oneshot::Receiver<UnreadsResult>>,
+    pub pending_mark_all_read: Option<tokio::sync::oneshot::Receiver<MarkAllReadResult>>,
+    pub ipc_requests: Option<tokio::sync::mpsc::UnboundedReceiver<IpcRequest>>,
+    pub log_handle: Option<LogHandle>,
+    pub info_popup: Option<InfoPopup>,
+    pub file_browser: Option<FileBrowser>,
+    pub user_picker: Option<UserPicker>,
+    pub workspace_prompt: Option<WorkspacePrompt>, // /workspace add|rename|reauth input box
+    pub mention_picker: Option<MentionPicker>, // Shown while typing `@name` in the composer
+    member_profiles: Vec<UserSummary>, // users.list prefetch, for the mention picker
+    pub copy_mode: Option<CopyMode>, // Keyboard selection over the focused pane (Alt+C)
+    pub downloads: Vec<Download>,
+    download_tx: tokio::sync::mpsc::UnboundedSender<DownloadEvent>,
+    download_rx: tokio::sync::mpsc::UnboundedReceiver<DownloadEvent>,
+    api_tx: tokio::sync::mpsc::UnboundedSender<ApiEvent>,
+    pub api_rx: tokio::sync::mpsc::UnboundedReceiver<ApiEvent>,
+    // Feeds the task that posts messages one at a time with the current client, so sends
+    // keep their order across calls; replaced when the client is
+    send_queue: Option<tokio::sync::mpsc::UnboundedSender<OutboxMessage>>,
 
     // Settings
     pub show_reactions: bool,
@@ -55,7 +297,37 @@ pub struct App {
     pub show_chat_list: bool,
     pub show_user_colors: bool,
     pub show_borders: bool,
+    pub screen_reader: bool, // Linear, text-only output for terminal screen readers
+    pub show_link_labels: bool, // `<URL|text>` shows the text; the URL on hover/click
+    hovered_link: Option<String>, // URL under the mouse, shown in the status bar
+    pub quick_reactions: Vec<String>, // Emoji for Alt+1..Alt+9
+    pub jumbo_emoji: bool, // Emoji-only messages (1-3 emoji) drawn larger
+    pub terminal_title: bool, // Unread/mention counts in the window title
+    last_terminal_title: String, // Last title written, so it is only sent on change
+    pub osc_notifications: bool, // Notifications as OSC 9 escapes to the terminal
     pub mouse_support: bool,
+    pub auto_away_minutes: u64, // 0 = disabled
+    pub highlight_keywords: Vec<String>,
+    pub highlight_rules: Vec<HighlightRule>,
+    highlight_styles: Vec<(regex::Regex, Style)>, // Compiled highlight_rules, invalid ones left out
+    pub notify_on_keywords: bool,
+    pub notification_cooldown_secs: u64, // Per conversation; 0 = no batching
+    notifier: Notifier,
+    pub show_system_messages: bool,
+    pub alias_prefix: String,
+    pub timestamp_style: TimestampStyle,
+    pub timestamp_format: String,
+    pub auto_scroll: AutoScroll, // Whether panes follow new messages from others
+    pub paste_snippet_lines: usize, // 0 = always paste as text
+    pub recent_chats: usize, // Size of the sidebar's "Recent" section (0 = hidden)
+    pub quiet_hours: String, // "22:00-07:00": no desktop notifications (empty = off)
+    pub bold_key: String, // "alt+b": wrap the word at the cursor in *bold*
+    pub italic_key: String,
+    pub code_key: String,
+    pub dnd_until: Option<i64>, // End of our Slack DND snooze (unix seconds)
+    pub pending_paste: Option<String>, // Long paste waiting for the snippet prompt
+    pub pending_long_message: Option<LongMessage>, // Draft over Slack's limit, waiting for split/snippet
+    pub pending_slack_command: Option<String>, // Unknown /command, waiting for "send it to Slack?"
     pub user_name_cache: std::collections::HashMap<String, String>,
     pub needs_redraw: bool,
     pub last_terminal_size: (u16, u16),
@@ -67,9 +339,19 @@ pub struct App {
     pub last_fallback_refresh_instant: std::time::Instant,
     pub last_fallback_refresh_at: Option<chrono::DateTime<chrono::Local>>,
     pub realtime_was_stale: bool,
+    pub connection_state: tokio::sync::watch::Receiver<ConnectionState>,
+    /// Presentation mode: input hidden, all mutating actions disabled
+    pub read_only: bool,
+    pub terminal_focused: bool, // From focus events; terminals that don't send them count as focused
+    pub last_input_instant: std::time::Instant,
+    pub auto_away_active: bool, // We set presence to away because of inactivity
+    /// Slack was unreachable at startup: panes show cached history, sends go to `outbox`
+    pub offline: bool,
+    pub outbox: Vec<OutboxMessage>,
+    pub last_online_attempt: std::time::Instant,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ChatSection {
     Public = 0,
     Private = 1,
@@ -90,6 +372,37 @@ impl ChatSection {
     }
 }
 
+/// Who besides our workspace can see a conversation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Sharing {
+    #[default]
+    Internal,
+    /// Other workspaces of the same Enterprise Grid organization
+    Organization,
+    /// Slack Connect: people from other organizations
+    External,
+}
+
+impl Sharing {
+    /// Sidebar badge
+    pub fn badge(&self) -> Option<&'static str> {
+        match self {
+            Sharing::Internal => None,
+            Sharing::Organization => Some("org"),
+            Sharing::External => Some("ext"),
+        }
+    }
+
+    /// Pane header tag
+    pub fn header_tag(&self) -> Option<&'static str> {
+        match self {
+            Sharing::Internal => None,
+            Sharing::Organization => Some("[ORG-SHARED] "),
+            Sharing::External => Some("[EXTERNAL] "),
+        }
+    }
+}
+
 /// Generate a consistent color for a username using a hash function
 fn username_color(username: &str) -> Color {
     // Use a palette of distinct, readable colors
@@ -127,36 +440,32 @@ enum ChatListRow {
     Chat(usize),
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatInfo {
     pub id: String,
     pub name: String,
     pub username: Option<String>,
     pub unread: u32,
+    #[serde(default)]
+    pub mentions: u32, // Unread messages that mention us (highlight keywords don't count)
+    #[serde(default)]
+    pub posting_restricted: bool, // Announcement or thread-only channel: no top-level posts
+    #[serde(default)]
+    pub sharing: Sharing,
     pub section: ChatSection,
 }
 
-fn detect_media_type(files: &[crate::slack::SlackFile]) -> Option<(String, Vec<String>, Vec<String>, Vec<String>)> {
-    use std::fs::OpenOptions;
-    use std::io::Write;
-    
-    let log_to_file = |msg: &str| {
-        if let Ok(mut file) = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("/tmp/slack_rust_debug.log")
-        {
-            let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-            let _ = writeln!(file, "[{}] {}", timestamp, msg);
-        }
-    };
-    
-    log_to_file(&format!("=== DETECT MEDIA TYPE DEBUG ==="));
-    log_to_file(&format!("Number of files: {}", files.len()));
+/// (media_type, file_ids, file_urls, file_names, attachments); media_type is
+/// "image" or "video" when the message carries one, the other fields list every file
+type MediaInfo = (Option<String>, Vec<String>, Vec<String>, Vec<String>, Vec<FileAttachment>);
+
+fn detect_media_type(files: &[crate::slack::SlackFile]) -> MediaInfo {
+    debug!("=== DETECT MEDIA TYPE DEBUG ===");
+    debug!("Number of files: {}", files.len());
     
     if files.is_empty() {
-        log_to_file("No files, returning None");
-        return None;
+        debug!("No files, returning None");
+        return (None, Vec::new(), Vec::new(), Vec::new(), Vec::new());
     }
     
     let mut has_image = false;
@@ -164,64 +473,302 @@ fn detect_media_type(files: &[crate::slack::SlackFile]) -> Option<(String, Vec<S
     let mut file_ids = Vec::new();
     let mut file_urls = Vec::new();
     let mut file_names = Vec::new();
+    let mut attachments = Vec::new();
     
     for (idx, file) in files.iter().enumerate() {
-        log_to_file(&format!("File {}: id={:?}, mimetype={:?}, filetype={:?}, url_private={:?}, name={:?}", 
-            idx, file.id, file.mimetype, file.filetype, file.url_private, file.name));
+        debug!("File {}: id={:?}, mimetype={:?}, filetype={:?}, url_private={:?}, name={:?}", 
+            idx, file.id, file.mimetype, file.filetype, file.url_private, file.name);
         
-        if let Some(ref id) = file.id {
-            file_ids.push(id.clone());
-        }
+        file_ids.push(file.id.clone().unwrap_or_default());
         
         // Prefer url_private_download, fallback to url_private
         // url_private_download is specifically for downloading files
+        // IDs and URLs are empty when missing, so the lists stay aligned per file
         let url = file.url_private_download.as_ref()
-            .or_else(|| file.url_private.as_ref())
+            .or(file.url_private.as_ref())
             .cloned();
-        if let Some(url) = url {
-            file_urls.push(url);
-        }
+        file_urls.push(url.unwrap_or_default());
         
-        if let Some(ref name) = file.name {
-            file_names.push(name.clone());
-        } else {
-            file_names.push("file".to_string());
-        }
+        let name = file.name.clone().unwrap_or_else(|| "file".to_string());
+        attachments.push(FileAttachment {
+            name: name.clone(),
+            kind: file
+                .pretty_type
+                .clone()
+                .or_else(|| file.filetype.clone())
+                .unwrap_or_default(),
+            size: file.size,
+        });
+        file_names.push(name);
         
         if let Some(ref mimetype) = file.mimetype {
-            log_to_file(&format!("  Checking mimetype: {}", mimetype));
+            debug!("  Checking mimetype: {}", mimetype);
             if mimetype.starts_with("image/") {
                 has_image = true;
-                log_to_file("  -> Detected as image");
+                debug!("  -> Detected as image");
             } else if mimetype.starts_with("video/") {
                 has_video = true;
-                log_to_file("  -> Detected as video");
+                debug!("  -> Detected as video");
             }
         } else if let Some(ref filetype) = file.filetype {
-            log_to_file(&format!("  Checking filetype: {}", filetype));
+            debug!("  Checking filetype: {}", filetype);
             if filetype == "jpg" || filetype == "jpeg" || filetype == "png" || 
                filetype == "gif" || filetype == "webp" || filetype == "svg" {
                 has_image = true;
-                log_to_file("  -> Detected as image");
+                debug!("  -> Detected as image");
             } else if filetype == "mp4" || filetype == "mov" || filetype == "webm" {
                 has_video = true;
-                log_to_file("  -> Detected as video");
+                debug!("  -> Detected as video");
             }
         }
     }
     
-    let result = if has_video {
-        log_to_file(&format!("Final result: video, {} files", file_urls.len()));
-        Some(("video".to_string(), file_ids, file_urls, file_names))
+    let media_type = if has_video {
+        debug!("Final result: video, {} files", file_urls.len());
+        Some("video".to_string())
     } else if has_image {
-        log_to_file(&format!("Final result: image, {} files", file_urls.len()));
-        Some(("image".to_string(), file_ids, file_urls, file_names))
+        debug!("Final result: image, {} files", file_urls.len());
+        Some("image".to_string())
     } else {
-        log_to_file("Final result: None (no media detected)");
+        debug!("Final result: no media, {} files", file_urls.len());
         None
     };
     
-    result
+    (media_type, file_ids, file_urls, file_names, attachments)
+}
+
+/// First line of a pinned message for the pane header, shortened
+fn pin_preview(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > 50 {
+        format!("{}...", line.chars().take(47).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+fn is_system_message(msg: &crate::slack::SlackMessage) -> bool {
+    msg.subtype.as_deref().is_some_and(is_system_subtype)
+}
+
+fn is_bot_message(msg: &crate::slack::SlackMessage) -> bool {
+    msg.bot_id.is_some() || msg.subtype.as_deref() == Some("bot_message")
+}
+
+/// Rebuild a pane from its saved layout entry (history is loaded separately).
+/// A pane from the saved layout, with its conversation's draft back in the composer.
+fn restore_pane(ps: &PaneState, drafts: &mut Drafts) -> ChatPane {
+    let mut pane = ChatPane::new();
+    pane.chat_id = ps.chat_id;
+    pane.channel_id_str = ps.channel_id.clone();
+    pane.chat_name = ps.chat_name.clone();
+    pane.username = ps.username.clone();
+    pane.scroll_offset = ps.scroll_offset;
+    pane.thread_ts = ps.thread_ts.clone();
+    pane.filter_type = ps.filter_type;
+    pane.filter_value = ps.filter_value.clone();
+    pane.hide_bots = ps.hide_bots;
+    if let (Some(channel_id), None) = (&ps.channel_id, &ps.thread_ts) {
+        pane.input_buffer = drafts.take(channel_id).unwrap_or_default();
+        pane.input_cursor = pane.input_buffer.len();
+    }
+    pane
+}
+
+fn line_text(line: &Line) -> String {
+    line.spans.iter().map(|s| s.content.as_ref()).collect()
+}
+
+/// Redraw the lines under the copy mode selection (or cursor) with the selected
+/// characters reversed; their other styling is dropped while selected.
+fn highlight_copy_selection(lines: &mut [Line], copy: &CopyMode) {
+    let ((start_line, start_col), (end_line, end_col)) = copy.range();
+    let selected = Style::default().add_modifier(Modifier::REVERSED);
+    for line_idx in start_line..=end_line.min(lines.len().saturating_sub(1)) {
+        let mut chars: Vec<char> = line_text(&lines[line_idx]).chars().collect();
+        let from = if line_idx == start_line { start_col } else { 0 };
+        let to = if line_idx == end_line { end_col + 1 } else { chars.len() };
+        // Show the cursor past the end of short lines
+        while chars.len() < to {
+            chars.push(' ');
+        }
+        let part = |a: usize, b: usize| chars[a.min(b)..b].iter().collect::<String>();
+        lines[line_idx] = Line::from(vec![
+            Span::raw(part(0, from)),
+            Span::styled(part(from, to), selected),
+            Span::raw(part(to, chars.len())),
+        ]);
+    }
+}
+
+/// Clickable parts of the drawn message lines: "[N replies]" opens the thread,
+/// a URL or link label opens in the browser. `ranges` maps line ranges to the
+/// message index and its links as (shown text, URL), since a wrapped URL is only
+/// partly on its first line.
+#[allow(clippy::type_complexity)]
+fn find_click_targets(
+    lines: &[Line],
+    ranges: &[(std::ops::Range<usize>, usize, Vec<(String, String)>)],
+    left: u16,
+) -> Vec<(usize, std::ops::Range<u16>, ClickAction)> {
+    let mut targets = Vec::new();
+    for (range, msg_idx, urls) in ranges {
+        for line_idx in range.clone() {
+            // Wrapping splits words into separate spans, so match on the whole line
+            let text: String = lines[line_idx].spans.iter().map(|s| s.content.as_ref()).collect();
+            let cols = |start: usize, end: usize| {
+                let x = left + UnicodeWidthStr::width(&text[..start]) as u16;
+                x..x + UnicodeWidthStr::width(&text[start..end]) as u16
+            };
+            for (end, marker) in text.match_indices(" replies]") {
+                let Some(start) = text[..end].rfind('[') else {
+                    continue;
+                };
+                let count = &text[start + 1..end];
+                if !count.is_empty() && count.chars().all(|c| c.is_ascii_digit()) {
+                    let range = cols(start, end + marker.len());
+                    targets.push((line_idx, range, ClickAction::OpenThread(*msg_idx)));
+                }
+            }
+            for (start, _) in text.match_indices("http") {
+                let fragment = text[start..].split_whitespace().next().unwrap_or("");
+                if let Some((_, url)) = urls.iter().find(|(shown, _)| shown.starts_with(fragment)) {
+                    let range = cols(start, start + fragment.len());
+                    targets.push((line_idx, range, ClickAction::OpenUrl(url.clone())));
+                }
+            }
+            // Link labels are only clickable where they fit on one line
+            for (label, url) in urls.iter().filter(|(shown, url)| shown != url) {
+                for (start, _) in text.match_indices(label.as_str()) {
+                    let range = cols(start, start + label.len());
+                    targets.push((line_idx, range, ClickAction::OpenUrl(url.clone())));
+                }
+            }
+        }
+    }
+    targets
+}
+
+/// Sidebar marker of a conversation with a live huddle.
+fn huddle_badge(show_emojis: bool) -> &'static str {
+    if show_emojis { " 🎧" } else { " huddle" }
+}
+
+/// Sidebar order: starred chats first, then by section and name.
+fn sort_chat_list(chats: &mut [ChatInfo], starred: &StarredChannels) {
+    chats.sort_by_key(|c| (!starred.contains(&c.id), c.section as u8, c.name.to_lowercase()));
+}
+
+/// Show nicknames instead of Slack names for DMs with nicknamed users.
+fn apply_nicknames_to_chats(chats: &mut [ChatInfo], nicknames: &Nicknames) {
+    for chat in chats {
+        if !matches!(chat.section, ChatSection::DirectMessage | ChatSection::Bot) {
+            continue;
+        }
+        if let Some(nick) = chat.username.as_ref().and_then(|id| nicknames.map.get(id)) {
+            chat.name = nick.clone();
+        }
+    }
+}
+
+/// A pane's `/filter`, prepared once per draw.
+enum PaneFilter {
+    Sender(String), // lowercased
+    Media,
+    Link,
+    Regex(regex::Regex),
+    Since(chrono::NaiveDate),
+    Until(chrono::NaiveDate),
+}
+
+impl PaneFilter {
+    /// None if the pane is unfiltered or its value no longer parses.
+    fn from_pane(pane: &ChatPane) -> Option<Self> {
+        let value = pane.filter_value.as_deref();
+        let date = || parse_filter_date(value?);
+        Some(match pane.filter_type? {
+            FilterType::Sender => PaneFilter::Sender(value?.to_lowercase()),
+            FilterType::Media => PaneFilter::Media,
+            FilterType::Link => PaneFilter::Link,
+            FilterType::Regex => PaneFilter::Regex(regex::Regex::new(value?).ok()?),
+            FilterType::Since => PaneFilter::Since(date()?),
+            FilterType::Until => PaneFilter::Until(date()?),
+        })
+    }
+
+    /// `text` is the message as displayed (mentions, links and emojis resolved),
+    /// except that links are always URLs so the link filter sees them.
+    fn matches(&self, msg: &MessageData, text: &str) -> bool {
+        let date = || ts_date(&msg.ts);
+        match self {
+            PaneFilter::Sender(name) => msg.sender_name.to_lowercase().contains(name.as_str()),
+            PaneFilter::Media => msg.media_type.is_some() || !msg.file_ids.is_empty(),
+            PaneFilter::Link => text.contains("http://") || text.contains("https://"),
+            PaneFilter::Regex(re) => re.is_match(text),
+            PaneFilter::Since(since) => date().is_some_and(|d| d >= *since),
+            PaneFilter::Until(until) => date().is_some_and(|d| d <= *until),
+        }
+    }
+}
+
+/// Local date of a Slack timestamp
+fn ts_date(ts: &str) -> Option<chrono::NaiveDate> {
+    let secs: i64 = ts.split('.').next()?.parse().ok()?;
+    Some(Local.timestamp_opt(secs, 0).single()?.date_naive())
+}
+
+/// `YYYY-MM-DD`, or `today` / `yesterday`.
+pub fn parse_filter_date(value: &str) -> Option<chrono::NaiveDate> {
+    let today = Local::now().date_naive();
+    match value.to_lowercase().as_str() {
+        "today" => Some(today),
+        "yesterday" => today.pred_opt(),
+        other => chrono::NaiveDate::parse_from_str(other, "%Y-%m-%d").ok(),
+    }
+}
+
+/// Fetch up to 100 messages of a channel or thread and the names of senders not in `known`.
+async fn fetch_history(
+    slack: &SlackClient,
+    channel_id: &str,
+    thread_ts: Option<&str>,
+    known: &std::collections::HashSet<String>,
+) -> Result<LoadedHistory> {
+    let messages = match thread_ts {
+        Some(thread_ts) => slack.get_thread_replies(channel_id, thread_ts, 100).await?,
+        None => slack.get_conversation_history(channel_id, 100).await?,
+    };
+
+    // Look up unknown users and bots in parallel
+    let mut fetch_tasks = Vec::new();
+    let mut requested = std::collections::HashSet::new();
+    for slack_msg in &messages {
+        if let Some(uid) = slack_msg.user.clone() {
+            if !known.contains(&uid) && requested.insert(uid.clone()) {
+                let slack = slack.clone();
+                fetch_tasks.push(tokio::spawn(async move {
+                    let name = slack.resolve_user_name(&uid).await;
+                    (uid, name)
+                }));
+            }
+        }
+        if let Some(bot_id) = slack_msg.bot_id.clone() {
+            if !known.contains(&bot_id) && requested.insert(bot_id.clone()) {
+                let slack = slack.clone();
+                fetch_tasks.push(tokio::spawn(async move {
+                    let name = slack.resolve_bot_name(&bot_id).await;
+                    (bot_id, name)
+                }));
+            }
+        }
+    }
+    let mut names = Vec::new();
+    for task in fetch_tasks {
+        if let Ok(pair) = task.await {
+            names.push(pair);
+        }
+    }
+    Ok((messages, names))
 }
 
 fn forwarded_preview(attachments: &[SlackAttachment]) -> Option<String> {
@@ -266,22 +813,7 @@ fn forwarded_preview(attachments: &[SlackAttachment]) -> Option<String> {
 }
 
 impl App {
-    /// Check if a message text contains a mention of the specified user ID
-    fn message_mentions_user(text: &str, user_id: &str) -> bool {
-        if user_id.is_empty() {
-            return false;
-        }
-        
-        // Look for <@USER_ID> or <@USER_ID|...>
-        let pattern1 = format!("<@{}>", user_id);
-        let pattern2 = format!("<@{}|", user_id);
-        
-        text.contains(&pattern1) || text.contains(&pattern2)
-    }
-
-    pub async fn new() -> Result<Self> {
-        let config = Config::load()?;
-        
+    pub async fn new(config: Config) -> Result<Self> {
         // Get the active workspace
         if config.workspaces.is_empty() {
             return Err(anyhow::anyhow!("No workspaces configured"));
@@ -290,24 +822,38 @@ impl App {
         let active_idx = config.active_workspace.min(config.workspaces.len() - 1);
         let workspace = &config.workspaces[active_idx];
         
-        let slack = SlackClient::new(&workspace.token, &workspace.app_token).await?;
-        let my_user_id = slack.get_my_user_id().await?;
-
-        // Start event listener
-        slack.start_event_listener(workspace.app_token.clone()).await?;
-
-        let app_state = AppState::load(&config).unwrap_or_else(|_| AppState {
+        let cache = SessionCache::load(&config);
+
+        // The first frame is drawn from the session cache; the client connects in the
+        // background (spawn_connect) and replaces this one when it's done
+        let slack = SlackClient::offline(&workspace.token, &config.network)?;
+        let workspace_name = workspace.name.clone();
+        let connection_state = slack.connection_state();
+        let my_user_id = cache.my_user_id.clone();
+        let (download_tx, download_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (api_tx, api_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let mut app_state = AppState::load(&config).unwrap_or_else(|_| AppState {
             settings: crate::persistence::AppSettings::default(),
             aliases: Aliases::default(),
+            snippets: Snippets::default(),
+            saved_searches: SavedSearches::default(),
+            nicknames: Nicknames::default(),
+            followed_threads: FollowedThreads::default(),
+            starred: StarredChannels::default(),
+            recent: RecentChats::default(),
+            sidebar_order: SidebarOrder::default(),
+            drafts: Drafts::default(),
             layout: LayoutData::default(),
         });
 
-        // Load initial chats
-        let mut chats = slack.get_conversations().await.unwrap_or_else(|e| {
-            eprintln!("Failed to load conversations: {e}");
-            Vec::new()
-        });
-        chats.sort_by_key(|c| (c.section as u8, c.name.to_lowercase()));
+        slack.add_group_members(cache.group_members.clone());
+
+        // Start from the last-known sidebar; it is refreshed once connected
+        let mut chats = cache.chats;
+        slack.add_user_names(cache.user_names.clone()).await;
+        apply_nicknames_to_chats(&mut chats, &app_state.nicknames);
+        sort_chat_list(&mut chats, &app_state.starred);
 
         // Load pane tree
         let (pane_tree, required_indices) = if let Some(saved_tree) = app_state.layout.pane_tree {
@@ -327,13 +873,7 @@ impl App {
         let mut panes: Vec<ChatPane> = Vec::new();
         for i in 0..total_panes_needed {
             if let Some(ps) = app_state.layout.panes.get(i) {
-                let mut pane = ChatPane::new();
-                pane.chat_id = ps.chat_id;
-                pane.channel_id_str = ps.channel_id.clone();
-                pane.chat_name = ps.chat_name.clone();
-                pane.scroll_offset = ps.scroll_offset;
-                pane.thread_ts = ps.thread_ts.clone();
-                panes.push(pane);
+                panes.push(restore_pane(ps, &mut app_state.drafts));
             } else {
                 panes.push(ChatPane::new());
             }
@@ -345,17 +885,32 @@ impl App {
             0
         };
 
-        let app = Self {
+        let mut app = Self {
             config,
             slack,
             my_user_id,
+            my_usergroups: Vec::new(),
             chats,
             selected_chat_idx: 0,
             panes,
             focused_pane_idx,
             pane_tree,
+            pane_zoomed: false,
             input_history: Vec::new(),
             aliases: app_state.aliases,
+            snippets: app_state.snippets,
+            saved_searches: app_state.saved_searches,
+            nicknames: app_state.nicknames,
+            followed_threads: app_state.followed_threads,
+            starred: app_state.starred,
+            recent: app_state.recent,
+            sidebar_order: app_state.sidebar_order,
+            drafts: app_state.drafts,
+            huddles: std::collections::HashMap::new(),
+            sync_stars: app_state.settings.sync_stars,
+            sidebar_width: app_state.settings.sidebar_width,
+            pane_tree_area: None,
+            drag: None,
             focus_on_chat_list: true,
             status_message: None,
             status_expire: None,
@@ -363,8 +918,31 @@ impl App {
             chat_list_scroll_offset: 0,
             pending_open_chat: false,
             pending_refresh_chats: false,
+            pending_open_thread: None,
             pending_reload_panes: false,
             pending_workspace_switch: None,
+            reauth_asked: false,
+            reauthenticating: false,
+            starting: true,
+            token_refreshes: std::collections::HashMap::new(),
+            token_refresh_failed: std::collections::HashSet::new(),
+            pending_unreads: None,
+            pending_mark_all_read: None,
+            ipc_requests: None,
+            log_handle: None,
+            info_popup: None,
+            file_browser: None,
+            user_picker: None,
+            workspace_prompt: None,
+            mention_picker: None,
+            member_profiles: Vec::new(),
+            copy_mode: None,
+            downloads: Vec::new(),
+            download_tx,
+            download_rx,
+            api_tx,
+            send_queue: None,
+            api_rx,
             pane_areas: std::collections::HashMap::new(),
             show_reactions: app_state.settings.show_reactions,
             show_notifications: app_state.settings.show_notifications,
@@ -375,8 +953,53 @@ impl App {
             show_chat_list: app_state.settings.show_chat_list,
             show_user_colors: app_state.settings.show_user_colors,
             show_borders: app_state.settings.show_borders,
+            screen_reader: app_state.settings.screen_reader,
+            show_link_labels: app_state.settings.show_link_labels,
+            hovered_link: None,
+            quick_reactions: app_state.settings.quick_reactions,
+            jumbo_emoji: app_state.settings.jumbo_emoji,
+            terminal_title: app_state.settings.terminal_title,
+            last_terminal_title: String::new(),
+            osc_notifications: app_state.settings.osc_notifications,
             mouse_support: app_state.settings.mouse_support,
-            user_name_cache: std::collections::HashMap::new(),
+            auto_away_minutes: app_state.settings.auto_away_minutes,
+            highlight_keywords: app_state.settings.highlight_keywords,
+            highlight_styles: app_state
+                .settings
+                .highlight_rules
+                .iter()
+                .filter_map(|rule| {
+                    rule.compile()
+                        .map_err(|e| warn!("Ignoring highlight rule {}", e))
+                        .ok()
+                })
+                .collect(),
+            highlight_rules: app_state.settings.highlight_rules,
+            notify_on_keywords: app_state.settings.notify_on_keywords,
+            notification_cooldown_secs: app_state.settings.notification_cooldown_secs,
+            notifier: Notifier::new(app_state.settings.notification_cooldown_secs),
+            show_system_messages: app_state.settings.show_system_messages,
+            alias_prefix: app_state.settings.alias_prefix,
+            timestamp_style: app_state.settings.timestamp_style,
+            auto_scroll: app_state.settings.auto_scroll,
+            timestamp_format: match app_state.settings.timestamp_format {
+                format if valid_time_format(&format) => format,
+                format => {
+                    warn!("Ignoring invalid timestamp_format {:?}, using %H:%M", format);
+                    "%H:%M".to_string()
+                }
+            },
+            paste_snippet_lines: app_state.settings.paste_snippet_lines,
+            recent_chats: app_state.settings.recent_chats,
+            quiet_hours: app_state.settings.quiet_hours,
+            bold_key: app_state.settings.bold_key,
+            italic_key: app_state.settings.italic_key,
+            code_key: app_state.settings.code_key,
+            dnd_until: None,
+            pending_paste: None,
+            pending_long_message: None,
+            pending_slack_command: None,
+            user_name_cache: cache.user_names,
             needs_redraw: true,
             last_terminal_size: (0, 0),
             next_local_echo_id: 1,
@@ -387,184 +1010,81 @@ impl App {
             last_fallback_refresh_instant: std::time::Instant::now(),
             last_fallback_refresh_at: None,
             realtime_was_stale: false,
+            connection_state,
+            read_only: false,
+            terminal_focused: true,
+            last_input_instant: std::time::Instant::now(),
+            auto_away_active: false,
+            offline: false,
+            outbox: cache.outbox,
+            last_online_attempt: std::time::Instant::now(),
         };
+        app.show_cached_histories();
+        app.spawn_connect();
+        app.set_status(&format!("Connecting to {}...", workspace_name));
 
         Ok(app)
     }
     
-    /// Load chat history for all panes that have channels assigned
-    pub async fn load_all_pane_histories(&mut self) -> Result<()> {
-        // Collect panes to load (with channel_id and optionally thread_ts)
-        let panes_to_load: Vec<(usize, String, Option<String>)> = self.panes
-            .iter()
-            .enumerate()
-            .filter_map(|(idx, pane)| {
-                pane.channel_id_str.as_ref().map(|id| {
-                    (idx, id.clone(), pane.thread_ts.clone())
-                })
-            })
-            .collect();
+    /// Offline: fill the panes from the session cache and show the queued messages again.
+    fn load_cached_histories(&mut self) {
+        self.show_cached_histories();
+        self.show_offline_status();
+    }
 
-        for (pane_idx, channel_id, thread_ts) in panes_to_load {
-            let result = if let Some(ref thread_ts) = thread_ts {
-                // This is a thread pane - load thread replies
-                self.slack.get_thread_replies(&channel_id, thread_ts, 100).await
-            } else {
-                // Regular channel pane - load channel history
-                self.slack.get_conversation_history(&channel_id, 100).await
-            };
-            
-            match result {
-                Ok(messages) => {
-                    // Collect unique user IDs and bot IDs and resolve names in batch
-                    let mut name_cache: std::collections::HashMap<String, String> =
-                        std::collections::HashMap::new();
-                    for slack_msg in &messages {
-                        if let Some(ref uid) = slack_msg.user {
-                            if !name_cache.contains_key(uid) {
-                                let name = self.slack.resolve_user_name(uid).await;
-                                name_cache.insert(uid.clone(), name);
-                            }
-                        }
-                        if let Some(ref bot_id) = slack_msg.bot_id {
-                            if !name_cache.contains_key(bot_id) {
-                                let name = self.slack.resolve_bot_name(bot_id).await;
-                                name_cache.insert(bot_id.clone(), name);
-                            }
-                        }
-                    }
+    fn show_offline_status(&mut self) {
+        self.set_status(&format!(
+            "Offline: showing cached messages{}",
+            match self.outbox.len() {
+                0 => String::new(),
+                n => format!(", {} queued to send", n),
+            }
+        ));
+    }
 
-                    // Add messages to pane
-                    let pane = &mut self.panes[pane_idx];
-                    pane.msg_data.clear();
-                    pane.invalidate_cache();
-                    
-                    // Thread replies come in chronological order, channel history comes newest first
-                    if thread_ts.is_some() {
-                        // Thread: keep chronological order (oldest first)
-                        for slack_msg in &messages {
-                        // Try to get sender name from user, bot_profile, username, or bot_id
-                        let sender_name = if let Some(ref user_id) = slack_msg.user {
-                            name_cache
-                                .get(user_id)
-                                .cloned()
-                                .unwrap_or_else(|| user_id.clone())
-                        } else if let Some(ref bot_profile) = slack_msg.bot_profile {
-                            // For Slack apps/webhooks, bot_profile.name contains the display name
-                            bot_profile.name.clone().unwrap_or_else(|| "Bot".to_string())
-                        } else if let Some(ref username) = slack_msg.username {
-                            // Fallback to username field
-                            username.clone()
-                        } else if let Some(ref bot_id) = slack_msg.bot_id {
-                            // Fallback to bot_id lookup
-                            name_cache
-                                .get(bot_id)
-                                .cloned()
-                                .unwrap_or_else(|| bot_id.clone())
-                        } else {
-                            "Unknown".to_string()
-                        };
-                        let reactions: Vec<(String, u32)> = slack_msg
-                            .reactions
-                            .iter()
-                            .map(|r| (r.name.clone(), r.count))
-                            .collect();
-                        let mentions_me = Self::message_mentions_user(&slack_msg.text, &self.my_user_id);
-                        let (media_type, file_ids, file_urls, file_names) = detect_media_type(&slack_msg.files)
-                            .map(|(mt, ids, urls, names)| (Some(mt), ids, urls, names))
-                            .unwrap_or((None, Vec::new(), Vec::new(), Vec::new()));
-                        let msg_data = crate::widgets::MessageData {
-                            sender_name,
-                            text: slack_msg.text.clone(),
-                            is_outgoing: slack_msg.user.as_deref() == Some(&self.my_user_id),
-                            ts: slack_msg.ts.clone(),
-                            reactions,
-                            reply_count: slack_msg.reply_count.unwrap_or(0),
-                            forwarded_text: forwarded_preview(&slack_msg.attachments),
-                            mentions_me,
-                            local_echo_id: None,
-                            is_edited: false,
-                            is_deleted: false,
-                            media_type,
-                            file_ids,
-                            file_urls,
-                            file_names,
-                        };
-                        pane.msg_data.push(msg_data);
-                        }
-                    } else {
-                        // Channel: reverse to show newest first
-                        for slack_msg in messages.iter().rev() {
-                            // Try to get sender name from user, bot_profile, username, or bot_id
-                            let sender_name = if let Some(ref user_id) = slack_msg.user {
-                                name_cache
-                                    .get(user_id)
-                                    .cloned()
-                                    .unwrap_or_else(|| user_id.clone())
-                            } else if let Some(ref bot_profile) = slack_msg.bot_profile {
-                                // For Slack apps/webhooks, bot_profile.name contains the display name
-                                bot_profile.name.clone().unwrap_or_else(|| "Bot".to_string())
-                            } else if let Some(ref username) = slack_msg.username {
-                                // Fallback to username field
-                                username.clone()
-                            } else if let Some(ref bot_id) = slack_msg.bot_id {
-                                // Fallback to bot_id lookup
-                                name_cache
-                                    .get(bot_id)
-                                    .cloned()
-                                    .unwrap_or_else(|| bot_id.clone())
-                            } else {
-                                "Unknown".to_string()
-                            };
-                            let reactions: Vec<(String, u32)> = slack_msg
-                                .reactions
-                                .iter()
-                                .map(|r| (r.name.clone(), r.count))
-                                .collect();
-                            let mentions_me = Self::message_mentions_user(&slack_msg.text, &self.my_user_id);
-                            let (media_type, file_ids, file_urls, file_names) = detect_media_type(&slack_msg.files)
-                                .map(|(mt, ids, urls, names)| (Some(mt), ids, urls, names))
-                                .unwrap_or((None, Vec::new(), Vec::new(), Vec::new()));
-                            let msg_data = crate::widgets::MessageData {
-                                sender_name,
-                                text: slack_msg.text.clone(),
-                                is_outgoing: slack_msg.user.as_deref() == Some(&self.my_user_id),
-                                ts: slack_msg.ts.clone(),
-                                reactions,
-                                reply_count: slack_msg.reply_count.unwrap_or(0),
-                                forwarded_text: forwarded_preview(&slack_msg.attachments),
-                                mentions_me,
-                                local_echo_id: None,
-                            is_edited: false,
-                            is_deleted: false,
-                            media_type,
-                            file_ids,
-                            file_urls,
-                            file_names,
-                        };
-                        pane.msg_data.push(msg_data);
-                        }
-                    }
-                    
-                    // Auto-scroll to bottom
-                    pane.scroll_offset = usize::MAX;
-                }
-                Err(e) => {
-                    eprintln!("Failed to load messages for pane {}: {}", pane_idx, e);
-                }
+    /// Fill the panes with the messages cached when the app last ran, plus local echoes
+    /// of the messages waiting in the outbox.
+    fn show_cached_histories(&mut self) {
+        let cached = SessionCache::load(&self.config).messages;
+        for pane in &mut self.panes {
+            let Some(channel_id) = pane.channel_id_str.as_deref() else {
+                continue;
+            };
+            let key = SessionCache::key(channel_id, pane.thread_ts.as_deref());
+            pane.msg_data = cached.get(&key).cloned().unwrap_or_default();
+            pane.invalidate_cache();
+            pane.scroll_to_bottom();
+        }
+        for i in 0..self.outbox.len() {
+            let entry = &self.outbox[i];
+            let pane_idx = self.panes.iter().position(|p| {
+                p.channel_id_str.as_deref() == Some(entry.channel_id.as_str()) && p.thread_ts == entry.thread_ts
+            });
+            if let Some(pane_idx) = pane_idx {
+                let text = entry.text.clone();
+                self.outbox[i].local_echo_id = Some(self.push_local_echo(pane_idx, &text));
             }
         }
-        
-        // Sync user name cache
-        self.user_name_cache = self.slack.get_user_name_cache().await;
-        
-        Ok(())
     }
 
     fn realtime_status_text(&self) -> String {
         let mut parts: Vec<String> = Vec::new();
         let now = std::time::Instant::now();
 
+        if self.offline {
+            parts.push(format!("OFFLINE, cached history, {} queued", self.outbox.len()));
+        } else {
+            parts.push(format!("WS:{}", self.connection_state.borrow().label()));
+        }
+
+        // Ages and clock times change every second, which screen readers re-announce
+        if self.screen_reader {
+            if let Some(until) = self.dnd_end() {
+                parts.push(format!("DND until {}", until.format("%H:%M")));
+            }
+            return format!(" | {}", parts.join(" | "));
+        }
+
         if let Some(last) = self.last_realtime_event_instant {
             let age = now.duration_since(last).as_secs();
             let state = if age >= REALTIME_STALE_SECS { "stale" } else { "ok" };
@@ -581,6 +1101,10 @@ impl App {
             parts.push(format!("FB {}", fb.format("%H:%M:%S")));
         }
 
+        if let Some(until) = self.dnd_end() {
+            parts.push(format!("DND until {}", until.format("%H:%M")));
+        }
+
         if parts.is_empty() {
             String::new()
         } else {
@@ -590,27 +1114,21 @@ impl App {
 
     pub async fn process_slack_events(&mut self) -> Result<()> {
         let updates = self.slack.get_pending_updates().await;
+        let auto_scroll = self.auto_scroll;
         
         if !updates.is_empty() {
             let now = std::time::Instant::now();
             self.last_realtime_event_instant = Some(now);
             self.last_realtime_event_at = Some(chrono::Local::now());
             self.realtime_was_stale = false;
-            let _ = std::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open("/tmp/slack_rust_debug.log")
-                .and_then(|mut f| {
-                    use std::io::Write;
-                    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                    writeln!(f, "[{}] Processing {} updates in app.rs", timestamp, updates.len())
-                });
+            debug!("Processing {} updates in app.rs", updates.len());
         }
 
         for update in updates {
             match update {
                 SlackUpdate::NewMessage {
                     channel_id,
+                    user_id,
                     user_name,
                     text,
                     ts,
@@ -620,62 +1138,62 @@ impl App {
                     forwarded,
                     mentions_me,
                     files,
+                    is_system,
                 } => {
-                    use std::fs::OpenOptions;
-                    use std::io::Write;
-                    
-                    let log_to_file = |msg: &str| {
-                        if let Ok(mut file) = OpenOptions::new()
-                            .create(true)
-                            .append(true)
-                            .open("/tmp/slack_rust_debug.log")
-                        {
-                            let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                            let _ = writeln!(file, "[{}] {}", timestamp, msg);
-                        }
-                    };
-                    
-                    log_to_file(&format!("=== PROCESS NEW MESSAGE UPDATE ==="));
-                    log_to_file(&format!("channel_id: {}, user_name: {}, ts: {}", channel_id, user_name, ts));
-                    log_to_file(&format!("thread_ts: {:?}, files count: {}", thread_ts, files.len()));
+                    let user_name = user_id
+                        .and_then(|id| self.nicknames.map.get(&id).cloned())
+                        .unwrap_or(user_name);
+                    debug!("=== PROCESS NEW MESSAGE UPDATE ===");
+                    debug!("channel_id: {}, user_name: {}, ts: {}", channel_id, user_name, ts);
+                    debug!("thread_ts: {:?}, files count: {}", thread_ts, files.len());
                     for (idx, file) in files.iter().enumerate() {
-                        log_to_file(&format!("  File {}: id={:?}, mimetype={:?}, filetype={:?}", 
-                            idx, file.id, file.mimetype, file.filetype));
+                        debug!("  File {}: id={:?}, mimetype={:?}, filetype={:?}", 
+                            idx, file.id, file.mimetype, file.filetype);
                     }
                     
-                    let (media_type, file_ids, file_urls, file_names) = detect_media_type(&files)
-                        .map(|(mt, ids, urls, names)| (Some(mt), ids, urls, names))
-                        .unwrap_or((None, Vec::new(), Vec::new(), Vec::new()));
+                    let (media_type, file_ids, file_urls, file_names, attachments) = detect_media_type(&files);
                     
-                    log_to_file(&format!("Detected media_type: {:?}, file_ids: {:?}, file_urls: {:?}, file_names: {:?}", 
-                        media_type, file_ids, file_urls, file_names));
+                    debug!("Detected media_type: {:?}, file_ids: {:?}, file_urls: {:?}, file_names: {:?}", 
+                        media_type, file_ids, file_urls, file_names);
+                    // Custom keywords are highlighted and notify, but don't count as mentions
+                    let keyword_hit = if is_self || is_system {
+                        None
+                    } else {
+                        find_keyword(&text, &self.highlight_keywords).map(str::to_string)
+                    };
+
                     let is_thread_reply = matches!(thread_ts.as_ref(), Some(t) if t != &ts);
                     let root_thread_ts = thread_ts.clone().unwrap_or_else(|| ts.clone());
 
                     // Update panes showing this channel/thread
                     let mut seen_in_open_pane = false;
-                    for pane in &mut self.panes {
+                    let focused_pane_idx = self.focused_pane_idx;
+                    let terminal_focused = self.terminal_focused;
+                    for (pane_idx, pane) in self.panes.iter_mut().enumerate() {
+                        let mark_unread = (pane_idx != focused_pane_idx || !terminal_focused)
+                            && !is_self
+                            && !is_system;
                         if let Some(ref pane_channel_id) = pane.channel_id_str {
                             if *pane_channel_id == channel_id {
                                 match &pane.thread_ts {
                                     Some(pane_thread) => {
                                         if let Some(msg_thread) = &thread_ts {
                                             if pane_thread == msg_thread {
-                                                // Check if message already exists (by timestamp)
+                                                // The parent's count feeds the pane header
+                                                if is_thread_reply {
+                                                    if let Some(parent) = pane
+                                                        .msg_data
+                                                        .iter_mut()
+                                                        .find(|m| &m.ts == pane_thread)
+                                                    {
+                                                        parent.reply_count =
+                                                            parent.reply_count.saturating_add(1);
+                                                    }
+                                                }
+                                                // Already shown, e.g. our own message whose local echo got its ts from chat.postMessage
                                                 let already_exists = pane.msg_data.iter().any(|m| m.ts == ts);
                                                 
                                                 if !already_exists {
-                                                    // Remove local echo if this is our own message
-                                                    if is_self {
-                                                        if let Some(pos) = pane.msg_data.iter().rposition(|m| {
-                                                            m.text == text 
-                                                            && m.is_outgoing
-                                                            && m.local_echo_id.is_some()
-                                                        }) {
-                                                            pane.msg_data.remove(pos);
-                                                        }
-                                                    }
-                                                    
                                                     let msg_data = crate::widgets::MessageData {
                                                         sender_name: user_name.clone(),
                                                         text: text.clone(),
@@ -685,17 +1203,28 @@ impl App {
                                                         reply_count: 0,
                                                         forwarded_text: forwarded.clone(),
                                                         mentions_me,
+                                                        matches_keyword: keyword_hit.is_some(),
                                                         local_echo_id: None,
                             is_edited: false,
                             is_deleted: false,
+                            is_system,
+                            is_bot,
                             media_type: media_type.clone(),
                             file_ids: file_ids.clone(),
                             file_urls: file_urls.clone(),
                             file_names: file_names.clone(),
+                            files: attachments.clone(),
                         };
                         pane.msg_data.push(msg_data);
+                                                    if mark_unread && pane.first_unread_ts.is_none() {
+                                                        pane.first_unread_ts = Some(ts.clone());
+                                                    }
                                                     pane.invalidate_cache();
-                                                    pane.scroll_offset = usize::MAX;
+                                                    if is_self {
+                                                        pane.scroll_to_bottom();
+                                                    } else {
+                                                        pane.follow_new_message(auto_scroll);
+                                                    }
                                                     seen_in_open_pane = true;
                                                 }
                                             }
@@ -712,23 +1241,10 @@ impl App {
                                                     parent.reply_count.saturating_add(1);
                                             }
                                         } else {
-                                            // Check if message already exists (by timestamp)
+                                            // Already shown, e.g. our own message whose local echo got its ts from chat.postMessage
                                             let already_exists = pane.msg_data.iter().any(|m| m.ts == ts);
                                             
                                             if !already_exists {
-                                                // Remove local echo if this is our own message
-                                                if is_self {
-                                                    // Find and remove the most recent local echo with matching text
-                                                    // that has a local_echo_id (to avoid removing old messages)
-                                                    if let Some(pos) = pane.msg_data.iter().rposition(|m| {
-                                                        m.text == text 
-                                                        && m.is_outgoing
-                                                        && m.local_echo_id.is_some()
-                                                    }) {
-                                                        pane.msg_data.remove(pos);
-                                                    }
-                                                }
-                                                
                                                 let msg_data = crate::widgets::MessageData {
                                                     sender_name: user_name.clone(),
                                                     text: text.clone(),
@@ -738,17 +1254,28 @@ impl App {
                                                     reply_count: 0,
                                                     forwarded_text: forwarded.clone(),
                                                     mentions_me,
+                                                    matches_keyword: keyword_hit.is_some(),
                                                     local_echo_id: None,
                             is_edited: false,
                             is_deleted: false,
+                            is_system,
+                            is_bot,
                             media_type: media_type.clone(),
                             file_ids: file_ids.clone(),
                             file_urls: file_urls.clone(),
                             file_names: file_names.clone(),
+                            files: attachments.clone(),
                         };
                         pane.msg_data.push(msg_data);
+                                                if mark_unread && pane.first_unread_ts.is_none() {
+                                                    pane.first_unread_ts = Some(ts.clone());
+                                                }
                                                 pane.invalidate_cache();
-                                                pane.scroll_offset = usize::MAX;
+                                                if is_self {
+                                                    pane.scroll_to_bottom();
+                                                } else {
+                                                    pane.follow_new_message(auto_scroll);
+                                                }
                                                 seen_in_open_pane = true;
                                             }
                                         }
@@ -758,19 +1285,81 @@ impl App {
                         }
                     }
 
+                    // Shown in a pane while the terminal has focus: no unread count, no notification
+                    let seen_by_user = seen_in_open_pane && self.terminal_focused;
+
                     // Mark channel as unread if it's not currently visible
                     if let Some(chat) = self.chats.iter_mut().find(|c| c.id == channel_id) {
-                        if seen_in_open_pane {
+                        if seen_by_user {
                             chat.unread = 0;
-                        } else if !is_self {
+                            chat.mentions = 0;
+                        } else if !is_self && !is_system {
                             chat.unread = chat.unread.saturating_add(1);
+                            if mentions_me {
+                                chat.mentions = chat.mentions.saturating_add(1);
+                            }
                         }
                     }
 
                     self.needs_redraw = true;
 
-                    // Send notification only when mentioned
-                    if self.show_notifications && !is_bot && !is_self && mentions_me {
+                    self.tee_message(&channel_id, &ts, &user_name, &text);
+                    if !is_self && !is_system {
+                        self.run_hooks(
+                            &channel_id,
+                            &ts,
+                            thread_ts.as_deref(),
+                            &user_name,
+                            &text,
+                            mentions_me,
+                            keyword_hit.as_deref(),
+                        );
+                    }
+
+                    // Screen readers follow the bottom line, so announce every message there
+                    if self.screen_reader && !is_self && !is_system {
+                        let chat_name = self
+                            .chats
+                            .iter()
+                            .find(|c| c.id == channel_id)
+                            .map(|c| c.name.clone())
+                            .unwrap_or_else(|| channel_id.clone());
+                        let place = if is_thread_reply { "thread in " } else { "" };
+                        self.set_status(&format!(
+                            "{} in {}{}: {}",
+                            user_name,
+                            place,
+                            chat_name,
+                            text.replace('\n', " ")
+                        ));
+                    }
+
+                    // Replies in followed threads that no open pane shows
+                    if is_thread_reply && !seen_in_open_pane && !is_self && !is_system {
+                        if let Some(followed) = self.followed_threads.find(&channel_id, &root_thread_ts) {
+                            let label = followed.label.clone();
+                            self.set_status(&format!("New reply in {}: {}: {}", label, user_name, text));
+                            let notified_below = mentions_me || (keyword_hit.is_some() && self.notify_on_keywords);
+                            if self.notifications_allowed() && !notified_below {
+                                self.notify(
+                                    &channel_id,
+                                    &label,
+                                    NotificationKind::Reply,
+                                    &format!("Slack: {} - New reply", label),
+                                    &format!("{}: {}", user_name, text),
+                                );
+                            }
+                        }
+                    }
+
+                    // Send notification only when mentioned (or a keyword matched, if enabled)
+                    let notify_keyword = keyword_hit.is_some() && self.notify_on_keywords;
+                    if self.notifications_allowed()
+                        && !is_bot
+                        && !is_self
+                        && !seen_by_user
+                        && (mentions_me || notify_keyword)
+                    {
                         let channel_name = self
                             .chats
                             .iter()
@@ -788,16 +1377,22 @@ impl App {
                         let title = channel_name;
                         
                         // Increment unread mention counter for current workspace
-                        let workspace_name = self.config.workspaces
-                            .get(self.config.active_workspace)
-                            .map(|w| w.name.clone())
-                            .unwrap_or_default();
-                        *self.unread_mentions.entry(workspace_name).or_insert(0) += 1;
+                        if mentions_me {
+                            let workspace_name = self.config.workspaces
+                                .get(self.config.active_workspace)
+                                .map(|w| w.name.clone())
+                                .unwrap_or_default();
+                            *self.unread_mentions.entry(workspace_name).or_insert(0) += 1;
+                        }
                         
-                        let _ = send_desktop_notification(
-                            &format!("Slack: {} - You were mentioned!", title),
-                            &format!("{}: {}", user_name, text),
-                        );
+                        let (kind, headline) = match keyword_hit {
+                            Some(ref keyword) if !mentions_me => (
+                                NotificationKind::Keyword,
+                                format!("Slack: {} - Keyword \"{}\"", title, keyword),
+                            ),
+                            _ => (NotificationKind::Mention, format!("Slack: {} - You were mentioned!", title)),
+                        };
+                        self.notify(&channel_id, &title, kind, &headline, &format!("{}: {}", user_name, text));
                     }
                 }
                 SlackUpdate::MessageChanged {
@@ -843,6 +1438,9 @@ impl App {
                     channel_id,
                     user_name,
                 } => {
+                    if self.screen_reader {
+                        continue;
+                    }
                     for pane in &mut self.panes {
                         if let Some(ref pane_channel_id) = pane.channel_id_str {
                             if pane_channel_id == &channel_id {
@@ -853,1861 +1451,6230 @@ impl App {
                     }
                     self.needs_redraw = true;
                 }
-            }
-        }
-
-        Ok(())
-    }
-
-    pub async fn refresh_chats(&mut self) -> Result<()> {
-        self.chats = self.slack.get_conversations().await?;
-        self.chats
-            .sort_by_key(|c| (c.section as u8, c.name.to_lowercase()));
-        if self.selected_chat_idx >= self.chats.len() {
-            self.selected_chat_idx = self.chats.len().saturating_sub(1);
+                SlackUpdate::ChannelAdded(chat) => {
+                    if !self.chats.iter().any(|c| c.id == chat.id) {
+                        let label = match chat.section {
+                            ChatSection::Public | ChatSection::Private => format!("#{}", chat.name),
+                            _ => chat.name.clone(),
+                        };
+                        self.chats.push(chat);
+                        self.sort_chats();
+                        self.set_status(&format!("New conversation: {}", label));
+                    }
+                }
+                SlackUpdate::ChannelRenamed { channel_id, name } => {
+                    self.rename_chat(&channel_id, name);
+                }
+                SlackUpdate::HuddleChanged { channel_id, participants } => {
+                    self.set_huddle(channel_id, participants);
+                }
+                SlackUpdate::MemberChanged { channel_id, user_id, joined } => {
+                    let is_group = self
+                        .chats
+                        .iter()
+                        .any(|c| c.id == channel_id && c.section == ChatSection::Group);
+                    if is_group {
+                        self.request_group_name(channel_id, Some((user_id, joined)));
+                    }
+                }
+                SlackUpdate::ChannelRemoved { channel_id } => {
+                    if let Some(pos) = self.chats.iter().position(|c| c.id == channel_id) {
+                        let chat = self.chats.remove(pos);
+                        if self.selected_chat_idx > pos {
+                            self.selected_chat_idx -= 1;
+                        }
+                        if self.selected_chat_idx >= self.chats.len() {
+                            self.selected_chat_idx = self.chats.len().saturating_sub(1);
+                        }
+                        self.set_status(&format!("Removed from sidebar: {}", chat.name));
+                    }
+                }
+                SlackUpdate::MessagePinned { channel_id, user_name, ts, text, event_ts, pinned } => {
+                    let preview = pin_preview(&text);
+                    for pane in &mut self.panes {
+                        if pane.channel_id_str.as_deref() != Some(channel_id.as_str()) || pane.thread_ts.is_some() {
+                            continue;
+                        }
+                        if pinned {
+                            pane.pinned_message = Some(preview.clone());
+                        } else if pane.pinned_message.as_deref() == Some(preview.as_str()) {
+                            pane.pinned_message = None;
+                        }
+                        // Workspaces that still post "pinned_item" messages already show one
+                        let pinned_at = event_ts.parse::<f64>().unwrap_or_default();
+                        let already_noted = pane.msg_data.iter().rev().take(5).any(|m| {
+                            m.is_system
+                                && m.text.contains("pinned")
+                                && (m.ts.parse::<f64>().unwrap_or_default() - pinned_at).abs() < 60.0
+                        });
+                        if pinned && !already_noted && !pane.msg_data.iter().any(|m| m.ts == event_ts) {
+                            pane.msg_data.push(MessageData {
+                                sender_name: user_name.clone(),
+                                text: format!("pinned a message: {}", preview),
+                                is_outgoing: false,
+                                ts: event_ts.clone(),
+                                reactions: Vec::new(),
+                                reply_count: 0,
+                                forwarded_text: None,
+                                mentions_me: false,
+                                matches_keyword: false,
+                                local_echo_id: None,
+                                is_edited: false,
+                                is_deleted: false,
+                                is_system: true,
+                                is_bot: false,
+                                media_type: None,
+                                file_ids: Vec::new(),
+                                file_urls: Vec::new(),
+                                file_names: Vec::new(),
+                                files: Vec::new(),
+                            });
+                            pane.invalidate_cache();
+                            pane.follow_new_message(auto_scroll);
+                        }
+                    }
+                    debug!("{} {} message {} in {}", user_name, if pinned { "pinned" } else { "unpinned" }, ts, channel_id);
+                    self.needs_redraw = true;
+                }
+                SlackUpdate::ChannelStarred { channel_id, starred } => {
+                    // Slack stars only mirror the local ones when sync_stars is on
+                    if !self.sync_stars || self.starred.contains(&channel_id) == starred {
+                        continue;
+                    }
+                    if starred {
+                        self.starred.channels.push(channel_id);
+                    } else {
+                        self.starred.channels.retain(|c| *c != channel_id);
+                    }
+                    if let Err(e) = self.starred.save(&self.config) {
+                        warn!("Saving starred channels failed: {}", e);
+                    }
+                    self.sort_chats();
+                    self.needs_redraw = true;
+                }
+            }
         }
-        self.set_status("Chats refreshed");
+
         Ok(())
     }
-    
-    async fn reload_pane_at(&mut self, pane_idx: usize) -> Result<()> {
-        if pane_idx >= self.panes.len() {
-            return Ok(());
+
+    /// Keep the sidebar order (section, then name) while the selection stays on the same chat.
+    fn sort_chats(&mut self) {
+        let selected_id = self.chats.get(self.selected_chat_idx).map(|c| c.id.clone());
+        sort_chat_list(&mut self.chats, &self.starred);
+        if let Some(id) = selected_id {
+            if let Some(idx) = self.chats.iter().position(|c| c.id == id) {
+                self.selected_chat_idx = idx;
+            }
         }
+    }
 
-        let (channel_id, thread_ts) = {
-            let pane = &self.panes[pane_idx];
-            (pane.channel_id_str.clone(), pane.thread_ts.clone())
+    /// Mirror an incoming message to every tee file configured for its channel.
+    fn tee_message(&mut self, channel_id: &str, ts: &str, user_name: &str, text: &str) {
+        let Some(workspace) = self.config.workspaces.get(self.config.active_workspace) else {
+            return;
         };
+        if workspace.tee.is_empty() {
+            return;
+        }
 
-        let Some(channel_id) = channel_id else {
-            return Ok(());
+        let channel_name = self
+            .chats
+            .iter()
+            .find(|c| c.id == channel_id)
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| channel_id.to_string());
+        let user_cache = &self.user_name_cache;
+        let resolve_user = |id: &str| -> String {
+            user_cache.get(id).cloned().unwrap_or_else(|| id.to_string())
         };
+        let plain_text = format_message_text(text, false, false, &resolve_user);
 
-        if let Some(thread_ts) = thread_ts {
-            match self.slack.get_thread_replies(&channel_id, &thread_ts, 100).await {
-                Ok(messages) => {
-                    let name_cache = self.user_name_cache.clone();
-                    let pane = &mut self.panes[pane_idx];
-                    pane.msg_data.clear();
-                    for slack_msg in &messages {
-                        let sender_name = if let Some(ref user_id) = slack_msg.user {
-                            name_cache.get(user_id).cloned().unwrap_or_else(|| user_id.clone())
-                        } else if let Some(ref bot_profile) = slack_msg.bot_profile {
-                            bot_profile.name.clone().unwrap_or_else(|| "Bot".to_string())
-                        } else if let Some(ref username) = slack_msg.username {
-                            username.clone()
-                        } else {
-                            "Unknown".to_string()
-                        };
-
-                        let (media_type, file_ids, file_urls, file_names) =
-                            detect_media_type(&slack_msg.files)
-                                .map(|(mt, ids, urls, names)| (Some(mt), ids, urls, names))
-                                .unwrap_or((None, Vec::new(), Vec::new(), Vec::new()));
-
-                        let msg_data = crate::widgets::MessageData {
-                            sender_name,
-                            text: slack_msg.text.clone(),
-                            is_outgoing: slack_msg.user.as_deref() == Some(&self.my_user_id),
-                            ts: slack_msg.ts.clone(),
-                            reactions: slack_msg
-                                .reactions
-                                .iter()
-                                .map(|r| (r.name.clone(), r.count))
-                                .collect(),
-                            reply_count: slack_msg.reply_count.unwrap_or(0),
-                            forwarded_text: None,
-                            mentions_me: false,
-                            local_echo_id: None,
-                            is_edited: false,
-                            is_deleted: false,
-                            media_type,
-                            file_ids,
-                            file_urls,
-                            file_names,
-                        };
-                        pane.msg_data.push(msg_data);
-                    }
-                    pane.invalidate_cache();
-                }
-                Err(_) => {}
+        let mut failed = None;
+        for rule in workspace.tee.iter().filter(|r| r.matches(channel_id, &channel_name)) {
+            if let Err(e) = append_tee_line(rule, &channel_name, ts, user_name, &plain_text) {
+                failed = Some(format!("Tee to {} failed: {}", rule.path.display(), e));
             }
-        } else {
-            match self.slack.get_conversation_history(&channel_id, 100).await {
-                Ok(messages) => {
-                    let name_cache = self.user_name_cache.clone();
-                    let pane = &mut self.panes[pane_idx];
-                    pane.msg_data.clear();
-                    for slack_msg in messages.iter().rev() {
-                        let sender_name = if let Some(ref user_id) = slack_msg.user {
-                            name_cache.get(user_id).cloned().unwrap_or_else(|| user_id.clone())
-                        } else if let Some(ref bot_profile) = slack_msg.bot_profile {
-                            bot_profile.name.clone().unwrap_or_else(|| "Bot".to_string())
-                        } else if let Some(ref username) = slack_msg.username {
-                            username.clone()
-                        } else {
-                            "Unknown".to_string()
-                        };
+        }
+        if let Some(msg) = failed {
+            self.set_status(&msg);
+        }
+    }
 
-                        let mentions_me =
-                            Self::message_mentions_user(&slack_msg.text, &self.my_user_id);
-                        let (media_type, file_ids, file_urls, file_names) =
-                            detect_media_type(&slack_msg.files)
-                                .map(|(mt, ids, urls, names)| (Some(mt), ids, urls, names))
-                                .unwrap_or((None, Vec::new(), Vec::new(), Vec::new()));
-
-                        let msg_data = crate::widgets::MessageData {
-                            sender_name,
-                            text: slack_msg.text.clone(),
-                            is_outgoing: slack_msg.user.as_deref() == Some(&self.my_user_id),
-                            ts: slack_msg.ts.clone(),
-                            reactions: slack_msg
-                                .reactions
-                                .iter()
-                                .map(|r| (r.name.clone(), r.count))
-                                .collect(),
-                            reply_count: slack_msg.reply_count.unwrap_or(0),
-                            forwarded_text: None,
-                            mentions_me,
-                            local_echo_id: None,
-                            is_edited: false,
-                            is_deleted: false,
-                            media_type,
-                            file_ids,
-                            file_urls,
-                            file_names,
-                        };
-                        pane.msg_data.push(msg_data);
-                    }
-                    pane.invalidate_cache();
-                }
-                Err(_) => {}
+    /// Run the configured hooks that match an incoming message.
+    #[allow(clippy::too_many_arguments)]
+    fn run_hooks(
+        &self,
+        channel_id: &str,
+        ts: &str,
+        thread_ts: Option<&str>,
+        user_name: &str,
+        text: &str,
+        mentions_me: bool,
+        keyword: Option<&str>,
+    ) {
+        if self.config.hooks.is_empty() {
+            return;
+        }
+        let chat = self.chats.iter().find(|c| c.id == channel_id);
+        let channel_name = chat.map(|c| c.name.clone()).unwrap_or_else(|| channel_id.to_string());
+        let is_dm = match chat {
+            Some(chat) => matches!(
+                chat.section,
+                ChatSection::DirectMessage | ChatSection::Group | ChatSection::Bot
+            ),
+            None => channel_id.starts_with('D'),
+        };
+
+        let mut payload = None;
+        for hook in &self.config.hooks {
+            let fires = match hook.on {
+                HookEvent::Mention => mentions_me || keyword.is_some(),
+                HookEvent::Dm => is_dm,
+                HookEvent::Message => true,
+            };
+            if !fires || !hook.matches(channel_id, &channel_name) {
+                continue;
             }
+            let payload = payload.get_or_insert_with(|| {
+                let resolve_user = |id: &str| -> String {
+                    self.user_name_cache.get(id).cloned().unwrap_or_else(|| id.to_string())
+                };
+                serde_json::json!({
+                    "event": "",
+                    "workspace": self
+                        .config
+                        .workspaces
+                        .get(self.config.active_workspace)
+                        .map(|w| w.name.as_str())
+                        .unwrap_or(""),
+                    "channel": channel_name,
+                    "channel_id": channel_id,
+                    "user": user_name,
+                    "text": format_message_text(text, false, false, &resolve_user),
+                    "ts": ts,
+                    "thread_ts": thread_ts,
+                    "mention": mentions_me,
+                    "keyword": keyword,
+                })
+            });
+            payload["event"] = serde_json::to_value(hook.on).unwrap_or_default();
+            run_hook(&hook.command, payload);
         }
+    }
 
-        Ok(())
+    /// Whether `method` already failed with missing_scope this session. If so, say which
+    /// scope `feature` needs instead of calling Slack again.
+    pub fn scope_unavailable(&mut self, method: &str, feature: &str) -> bool {
+        let Some(scope) = self.slack.missing_scope(method) else {
+            return false;
+        };
+        self.set_status(&format!(
+            "{} needs {} (add the scope to your Slack app and reinstall it, /doctor lists all)",
+            feature, scope
+        ));
+        true
     }
 
-    pub async fn maybe_run_fallback_refresh(&mut self) -> Result<()> {
-        let now = std::time::Instant::now();
-        let app_age_secs = now.duration_since(self.app_start_instant).as_secs();
-        let now_stale = if app_age_secs < REALTIME_STALE_SECS {
-            false
-        } else if let Some(last) = self.last_realtime_event_instant {
-            now.duration_since(last).as_secs() >= REALTIME_STALE_SECS
-        } else {
-            true
+    /// Set presence manually (`/away`, `/active`). Clears any auto-away state.
+    pub async fn set_presence(&mut self, away: bool) {
+        if self.scope_unavailable("users.setPresence", "Setting presence") {
+            return;
+        }
+        self.auto_away_active = false;
+        match self.slack.set_presence(away).await {
+            Ok(_) => self.set_status(if away { "Presence: away" } else { "Presence: active" }),
+            Err(e) => self.set_status(&format!("Failed to set presence: {}", e)),
+        }
+    }
+
+    /// End of the active DND snooze, if any.
+    fn dnd_end(&self) -> Option<chrono::DateTime<Local>> {
+        let until = self.dnd_until.filter(|&t| t > Local::now().timestamp())?;
+        Local.timestamp_opt(until, 0).single()
+    }
+
+    /// Desktop or terminal notifications are on and neither snoozed nor in quiet hours.
+    fn notifications_allowed(&self) -> bool {
+        (self.show_notifications || self.osc_notifications)
+            && self.dnd_end().is_none()
+            && !crate::utils::in_quiet_hours(&self.quiet_hours, Local::now().time())
+    }
+
+    /// Notify about a message in `channel_id`, unless that conversation is in its
+    /// cooldown; then it goes into the summary `flush_notifications` sends later.
+    fn notify(&mut self, channel_id: &str, label: &str, kind: NotificationKind, title: &str, body: &str) {
+        let notification = Notification {
+            title: title.to_string(),
+            body: body.to_string(),
         };
+        let now = std::time::Instant::now();
+        if let Some(notification) = self.notifier.push(channel_id, label, kind, notification, now) {
+            self.deliver_notification(&notification);
+        }
+    }
 
-        if now_stale && !self.realtime_was_stale {
-            self.realtime_was_stale = true;
-            self.set_status("Realtime stale; fallback refresh active");
-        } else if !now_stale && self.realtime_was_stale {
-            self.realtime_was_stale = false;
+    /// Send the summaries of conversations whose notification cooldown has ended.
+    pub fn flush_notifications(&mut self) {
+        let ready = self.notifier.flush(std::time::Instant::now());
+        if !ready.is_empty() && self.notifications_allowed() {
+            for notification in &ready {
+                self.deliver_notification(notification);
+            }
         }
+    }
 
-        if !now_stale {
-            return Ok(());
+    /// Show a notification the ways the settings ask for (desktop and/or OSC 9).
+    fn deliver_notification(&self, notification: &Notification) {
+        let Notification { title, body } = notification;
+        if self.show_notifications {
+            send_desktop_notification(title, body);
         }
+        if self.osc_notifications {
+            send_terminal_notification(&format!("{} - {}", title, body));
+        }
+    }
 
-        if now
-            .duration_since(self.last_fallback_refresh_instant)
-            .as_secs()
-            < FALLBACK_REFRESH_SECS
-        {
-            return Ok(());
+    /// Put the unread and mention counts in the terminal title, so window lists,
+    /// taskbars and tmux status lines show activity while the client is hidden.
+    pub fn update_terminal_title(&mut self) {
+        if !self.terminal_title {
+            return;
+        }
+        let unread: u32 = self.chats.iter().map(|c| c.unread).sum();
+        let mentions: u32 = self.unread_mentions.values().sum();
+        let workspace = self
+            .config
+            .workspaces
+            .get(self.config.active_workspace)
+            .map(|w| w.name.as_str())
+            .unwrap_or("");
+        let mut title = if workspace.is_empty() {
+            "Slack".to_string()
+        } else {
+            format!("Slack - {}", workspace)
+        };
+        match (unread, mentions) {
+            (0, 0) => {}
+            (unread, 0) => title = format!("({}) {}", unread, title),
+            (unread, mentions) => title = format!("({}, {}@) {}", unread, mentions, title),
+        }
+        if title != self.last_terminal_title {
+            set_terminal_title(&title);
+            self.last_terminal_title = title;
         }
+    }
 
-        self.last_fallback_refresh_instant = now;
-        self.last_fallback_refresh_at = Some(chrono::Local::now());
-        let pane_idx = self.focused_pane_idx;
-        let _ = self.reload_pane_at(pane_idx).await;
+    fn apply_dnd_info(&mut self, info: &crate::slack::DndInfo) {
+        self.dnd_until = info.snooze_endtime.filter(|_| info.snooze_enabled);
         self.needs_redraw = true;
-        Ok(())
     }
 
-    pub async fn reload_pane_contents(&mut self) -> Result<()> {
-        // Reload messages for all panes that have a channel set
-        for idx in 0..self.panes.len() {
-            let _ = self.reload_pane_at(idx).await;
+    /// Fetch our DND snooze state (at startup and after a workspace switch).
+    pub async fn refresh_dnd(&mut self) {
+        match self.slack.get_dnd_info().await {
+            Ok(info) => self.apply_dnd_info(&info),
+            Err(e) => {
+                debug!("dnd.info failed: {}", e);
+                self.dnd_until = None;
+            }
         }
-        Ok(())
     }
 
-    pub async fn open_selected_chat(&mut self) -> Result<()> {
-        self.ensure_valid_pane_idx();
-        if self.selected_chat_idx >= self.chats.len() {
-            return Ok(());
+    /// `/dnd <duration>` snoozes notifications, `/dnd off` ends the snooze, `/dnd` shows the state.
+    pub async fn set_dnd(&mut self, arg: Option<&str>) {
+        let method = match arg {
+            None => "dnd.info",
+            Some("off") => "dnd.endSnooze",
+            Some(_) => "dnd.setSnooze",
+        };
+        if self.scope_unavailable(method, "Do Not Disturb") {
+            return;
+        }
+        match arg {
+            None => {
+                self.refresh_dnd().await;
+                let quiet = if self.quiet_hours.is_empty() {
+                    String::new()
+                } else {
+                    format!(" (quiet hours {})", self.quiet_hours)
+                };
+                let status = match self.dnd_end() {
+                    Some(until) => format!("Do Not Disturb until {}{}", until.format("%H:%M"), quiet),
+                    None => format!("Do Not Disturb is off{}", quiet),
+                };
+                self.set_status(&status);
+            }
+            Some("off") => match self.slack.end_snooze().await {
+                Ok(()) => {
+                    self.dnd_until = None;
+                    self.set_status("Do Not Disturb ended");
+                }
+                Err(e) => self.set_status(&format!("Failed to end Do Not Disturb: {}", e)),
+            },
+            Some(duration) => {
+                let Some(minutes) = crate::utils::parse_duration_minutes(duration) else {
+                    self.set_status("Usage: /dnd <duration> (45m, 2h, 1h30m) | /dnd off");
+                    return;
+                };
+                match self.slack.set_snooze(minutes).await {
+                    Ok(info) => {
+                        self.apply_dnd_info(&info);
+                        let until = self.dnd_end().map(|t| t.format("%H:%M").to_string());
+                        self.set_status(&format!(
+                            "Do Not Disturb until {}",
+                            until.unwrap_or_else(|| format!("{} minutes from now", minutes))
+                        ));
+                    }
+                    Err(e) => self.set_status(&format!("Failed to snooze: {}", e)),
+                }
+            }
         }
+    }
 
-        let chat = self.chats[self.selected_chat_idx].clone();
-        let pane = &mut self.panes[self.focused_pane_idx];
+    /// Record keyboard activity; returns from auto-away on the first keypress.
+    pub async fn note_user_activity(&mut self) {
+        self.last_input_instant = std::time::Instant::now();
+        if self.auto_away_active {
+            self.auto_away_active = false;
+            if self.slack.set_presence(false).await.is_ok() {
+                self.set_status("Welcome back: presence set to active");
+            }
+        }
+    }
 
-        // Use string channel ID (Slack IDs are not numeric)
-        pane.chat_id = None;
-        pane.channel_id_str = Some(chat.id.clone());
-        pane.chat_name = chat.name.clone();
-        pane.username = chat.username.clone();
-        pane.thread_ts = None;
-        pane.msg_data.clear();
-        pane.invalidate_cache();
+    /// Go away automatically after `auto_away_minutes` without keyboard input.
+    pub async fn maybe_auto_away(&mut self) {
+        if self.auto_away_minutes == 0 || self.auto_away_active {
+            return;
+        }
+        let idle = self.last_input_instant.elapsed();
+        if idle < std::time::Duration::from_secs(self.auto_away_minutes * 60) {
+            return;
+        }
+        if self.slack.set_presence(true).await.is_ok() {
+            self.auto_away_active = true;
+        } else {
+            // Don't hammer the API every tick if it keeps failing
+            self.last_input_instant = std::time::Instant::now();
+        }
+    }
 
-        // Clear unread counter when opening the chat
-        if let Some(chat_info) = self.chats.get_mut(self.selected_chat_idx) {
-            chat_info.unread = 0;
+    /// Overlay `/nick` nicknames on the name cache and DM labels.
+    fn apply_nicknames(&mut self) {
+        for (user_id, nick) in &self.nicknames.map {
+            self.user_name_cache.insert(user_id.clone(), nick.clone());
         }
-        
-        // Clear mention counter for current workspace when opening any chat
-        let workspace_name = self.config.workspaces
-            .get(self.config.active_workspace)
-            .map(|w| w.name.clone())
-            .unwrap_or_default();
-        self.unread_mentions.insert(workspace_name, 0);
+        apply_nicknames_to_chats(&mut self.chats, &self.nicknames);
+    }
 
-        // Load messages (reduced from 500 to 100 for faster loading)
-        match self.slack.get_conversation_history(&chat.id, 100).await {
-            Ok(messages) => {
-                // Use the global user name cache instead of fetching names again
-                let name_cache = self.user_name_cache.clone();
-                
-                // Collect unique user IDs and bot IDs that we don't have cached yet
-                let mut users_to_fetch = Vec::new();
-                let mut bots_to_fetch = Vec::new();
-                
-                for slack_msg in &messages {
-                    if let Some(ref uid) = slack_msg.user {
-                        if !name_cache.contains_key(uid) {
-                            users_to_fetch.push(uid.clone());
-                        }
-                    }
-                    if let Some(ref bot_id) = slack_msg.bot_id {
-                        if !name_cache.contains_key(bot_id) {
-                            bots_to_fetch.push(bot_id.clone());
-                        }
-                    }
-                }
-                
-                // Fetch names in parallel for better performance
-                let mut fetch_tasks = Vec::new();
-                for uid in users_to_fetch {
-                    let slack = self.slack.clone();
-                    fetch_tasks.push(tokio::spawn(async move {
-                        (uid.clone(), slack.resolve_user_name(&uid).await)
-                    }));
-                }
-                for bot_id in bots_to_fetch {
-                    let slack = self.slack.clone();
-                    fetch_tasks.push(tokio::spawn(async move {
-                        (bot_id.clone(), slack.resolve_bot_name(&bot_id).await)
-                    }));
-                }
-                
-                // Wait for all fetches to complete and update cache
-                for task in fetch_tasks {
-                    if let Ok((id, name)) = task.await {
-                        self.user_name_cache.insert(id, name);
-                    }
+    /// `/nick @user [nickname]`: set (or with no nickname, clear) a local nickname.
+    pub async fn set_nickname(&mut self, who: &str, nickname: Option<&str>) {
+        let Some(user_id) = self.find_user_id(who) else {
+            self.set_status(&format!("Unknown user: {}", who));
+            return;
+        };
+        let old_name = self
+            .user_name_cache
+            .get(&user_id)
+            .cloned()
+            .unwrap_or_else(|| user_id.clone());
+        let new_name = match nickname {
+            Some(nick) => {
+                self.nicknames.map.insert(user_id.clone(), nick.to_string());
+                nick.to_string()
+            }
+            None => {
+                if self.nicknames.map.remove(&user_id).is_none() {
+                    self.set_status(&format!("{} has no nickname", old_name));
+                    return;
                 }
-                
-                let name_cache = &self.user_name_cache;
+                self.slack.resolve_user_name(&user_id).await
+            }
+        };
 
-                for slack_msg in messages.iter().rev() {
-                    // Try to get sender name from user, bot_profile, username, or bot_id
-                    let sender_name = if let Some(ref user_id) = slack_msg.user {
-                        name_cache
-                            .get(user_id)
-                            .cloned()
-                            .unwrap_or_else(|| user_id.clone())
-                    } else if let Some(ref bot_profile) = slack_msg.bot_profile {
-                        // For Slack apps/webhooks, bot_profile.name contains the display name
-                        bot_profile.name.clone().unwrap_or_else(|| "Bot".to_string())
-                    } else if let Some(ref username) = slack_msg.username {
-                        // Fallback to username field
-                        username.clone()
-                    } else if let Some(ref bot_id) = slack_msg.bot_id {
-                        // Fallback to bot_id lookup
-                        name_cache
-                            .get(bot_id)
-                            .cloned()
-                            .unwrap_or_else(|| bot_id.clone())
-                    } else {
-                        "Unknown".to_string()
-                    };
-                    let reactions: Vec<(String, u32)> = slack_msg
-                        .reactions
-                        .iter()
-                        .map(|r| (r.name.clone(), r.count))
-                        .collect();
-                    let mentions_me = Self::message_mentions_user(&slack_msg.text, &self.my_user_id);
-                    let (media_type, file_ids, file_urls, file_names) = detect_media_type(&slack_msg.files)
-                        .map(|(mt, ids, urls, names)| (Some(mt), ids, urls, names))
-                        .unwrap_or((None, Vec::new(), Vec::new(), Vec::new()));
-                    let msg_data = crate::widgets::MessageData {
-                        sender_name,
-                        text: slack_msg.text.clone(),
-                        is_outgoing: slack_msg.user.as_deref() == Some(&self.my_user_id),
-                        ts: slack_msg.ts.clone(),
-                        reactions,
-                        reply_count: slack_msg.reply_count.unwrap_or(0),
-                        forwarded_text: forwarded_preview(&slack_msg.attachments),
-                        mentions_me,
-                        local_echo_id: None,
-                            is_edited: false,
-                            is_deleted: false,
-                            media_type,
-                            file_ids,
-                            file_urls,
-                            file_names,
-                        };
-                        pane.msg_data.push(msg_data);
-                }
+        // Loaded messages and DM labels carry the old name
+        self.user_name_cache.insert(user_id.clone(), new_name.clone());
+        for pane in &mut self.panes {
+            for msg in pane.msg_data.iter_mut().filter(|m| m.sender_name == old_name) {
+                msg.sender_name = new_name.clone();
             }
-            Err(e) => {
-                self.set_status(&format!("Failed to load messages: {}", e));
+            pane.invalidate_cache();
+        }
+        for chat in &mut self.chats {
+            if matches!(chat.section, ChatSection::DirectMessage | ChatSection::Bot)
+                && chat.username.as_deref() == Some(user_id.as_str())
+            {
+                chat.name = new_name.clone();
             }
         }
+        self.sort_chats();
 
-        // Sync user name cache
-        self.user_name_cache = self.slack.get_user_name_cache().await;
+        let status = match self.nicknames.save(&self.config) {
+            Ok(()) if nickname.is_some() => format!("{} is now shown as {}", old_name, new_name),
+            Ok(()) => format!("Nickname cleared, showing {}", new_name),
+            Err(e) => format!("Nickname changed but not saved: {}", e),
+        };
+        self.set_status(&status);
+    }
 
-        // Auto-scroll to bottom
-        self.panes[self.focused_pane_idx].scroll_offset = usize::MAX;
-        self.focus_on_chat_list = false;
+    pub async fn refresh_chats(&mut self) -> Result<()> {
+        let chats = self.slack.get_conversations().await?;
+        self.update_chats(chats);
+        self.set_status("Chats refreshed");
         Ok(())
     }
 
-    pub async fn open_thread(
-        &mut self,
-        channel_id_str: &str,
-        thread_ts: &str,
-        parent_user: &str,
-    ) -> Result<()> {
-        // Create new pane for thread
-        let new_idx = self.panes.len();
-        let mut thread_pane = ChatPane::new();
-        thread_pane.channel_id_str = Some(channel_id_str.to_string());
-        thread_pane.thread_ts = Some(thread_ts.to_string());
-        thread_pane.chat_name = format!("Thread: {}", parent_user);
-        self.panes.push(thread_pane);
+    /// Record that `channel_id` has a huddle with `participants` people, or none.
+    fn set_huddle(&mut self, channel_id: String, participants: Option<usize>) {
+        let changed = match participants {
+            Some(count) => self.huddles.insert(channel_id, count) != Some(count),
+            None => self.huddles.remove(&channel_id).is_some(),
+        };
+        self.needs_redraw |= changed;
+    }
 
-        // Check if the focused pane is already a thread
-        let focused_pane = &self.panes[self.focused_pane_idx];
-        let is_already_thread = focused_pane.thread_ts.is_some();
-        
-        // Check if there are any existing thread panes open
-        let existing_thread_pane_idx = self.panes.iter()
-            .enumerate()
-            .find(|(_, p)| p.thread_ts.is_some())
-            .map(|(idx, _)| idx);
-        
-        if is_already_thread {
-            // If we're already in a thread view, split the focused thread pane horizontally
-            // This will create a horizontal split within the thread area
-            if !self.pane_tree.split_pane_with_ratio(self.focused_pane_idx, SplitDirection::Horizontal, new_idx, 50) {
-                // If split failed (e.g., pane is deeply nested), try fallback
-                // Fallback: split root horizontally (less ideal but should work)
-                self.pane_tree.split_with_ratio(SplitDirection::Horizontal, new_idx, 50);
-            }
-        } else if let Some(thread_pane_idx) = existing_thread_pane_idx {
-            // If we're in a regular channel but there's already a thread open,
-            // split that existing thread pane horizontally (new thread goes under it)
-            if !self.pane_tree.split_pane_with_ratio(thread_pane_idx, SplitDirection::Horizontal, new_idx, 50) {
-                // Fallback: try splitting the focused pane vertically
-                if !self.pane_tree.split_pane_with_ratio(self.focused_pane_idx, SplitDirection::Vertical, new_idx, 33) {
-                    self.pane_tree.split_with_ratio(SplitDirection::Vertical, new_idx, 33);
-                }
-            }
-        } else {
-            // If we're in a regular channel and no threads are open, split vertically (thread takes 1/3)
-            if !self.pane_tree.split_pane_with_ratio(self.focused_pane_idx, SplitDirection::Vertical, new_idx, 33) {
-                // Fallback if split failed
-                self.pane_tree.split_with_ratio(SplitDirection::Vertical, new_idx, 33);
+    /// Show a conversation under a new name, in the sidebar and in the panes showing it.
+    fn rename_chat(&mut self, channel_id: &str, name: String) {
+        if let Some(chat) = self.chats.iter_mut().find(|c| c.id == channel_id) {
+            chat.name = name.clone();
+        }
+        for pane in &mut self.panes {
+            if pane.channel_id_str.as_deref() == Some(channel_id) && pane.thread_ts.is_none() {
+                pane.chat_name = name.clone();
             }
         }
-        
-        // Focus the new thread pane
-        self.focused_pane_idx = new_idx;
+        self.sort_chats();
+        self.needs_redraw = true;
+    }
 
-        // Load thread replies
-        match self
-            .slack
-            .get_thread_replies(channel_id_str, thread_ts, 100)
-            .await
-        {
-            Ok(messages) => {
-                let mut name_cache: std::collections::HashMap<String, String> =
-                    std::collections::HashMap::new();
-                for slack_msg in &messages {
-                    if let Some(ref uid) = slack_msg.user {
-                        if !name_cache.contains_key(uid) {
-                            let name = self.slack.resolve_user_name(uid).await;
-                            name_cache.insert(uid.clone(), name);
-                        }
-                    }
-                    if let Some(ref bot_id) = slack_msg.bot_id {
-                        if !name_cache.contains_key(bot_id) {
-                            let name = self.slack.resolve_bot_name(bot_id).await;
-                            name_cache.insert(bot_id.clone(), name);
-                        }
+    /// Recompute a group DM's name in the background (`ApiEvent::GroupRenamed`): after a
+    /// member joined or left, or with fresh members when `change` is None.
+    fn request_group_name(&self, channel_id: String, change: Option<(String, bool)>) {
+        let slack = self.slack.clone();
+        let tx = self.api_tx.clone();
+        tokio::spawn(async move {
+            let updated = match change {
+                Some((user_id, joined)) => slack.group_member_changed(&channel_id, &user_id, joined).await,
+                None => None,
+            };
+            let name = match updated {
+                Some(name) => name,
+                None => match slack.group_dm_name(&channel_id, true).await {
+                    Ok(name) => name,
+                    Err(e) => {
+                        warn!("Refreshing group DM {} failed: {}", channel_id, e);
+                        return;
                     }
-                }
+                },
+            };
+            if !name.is_empty() {
+                let _ = tx.send(ApiEvent::GroupRenamed { channel_id, name });
+            }
+        });
+    }
 
-                let pane = &mut self.panes[new_idx];
-                for slack_msg in &messages {
-                    // Try to get sender name from user, bot_profile, username, or bot_id
-                    let sender_name = if let Some(ref user_id) = slack_msg.user {
-                        name_cache
-                            .get(user_id)
-                            .cloned()
-                            .unwrap_or_else(|| user_id.clone())
-                    } else if let Some(ref bot_profile) = slack_msg.bot_profile {
-                        // For Slack apps/webhooks, bot_profile.name contains the display name
-                        bot_profile.name.clone().unwrap_or_else(|| "Bot".to_string())
-                    } else if let Some(ref username) = slack_msg.username {
-                        // Fallback to username field
-                        username.clone()
-                    } else if let Some(ref bot_id) = slack_msg.bot_id {
-                        // Fallback to bot_id lookup
-                        name_cache
-                            .get(bot_id)
-                            .cloned()
-                            .unwrap_or_else(|| bot_id.clone())
-                    } else {
-                        "Unknown".to_string()
-                    };
-                    let reactions: Vec<(String, u32)> = slack_msg
-                        .reactions
-                        .iter()
-                        .map(|r| (r.name.clone(), r.count))
-                        .collect();
-                    let mentions_me = Self::message_mentions_user(&slack_msg.text, &self.my_user_id);
-                    let (media_type, file_ids, file_urls, file_names) = detect_media_type(&slack_msg.files)
-                        .map(|(mt, ids, urls, names)| (Some(mt), ids, urls, names))
-                        .unwrap_or((None, Vec::new(), Vec::new(), Vec::new()));
-                    let msg_data = crate::widgets::MessageData {
-                        sender_name,
-                        text: slack_msg.text.clone(),
-                        is_outgoing: slack_msg.user.as_deref() == Some(&self.my_user_id),
-                        ts: slack_msg.ts.clone(),
-                        reactions,
-                        reply_count: 0,
-                        forwarded_text: forwarded_preview(&slack_msg.attachments),
-                        mentions_me,
-                        local_echo_id: None,
-                            is_edited: false,
-                            is_deleted: false,
-                            media_type,
-                            file_ids,
-                            file_urls,
-                            file_names,
-                        };
-                        pane.msg_data.push(msg_data);
+    /// Fetch the conversation list in the background (`ApiEvent::Chats`).
+    /// Load all users of the workspace in the background (one paged users.list instead
+    /// of a users.info call per unknown author).
+    fn request_users(&self) {
+        let slack = self.slack.clone();
+        let tx = self.api_tx.clone();
+        tokio::spawn(async move {
+            let result = match slack.prefetch_users().await {
+                Ok(count) => {
+                    debug!("Prefetched {} users", count);
+                    Ok((slack.user_profiles().await, slack.get_user_name_cache().await))
                 }
-            }
-            Err(e) => {
-                self.set_status(&format!("Failed to load thread: {}", e));
+                Err(e) => Err(e.to_string()),
+            };
+            let _ = tx.send(ApiEvent::Users(result));
+        });
+    }
+
+    pub fn request_chats(&self) {
+        let slack = self.slack.clone();
+        let tx = self.api_tx.clone();
+        tokio::spawn(async move {
+            let result = slack.get_conversations().await.map_err(|e| e.to_string());
+            let _ = tx.send(ApiEvent::Chats(result));
+        });
+    }
+
+    /// Replace the sidebar with a fresh list, keeping the selection on the same chat.
+    /// Returns how many chats were added and removed.
+    fn update_chats(&mut self, mut chats: Vec<ChatInfo>) -> (usize, usize) {
+        let added = chats
+            .iter()
+            .filter(|c| !self.chats.iter().any(|old| old.id == c.id))
+            .count();
+        let removed = self
+            .chats
+            .iter()
+            .filter(|old| !chats.iter().any(|c| c.id == old.id))
+            .count();
+        apply_nicknames_to_chats(&mut chats, &self.nicknames);
+        // conversations.list has no mention counts: keep ours while the chat is still unread.
+        // Posting restrictions learned from a refused message are kept too.
+        for chat in chats.iter_mut() {
+            if let Some(old) = self.chats.iter().find(|old| old.id == chat.id) {
+                chat.mentions = old.mentions.min(chat.unread);
+                chat.posting_restricted |= old.posting_restricted;
             }
         }
+        let selected_id = self.chats.get(self.selected_chat_idx).map(|c| c.id.clone());
+        self.chats = chats;
+        sort_chat_list(&mut self.chats, &self.starred);
+        self.selected_chat_idx = selected_id
+            .and_then(|id| self.chats.iter().position(|c| c.id == id))
+            .unwrap_or(self.selected_chat_idx)
+            .min(self.chats.len().saturating_sub(1));
+        self.needs_redraw = true;
+        (added, removed)
+    }
 
-        // Sync user name cache
-        self.user_name_cache = self.slack.get_user_name_cache().await;
-
-        // Auto-scroll to bottom
-        self.panes[new_idx].scroll_offset = usize::MAX;
-        self.focused_pane_idx = new_idx;
-        self.focus_on_chat_list = false;
-        Ok(())
+    /// Write the sidebar and user names for the next start (`SessionCache`).
+    pub fn save_cache(&self) -> Result<()> {
+        // Open panes that have nothing loaded (e.g. offline) keep their cached messages
+        let mut cached = SessionCache::load(&self.config).messages;
+        let mut messages = std::collections::HashMap::new();
+        for pane in &self.panes {
+            let Some(channel_id) = pane.channel_id_str.as_deref() else {
+                continue;
+            };
+            let key = SessionCache::key(channel_id, pane.thread_ts.as_deref());
+            let sent: Vec<&MessageData> = pane.msg_data.iter().filter(|m| m.local_echo_id.is_none()).collect();
+            let newest = sent[sent.len().saturating_sub(CACHED_MESSAGES)..].iter().map(|m| (*m).clone());
+            match cached.remove(&key) {
+                Some(old) if sent.is_empty() => messages.insert(key, old),
+                _ => messages.insert(key, newest.collect()),
+            };
+        }
+        SessionCache {
+            chats: self.chats.clone(),
+            user_names: self.user_name_cache.clone(),
+            my_user_id: self.my_user_id.clone(),
+            group_members: self.slack.group_members(),
+            messages,
+            outbox: self.outbox.clone(),
+        }
+        .save(&self.config)
+    }
+    
+    fn reload_pane_at(&mut self, pane_idx: usize) {
+        let Some(pane) = self.panes.get(pane_idx) else {
+            return;
+        };
+        if let Some(channel_id) = pane.channel_id_str.clone() {
+            let thread_ts = pane.thread_ts.clone();
+            self.request_history(channel_id, thread_ts, None);
+        }
     }
 
-    /// Convert @username mentions to Slack's <@USER_ID> format
-    fn convert_mentions_to_ids(&self, text: &str) -> String {
-        let mut result = text.to_string();
-        
-        // Build a reverse lookup map: name -> user_id
-        let mut name_to_id: std::collections::HashMap<String, String> = std::collections::HashMap::new();
-        for (user_id, user_name) in &self.user_name_cache {
-            name_to_id.insert(user_name.to_lowercase(), user_id.clone());
+    /// Load a channel's or thread's messages in the background; every pane showing it
+    /// is marked as loading until `ApiEvent::History` arrives.
+    fn request_history(&mut self, channel_id: String, thread_ts: Option<String>, unread: Option<u32>) {
+        for pane in &mut self.panes {
+            if pane.channel_id_str.as_deref() == Some(channel_id.as_str()) && pane.thread_ts == thread_ts {
+                pane.loading = true;
+            }
         }
-        
-        // Find all @mentions in the text
-        let mut offset = 0;
-        while let Some(at_pos) = result[offset..].find('@') {
-            let abs_pos = offset + at_pos;
-            let after_at = &result[abs_pos + 1..];
-            
-            // Find the end of the mention (space, punctuation, or end of string)
-            let mention_end = after_at
-                .find(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
-                .unwrap_or(after_at.len());
-            
-            if mention_end > 0 {
-                let mention_name = &after_at[..mention_end];
-                let mention_lower = mention_name.to_lowercase();
-                
-                // Look up the user ID
-                if let Some(user_id) = name_to_id.get(&mention_lower) {
-                    // Replace @username with <@USER_ID>
-                    let replacement = format!("<@{}>", user_id);
-                    result.replace_range(abs_pos..abs_pos + 1 + mention_end, &replacement);
-                    offset = abs_pos + replacement.len();
+        let slack = self.slack.clone();
+        let known: std::collections::HashSet<String> = self.user_name_cache.keys().cloned().collect();
+        let tx = self.api_tx.clone();
+        tokio::spawn(async move {
+            let result = fetch_history(&slack, &channel_id, thread_ts.as_deref(), &known)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(ApiEvent::History { channel_id, thread_ts, unread, result });
+        });
+    }
+
+    /// Apply the result of a spawned Slack call.
+    pub fn handle_api_event(&mut self, event: ApiEvent) {
+        match event {
+            ApiEvent::Sent { message, result: Ok(ts), .. } => {
+                let Some(local_echo_id) = message.local_echo_id else {
+                    return;
+                };
+                let pane = self
+                    .panes
+                    .iter_mut()
+                    .find(|p| p.msg_data.iter().any(|m| m.local_echo_id == Some(local_echo_id)));
+                // The echo takes the real ts; the copy coming back via events is then skipped
+                if let Some(pane) = pane {
+                    pane.confirm_local_echo(local_echo_id, &ts, &message.text);
+                }
+            }
+            ApiEvent::Sent { message, result: Err(e), retry: true, .. } => {
+                // The echo stays pending; the outbox is sent again once Slack is reachable
+                self.outbox.push(message);
+                self.send_queue = None;
+                self.last_online_attempt = std::time::Instant::now();
+                self.set_status(&format!("Not sent: {} ({} queued to send again)", e, self.outbox.len()));
+            }
+            ApiEvent::Sent { message, result: Err(e), restricted, .. } => {
+                let reason = if restricted {
+                    if let Some(chat) = self.chats.iter_mut().find(|c| c.id == message.channel_id) {
+                        chat.posting_restricted = true;
+                    }
+                    "only some members can post in this channel; you can still reply in threads".to_string()
                 } else {
-                    offset = abs_pos + 1;
+                    e
+                };
+                self.return_unsent(message);
+                self.set_status(&format!("Failed to send: {} (the text is back in the composer)", reason));
+            }
+            ApiEvent::Chats(Ok(chats)) => {
+                let (added, removed) = self.update_chats(chats);
+                if added > 0 || removed > 0 {
+                    self.set_status(&format!("Sidebar updated: {} new, {} removed", added, removed));
+                }
+            }
+            ApiEvent::Chats(Err(e)) => self.set_status(&format!("Failed to refresh chats: {}", e)),
+            ApiEvent::Users(Ok((profiles, names))) => {
+                self.member_profiles = profiles;
+                self.user_name_cache.extend(names);
+                self.apply_nicknames();
+            }
+            ApiEvent::Users(Err(e)) => warn!("users.list prefetch failed, falling back to users.info: {}", e),
+            ApiEvent::TokenRefreshed { workspace, result } => {
+                let Some(idx) = self.config.workspaces.iter().position(|ws| ws.name == workspace) else {
+                    return;
+                };
+                match result {
+                    Ok(rotated) => {
+                        self.token_refreshes.remove(&workspace);
+                        self.token_refresh_failed.remove(&workspace);
+                        if idx == self.config.active_workspace {
+                            self.slack.set_token(&rotated.access_token);
+                            self.flush_outbox();
+                        }
+                        let saved = self.config.set_rotated_token(
+                            idx,
+                            &rotated.access_token,
+                            &rotated.refresh_token,
+                            rotated.expires_at,
+                        );
+                        match saved {
+                            Ok(()) => debug!("Refreshed the token of {}", workspace),
+                            Err(e) => warn!("Refreshed the token of {} but saving it failed: {}", workspace, e),
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Refreshing the token of {} failed: {}", workspace, e);
+                        self.token_refresh_failed.insert(workspace.clone());
+                        self.set_status(&format!("Refreshing the token of {} failed: {}", workspace, e));
+                    }
+                }
+            }
+            ApiEvent::GroupRenamed { channel_id, name } => {
+                let changed = self.chats.iter().any(|c| c.id == channel_id && c.name != name);
+                if changed {
+                    self.rename_chat(&channel_id, name);
+                }
+            }
+            ApiEvent::History { channel_id, thread_ts, unread, result } => {
+                let (messages, names) = match result {
+                    Ok(loaded) => loaded,
+                    Err(e) => {
+                        let cached = if self.offline {
+                            let key = SessionCache::key(&channel_id, thread_ts.as_deref());
+                            SessionCache::load(&self.config).messages.remove(&key)
+                        } else {
+                            None
+                        };
+                        for pane in &mut self.panes {
+                            if pane.channel_id_str.as_deref() == Some(channel_id.as_str()) && pane.thread_ts == thread_ts {
+                                pane.loading = false;
+                                if let Some(cached) = &cached {
+                                    pane.msg_data = cached.clone();
+                                    pane.invalidate_cache();
+                                    pane.scroll_to_bottom();
+                                }
+                            }
+                        }
+                        match (self.offline, cached.is_some()) {
+                            (true, true) => self.set_status("Offline: showing cached messages"),
+                            (true, false) => self.set_status("Offline: no cached messages for this conversation"),
+                            _ => self.set_status(&format!("Failed to load messages: {}", e)),
+                        }
+                        return;
+                    }
+                };
+                if !names.is_empty() {
+                    self.user_name_cache.extend(names);
+                    self.apply_nicknames();
+                }
+                if thread_ts.is_none() {
+                    // The newest huddle message says whether a call is going on now
+                    let huddle = messages.iter().find_map(|m| m.room.as_ref());
+                    self.set_huddle(channel_id.clone(), huddle.and_then(|r| r.live_participants()));
+                }
+                // History comes newest first, thread replies oldest first
+                let msg_data: Vec<MessageData> = if thread_ts.is_some() {
+                    messages.iter().map(|m| self.message_from_slack(m)).collect()
+                } else {
+                    messages.iter().rev().map(|m| self.message_from_slack(m)).collect()
+                };
+                for pane in &mut self.panes {
+                    if pane.channel_id_str.as_deref() != Some(channel_id.as_str()) || pane.thread_ts != thread_ts {
+                        continue;
+                    }
+                    pane.loading = false;
+                    pane.msg_data = msg_data.clone();
+                    pane.invalidate_cache();
+                    if let Some(unread) = unread {
+                        // The last `unread` messages are the ones we hadn't seen
+                        if unread > 0 && !pane.msg_data.is_empty() {
+                            let first = pane.msg_data.len().saturating_sub(unread as usize);
+                            pane.first_unread_ts = Some(pane.msg_data[first].ts.clone());
+                        }
+                        pane.scroll_to_bottom();
+                    }
                 }
-            } else {
-                offset = abs_pos + 1;
             }
         }
-        
-        result
+        self.needs_redraw = true;
     }
 
-    pub async fn send_message(&mut self) -> Result<()> {
-        self.ensure_valid_pane_idx();
-        let pane_idx = self.focused_pane_idx;
-        let input = self.panes[pane_idx].input_buffer.trim().to_string();
-
-        if input.is_empty() {
-            return Ok(());
-        }
-
-        // Check if it's a command
-        if input.starts_with('/') {
-            let mut handler = CommandHandler::new();
-            handler.handle_command(self, &input).await?;
-            // After handle_command, pane_idx might be invalid if workspace was switched
-            // Use focused_pane_idx which is always kept valid by ensure_valid_pane_idx
-            self.ensure_valid_pane_idx();
-            let idx = self.focused_pane_idx;
-            self.panes[idx].input_buffer.clear();
-            self.panes[idx].input_cursor = 0;
-            self.panes[idx].tab_complete_state = None;
-            return Ok(());
+    /// Convert a message from history or thread replies for display.
+    fn message_from_slack(&self, slack_msg: &SlackMessage) -> MessageData {
+        let name_cache = &self.user_name_cache;
+        // Try to get sender name from user, bot_profile, username, or bot_id
+        let sender_name = if let Some(ref user_id) = slack_msg.user {
+            name_cache
+                .get(user_id)
+                .cloned()
+                .unwrap_or_else(|| user_id.clone())
+        } else if let Some(ref bot_profile) = slack_msg.bot_profile {
+            // For Slack apps/webhooks, bot_profile.name contains the display name
+            bot_profile.name.clone().unwrap_or_else(|| "Bot".to_string())
+        } else if let Some(ref username) = slack_msg.username {
+            username.clone()
+        } else if let Some(ref bot_id) = slack_msg.bot_id {
+            name_cache
+                .get(bot_id)
+                .cloned()
+                .unwrap_or_else(|| bot_id.clone())
+        } else {
+            "Unknown".to_string()
+        };
+        let mentions_me = !is_system_message(slack_msg)
+            && text_mentions_me(&slack_msg.text, &self.my_user_id, &self.my_usergroups);
+        let matches_keyword = !is_system_message(slack_msg)
+            && slack_msg.user.as_deref() != Some(&self.my_user_id)
+            && find_keyword(&slack_msg.text, &self.highlight_keywords).is_some();
+        let (media_type, file_ids, file_urls, file_names, attachments) = detect_media_type(&slack_msg.files);
+        MessageData {
+            sender_name,
+            text: slack_msg.text.clone(),
+            is_outgoing: slack_msg.user.as_deref() == Some(&self.my_user_id),
+            ts: slack_msg.ts.clone(),
+            reactions: slack_msg
+                .reactions
+                .iter()
+                .map(|r| (r.name.clone(), r.count))
+                .collect(),
+            reply_count: slack_msg.reply_count.unwrap_or(0),
+            forwarded_text: forwarded_preview(&slack_msg.attachments),
+            mentions_me,
+            matches_keyword,
+            local_echo_id: None,
+            is_edited: false,
+            is_deleted: false,
+            is_system: is_system_message(slack_msg),
+            is_bot: is_bot_message(slack_msg),
+            media_type,
+            file_ids,
+            file_urls,
+            file_names,
+            files: attachments,
         }
+    }
 
-        let channel_id_str = self.panes[pane_idx].channel_id_str.clone();
-        let thread_ts = self.panes[pane_idx].thread_ts.clone();
-        if let Some(channel_id) = channel_id_str {
-            // Convert @username mentions to <@USER_ID> format
-            let message_to_send = self.convert_mentions_to_ids(&input);
-            
-            // Local echo: Add message immediately to UI (with original text)
-            let my_name = self.user_name_cache.get(&self.my_user_id)
-                .cloned()
-                .unwrap_or_else(|| "You".to_string());
-            
-            let local_echo_id = self.next_local_echo_id;
-            self.next_local_echo_id += 1;
-            
-            let local_msg = crate::widgets::MessageData {
-                sender_name: my_name,
-                text: input.clone(),
-                is_outgoing: true,
-                ts: format!("{}.local.{}", chrono::Local::now().timestamp(), local_echo_id),
-                reactions: Vec::new(),
-                reply_count: 0,
-                forwarded_text: None,
-                mentions_me: false,
-                local_echo_id: Some(local_echo_id),
-                is_edited: false,
-                is_deleted: false,
-                media_type: None,
-                file_ids: Vec::new(),
-                file_urls: Vec::new(),
-                file_names: Vec::new(),
-            };
-            
-            self.panes[pane_idx].msg_data.push(local_msg);
-            self.panes[pane_idx].invalidate_cache();
-            self.panes[pane_idx].scroll_offset = usize::MAX;
-            self.needs_redraw = true;
-            
-            // Clear input immediately
-            self.input_history.push(input.clone());
-            self.panes[pane_idx].input_buffer.clear();
-            self.panes[pane_idx].input_cursor = 0;
-            self.panes[pane_idx].tab_complete_state = None;
-            
-            // Send to Slack with converted mentions
-            match self
-                .slack
-                .send_message(&channel_id, &message_to_send, thread_ts.as_deref())
-                .await
-            {
-                Ok(_) => {
-                    // Message sent successfully, the real message will come via WebSocket
-                    // and replace the local echo (or we can keep it since it has .local timestamp)
+    /// Redraw when the Socket Mode connection state changed.
+    pub fn poll_connection_state(&mut self) {
+        if self.connection_state.has_changed().unwrap_or(false) {
+            let state = self.connection_state.borrow_and_update().clone();
+            match state {
+                ConnectionState::Reconnecting { reason, attempt: 0, .. } => {
+                    self.set_status(&format!("Connection lost ({}), reconnecting...", reason));
+                }
+                ConnectionState::Reconnecting { reason, attempt, retry_at, .. } => {
+                    self.set_status(&format!(
+                        "Connection lost ({}), retry {} at {} (/reconnect to try now)",
+                        reason,
+                        attempt + 1,
+                        retry_at.format("%H:%M:%S")
+                    ));
                 }
-                Err(e) => {
-                    self.set_status(&format!("Failed to send: {}", e));
-                    // Optionally: remove the local echo message on error
-                    // self.panes[pane_idx].msg_data.pop();
+                ConnectionState::Polling { reason, .. } => {
+                    self.set_status(&format!(
+                        "Socket Mode unavailable ({}); polling open panes for new messages",
+                        reason
+                    ));
                 }
+                _ => {}
             }
+            self.needs_redraw = true;
         }
+    }
 
-        Ok(())
+    /// True when Socket Mode is unavailable and messages only arrive by polling
+    pub fn is_polling(&self) -> bool {
+        matches!(*self.connection_state.borrow(), ConnectionState::Polling { .. })
     }
 
-    pub fn draw(&mut self, f: &mut Frame) {
-        let has_status = self.status_message.is_some();
-        
-        // Check if we have mentions in other workspaces
-        let current_workspace_name = self.config.workspaces
-            .get(self.config.active_workspace)
-            .map(|w| w.name.clone())
-            .unwrap_or_default();
-        let other_workspace_mentions: Vec<(String, u32)> = self.unread_mentions
-            .iter()
-            .filter(|(ws_name, count)| **ws_name != current_workspace_name && **count > 0)
-            .map(|(name, count)| (name.clone(), *count))
-            .collect();
-        let has_other_mentions = !other_workspace_mentions.is_empty();
-        
-        let main_constraints = if has_status && has_other_mentions {
-            vec![Constraint::Min(0), Constraint::Length(1), Constraint::Length(1)]
-        } else if has_status {
-            vec![Constraint::Min(0), Constraint::Length(1)]
-        } else if has_other_mentions {
-            vec![Constraint::Min(0), Constraint::Length(1)]
+    pub fn maybe_run_fallback_refresh(&mut self) {
+        if self.offline {
+            return;
+        }
+        let now = std::time::Instant::now();
+
+        // Without Socket Mode every open pane is refreshed, not just the focused one
+        if self.is_polling() {
+            if now.duration_since(self.last_fallback_refresh_instant).as_secs() >= FALLBACK_REFRESH_SECS {
+                self.last_fallback_refresh_instant = now;
+                self.last_fallback_refresh_at = Some(chrono::Local::now());
+                self.reload_pane_contents();
+                self.needs_redraw = true;
+            }
+            return;
+        }
+
+        let app_age_secs = now.duration_since(self.app_start_instant).as_secs();
+        let now_stale = if app_age_secs < REALTIME_STALE_SECS {
+            false
+        } else if let Some(last) = self.last_realtime_event_instant {
+            now.duration_since(last).as_secs() >= REALTIME_STALE_SECS
         } else {
-            vec![Constraint::Min(0)]
+            true
         };
 
-        let outer = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(main_constraints)
-            .split(f.area());
+        if now_stale && !self.realtime_was_stale {
+            self.realtime_was_stale = true;
+            self.set_status("Realtime stale; fallback refresh active");
+        } else if !now_stale && self.realtime_was_stale {
+            self.realtime_was_stale = false;
+        }
 
-        let (chat_area, pane_area) = if self.show_chat_list {
-            // Calculate dynamic width based on longest chat name
-            let max_name_len = self.chats.iter()
-                .map(|c| {
-                    let prefix = if c.unread > 0 { format!("({}) ", c.unread) } else { String::new() };
-                    let emoji = match c.section {
-                        ChatSection::Public => "# ",
-                        ChatSection::Private => "🔒 ",
-                        ChatSection::DirectMessage => "👤 ",
-                        ChatSection::Group => "👥 ",
-                        ChatSection::Bot => "🤖 ",
-                    };
-                    prefix.len() + emoji.len() + c.name.len()
-                })
-                .max()
-                .unwrap_or(20);
-            
-            // Add padding for borders and some breathing room (narrower: ~2/3 of before)
-            let chat_list_width = (max_name_len + 4).min(27).max(10) as u16;
-            
-            let chunks = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([Constraint::Length(chat_list_width), Constraint::Min(0)])
-                .split(outer[0]);
-            (Some(chunks[0]), chunks[1])
-        } else {
-            (None, outer[0])
-        };
+        if !now_stale {
+            return;
+        }
 
-        if let Some(area) = chat_area {
-            self.chat_list_area = Some(area);
-            self.draw_chat_list(f, area);
-        } else {
-            self.chat_list_area = None;
+        if now
+            .duration_since(self.last_fallback_refresh_instant)
+            .as_secs()
+            < FALLBACK_REFRESH_SECS
+        {
+            return;
         }
 
-        // Draw panes
-        let render_fn = |f: &mut Frame, area: Rect, pane: &ChatPane, is_focused: bool| {
-            self.draw_chat_pane_impl(f, area, pane, is_focused);
-        };
+        self.last_fallback_refresh_instant = now;
+        self.last_fallback_refresh_at = Some(chrono::Local::now());
+        let pane_idx = self.focused_pane_idx;
+        self.reload_pane_at(pane_idx);
+        self.needs_redraw = true;
+    }
 
-        let mut pane_areas = std::collections::HashMap::new();
-        self.pane_tree.render(
-            f,
-            pane_area,
-            &self.panes,
-            self.focused_pane_idx,
-            &render_fn,
-            &mut pane_areas,
-        );
-        self.pane_areas = pane_areas;
+    pub fn reload_pane_contents(&mut self) {
+        // Reload messages for all panes that have a channel set
+        for idx in 0..self.panes.len() {
+            self.reload_pane_at(idx);
+        }
+    }
 
-        // Draw notification bar for mentions in other workspaces
-        if has_other_mentions {
-            let mention_text: String = other_workspace_mentions
-                .iter()
-                .map(|(name, count)| format!("{}: {}@", name, count))
-                .collect::<Vec<_>>()
-                .join(" | ");
-            let notification = Paragraph::new(format!(" Mentions in other workspaces: {} (Ctrl+N to switch)", mention_text))
-                .style(Style::default().bg(Color::Yellow).fg(Color::Black).add_modifier(Modifier::BOLD))
-                .block(Block::default());
-            let notification_idx = if has_status { outer.len() - 2 } else { outer.len() - 1 };
-            f.render_widget(notification, outer[notification_idx]);
+    pub fn open_selected_chat(&mut self) {
+        self.ensure_valid_pane_idx();
+        if self.selected_chat_idx >= self.chats.len() {
+            return;
         }
 
-        // Draw status bar
-        if has_status {
-            let status = Paragraph::new(self.status_message.as_ref().unwrap().clone())
-                .style(Style::default().bg(Color::DarkGray).fg(Color::White))
-                .block(Block::default());
-            f.render_widget(status, outer[outer.len() - 1]);
+        let chat = self.chats[self.selected_chat_idx].clone();
+        let pane = &mut self.panes[self.focused_pane_idx];
+
+        // The composer's text stays with the conversation it was written for
+        if pane.thread_ts.is_none() && pane.channel_id_str.as_deref() != Some(chat.id.as_str()) {
+            if let Some(old) = &pane.channel_id_str {
+                self.drafts.set(old, &pane.input_buffer);
+                pane.input_buffer.clear();
+            }
+            if pane.input_buffer.is_empty() {
+                pane.input_buffer = self.drafts.take(&chat.id).unwrap_or_default();
+            }
+            pane.input_cursor = pane.input_buffer.len();
+            pane.tab_complete_state = None;
+        }
+
+        // Use string channel ID (Slack IDs are not numeric)
+        pane.chat_id = None;
+        pane.channel_id_str = Some(chat.id.clone());
+        pane.chat_name = chat.name.clone();
+        pane.username = chat.username.clone();
+        pane.thread_ts = None;
+        pane.reply_to_message = None;
+        pane.hide_reply_preview();
+        pane.msg_data.clear();
+        pane.first_unread_ts = None;
+        pane.invalidate_cache();
+
+        // Clear unread counter when opening the chat
+        if let Some(chat_info) = self.chats.get_mut(self.selected_chat_idx) {
+            chat_info.unread = 0;
+            chat_info.mentions = 0;
+        }
+        self.recent.touch(&chat.id);
+        // Members may have changed while we weren't connected
+        if chat.section == ChatSection::Group && !self.offline {
+            self.request_group_name(chat.id.clone(), None);
         }
+        
+        // Clear mention counter for current workspace when opening any chat
+        let workspace_name = self.config.workspaces
+            .get(self.config.active_workspace)
+            .map(|w| w.name.clone())
+            .unwrap_or_default();
+        self.unread_mentions.insert(workspace_name, 0);
+
+        // Messages arrive as ApiEvent::History; the pane shows [LOADING] until then
+        self.request_history(chat.id.clone(), None, Some(chat.unread));
+        self.focus_on_chat_list = false;
     }
 
-    /// Build the display rows for the chat list with a "New" section on top.
-    fn build_chat_list_rows(&self) -> Vec<ChatListRow> {
-        let sections = [
-            ChatSection::Public,
-            ChatSection::Private,
-            ChatSection::Group,
-            ChatSection::DirectMessage,
-            ChatSection::Bot,
-        ];
+    /// Open the thread of message `msg_idx` of the focused pane (/thread N, or a click on its replies).
+    pub async fn open_message_thread(&mut self, msg_idx: usize) -> Result<()> {
+        self.ensure_valid_pane_idx();
+        let pane = &self.panes[self.focused_pane_idx];
+        let Some(msg) = pane.msg_data.get(msg_idx) else {
+            return Ok(());
+        };
+        let thread_ts = msg.ts.clone();
+        let parent_user = msg.sender_name.clone();
+        let Some(channel_id_str) = pane.channel_id_str.clone() else {
+            self.set_status("No channel selected");
+            return Ok(());
+        };
 
-        let mut rows: Vec<ChatListRow> = Vec::new();
+        self.open_thread(&channel_id_str, &thread_ts, &parent_user).await
+    }
 
-        // New section (unread > 0)
-        let new_chats: Vec<usize> = self
-            .chats
-            .iter()
+    pub async fn open_thread(
+        &mut self,
+        channel_id_str: &str,
+        thread_ts: &str,
+        parent_user: &str,
+    ) -> Result<()> {
+        // Create new pane for thread
+        let new_idx = self.panes.len();
+        let mut thread_pane = ChatPane::new();
+        thread_pane.channel_id_str = Some(channel_id_str.to_string());
+        thread_pane.thread_ts = Some(thread_ts.to_string());
+        thread_pane.chat_name = format!("Thread: {}", parent_user);
+        self.panes.push(thread_pane);
+        self.pane_zoomed = false;
+
+        // Check if the focused pane is already a thread
+        let focused_pane = &self.panes[self.focused_pane_idx];
+        let is_already_thread = focused_pane.thread_ts.is_some();
+        
+        // Check if there are any existing thread panes open
+        let existing_thread_pane_idx = self.panes.iter()
             .enumerate()
-            .filter(|(_, c)| c.unread > 0)
-            .map(|(i, _)| i)
-            .collect();
-        if !new_chats.is_empty() {
-            rows.push(ChatListRow::Header("New".to_string()));
-            for idx in new_chats {
-                rows.push(ChatListRow::Chat(idx));
+            .find(|(_, p)| p.thread_ts.is_some())
+            .map(|(idx, _)| idx);
+        
+        if is_already_thread {
+            // If we're already in a thread view, split the focused thread pane horizontally
+            // This will create a horizontal split within the thread area
+            if !self.pane_tree.split_pane_with_ratio(self.focused_pane_idx, SplitDirection::Horizontal, new_idx, 50) {
+                // If split failed (e.g., pane is deeply nested), try fallback
+                // Fallback: split root horizontally (less ideal but should work)
+                self.pane_tree.split_with_ratio(SplitDirection::Horizontal, new_idx, 50);
+            }
+        } else if let Some(thread_pane_idx) = existing_thread_pane_idx {
+            // If we're in a regular channel but there's already a thread open,
+            // split that existing thread pane horizontally (new thread goes under it)
+            if !self.pane_tree.split_pane_with_ratio(thread_pane_idx, SplitDirection::Horizontal, new_idx, 50) {
+                // Fallback: try splitting the focused pane vertically
+                if !self.pane_tree.split_pane_with_ratio(self.focused_pane_idx, SplitDirection::Vertical, new_idx, 33) {
+                    self.pane_tree.split_with_ratio(SplitDirection::Vertical, new_idx, 33);
+                }
+            }
+        } else {
+            // If we're in a regular channel and no threads are open, split vertically (thread takes 1/3)
+            if !self.pane_tree.split_pane_with_ratio(self.focused_pane_idx, SplitDirection::Vertical, new_idx, 33) {
+                // Fallback if split failed
+                self.pane_tree.split_with_ratio(SplitDirection::Vertical, new_idx, 33);
             }
         }
+        
+        // Focus the new thread pane
+        self.focused_pane_idx = new_idx;
 
-        // Regular sections with only read chats
-        for section in &sections {
-            let section_chats: Vec<usize> = self
-                .chats
-                .iter()
-                .enumerate()
-                .filter(|(_, c)| c.section == *section && c.unread == 0)
-                .map(|(i, _)| i)
-                .collect();
+        // Load thread replies in the background; names come from the nickname-aware cache
+        self.request_history(channel_id_str.to_string(), Some(thread_ts.to_string()), Some(0));
 
-            if section_chats.is_empty() {
-                continue;
-            }
+        // Auto-scroll to bottom
+        self.panes[new_idx].scroll_offset = usize::MAX;
+        self.focused_pane_idx = new_idx;
+        self.focus_on_chat_list = false;
+        Ok(())
+    }
 
-            rows.push(ChatListRow::Header(section.label().to_string()));
-            for idx in section_chats {
-                rows.push(ChatListRow::Chat(idx));
+    /// List the files shared in the focused channel (/files).
+    pub async fn show_channel_files(&mut self) {
+        self.ensure_valid_pane_idx();
+        let pane = &self.panes[self.focused_pane_idx];
+        let Some(channel_id) = pane.channel_id_str.clone() else {
+            self.set_status("No channel selected");
+            return;
+        };
+        let chat_name = pane.chat_name.clone();
+        if self.scope_unavailable("files.list", "Browsing files") {
+            return;
+        }
+
+        let files = match self.slack.list_channel_files(&channel_id, 100).await {
+            Ok(files) => files,
+            Err(e) => {
+                self.set_status(&format!("Failed to list files: {}", e));
+                return;
             }
+        };
+        if files.is_empty() {
+            self.set_status(&format!("No files shared in {}", chat_name));
+            return;
         }
-        rows
+
+        let mut entries = Vec::with_capacity(files.len());
+        for file in files {
+            let uploader = match file.user.as_deref() {
+                Some(uid) => match self.user_name_cache.get(uid) {
+                    Some(name) => name.clone(),
+                    None => self.slack.resolve_user_name(uid).await,
+                },
+                None => "unknown".to_string(),
+            };
+            let date = Local
+                .timestamp_opt(file.created, 0)
+                .single()
+                .map(|dt| dt.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            entries.push(FileEntry {
+                name: file
+                    .name
+                    .or(file.title)
+                    .unwrap_or_else(|| file.id.clone()),
+                kind: file
+                    .pretty_type
+                    .or(file.filetype)
+                    .unwrap_or_default(),
+                size: file.size,
+                uploader,
+                date,
+                url: file.url_private_download.or(file.url_private),
+            });
+        }
+
+        self.file_browser = Some(FileBrowser {
+            title: format!("Files in {}", chat_name),
+            files: entries,
+            selected: 0,
+        });
+        self.needs_redraw = true;
     }
 
-    /// Find the display row index for a given chat index.
-    fn chat_idx_to_row(&self, rows: &[ChatListRow], chat_idx: usize) -> usize {
-        rows.iter()
-            .position(|r| matches!(r, ChatListRow::Chat(idx) if *idx == chat_idx))
-            .unwrap_or(0)
+    pub fn file_browser_move(&mut self, delta: isize) {
+        if let Some(browser) = self.file_browser.as_mut() {
+            let last = browser.files.len().saturating_sub(1);
+            browser.selected = browser.selected.saturating_add_signed(delta).min(last);
+            self.needs_redraw = true;
+        }
     }
 
-    /// Find the chat index from a display row click.
-    fn row_to_chat_idx(rows: &[ChatListRow], row: usize) -> Option<usize> {
-        rows.get(row).and_then(|r| match r {
-            ChatListRow::Chat(idx) => Some(*idx),
-            _ => None,
-        })
+    /// Download the selected file of the /files browser; it is opened when done.
+    pub fn open_selected_file(&mut self) {
+        let Some(browser) = self.file_browser.take() else {
+            return;
+        };
+        self.needs_redraw = true;
+        let Some(file) = browser.files.get(browser.selected) else {
+            return;
+        };
+        let Some(url) = file.url.as_deref() else {
+            self.set_status(&format!("{} has no download URL", file.name));
+            return;
+        };
+
+        self.start_download(file.name.clone(), None, Some(url.to_string()));
     }
 
-    fn draw_chat_list(&mut self, f: &mut Frame, area: Rect) {
-        let visible_height = area.height.saturating_sub(2) as usize;
-        if visible_height == 0 {
+    /// `/finduser <query>`: list matching users with their presence; Enter opens a DM.
+    pub async fn find_users(&mut self, query: &str) {
+        let matches = match self.slack.search_users(query).await {
+            Ok(m) => m,
+            Err(e) => {
+                self.set_status(&format!("User search failed: {}", e));
+                return;
+            }
+        };
+        if matches.is_empty() {
+            self.set_status(&format!("No users match \"{}\"", query));
             return;
         }
 
-        let rows = self.build_chat_list_rows();
-        let selected_row = self.chat_idx_to_row(&rows, self.selected_chat_idx);
-
-        // Ensure scroll offset keeps selected row visible
-        if selected_row < self.chat_list_scroll_offset {
-            self.chat_list_scroll_offset = selected_row;
-        } else if selected_row >= self.chat_list_scroll_offset + visible_height {
-            self.chat_list_scroll_offset = selected_row + 1 - visible_height;
+        let mut users = Vec::new();
+        for (i, user) in matches.into_iter().enumerate() {
+            // One users.getPresence call each, so only for the first few
+            let presence = if i < FIND_USER_PRESENCE_LOOKUPS {
+                self.slack.get_presence(&user.id).await.ok()
+            } else {
+                None
+            };
+            let name = self.user_name_cache.get(&user.id).cloned().unwrap_or(user.name);
+            users.push(UserEntry {
+                id: user.id,
+                name,
+                real_name: user.real_name,
+                title: user.title,
+                email: user.email,
+                presence,
+            });
         }
 
-        let items: Vec<ListItem> = rows
-            .iter()
-            .enumerate()
-            .skip(self.chat_list_scroll_offset)
-            .take(visible_height)
-            .map(|(_, row)| match row {
-                ChatListRow::Header(label) => ListItem::new(Line::from(Span::styled(
-                    format!("-- {} --", label),
-                    Style::default()
-                        .fg(Color::DarkGray)
-                        .add_modifier(Modifier::BOLD),
-                ))),
-                ChatListRow::Chat(chat_idx) => {
-                    let chat = &self.chats[*chat_idx];
-                    let mut style = if *chat_idx == self.selected_chat_idx {
-                        Style::default().bg(Color::Blue).fg(Color::White)
-                    } else if chat.unread > 0 {
-                        Style::default().fg(Color::Red)
-                    } else {
-                        Style::default()
-                    };
+        self.user_picker = Some(UserPicker {
+            query: query.to_string(),
+            users,
+            selected: 0,
+        });
+        self.needs_redraw = true;
+    }
 
-                    if chat.unread > 0 && *chat_idx == self.selected_chat_idx {
-                        style = style.add_modifier(Modifier::BOLD);
-                    }
+    pub fn user_picker_move(&mut self, delta: isize) {
+        if let Some(picker) = self.user_picker.as_mut() {
+            let last = picker.users.len().saturating_sub(1);
+            picker.selected = picker.selected.saturating_add_signed(delta).min(last);
+            self.needs_redraw = true;
+        }
+    }
 
-                    let unread_marker = if chat.unread > 0 {
-                        format!(" ({})", chat.unread)
-                    } else {
-                        String::new()
-                    };
+    /// Open a DM with the user selected in the /finduser list.
+    pub async fn open_selected_user_dm(&mut self) -> Result<()> {
+        let Some(picker) = self.user_picker.take() else {
+            return Ok(());
+        };
+        self.needs_redraw = true;
+        match picker.users.get(picker.selected) {
+            Some(user) => self.open_dm(&user.id).await,
+            None => Ok(()),
+        }
+    }
 
-                    let mut spans = vec![];
-                    if chat.unread > 0 {
-                        spans.push(Span::styled(
-                            "! ",
-                            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-                        ));
-                    } else {
-                        spans.push(Span::raw("  "));
-                    }
-                    spans.push(Span::raw(format!("{}{}", chat.name, unread_marker)));
+    pub fn mention_picker_open(&self) -> bool {
+        self.mention_picker.is_some() && !self.focus_on_chat_list
+    }
 
-                    ListItem::new(Line::from(spans)).style(style)
-                }
-            })
-            .collect();
+    /// Open, filter or close the mention picker for an `@word` ending at the cursor.
+    fn update_mention_picker(&mut self) {
+        let pane = &self.panes[self.focused_pane_idx];
+        let before = &pane.input_buffer[..pane.input_cursor.min(pane.input_buffer.len())];
+        let start = before
+            .rfind('@')
+            .filter(|&at| !before[at + 1..].contains(char::is_whitespace))
+            // Only at a word start, not in an email address
+            .filter(|&at| before[..at].chars().next_back().is_none_or(char::is_whitespace));
+        let Some(start) = start else {
+            if self.mention_picker.take().is_some() {
+                self.needs_redraw = true;
+            }
+            return;
+        };
+        let query = before[start + 1..].to_lowercase();
 
-        let list_block = if self.show_borders {
-            Block::default()
-                .borders(Borders::ALL)
-                .title(if self.focus_on_chat_list {
-                    "Channels [FOCUSED]"
-                } else {
-                    "Channels"
-                })
-                .border_style(if self.focus_on_chat_list {
-                    Style::default().fg(Color::Cyan)
-                } else {
-                    Style::default()
+        let mut users: Vec<UserEntry> = if self.member_profiles.is_empty() {
+            // Without the users.list prefetch only the names seen so far are known
+            self.user_name_cache
+                .iter()
+                .map(|(id, name)| UserEntry {
+                    id: id.clone(),
+                    name: name.clone(),
+                    real_name: String::new(),
+                    title: String::new(),
+                    email: String::new(),
+                    presence: None,
                 })
+                .collect()
         } else {
-            Block::default()
+            self.member_profiles
+                .iter()
+                .map(|user| UserEntry {
+                    id: user.id.clone(),
+                    name: self.user_name_cache.get(&user.id).cloned().unwrap_or(user.name.clone()),
+                    real_name: user.real_name.clone(),
+                    title: user.title.clone(),
+                    email: String::new(),
+                    presence: None,
+                })
+                .collect()
         };
-        let list = List::new(items).block(list_block);
+        users.retain(|user| {
+            [&user.name, &user.real_name]
+                .iter()
+                .flat_map(|field| field.split_whitespace())
+                .any(|word| word.to_lowercase().starts_with(&query))
+        });
+        // Display names that start with the query first
+        users.sort_by_key(|user| {
+            let name = user.name.to_lowercase();
+            (!name.starts_with(&query), name)
+        });
+        users.truncate(MENTION_PICKER_MAX);
 
-        f.render_widget(list, area);
+        if users.is_empty() {
+            self.mention_picker = None;
+        } else {
+            let selected = match &self.mention_picker {
+                Some(picker) if picker.start == start => {
+                    picker.selected.min(users.len() - 1)
+                }
+                _ => 0,
+            };
+            self.mention_picker = Some(MentionPicker { start, users, selected });
+        }
+        self.needs_redraw = true;
     }
 
-    fn draw_chat_pane_impl(&self, f: &mut Frame, area: Rect, pane: &ChatPane, is_focused: bool) {
-        let has_reply_preview = pane.reply_preview.is_some();
-        let header_height = if !self.show_borders { 2 } else if self.compact_mode { 2 } else { 3 };
-        let input_height: u16 = 3; // top margin + 1 line + bottom margin
-        let constraints = if has_reply_preview {
-            vec![
-                Constraint::Length(header_height),
-                Constraint::Min(0),
-                Constraint::Length(1),
-                Constraint::Length(input_height),
-            ]
-        } else {
-            vec![
-                Constraint::Length(header_height),
-                Constraint::Min(0),
-                Constraint::Length(input_height),
-            ]
+    pub fn mention_picker_move(&mut self, delta: isize) {
+        if let Some(picker) = self.mention_picker.as_mut() {
+            let last = picker.users.len().saturating_sub(1);
+            picker.selected = picker.selected.saturating_add_signed(delta).min(last);
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Replace the `@word` at the cursor with a `<@U…>` mention of the selected user.
+    pub fn insert_selected_mention(&mut self) {
+        let Some(picker) = self.mention_picker.take() else {
+            return;
+        };
+        self.needs_redraw = true;
+        let Some(user) = picker.users.get(picker.selected) else {
+            return;
         };
+        let pane = &mut self.panes[self.focused_pane_idx];
+        let cursor = pane.input_cursor.min(pane.input_buffer.len());
+        if picker.start >= cursor || !pane.input_buffer[picker.start..].starts_with('@') {
+            return;
+        }
+        let mention = format!("<@{}> ", user.id);
+        pane.input_buffer.replace_range(picker.start..cursor, &mention);
+        pane.input_cursor = picker.start + mention.len();
+        pane.tab_complete_state = None;
+    }
 
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(constraints)
-            .split(area);
+    /// Download a file in the background; progress and the result arrive via `poll_downloads`.
+    /// With a file ID, a direct URL from files.sharedPublicURL is tried before `url`.
+    pub fn start_download(&mut self, name: String, file_id: Option<String>, url: Option<String>) {
+        if url.is_none() && file_id.is_some() && self.scope_unavailable("files.sharedPublicURL", "File download") {
+            return;
+        }
+        // Without the scope for files.sharedPublicURL only the private URL is tried
+        let file_id = file_id.filter(|_| self.slack.missing_scope("files.sharedPublicURL").is_none());
+        let id = self.downloads.len();
+        self.downloads.push(Download {
+            name: name.clone(),
+            received: 0,
+            total: None,
+            state: DownloadState::InProgress,
+        });
+        self.set_status(&format!("Downloading {}...", name));
 
-        // Header
-        let header_style = if is_focused {
-            if self.focus_on_chat_list {
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD)
+        let slack = self.slack.clone();
+        let dir = self.config.download_dir();
+        let tx = self.download_tx.clone();
+        tokio::spawn(async move {
+            let progress_tx = tx.clone();
+            let on_progress = move |received, total| {
+                let _ = progress_tx.send(DownloadEvent::Progress { id, received, total });
+            };
+
+            let mut result = Err(anyhow::anyhow!("No download URL"));
+            if let Some(file_id) = file_id {
+                result = slack.get_shared_public_url(&file_id, &name, &dir, &on_progress).await;
             }
-        } else {
-            Style::default().fg(Color::Cyan)
-        };
+            if let (Err(e), Some(url)) = (&result, url) {
+                warn!("Failed to get shared public URL: {}. Trying the file URL...", e);
+                result = slack.download_file_from_url(&url, &name, &dir, &on_progress).await;
+            }
+            let _ = tx.send(DownloadEvent::Finished {
+                id,
+                result: result.map_err(|e| e.to_string()),
+            });
+        });
+    }
 
-        let mut header_text = String::new();
-        if is_focused && self.focus_on_chat_list {
-            header_text.push_str("[TARGET] ");
+    /// Apply progress and results from download tasks. Returns true if anything changed.
+    pub fn poll_downloads(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(event) = self.download_rx.try_recv() {
+            changed = true;
+            match event {
+                DownloadEvent::Progress { id, received, total } => {
+                    let Some(download) = self.downloads.get_mut(id) else {
+                        continue;
+                    };
+                    download.received = received;
+                    download.total = total;
+                    let text = format!("Downloading {}: {}", download.name, download.progress_text());
+                    self.set_status(&text);
+                }
+                DownloadEvent::Finished { id, result } => {
+                    let Some(download) = self.downloads.get_mut(id) else {
+                        continue;
+                    };
+                    let text = match result {
+                        Ok(path) => {
+                            crate::utils::open_file(&path, self.config.opener_for(&path));
+                            let text = format!("Saved {} to: {}", download.name, path.display());
+                            download.state = DownloadState::Done(path);
+                            text
+                        }
+                        Err(e) => {
+                            let text = format!("Failed to download {}: {}", download.name, e);
+                            download.state = DownloadState::Failed(e);
+                            text
+                        }
+                    };
+                    self.set_status(&text);
+                }
+            }
         }
-        header_text.push_str(&pane.header_text());
-        if is_focused {
-            header_text.push_str(&self.realtime_status_text());
+
+        if changed && self.info_popup.as_ref().is_some_and(|p| p.title == DOWNLOADS_TITLE) {
+            self.show_downloads();
         }
+        changed
+    }
 
-        let header = Paragraph::new(header_text)
-            .block(if self.show_borders {
-                Block::default().borders(Borders::ALL)
-            } else {
-                Block::default()
+    /// Show in-progress and finished transfers of this session (/downloads).
+    pub fn show_downloads(&mut self) {
+        if self.downloads.is_empty() {
+            self.set_status("No downloads yet");
+            return;
+        }
+        let lines = self
+            .downloads
+            .iter()
+            .rev()
+            .map(|d| match &d.state {
+                DownloadState::InProgress => format!("{} - {}", d.name, d.progress_text()),
+                DownloadState::Done(path) => format!("{} - saved to {}", d.name, path.display()),
+                DownloadState::Failed(e) => format!("{} - failed: {}", d.name, e),
             })
-            .style(header_style);
-        f.render_widget(header, chunks[0]);
+            .collect();
+        self.info_popup = Some(InfoPopup {
+            title: DOWNLOADS_TITLE.to_string(),
+            lines,
+        });
+        self.needs_redraw = true;
+    }
 
-        let messages_block = if self.show_borders {
-            Block::default().borders(Borders::ALL).title("Messages")
-        } else {
-            Block::default().padding(Padding::left(2))
+    /// `/stats`: Web API calls per method this session, busiest first.
+    pub fn show_api_stats(&mut self) {
+        let stats = self.slack.api_stats();
+        if stats.is_empty() {
+            self.set_status("No API calls yet");
+            return;
+        }
+        let width = stats.iter().map(|(method, _)| method.len()).max().unwrap_or(0).max(6);
+        let row = |method: &str, calls: &str, failures: &str, limited: &str, avg: &str| {
+            format!("{:<width$}  {:>6}  {:>6}  {:>5}  {:>6}", method, calls, failures, limited, avg, width = width)
         };
-        let msg_inner = messages_block.inner(chunks[1]);
-        let msg_width = msg_inner.width as usize;
-        let msg_area_height = msg_inner.height as usize;
+        let mut lines = vec![row("method", "calls", "failed", "429s", "avg ms")];
+        let (mut calls, mut failures, mut limited) = (0, 0, 0);
+        for (method, s) in &stats {
+            let avg = s.total_time.as_millis() / u128::from(s.calls.max(1));
+            lines.push(row(
+                method,
+                &s.calls.to_string(),
+                &s.failures.to_string(),
+                &s.rate_limited.to_string(),
+                &avg.to_string(),
+            ));
+            calls += s.calls;
+            failures += s.failures;
+            limited += s.rate_limited;
+        }
+        lines.push(row("total", &calls.to_string(), &failures.to_string(), &limited.to_string(), ""));
+        self.info_popup = Some(InfoPopup {
+            title: "API calls this session (/stats)".to_string(),
+            lines,
+        });
+        self.needs_redraw = true;
+    }
 
-        let show_emojis = self.show_emojis;
-        let show_reactions = self.show_reactions;
-        let show_line_numbers = self.show_line_numbers;
-        let show_timestamps = self.show_timestamps;
-        let show_user_colors = self.show_user_colors;
-        let user_cache = &self.user_name_cache;
-        let resolve_user = |id: &str| -> String {
-            user_cache
-                .get(id)
-                .cloned()
-                .unwrap_or_else(|| id.to_string())
+    /// `/doctor`: check the workspace's token scopes and Socket Mode in a popup.
+    pub async fn run_doctor(&mut self) {
+        let Some(workspace) = self.config.workspaces.get(self.config.active_workspace) else {
+            self.set_status("No workspace configured");
+            return;
         };
-        let format_ts = |ts: &str| -> Option<String> {
-            if !show_timestamps {
-                return None;
-            }
-            let secs: i64 = ts.split('.').next()?.parse().ok()?;
-            let dt = Local.timestamp_opt(secs, 0).single()?;
-            Some(dt.format("%H:%M").to_string())
+        let (title, app_token) = (format!("Doctor: {}", workspace.name), workspace.app_token.clone());
+        let live = self.connection_state.borrow().clone();
+        let report = crate::doctor::check(&self.slack, &app_token, Some(&live)).await;
+        self.info_popup = Some(InfoPopup {
+            title,
+            lines: report.lines,
+        });
+        self.needs_redraw = true;
+    }
+
+    /// `/diagnose [lines]`: write a bug report bundle to the config directory.
+    pub fn write_diagnostics(&mut self, log_lines: usize) {
+        let log_file = match &self.log_handle {
+            Some(handle) => handle.path.clone(),
+            None => self
+                .config
+                .logging
+                .file
+                .clone()
+                .unwrap_or_else(|| self.config.config_dir.join("slack_rust.log")),
         };
+        let settings = self.current_settings();
+        let connection = self.connection_state.borrow().clone();
+        let api_errors = self.slack.recent_errors();
+        let api_stats = self.slack.api_stats();
+        let bundle = crate::diagnostics::Bundle {
+            config: &self.config,
+            settings: &settings,
+            connection: &connection,
+            api_errors: &api_errors,
+            api_stats: &api_stats,
+            log_file: &log_file,
+            log_lines,
+        };
+        match bundle.write() {
+            Ok(path) => self.set_status(&format!(
+                "Diagnostics written to {} (tokens redacted; check the log lines before sharing)",
+                path.display()
+            )),
+            Err(e) => self.set_status(&format!("Failed to write diagnostics: {}", e)),
+        }
+    }
 
-        // Messages with emojis, reactions, and thread indicators
-        let mut message_lines: Vec<Line> = Vec::new();
-        for (idx, msg) in pane.msg_data.iter().enumerate() {
-            let name_style = if msg.is_outgoing {
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD)
-            };
+    /// Run `scripts/<name>.rhai` as a slash command. False if there is no such script.
+    pub async fn run_script(&mut self, name: &str, args: &[String]) -> bool {
+        let Some(path) = crate::scripting::find_script(&self.config.config_dir, name) else {
+            return false;
+        };
+        self.ensure_valid_pane_idx();
+        let pane = &self.panes[self.focused_pane_idx];
+        let context = crate::scripting::ScriptContext {
+            args: args.to_vec(),
+            workspace: self
+                .config
+                .workspaces
+                .get(self.config.active_workspace)
+                .map(|w| w.name.clone())
+                .unwrap_or_default(),
+            channel: pane.chat_name.clone(),
+            channel_id: pane.channel_id_str.clone().unwrap_or_default(),
+            thread_ts: pane.thread_ts.clone().unwrap_or_default(),
+            messages: pane
+                .msg_data
+                .iter()
+                .filter(|m| !m.is_system && !m.is_deleted)
+                .map(|m| (m.sender_name.clone(), m.text.clone(), m.ts.clone()))
+                .collect(),
+        };
 
-            let formatted_text = format_message_text(&msg.text, show_emojis, &resolve_user);
+        let actions = match crate::scripting::run_script(&path, &context) {
+            Ok(actions) => actions,
+            Err(e) => {
+                self.set_status(&format!("/{} failed: {}", name, e));
+                return true;
+            }
+        };
+        for action in actions {
+            match action {
+                ScriptAction::Status(text) => self.set_status(&text),
+                ScriptAction::Send { channel, text } => {
+                    if self.reject_if_read_only() {
+                        return true;
+                    }
+                    let Some(channel) = channel else {
+                        let pane_idx = self.focused_pane_idx;
+                        self.send_text(pane_idx, &text, false);
+                        continue;
+                    };
+                    let Some(idx) = self.find_chat_idx(&channel) else {
+                        self.set_status(&format!("/{}: channel '{}' not found", name, channel));
+                        continue;
+                    };
+                    let channel_id = self.chats[idx].id.clone();
+                    let text = self.convert_mentions_to_ids(&text);
+                    if let Err(e) = self.slack.send_message(&channel_id, &text, None, false).await {
+                        self.set_status(&format!("/{}: failed to send: {}", name, e));
+                    }
+                }
+                ScriptAction::React { ts, emoji } => {
+                    if self.reject_if_read_only() || self.scope_unavailable("reactions.add", "Reacting") {
+                        return true;
+                    }
+                    let latest = context.messages.last().map(|(_, _, ts)| ts.clone());
+                    let Some(ts) = ts.or(latest).filter(|_| !context.channel_id.is_empty()) else {
+                        self.set_status(&format!("/{}: no message to react to", name));
+                        continue;
+                    };
+                    if let Err(e) = self.slack.add_reaction(&context.channel_id, &ts, &emoji).await {
+                        self.set_status(&format!("/{}: failed to add reaction: {}", name, e));
+                    }
+                }
+            }
+        }
+        self.needs_redraw = true;
+        true
+    }
 
-            let mut prefix_spans = Vec::new();
+    /// `/info`: show details of the focused pane's conversation in a popup.
+    pub async fn show_channel_info(&mut self) {
+        self.ensure_valid_pane_idx();
+        let pane = &self.panes[self.focused_pane_idx];
+        let Some(channel_id) = pane.channel_id_str.clone() else {
+            self.set_status("No channel selected");
+            return;
+        };
+        let chat_name = pane.chat_name.clone();
 
-            // Add highlight indicator if message mentions the user
-            if msg.mentions_me {
-                prefix_spans.push(Span::styled(
-                    "@ ",
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                ));
+        let details = match self.slack.get_channel_details(&channel_id).await {
+            Ok(d) => d,
+            Err(e) => {
+                self.set_status(&format!("Failed to load channel info: {}", e));
+                return;
             }
+        };
 
-            // Add deleted indicator
-            if msg.is_deleted {
-                prefix_spans.push(Span::styled(
-                    "[DELETED] ",
-                    Style::default()
-                        .fg(Color::Red)
-                        .add_modifier(Modifier::ITALIC),
-                ));
-            }
-
-            if show_line_numbers {
-                prefix_spans.push(Span::styled(
-                    format!("#{} ", idx + 1),
-                    Style::default().fg(Color::DarkGray),
-                ));
-            }
-
-            if let Some(ts_fmt) = format_ts(&msg.ts) {
-                prefix_spans.push(Span::styled(
-                    format!("[{}] ", ts_fmt),
-                    Style::default().fg(Color::DarkGray),
-                ));
-            }
+        let text_or_none = |t: &Option<crate::slack::ChannelText>| {
+            t.as_ref()
+                .map(|t| t.value.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| "(none)".to_string())
+        };
+        let created = Local
+            .timestamp_opt(details.created, 0)
+            .single()
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let members = details
+            .num_members
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let shared = if details.is_ext_shared {
+            "yes (external organization)"
+        } else if details.is_org_shared {
+            "yes (across the organization)"
+        } else if details.is_shared {
+            "yes"
+        } else {
+            "no"
+        };
 
-            // Use color-coded username for better visual distinction
-            let username_style = if msg.is_outgoing {
-                name_style  // Keep own messages with original style
-            } else if show_user_colors {
-                Style::default()
-                    .fg(username_color(&msg.sender_name))
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                name_style  // Use default style if colors are disabled
-            };
-            prefix_spans.push(Span::styled(
-                format!("{}: ", msg.sender_name),
-                username_style,
-            ));
+        let mut lines = vec![
+            format!("ID:       {}", details.id),
+            format!("Members:  {}", members),
+            format!("Created:  {}", created),
+            format!("Topic:    {}", text_or_none(&details.topic)),
+            format!("Purpose:  {}", text_or_none(&details.purpose)),
+            format!("Shared:   {}", shared),
+        ];
+        if details.is_archived {
+            lines.push("Archived".to_string());
+        }
 
-            let mut content_spans = Vec::new();
-            content_spans.push(Span::raw(formatted_text));
+        let title = details
+            .name
+            .map(|n| format!("#{}", n))
+            .unwrap_or(chat_name);
+        self.info_popup = Some(InfoPopup { title, lines });
+        self.needs_redraw = true;
+    }
 
-            // Add media indicator
-            if let Some(ref media_type) = msg.media_type {
-                let indicator = match media_type.as_str() {
-                    "image" => "[img]",
-                    "video" => "[video]",
-                    _ => "",
-                };
-                if !indicator.is_empty() {
-                    content_spans.push(Span::styled(
-                        format!(" {}", indicator),
-                        Style::default()
-                            .fg(Color::Blue)
-                            .add_modifier(Modifier::BOLD),
-                    ));
-                }
+    /// `/join #channel`: open a conversation from the sidebar in the focused pane.
+    pub fn join_chat(&mut self, channel: &str) {
+        match self.find_chat_idx(channel) {
+            Some(idx) => {
+                self.selected_chat_idx = idx;
+                self.open_selected_chat();
             }
+            None => self.set_status(&format!("Channel not found: {}", channel)),
+        }
+    }
 
-            // Add edited indicator
-            if msg.is_edited && !msg.is_deleted {
-                content_spans.push(Span::styled(
-                    " (edited)",
-                    Style::default()
-                        .fg(Color::DarkGray)
-                        .add_modifier(Modifier::ITALIC),
-                ));
+    /// `/forward #N #channel`: share message N of the focused pane in another conversation
+    /// by posting its permalink, which Slack shows with the original message.
+    pub async fn forward_message(&mut self, msg_num: usize, channel: &str) {
+        if self.reject_if_read_only() {
+            return;
+        }
+        self.ensure_valid_pane_idx();
+        let pane = &self.panes[self.focused_pane_idx];
+        let Some(channel_id) = pane.channel_id_str.clone() else {
+            self.set_status("No channel selected");
+            return;
+        };
+        let Some(ts) = msg_num.checked_sub(1).and_then(|i| pane.msg_data.get(i)).map(|m| m.ts.clone()) else {
+            self.set_status(&format!("Message #{} not found", msg_num));
+            return;
+        };
+        let Some(idx) = self.find_chat_idx(channel) else {
+            self.set_status(&format!("Channel not found: {}", channel));
+            return;
+        };
+        let (target, target_name) = (self.chats[idx].id.clone(), self.chats[idx].name.clone());
+        let permalink = match self.slack.get_permalink(&channel_id, &ts).await {
+            Ok(link) => link,
+            Err(e) => {
+                self.set_status(&format!("Failed to forward: {}", e));
+                return;
             }
+        };
+        let message = OutboxMessage {
+            channel_id: target,
+            thread_ts: None,
+            text: permalink,
+            broadcast: false,
+            local_echo_id: None,
+        };
+        if !self.send_or_queue(vec![message]) {
+            self.set_status(&format!("Forwarded message #{} to {}", msg_num, target_name));
+        }
+    }
 
-            // Thread reply indicator
-            if msg.reply_count > 0 {
-                content_spans.push(Span::styled(
-                    format!(" [{} replies]", msg.reply_count),
-                    Style::default()
-                        .fg(Color::Magenta)
-                        .add_modifier(Modifier::BOLD),
-                ));
-            }
+    /// Raw details of message N of the focused pane (/msginfo), for debugging and referencing.
+    pub async fn show_message_info(&mut self, msg_num: usize) {
+        self.ensure_valid_pane_idx();
+        let pane = &self.panes[self.focused_pane_idx];
+        let Some(channel_id) = pane.channel_id_str.clone() else {
+            self.set_status("No channel selected");
+            return;
+        };
+        let Some(msg) = msg_num.checked_sub(1).and_then(|i| pane.msg_data.get(i)).cloned() else {
+            self.set_status(&format!("Message #{} not found", msg_num));
+            return;
+        };
+        // Thread parents are their own thread_ts; replies only show up in thread panes
+        let thread_ts = pane
+            .thread_ts
+            .clone()
+            .or_else(|| (msg.reply_count > 0).then(|| msg.ts.clone()));
+
+        let permalink = match self.slack.get_permalink(&channel_id, &msg.ts).await {
+            Ok(link) => link,
+            Err(e) => format!("(unavailable: {})", e),
+        };
 
-            // Reactions inline
-            if show_reactions && !msg.reactions.is_empty() {
-                let reaction_str: String = msg
-                    .reactions
-                    .iter()
-                    .map(|(name, count)| {
-                        let emoji = slack_emoji_to_unicode(name);
-                        if *count > 1 {
-                            format!("{}x{}", count, emoji)
-                        } else {
-                            emoji
-                        }
-                    })
-                    .collect::<Vec<_>>()
-                    .join(" ");
-                content_spans.push(Span::styled(
-                    format!("  {}", reaction_str),
-                    Style::default().fg(Color::DarkGray),
-                ));
-            }
+        let mut lines = vec![
+            format!("Channel:   {}", channel_id),
+            format!("ts:        {}", msg.ts),
+            format!("thread_ts: {}", thread_ts.as_deref().unwrap_or("(none)")),
+            format!("Permalink: {}", permalink),
+            format!("Sender:    {}", msg.sender_name),
+            format!("Replies:   {}", msg.reply_count),
+            format!("Edited:    {}", if msg.is_edited { "yes" } else { "no" }),
+        ];
+        if msg.is_deleted {
+            lines.push("Deleted".to_string());
+        }
 
-            let prefix_width = spans_width(&prefix_spans);
-            let indent = " ".repeat(prefix_width);
-            let indent_width = UnicodeWidthStr::width(indent.as_str());
-            let first_width = msg_width.saturating_sub(prefix_width);
-            let rest_width = msg_width.saturating_sub(indent_width);
-            let mut wrapped =
-                wrap_spans_hanging(&content_spans, first_width, rest_width, indent.as_str());
-            if wrapped.is_empty() {
-                wrapped.push(Vec::new());
-            }
-            let mut first_line = prefix_spans;
-            first_line.extend(wrapped.remove(0));
-            message_lines.push(Line::from(first_line));
-            for line in wrapped {
-                message_lines.push(Line::from(line));
+        if msg.files.is_empty() {
+            lines.push("Files:     (none)".to_string());
+        } else {
+            lines.push("Files:".to_string());
+            for (i, file) in msg.files.iter().enumerate() {
+                let id = msg.file_ids.get(i).filter(|id| !id.is_empty());
+                let size = file.size.map(crate::formatting::format_file_size);
+                lines.push(format!(
+                    "  {}. {} ({}{}) {}",
+                    i + 1,
+                    file.name,
+                    file.kind,
+                    size.map(|s| format!(", {}", s)).unwrap_or_default(),
+                    id.map(String::as_str).unwrap_or("")
+                ));
             }
+        }
 
-            // Show quoted/forwarded message as indented block (max 3 lines)
-            if let Some(ref fwd) = msg.forwarded_text {
-                let quote_style = Style::default()
-                    .fg(Color::DarkGray)
-                    .add_modifier(Modifier::ITALIC);
-                let quote_prefix = vec![Span::styled("│ ", quote_style)];
-                let quote_prefix_width = spans_width(&quote_prefix);
-                let quote_indent = format!("{}{}", "│ ", " ".repeat(quote_prefix_width.saturating_sub(2)));
-                let quote_first_width = msg_width.saturating_sub(quote_prefix_width);
-                let quote_rest_width =
-                    msg_width.saturating_sub(UnicodeWidthStr::width(quote_indent.as_str()));
-                let quote_spans = vec![Span::styled(fwd.as_str(), quote_style)];
-                let mut quote_lines = wrap_spans_hanging(
-                    &quote_spans,
-                    quote_first_width,
-                    quote_rest_width,
-                    quote_indent.as_str(),
-                );
-                if quote_lines.is_empty() {
-                    quote_lines.push(Vec::new());
-                }
-                if quote_lines.len() > 3 {
-                    quote_lines.truncate(3);
-                    quote_lines.push(vec![Span::styled("│ ...", quote_style)]);
-                }
-                let mut first_line = quote_prefix;
-                first_line.extend(quote_lines.remove(0));
-                message_lines.push(Line::from(first_line));
-                for line in quote_lines {
-                    message_lines.push(Line::from(line));
-                }
-            }
+        if msg.reactions.is_empty() {
+            lines.push("Reactions: (none)".to_string());
+        } else {
+            let reactions: Vec<String> = msg
+                .reactions
+                .iter()
+                .map(|(name, count)| format!(":{}: x{}", name, count))
+                .collect();
+            lines.push(format!("Reactions: {}", reactions.join("  ")));
         }
 
-        let messages = Paragraph::new(message_lines)
-            .block(messages_block);
+        self.info_popup = Some(InfoPopup {
+            title: format!("Message #{}", msg_num),
+            lines,
+        });
+        self.needs_redraw = true;
+    }
 
-        // Use ratatui's own line_count with inner width for accurate wrapping
-        // line_count adds vertical space back, so subtract it to get content lines only
-        let vertical_space = if self.show_borders { 2u16 } else { 0u16 };
-        let total_wrapped_lines = messages.line_count(msg_inner.width)
-            .saturating_sub(vertical_space as usize);
-        let max_scroll = total_wrapped_lines.saturating_sub(msg_area_height);
-        let scroll_offset = pane.scroll_offset.min(max_scroll);
+    /// Alt+C: move a cursor over the focused pane's messages to select and copy text.
+    pub fn enter_copy_mode(&mut self) {
+        self.ensure_valid_pane_idx();
+        let pane = &self.panes[self.focused_pane_idx];
+        let line_count = pane.rendered_lines.borrow().len();
+        if line_count == 0 {
+            self.set_status("Nothing to copy");
+            return;
+        }
+        let (first, height) = pane.rendered_view.get();
+        let last_visible = (first + height).min(line_count).saturating_sub(1);
+        self.copy_mode = Some(CopyMode {
+            cursor: (last_visible, 0),
+            anchor: None,
+        });
+        self.focus_on_chat_list = false;
+        self.set_status("COPY MODE: arrows/hjkl move, 0/$ line start/end, g/G top/bottom, v select, y copy, r reply in thread, Esc exit");
+        self.status_expire = None; // Stays until copy mode ends
+    }
 
-        let messages = messages.scroll((scroll_offset as u16, 0));
+    pub fn exit_copy_mode(&mut self) {
+        self.copy_mode = None;
+        self.status_message = None;
+        self.needs_redraw = true;
+    }
 
-        f.render_widget(messages, chunks[1]);
+    /// Move the copy mode cursor by lines/characters; `None` jumps to the last line or column.
+    pub fn copy_mode_move(&mut self, lines: Option<isize>, cols: Option<isize>) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        let Some(copy) = self.copy_mode.as_mut() else {
+            return;
+        };
+        let rendered = pane.rendered_lines.borrow();
+        let last_line = rendered.len().saturating_sub(1);
+        let (mut line, mut col) = copy.cursor;
+        line = match lines {
+            Some(delta) => line.saturating_add_signed(delta).min(last_line),
+            None => last_line,
+        };
+        let line_len = rendered.get(line).map_or(0, |l| l.chars().count());
+        col = match cols {
+            Some(delta) => col.saturating_add_signed(delta),
+            None => line_len.saturating_sub(1),
+        }
+        .min(line_len.saturating_sub(1));
+        drop(rendered);
+        copy.cursor = (line, col);
+
+        // Scroll so the cursor stays on screen
+        let (first, height) = pane.rendered_view.get();
+        if line < first {
+            pane.scroll_offset = line;
+        } else if height > 0 && line >= first + height {
+            pane.scroll_offset = line + 1 - height;
+        }
+        self.needs_redraw = true;
+    }
 
-        // Reply preview if present
-        if has_reply_preview {
-            if let Some(ref preview) = pane.reply_preview {
-                let reply_bar =
-                    Paragraph::new(preview.as_str()).style(Style::default().fg(Color::Yellow));
-                f.render_widget(reply_bar, chunks[2]);
-            }
+    pub fn copy_mode_toggle_selection(&mut self) {
+        if let Some(copy) = self.copy_mode.as_mut() {
+            copy.anchor = match copy.anchor {
+                Some(_) => None,
+                None => Some(copy.cursor),
+            };
+            self.needs_redraw = true;
         }
+    }
 
-        // Input
-        let input_chunk = if has_reply_preview {
-            chunks[3]
-        } else {
-            chunks[2]
+    /// Copy the selection (or the cursor's line without one) and leave copy mode.
+    pub fn copy_mode_yank(&mut self) {
+        let Some(copy) = self.copy_mode.take() else {
+            return;
         };
-        let input_style = if is_focused && !self.focus_on_chat_list {
-            Style::default().fg(Color::Green)
-        } else {
-            Style::default().fg(Color::Gray)
+        let rendered = self.panes[self.focused_pane_idx].rendered_lines.borrow().clone();
+        let ((start_line, start_col), (end_line, end_col)) = match copy.anchor {
+            Some(_) => copy.range(),
+            None => ((copy.cursor.0, 0), (copy.cursor.0, usize::MAX - 1)),
         };
+        let mut parts = Vec::new();
+        for (line_idx, line) in rendered.iter().enumerate().take(end_line + 1).skip(start_line) {
+            let from = if line_idx == start_line { start_col } else { 0 };
+            let to = if line_idx == end_line { end_col + 1 } else { usize::MAX };
+            let part: String = line.chars().skip(from).take(to.saturating_sub(from)).collect();
+            parts.push(part.trim_end().to_string());
+        }
+        let text = parts.join("\n");
 
-        // Render input with blank line above/below
-        let top_margin: u16 = 1;
-        let bottom_margin: u16 = 1;
-        let input_inner = Rect {
-            x: input_chunk.x,
-            y: input_chunk.y + top_margin,
-            width: input_chunk.width,
-            height: input_chunk.height.saturating_sub(top_margin + bottom_margin),
+        let method = crate::utils::copy_to_clipboard(&text);
+        self.set_status(&format!("Copied {} characters ({})", text.chars().count(), method));
+    }
+
+    /// Alt+1..Alt+9: react with the `slot`th of `quick_reactions` to the latest message
+    /// of the focused pane, or in copy mode to the message under the cursor.
+    pub async fn quick_react(&mut self, slot: usize) {
+        if self.reject_if_read_only() {
+            return;
+        }
+        self.ensure_valid_pane_idx();
+        let Some(emoji) = self.quick_reactions.get(slot).cloned() else {
+            self.set_status(&format!("No quick reaction {} (set quick_reactions in settings.json)", slot + 1));
+            return;
         };
-        let (cursor_line, cursor_col) = cursor_visual_pos(
-            pane.input_buffer.as_str(),
-            pane.input_cursor,
-            input_inner.width as usize,
-        );
-        let input_scroll = if input_inner.height > 0 {
-            cursor_line.saturating_sub(input_inner.height as usize - 1)
-        } else {
-            0
+        let pane = &self.panes[self.focused_pane_idx];
+        let Some(channel_id) = pane.channel_id_str.clone() else {
+            self.set_status("No channel selected");
+            return;
         };
+        let msg_idx = match &self.copy_mode {
+            Some(copy) => pane.rendered_msgs.borrow().get(copy.cursor.0).copied().flatten(),
+            // Local echoes have no Slack timestamp yet, and notices can't be reacted to
+            None => pane
+                .msg_data
+                .iter()
+                .rposition(|m| m.local_echo_id.is_none() && !m.is_system),
+        };
+        let Some(msg) = msg_idx.and_then(|idx| pane.msg_data.get(idx)) else {
+            self.set_status("No message to react to");
+            return;
+        };
+        let (ts, sender) = (msg.ts.clone(), msg.sender_name.clone());
+        if self.scope_unavailable("reactions.add", "Reacting") {
+            return;
+        }
 
-        let input = Paragraph::new(pane.input_buffer.as_str())
-            .style(input_style)
-            .wrap(Wrap { trim: false })
-            .scroll((input_scroll as u16, 0));
-
-        f.render_widget(input, input_inner);
-
-        // Set cursor position only when input is focused
-        if is_focused && !self.focus_on_chat_list {
-            let cursor_y = input_inner.y + cursor_line.saturating_sub(input_scroll) as u16;
-            let cursor_x = input_inner.x + cursor_col as u16;
-            f.set_cursor_position((cursor_x, cursor_y));
+        match self.slack.add_reaction(&channel_id, &ts, &emoji).await {
+            Ok(_) => self.set_status(&format!(
+                "Reacted {} to {}'s message",
+                slack_emoji_to_unicode(&emoji),
+                sender
+            )),
+            Err(e) => self.set_status(&format!("Failed to add reaction: {}", e)),
+        }
+        if self.copy_mode.take().is_some() {
+            self.needs_redraw = true;
         }
     }
 
-    pub fn set_status(&mut self, message: &str) {
-        self.status_message = Some(message.to_string());
-        self.status_expire = Some(std::time::Instant::now() + std::time::Duration::from_secs(3));
+    /// `/fav`: star the focused pane's conversation, or unstar it if it is starred.
+    pub async fn toggle_favorite(&mut self) {
+        self.ensure_valid_pane_idx();
+        let pane = &self.panes[self.focused_pane_idx];
+        let Some(channel_id) = pane.channel_id_str.clone() else {
+            self.set_status("No channel selected");
+            return;
+        };
+        let name = self
+            .chats
+            .iter()
+            .find(|c| c.id == channel_id)
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| pane.chat_name.clone());
+
+        let star = !self.starred.contains(&channel_id);
+        if star {
+            self.starred.channels.push(channel_id.clone());
+        } else {
+            self.starred.channels.retain(|c| *c != channel_id);
+        }
+        self.sort_chats();
         self.needs_redraw = true;
-    }
 
-    pub fn save_state(&self) -> Result<()> {
-        let state = AppState {
-            settings: crate::persistence::AppSettings {
-                show_reactions: self.show_reactions,
-                show_notifications: self.show_notifications,
-                compact_mode: self.compact_mode,
-                show_emojis: self.show_emojis,
-                show_line_numbers: self.show_line_numbers,
-                show_timestamps: self.show_timestamps,
-                show_chat_list: self.show_chat_list,
-                show_user_colors: self.show_user_colors,
-                show_borders: self.show_borders,
-                mouse_support: self.mouse_support,
-            },
-            aliases: self.aliases.clone(),
-            layout: LayoutData {
-                panes: self
-                    .panes
-                    .iter()
-                    .map(|p| crate::persistence::PaneState {
-                        chat_id: p.chat_id,
-                        channel_id: p.channel_id_str.clone(),
-                        chat_name: p.chat_name.clone(),
-                        scroll_offset: p.scroll_offset,
-                        filter_type: None,
-                        filter_value: None,
-                        thread_ts: p.thread_ts.clone(),
+        let mut status = match self.starred.save(&self.config) {
+            Ok(()) if star => format!("Starred {}", name),
+            Ok(()) => format!("Unstarred {}", name),
+            Err(e) => format!("Star changed but not saved: {}", e),
+        };
+        if self.sync_stars {
+            if let Err(e) = self.slack.set_channel_starred(&channel_id, star).await {
+                status.push_str(&format!(" (Slack sync failed: {})", e));
+            }
+        }
+        self.set_status(&status);
+    }
+
+    /// `/follow` (or `/unfollow` with `follow = false`) the thread in the focused pane.
+    pub fn set_thread_followed(&mut self, follow: bool) {
+        self.ensure_valid_pane_idx();
+        let pane = &self.panes[self.focused_pane_idx];
+        let (Some(channel_id), Some(thread_ts)) = (pane.channel_id_str.clone(), pane.thread_ts.clone())
+        else {
+            self.set_status("Not a thread pane (open one with /thread N)");
+            return;
+        };
+        let label = pane.chat_name.clone();
+
+        let followed = self.followed_threads.find(&channel_id, &thread_ts).is_some();
+        if follow == followed {
+            let state = if follow { "already" } else { "not" };
+            self.set_status(&format!("{} is {} followed", label, state));
+            return;
+        }
+        if follow {
+            self.followed_threads.threads.push(FollowedThread {
+                channel_id,
+                thread_ts,
+                label: label.clone(),
+            });
+        } else {
+            self.followed_threads
+                .threads
+                .retain(|t| !(t.channel_id == channel_id && t.thread_ts == thread_ts));
+        }
+
+        let status = match self.followed_threads.save(&self.config) {
+            Ok(()) if follow => format!("Following {}; new replies will notify you", label),
+            Ok(()) => format!("Unfollowed {}", label),
+            Err(e) => format!("Follow state changed but not saved: {}", e),
+        };
+        self.set_status(&status);
+    }
+
+    /// Look up a user ID by display name (case-insensitive, '@' optional) or raw ID.
+    pub fn find_user_id(&self, wanted: &str) -> Option<String> {
+        let name = wanted.trim_start_matches('@');
+        if self.user_name_cache.contains_key(name) {
+            return Some(name.to_string());
+        }
+        self.user_name_cache
+            .iter()
+            .find(|(_, n)| n.eq_ignore_ascii_case(name))
+            .map(|(id, _)| id.clone())
+    }
+
+    /// Open the DM with `name` in the focused pane, adding it to the sidebar if needed.
+    pub async fn open_dm(&mut self, name: &str) -> Result<()> {
+        let Some(user_id) = self.find_user_id(name) else {
+            self.set_status(&format!("Unknown user: {}", name));
+            return Ok(());
+        };
+
+        let channel_id = match self.slack.open_dm(&user_id).await {
+            Ok(id) => id,
+            Err(e) => {
+                self.set_status(&format!("Failed to open DM: {}", e));
+                return Ok(());
+            }
+        };
+
+        if !self.chats.iter().any(|c| c.id == channel_id) {
+            let section = if self.slack.is_user_bot(&user_id).await {
+                ChatSection::Bot
+            } else {
+                ChatSection::DirectMessage
+            };
+            let display = self
+                .user_name_cache
+                .get(&user_id)
+                .cloned()
+                .unwrap_or_else(|| user_id.clone());
+            self.chats.push(ChatInfo {
+                id: channel_id.clone(),
+                name: display,
+                username: Some(user_id),
+                unread: 0,
+                mentions: 0,
+                posting_restricted: false,
+                sharing: Sharing::Internal,
+                section,
+            });
+            self.sort_chats();
+        }
+
+        if let Some(idx) = self.find_chat_idx(&channel_id) {
+            self.selected_chat_idx = idx;
+            self.open_selected_chat();
+            self.focus_on_chat_list = false;
+        }
+        Ok(())
+    }
+
+    /// Unsent drafts as (channel ID, text): the open panes' composers first, then the
+    /// stashed ones, newest first.
+    fn draft_list(&self) -> Vec<(String, String)> {
+        let mut list: Vec<(String, String)> = Vec::new();
+        for pane in &self.panes {
+            if let (Some(id), None) = (&pane.channel_id_str, &pane.thread_ts) {
+                if !pane.input_buffer.trim().is_empty() && !list.iter().any(|(c, _)| c == id) {
+                    list.push((id.clone(), pane.input_buffer.clone()));
+                }
+            }
+        }
+        for draft in &self.drafts.drafts {
+            if !list.iter().any(|(c, _)| *c == draft.channel_id) {
+                list.push((draft.channel_id.clone(), draft.text.clone()));
+            }
+        }
+        list
+    }
+
+    /// The drafts to save: open panes' composers are restored into them on the next start.
+    fn drafts_with_open_panes(&self) -> Drafts {
+        let mut drafts = Drafts::default();
+        for (channel_id, text) in self.draft_list().into_iter().rev() {
+            drafts.set(&channel_id, &text);
+        }
+        drafts
+    }
+
+    /// `/drafts`: unsent messages by conversation, numbered for `/drafts N`.
+    pub fn show_drafts(&mut self) {
+        let list = self.draft_list();
+        if list.is_empty() {
+            self.set_status("No drafts");
+            return;
+        }
+        let mut lines: Vec<String> = list
+            .iter()
+            .enumerate()
+            .map(|(i, (channel_id, text))| {
+                let name = self
+                    .find_chat_idx(channel_id)
+                    .map_or(channel_id.as_str(), |idx| self.chats[idx].name.as_str());
+                format!("{}. {}: {}", i + 1, name, pin_preview(text))
+            })
+            .collect();
+        lines.push(String::new());
+        lines.push("/drafts N opens one, /drafts discard N deletes it".to_string());
+        self.info_popup = Some(InfoPopup {
+            title: "Drafts".to_string(),
+            lines,
+        });
+        self.needs_redraw = true;
+    }
+
+    /// `/drafts N`: go to draft N's conversation, in the pane that shows it if there is one.
+    pub fn open_draft(&mut self, n: usize) {
+        let Some((channel_id, _)) = n.checked_sub(1).and_then(|i| self.draft_list().into_iter().nth(i)) else {
+            self.set_status(&format!("No draft {} (/drafts lists them)", n));
+            return;
+        };
+        let open = self.panes.iter().position(|p| {
+            p.thread_ts.is_none() && p.channel_id_str.as_deref() == Some(channel_id.as_str())
+        });
+        if let Some(pane_idx) = open {
+            self.focused_pane_idx = pane_idx;
+            self.focus_on_chat_list = false;
+        } else if let Some(idx) = self.find_chat_idx(&channel_id) {
+            self.selected_chat_idx = idx;
+            self.open_selected_chat();
+        } else {
+            self.set_status("That conversation is no longer in the sidebar (/drafts discard N deletes its draft)");
+            return;
+        }
+        self.info_popup = None;
+        self.needs_redraw = true;
+    }
+
+    /// `/drafts discard N`
+    pub fn discard_draft(&mut self, n: usize) {
+        let Some((channel_id, _)) = n.checked_sub(1).and_then(|i| self.draft_list().into_iter().nth(i)) else {
+            self.set_status(&format!("No draft {} (/drafts lists them)", n));
+            return;
+        };
+        for pane in &mut self.panes {
+            if pane.thread_ts.is_none() && pane.channel_id_str.as_deref() == Some(channel_id.as_str()) {
+                pane.input_buffer.clear();
+                pane.input_cursor = 0;
+                pane.tab_complete_state = None;
+            }
+        }
+        self.drafts.take(&channel_id);
+        self.info_popup = None;
+        self.needs_redraw = true;
+        match self.drafts.save(&self.config) {
+            Ok(()) => self.set_status(&format!("Discarded draft {}", n)),
+            Err(e) => self.set_status(&format!("Discarded draft {} but not saved: {}", n, e)),
+        }
+    }
+
+    /// Convert #channel references to Slack's <#CHANNEL_ID|name> format.
+    /// Only whole words starting with '#' are considered, so URL fragments are left alone.
+    fn convert_channel_refs(&self, text: &str) -> String {
+        let is_name_char = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(hash) = rest.find('#') {
+            let at_word_start = rest[..hash]
+                .chars()
+                .next_back()
+                .or_else(|| result.chars().next_back())
+                .is_none_or(char::is_whitespace);
+            result.push_str(&rest[..hash]);
+            let after = &rest[hash + 1..];
+            let end = after.find(|c: char| !is_name_char(c)).unwrap_or(after.len());
+            let channel = (at_word_start && end > 0)
+                .then(|| {
+                    self.chats.iter().find(|c| {
+                        matches!(c.section, ChatSection::Public | ChatSection::Private)
+                            && c.name.eq_ignore_ascii_case(&after[..end])
                     })
-                    .collect(),
-                focused_pane: self.focused_pane_idx,
-                pane_tree: Some(self.pane_tree.clone()),
-            },
+                })
+                .flatten();
+            match channel {
+                Some(chat) => {
+                    result.push_str(&format!("<#{}|{}>", chat.id, chat.name));
+                    rest = &after[end..];
+                }
+                None => {
+                    result.push('#');
+                    rest = after;
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    /// Convert @username mentions to Slack's <@USER_ID> format (and #channel to <#ID|name>)
+    fn convert_mentions_to_ids(&self, text: &str) -> String {
+        let mut result = self.convert_channel_refs(text);
+        
+        // Build a reverse lookup map: name -> user_id
+        let mut name_to_id: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+        for (user_id, user_name) in &self.user_name_cache {
+            name_to_id.insert(user_name.to_lowercase(), user_id.clone());
+        }
+        
+        // Find all @mentions in the text
+        let mut offset = 0;
+        while let Some(at_pos) = result[offset..].find('@') {
+            let abs_pos = offset + at_pos;
+            let after_at = &result[abs_pos + 1..];
+            
+            // Find the end of the mention (space, punctuation, or end of string)
+            let mention_end = after_at
+                .find(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+                .unwrap_or(after_at.len());
+            
+            if mention_end > 0 {
+                let mention_name = &after_at[..mention_end];
+                let mention_lower = mention_name.to_lowercase();
+                
+                // Look up the user ID
+                if let Some(user_id) = name_to_id.get(&mention_lower) {
+                    // Replace @username with <@USER_ID>
+                    let replacement = format!("<@{}>", user_id);
+                    result.replace_range(abs_pos..abs_pos + 1 + mention_end, &replacement);
+                    offset = abs_pos + replacement.len();
+                } else {
+                    offset = abs_pos + 1;
+                }
+            } else {
+                offset = abs_pos + 1;
+            }
+        }
+        
+        result
+    }
+
+    pub async fn send_message(&mut self) -> Result<()> {
+        if self.reject_if_read_only() {
+            return Ok(());
+        }
+        self.mention_picker = None;
+        self.ensure_valid_pane_idx();
+        let pane_idx = self.focused_pane_idx;
+        // A snippet typed right before Enter hasn't been expanded by a space yet
+        if !self.panes[pane_idx].input_buffer.starts_with('/') {
+            self.panes[pane_idx].input_cursor = self.panes[pane_idx].input_buffer.len();
+            self.expand_snippet_before_cursor();
+        }
+        let input = self.panes[pane_idx].input_buffer.trim().to_string();
+
+        if input.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(code) = input.strip_prefix("/code").filter(|rest| {
+            rest.is_empty() || rest.starts_with(char::is_whitespace)
+        }) {
+            self.wrap_draft_in_code_block(pane_idx, code.trim_start_matches(' '));
+            return Ok(());
+        }
+
+        // Check if it's a command. Commands are one line, so a pasted block that
+        // happens to start with a path like /usr/bin is sent as a message.
+        if input.starts_with('/') && !input.contains('\n') {
+            let mut handler = CommandHandler::new();
+            handler.handle_command(self, &input).await?;
+            // After handle_command, pane_idx might be invalid if workspace was switched
+            // Use focused_pane_idx which is always kept valid by ensure_valid_pane_idx
+            self.ensure_valid_pane_idx();
+            let idx = self.focused_pane_idx;
+            self.panes[idx].input_buffer.clear();
+            self.panes[idx].input_cursor = 0;
+            self.panes[idx].tab_complete_state = None;
+            return Ok(());
+        }
+
+        self.send_text(pane_idx, &input, false);
+        Ok(())
+    }
+
+    /// Send `input` (aliases expanded, mentions converted) to the conversation of a pane,
+    /// with a local echo. `broadcast` also shows a thread reply in the channel.
+    pub fn send_text(&mut self, pane_idx: usize, input: &str, broadcast: bool) {
+        let input = self.aliases.expand(input, &self.alias_prefix);
+
+        let channel_id_str = self.panes[pane_idx].channel_id_str.clone();
+        let thread_ts = self.reply_thread(pane_idx);
+        // A reply armed in a channel pane shows up in the thread, not in this pane
+        let armed_reply = self.panes[pane_idx].thread_ts.is_none() && thread_ts.is_some();
+        if self.posting_restricted(&self.panes[pane_idx]) {
+            self.set_status(&format!("{} (the draft is kept)", POSTING_RESTRICTED_HINT));
+            return;
+        }
+        if let Some(channel_id) = channel_id_str {
+            // Replying means we've caught up
+            self.panes[pane_idx].first_unread_ts = None;
+            self.recent.touch(&channel_id);
+
+            // Convert @username mentions to <@USER_ID> format
+            let message_to_send = self.convert_mentions_to_ids(&input);
+
+            // Too long for chat.postMessage: the draft stays until split or uploaded
+            let length = message_to_send.chars().count();
+            if length > MAX_MESSAGE_CHARS {
+                let parts = split_message(&message_to_send, MAX_MESSAGE_CHARS).len();
+                self.set_status(&format!(
+                    "Message is {} characters (Slack allows {}): s = send as {} messages, u = upload as a snippet, Esc = keep editing",
+                    length, MAX_MESSAGE_CHARS, parts
+                ));
+                self.status_expire = None; // Stays until answered
+                self.pending_long_message = Some(LongMessage {
+                    pane_idx,
+                    input,
+                    text: message_to_send,
+                    broadcast,
+                });
+                return;
+            }
+            
+            // Local echo: Add message immediately to UI (with original text)
+            let local_echo_id = if armed_reply {
+                self.set_status("Reply sent to the thread");
+                None
+            } else {
+                Some(self.push_local_echo(pane_idx, &input))
+            };
+            self.clear_sent_draft(pane_idx, input);
+
+            let message = OutboxMessage {
+                channel_id,
+                thread_ts,
+                text: message_to_send,
+                broadcast,
+                local_echo_id,
+            };
+            if self.offline {
+                self.outbox.push(message);
+                self.set_status(&format!("Offline: queued ({} to send when reconnected)", self.outbox.len()));
+                return;
+            }
+            // ApiEvent::Sent confirms the echo
+            self.send_or_queue(vec![message]);
+        }
+    }
+
+    /// Send messages, or add them to the outbox while offline or while earlier messages
+    /// wait there, so they don't overtake those. Returns whether they were queued.
+    fn send_or_queue(&mut self, messages: Vec<OutboxMessage>) -> bool {
+        if !self.offline && self.outbox.is_empty() {
+            self.send_in_order(messages);
+            return false;
+        }
+        self.outbox.extend(messages);
+        let state = if self.offline { "Offline" } else { "Not connected" };
+        self.set_status(&format!("{}: queued ({} to send when reconnected)", state, self.outbox.len()));
+        true
+    }
+
+    /// A message Slack refused for good: drop its local echo and put the text back into
+    /// the composer of its pane, above anything typed since.
+    fn return_unsent(&mut self, message: OutboxMessage) {
+        let mut text = message.text;
+        let mut pane_idx = None;
+        if let Some(id) = message.local_echo_id {
+            for (idx, pane) in self.panes.iter_mut().enumerate() {
+                if let Some(pos) = pane.msg_data.iter().position(|m| m.local_echo_id == Some(id)) {
+                    text = pane.msg_data.remove(pos).text; // As typed, mentions not converted
+                    pane.invalidate_cache();
+                    pane_idx = Some(idx);
+                }
+            }
+        }
+        let pane_idx = pane_idx.or_else(|| {
+            self.panes
+                .iter()
+                .position(|p| p.channel_id_str.as_deref() == Some(message.channel_id.as_str()))
+        });
+        let Some(pane) = pane_idx.and_then(|idx| self.panes.get_mut(idx)) else {
+            return;
         };
+        if !pane.input_buffer.is_empty() {
+            text.push('\n');
+        }
+        pane.input_buffer.insert_str(0, &text);
+        pane.input_cursor = text.len();
+    }
 
-        state.save(&self.config)
+    /// The draft was sent: remember it, empty the composer and disarm the reply.
+    fn clear_sent_draft(&mut self, pane_idx: usize, input: String) {
+        self.input_history.push(input);
+        let pane = &mut self.panes[pane_idx];
+        pane.input_buffer.clear();
+        pane.input_cursor = 0;
+        pane.tab_complete_state = None;
+        pane.reply_to_message = None;
+        pane.hide_reply_preview();
     }
 
-    // Navigation methods
-    pub fn select_next_chat(&mut self) {
-        if !self.chats.is_empty() {
-            self.selected_chat_idx = (self.selected_chat_idx + 1).min(self.chats.len() - 1);
+    /// Long message prompt, s: post the draft as several messages, in order.
+    pub fn send_long_message_split(&mut self) {
+        let Some(long) = self.pending_long_message.take() else {
+            return;
+        };
+        self.status_message = None;
+        let Some(channel_id) = self.panes.get(long.pane_idx).and_then(|p| p.channel_id_str.clone()) else {
+            return;
+        };
+        let thread_ts = self.reply_thread(long.pane_idx);
+        let echo = self.panes[long.pane_idx].reply_to_message.is_none();
+        let messages: Vec<OutboxMessage> = split_message(&long.text, MAX_MESSAGE_CHARS)
+            .into_iter()
+            .map(|text| OutboxMessage {
+                channel_id: channel_id.clone(),
+                thread_ts: thread_ts.clone(),
+                local_echo_id: echo.then(|| self.push_local_echo(long.pane_idx, &text)),
+                text,
+                broadcast: long.broadcast,
+            })
+            .collect();
+        self.clear_sent_draft(long.pane_idx, long.input);
+        let count = messages.len();
+        if !self.send_or_queue(messages) {
+            self.set_status(&format!("Sending as {} messages", count));
+        }
+    }
+
+    /// Long message prompt, u: share the draft as a text snippet instead.
+    pub async fn upload_long_message(&mut self) {
+        let Some(long) = self.pending_long_message.take() else {
+            return;
+        };
+        let Some(channel_id) = self.panes.get(long.pane_idx).and_then(|p| p.channel_id_str.clone()) else {
+            return;
+        };
+        let thread_ts = self.reply_thread(long.pane_idx);
+        self.set_status("Uploading snippet...");
+        match self
+            .slack
+            .upload_snippet(&channel_id, &long.text, "message.txt", thread_ts.as_deref())
+            .await
+        {
+            Ok(()) => {
+                self.clear_sent_draft(long.pane_idx, long.input);
+                self.set_status("Uploaded the message as a snippet");
+            }
+            // The draft is still in the composer
+            Err(e) => self.set_status(&format!("Snippet upload failed: {}", e)),
+        }
+    }
+
+    /// Not a local command or script: offer to post it, since it may be one of the
+    /// workspace's slash commands or a workflow trigger.
+    pub fn ask_send_slack_command(&mut self, text: &str) {
+        let name = text.split_whitespace().next().unwrap_or(text);
+        if self.panes[self.focused_pane_idx].channel_id_str.is_none() {
+            self.set_status(&format!("Unknown command: {}", name));
+            return;
+        }
+        self.set_status(&format!(
+            "Unknown command {}: send it to this conversation as a message? (y = send, n/Esc = keep editing)",
+            name
+        ));
+        self.status_expire = None; // Stays until answered
+        self.pending_slack_command = Some(text.to_string());
+    }
+
+    /// Unknown command prompt, y: post it as typed.
+    pub fn send_pending_slack_command(&mut self) {
+        if let Some(text) = self.pending_slack_command.take() {
+            self.status_message = None;
+            self.send_text(self.focused_pane_idx, &text, false);
+        }
+    }
+
+    /// Unknown command prompt, n/Esc: put the command back into the composer.
+    pub fn cancel_slack_command(&mut self) {
+        if let Some(text) = self.pending_slack_command.take() {
+            self.status_message = None;
+            self.insert_text(&text);
+        }
+    }
+
+    /// Long message prompt, Esc: back to the draft.
+    pub fn cancel_long_message(&mut self) {
+        if self.pending_long_message.take().is_some() {
+            self.set_status("Not sent; shorten the message or press Enter again");
         }
     }
 
-    pub fn select_previous_chat(&mut self) {
-        if !self.chats.is_empty() {
-            self.selected_chat_idx = self.selected_chat_idx.saturating_sub(1);
+    /// Show a message we're sending in a pane until Slack confirms it; returns its echo ID.
+    fn push_local_echo(&mut self, pane_idx: usize, text: &str) -> u64 {
+        let my_name = self.user_name_cache.get(&self.my_user_id)
+            .cloned()
+            .unwrap_or_else(|| "You".to_string());
+        
+        let local_echo_id = self.next_local_echo_id;
+        self.next_local_echo_id += 1;
+        
+        let local_msg = crate::widgets::MessageData {
+            sender_name: my_name,
+            text: text.to_string(),
+            is_outgoing: true,
+            ts: format!("{}.local.{}", chrono::Local::now().timestamp(), local_echo_id),
+            reactions: Vec::new(),
+            reply_count: 0,
+            forwarded_text: None,
+            mentions_me: false,
+            matches_keyword: false,
+            local_echo_id: Some(local_echo_id),
+            is_edited: false,
+            is_deleted: false,
+            is_system: false,
+            is_bot: false,
+            media_type: None,
+            file_ids: Vec::new(),
+            file_urls: Vec::new(),
+            file_names: Vec::new(),
+            files: Vec::new(),
+        };
+        
+        self.panes[pane_idx].msg_data.push(local_msg);
+        self.panes[pane_idx].invalidate_cache();
+        self.panes[pane_idx].scroll_offset = usize::MAX;
+        self.needs_redraw = true;
+        local_echo_id
+    }
+
+    pub fn draw(&mut self, f: &mut Frame) {
+        let has_status = self.status_message.is_some();
+        
+        // Check if we have mentions in other workspaces
+        let current_workspace_name = self.config.workspaces
+            .get(self.config.active_workspace)
+            .map(|w| w.name.clone())
+            .unwrap_or_default();
+        let other_workspace_mentions: Vec<(String, u32)> = self.unread_mentions
+            .iter()
+            .filter(|(ws_name, count)| **ws_name != current_workspace_name && **count > 0)
+            .map(|(name, count)| (name.clone(), *count))
+            .collect();
+        let has_other_mentions = !other_workspace_mentions.is_empty();
+        
+        let main_constraints = if has_status && has_other_mentions {
+            vec![Constraint::Min(0), Constraint::Length(1), Constraint::Length(1)]
+        } else if has_status || has_other_mentions {
+            vec![Constraint::Min(0), Constraint::Length(1)]
+        } else {
+            vec![Constraint::Min(0)]
+        };
+
+        let outer = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(main_constraints)
+            .split(f.area());
+
+        let (chat_area, pane_area) = if self.show_chat_list {
+            // Calculate dynamic width based on longest chat name
+            let max_name_len = self.chats.iter()
+                .map(|c| {
+                    let mut prefix = if c.unread > 0 { format!("({}) ", c.unread) } else { String::new() };
+                    if c.mentions > 0 {
+                        prefix.push_str(&format!("@{} ", c.mentions));
+                    }
+                    if let Some(badge) = c.sharing.badge() {
+                        prefix.push_str(&format!("{} ", badge));
+                    }
+                    if self.huddles.contains_key(&c.id) {
+                        prefix.push_str(huddle_badge(self.show_emojis));
+                    }
+                    let emoji = match c.section {
+                        ChatSection::Public => "# ",
+                        ChatSection::Private => "🔒 ",
+                        ChatSection::DirectMessage => "👤 ",
+                        ChatSection::Group => "👥 ",
+                        ChatSection::Bot => "🤖 ",
+                    };
+                    prefix.len() + emoji.len() + c.name.len()
+                })
+                .max()
+                .unwrap_or(20);
+            
+            // Add padding for borders and some breathing room (narrower: ~2/3 of before)
+            let chat_list_width = if self.sidebar_width > 0 {
+                self.sidebar_width.min(outer[0].width / 2).max(MIN_SIDEBAR_WIDTH)
+            } else {
+                (max_name_len + 4).clamp(10, 27) as u16
+            };
+            
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(chat_list_width), Constraint::Min(0)])
+                .split(outer[0]);
+            (Some(chunks[0]), chunks[1])
+        } else {
+            (None, outer[0])
+        };
+
+        if let Some(area) = chat_area {
+            self.chat_list_area = Some(area);
+            self.draw_chat_list(f, area);
+        } else {
+            self.chat_list_area = None;
+        }
+
+        // Draw panes
+        self.pane_tree_area = Some(pane_area);
+        let render_fn = |f: &mut Frame, area: Rect, pane: &ChatPane, is_focused: bool| {
+            self.draw_chat_pane_impl(f, area, pane, is_focused);
+        };
+
+        let mut pane_areas = std::collections::HashMap::new();
+        let zoomed_tree;
+        let tree = if self.pane_zoomed {
+            zoomed_tree = PaneNode::Single(self.focused_pane_idx);
+            &zoomed_tree
+        } else {
+            &self.pane_tree
+        };
+        tree.render(
+            f,
+            pane_area,
+            &self.panes,
+            self.focused_pane_idx,
+            &render_fn,
+            &mut pane_areas,
+        );
+        self.pane_areas = pane_areas;
+
+        // Jumps to a message (search, first unread) are resolved to a line offset while drawing
+        for pane in &mut self.panes {
+            if let Some(offset) = pane.resolved_scroll.take() {
+                pane.scroll_offset = offset;
+                pane.scroll_to_msg = None;
+            }
+            if pane.at_bottom.get() {
+                pane.new_below = 0;
+            }
+        }
+
+        // Draw notification bar for mentions in other workspaces
+        if has_other_mentions {
+            let mention_text: String = other_workspace_mentions
+                .iter()
+                .map(|(name, count)| format!("{}: {}@", name, count))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            let notification = Paragraph::new(format!(" Mentions in other workspaces: {} (Ctrl+N to switch)", mention_text))
+                .style(Style::default().bg(Color::Yellow).fg(Color::Black).add_modifier(Modifier::BOLD))
+                .block(Block::default());
+            let notification_idx = if has_status { outer.len() - 2 } else { outer.len() - 1 };
+            f.render_widget(notification, outer[notification_idx]);
+        }
+
+        // Draw status bar
+        if has_status {
+            let status = Paragraph::new(self.status_message.as_ref().unwrap().clone())
+                .style(Style::default().bg(Color::DarkGray).fg(Color::White))
+                .block(Block::default());
+            f.render_widget(status, outer[outer.len() - 1]);
+        }
+
+        if let Some(popup) = &self.info_popup {
+            Self::draw_info_popup(f, outer[0], popup);
+        }
+        if let Some(browser) = &self.file_browser {
+            Self::draw_file_browser(f, outer[0], browser);
+        }
+        if let Some(picker) = &self.user_picker {
+            Self::draw_user_picker(f, outer[0], picker);
+        }
+        if let Some(prompt) = &self.workspace_prompt {
+            Self::draw_workspace_prompt(f, outer[0], prompt);
+        }
+    }
+
+    /// `/workspace add|rename` input box; tokens are drawn as dots. Also used by first-run setup.
+    pub fn draw_workspace_prompt(f: &mut Frame, area: Rect, prompt: &WorkspacePrompt) {
+        let (title, label, secret) = match prompt.step {
+            WorkspaceStep::Name => ("Add workspace (1/3)", "Workspace name:", false),
+            WorkspaceStep::Token => ("Add workspace (2/3)", "Token (xoxp-... or xoxb-...):", true),
+            WorkspaceStep::AppToken => (
+                "Add workspace (3/3)",
+                "App token (xapp-..., empty to poll without Socket Mode):",
+                true,
+            ),
+            WorkspaceStep::Rename(_) => ("Rename workspace", "New name:", false),
+            WorkspaceStep::Reauth(_) => ("Sign in again", "New token (xoxp-... or xoxb-...):", true),
+        };
+        let shown = if secret {
+            "•".repeat(prompt.input.chars().count())
+        } else {
+            prompt.input.clone()
+        };
+
+        let mut lines = vec![
+            Line::from(label),
+            Line::from(vec![
+                Span::raw(format!("> {}", shown)),
+                Span::styled("█", Style::default().fg(Color::Cyan)),
+            ]),
+        ];
+        if let Some(error) = &prompt.error {
+            lines.push(Line::from(Span::styled(error.clone(), Style::default().fg(Color::Red))));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Enter continue, Esc cancel",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        let width = area.width.saturating_sub(4).min(70);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let rect = Rect {
+            x: area.x + (area.width.saturating_sub(width)) / 2,
+            y: area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        };
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(format!(" {} ", title)),
+        );
+        f.render_widget(Clear, rect);
+        f.render_widget(paragraph, rect);
+    }
+
+    /// Popup just above the composer, inside the pane's `area`.
+    fn draw_mention_picker(f: &mut Frame, area: Rect, input: Rect, picker: &MentionPicker) {
+        let height = (picker.users.len() as u16 + 2).min(input.y.saturating_sub(area.y));
+        if height < 3 {
+            return;
+        }
+        let width = area.width.min(70);
+        let rect = Rect {
+            x: area.x,
+            y: input.y - height,
+            width,
+            height,
+        };
+
+        let lines: Vec<Line> = picker
+            .users
+            .iter()
+            .enumerate()
+            .map(|(i, user)| {
+                let detail = [user.real_name.as_str(), user.title.as_str()]
+                    .iter()
+                    .filter(|s| !s.is_empty() && **s != user.name)
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(" - ");
+                let style = if i == picker.selected {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                Line::from(vec![
+                    Span::styled(format!(" @{} ", user.name), style.add_modifier(Modifier::BOLD)),
+                    Span::styled(detail, style.fg(Color::DarkGray)),
+                ])
+            })
+            .collect();
+
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(" Mention (Up/Down, Enter/Tab insert, Esc close) "),
+        );
+        f.render_widget(Clear, rect);
+        f.render_widget(paragraph, rect);
+    }
+
+    fn draw_user_picker(f: &mut Frame, area: Rect, picker: &UserPicker) {
+        let width = area.width.saturating_sub(4).min(100);
+        let height = ((picker.users.len() + 4) as u16).min(area.height);
+        let rect = Rect {
+            x: area.x + (area.width.saturating_sub(width)) / 2,
+            y: area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        };
+
+        // Keep the selection visible: rows minus borders, blank line and hint
+        let visible = (height as usize).saturating_sub(4).max(1);
+        let first = picker.selected.saturating_sub(visible - 1);
+        let mut lines: Vec<Line> = picker
+            .users
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(visible)
+            .map(|(i, user)| {
+                let (dot, dot_color) = match user.presence.as_deref() {
+                    Some("active") => ("●", Color::Green),
+                    Some(_) => ("○", Color::DarkGray),
+                    None => (" ", Color::DarkGray),
+                };
+                let name: String = user.name.chars().take(20).collect();
+                let real_name: String = user.real_name.chars().take(24).collect();
+                let title: String = user.title.chars().take(24).collect();
+                let row = format!(" {:<20} {:<24} {:<24} {}", name, real_name, title, user.email);
+                let style = if i == picker.selected {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                Line::from(vec![
+                    Span::styled(dot, Style::default().fg(dot_color)),
+                    Span::styled(row, style),
+                ])
+            })
+            .collect();
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Up/Down select, Enter open DM, Esc close",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(format!(" Users matching \"{}\" ({}) ", picker.query, picker.users.len())),
+        );
+        f.render_widget(Clear, rect);
+        f.render_widget(paragraph, rect);
+    }
+
+    fn draw_file_browser(f: &mut Frame, area: Rect, browser: &FileBrowser) {
+        let width = area.width.saturating_sub(4).min(100);
+        let height = ((browser.files.len() + 4) as u16).min(area.height);
+        let rect = Rect {
+            x: area.x + (area.width.saturating_sub(width)) / 2,
+            y: area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        };
+
+        // Keep the selection visible: rows minus borders, blank line and hint
+        let visible = (height as usize).saturating_sub(4).max(1);
+        let first = browser.selected.saturating_sub(visible - 1);
+        let mut lines: Vec<Line> = browser
+            .files
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(visible)
+            .map(|(i, file)| {
+                let name: String = file.name.chars().take(36).collect();
+                let kind: String = file.kind.chars().take(12).collect();
+                let row = format!(
+                    "{:<36} {:<12} {:>9}  {:<16} {}",
+                    name,
+                    kind,
+                    format_file_size(file.size),
+                    file.uploader,
+                    file.date
+                );
+                if i == browser.selected {
+                    Line::from(Span::styled(row, Style::default().add_modifier(Modifier::REVERSED)))
+                } else {
+                    Line::from(row)
+                }
+            })
+            .collect();
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Up/Down select, Enter download and open, Esc close",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(format!(" {} ({}) ", browser.title, browser.files.len())),
+        );
+        f.render_widget(Clear, rect);
+        f.render_widget(paragraph, rect);
+    }
+
+    /// Centered bordered box over `area`; wrapped lines are clipped to the terminal.
+    fn draw_info_popup(f: &mut Frame, area: Rect, popup: &InfoPopup) {
+        let width = area.width.saturating_sub(4).min(70);
+        let inner_width = width.saturating_sub(2).max(1) as usize;
+        let text_rows: usize = popup
+            .lines
+            .iter()
+            .map(|l| (l.width().max(1)).div_ceil(inner_width))
+            .sum();
+        // Text, blank line, hint, and the two borders
+        let height = ((text_rows + 4) as u16).min(area.height);
+        let rect = Rect {
+            x: area.x + (area.width.saturating_sub(width)) / 2,
+            y: area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        };
+
+        let mut lines: Vec<Line> = popup.lines.iter().map(|l| Line::from(l.clone())).collect();
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Esc to close",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        let paragraph = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan))
+                    .title(format!(" {} ", popup.title)),
+            );
+        f.render_widget(Clear, rect);
+        f.render_widget(paragraph, rect);
+    }
+
+    /// Chat indices in the order the sidebar lists them.
+    fn sidebar_order(&self) -> Vec<usize> {
+        let rows = self.build_chat_list_rows();
+        (0..rows.len()).filter_map(|row| Self::row_to_chat_idx(&rows, row)).collect()
+    }
+
+    /// Build the display rows for the chat list: Recent, Starred and New on top.
+    fn build_chat_list_rows(&self) -> Vec<ChatListRow> {
+        let sections = [
+            ChatSection::Public,
+            ChatSection::Private,
+            ChatSection::Group,
+            ChatSection::DirectMessage,
+            ChatSection::Bot,
+        ];
+
+        let mut rows: Vec<ChatListRow> = Vec::new();
+
+        // Recent section: the last chats opened or written to, newest first
+        let recent_chats: Vec<usize> = self
+            .recent
+            .channels
+            .iter()
+            .filter_map(|id| self.chats.iter().position(|c| c.id == *id))
+            .take(self.recent_chats)
+            .collect();
+        if !recent_chats.is_empty() {
+            rows.push(ChatListRow::Header("Recent".to_string()));
+            for &idx in &recent_chats {
+                rows.push(ChatListRow::Chat(idx));
+            }
+        }
+
+        // Starred section (/fav), read or not
+        let mut starred_chats: Vec<usize> = self
+            .chats
+            .iter()
+            .enumerate()
+            .filter(|(i, c)| self.starred.contains(&c.id) && !recent_chats.contains(i))
+            .map(|(i, _)| i)
+            .collect();
+        self.sidebar_order.sort(&mut starred_chats, &self.chats);
+        if !starred_chats.is_empty() {
+            rows.push(ChatListRow::Header("Starred".to_string()));
+            for idx in starred_chats {
+                rows.push(ChatListRow::Chat(idx));
+            }
+        }
+
+        // New section (unread > 0), chats that mention us first
+        let mut new_chats: Vec<usize> = self
+            .chats
+            .iter()
+            .enumerate()
+            .filter(|(i, c)| c.unread > 0 && !self.starred.contains(&c.id) && !recent_chats.contains(i))
+            .map(|(i, _)| i)
+            .collect();
+        new_chats.sort_by_key(|&i| self.chats[i].mentions == 0);
+        if !new_chats.is_empty() {
+            rows.push(ChatListRow::Header("New".to_string()));
+            for idx in new_chats {
+                rows.push(ChatListRow::Chat(idx));
+            }
+        }
+
+        // Regular sections with only read chats
+        for section in &sections {
+            let mut section_chats: Vec<usize> = self
+                .chats
+                .iter()
+                .enumerate()
+                .filter(|(i, c)| {
+                    c.section == *section
+                        && c.unread == 0
+                        && !self.starred.contains(&c.id)
+                        && !recent_chats.contains(i)
+                })
+                .map(|(i, _)| i)
+                .collect();
+            self.sidebar_order.sort(&mut section_chats, &self.chats);
+
+            if section_chats.is_empty() {
+                continue;
+            }
+
+            rows.push(ChatListRow::Header(section.label().to_string()));
+            for idx in section_chats {
+                rows.push(ChatListRow::Chat(idx));
+            }
+        }
+        rows
+    }
+
+    /// `/move up|down`: move the selected chat within its sidebar section. Recent and
+    /// New are ordered by activity, so chats there can't be moved.
+    pub fn move_selected_chat(&mut self, up: bool) {
+        let rows = self.build_chat_list_rows();
+        let row = self.chat_idx_to_row(&rows, self.selected_chat_idx);
+        if !matches!(rows.get(row), Some(ChatListRow::Chat(idx)) if *idx == self.selected_chat_idx) {
+            self.set_status("No chat selected");
+            return;
+        }
+        let start = rows[..row]
+            .iter()
+            .rposition(|r| matches!(r, ChatListRow::Header(_)))
+            .unwrap_or(0);
+        let label = match &rows[start] {
+            ChatListRow::Header(label) => label.clone(),
+            ChatListRow::Chat(_) => String::new(),
+        };
+        if label == "Recent" || label == "New" {
+            self.set_status(&format!("Chats in {} are ordered by activity", label));
+            return;
+        }
+        let end = rows[row..]
+            .iter()
+            .position(|r| matches!(r, ChatListRow::Header(_)))
+            .map_or(rows.len(), |n| row + n);
+        let mut channel_ids: Vec<String> = (start..end)
+            .filter_map(|r| Self::row_to_chat_idx(&rows, r))
+            .map(|idx| self.chats[idx].id.clone())
+            .collect();
+        let pos = row - start - 1;
+        let target = if up {
+            pos.checked_sub(1)
+        } else {
+            Some(pos + 1).filter(|&p| p < channel_ids.len())
+        };
+        let Some(target) = target else {
+            let edge = if up { "top" } else { "bottom" };
+            self.set_status(&format!("Already at the {} of {}", edge, label));
+            return;
+        };
+        channel_ids.swap(pos, target);
+        self.sidebar_order.arrange(&channel_ids);
+        self.needs_redraw = true;
+        if let Err(e) = self.sidebar_order.save(&self.config) {
+            self.set_status(&format!("Moved but not saved: {}", e));
+        }
+    }
+
+    /// Find the display row index for a given chat index.
+    fn chat_idx_to_row(&self, rows: &[ChatListRow], chat_idx: usize) -> usize {
+        rows.iter()
+            .position(|r| matches!(r, ChatListRow::Chat(idx) if *idx == chat_idx))
+            .unwrap_or(0)
+    }
+
+    /// Find the chat index from a display row click.
+    fn row_to_chat_idx(rows: &[ChatListRow], row: usize) -> Option<usize> {
+        rows.get(row).and_then(|r| match r {
+            ChatListRow::Chat(idx) => Some(*idx),
+            _ => None,
+        })
+    }
+
+    fn draw_chat_list(&mut self, f: &mut Frame, area: Rect) {
+        let visible_height = area.height.saturating_sub(2) as usize;
+        if visible_height == 0 {
+            return;
+        }
+
+        let rows = self.build_chat_list_rows();
+        let selected_row = self.chat_idx_to_row(&rows, self.selected_chat_idx);
+
+        // Ensure scroll offset keeps selected row visible
+        if selected_row < self.chat_list_scroll_offset {
+            self.chat_list_scroll_offset = selected_row;
+        } else if selected_row >= self.chat_list_scroll_offset + visible_height {
+            self.chat_list_scroll_offset = selected_row + 1 - visible_height;
+        }
+
+        let items: Vec<ListItem> = rows
+            .iter()
+            .enumerate()
+            .skip(self.chat_list_scroll_offset)
+            .take(visible_height)
+            .map(|(_, row)| match row {
+                ChatListRow::Header(label) => ListItem::new(Line::from(Span::styled(
+                    format!("-- {} --", label),
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::BOLD),
+                ))),
+                ChatListRow::Chat(chat_idx) => {
+                    let chat = &self.chats[*chat_idx];
+                    let mut style = if *chat_idx == self.selected_chat_idx {
+                        Style::default().bg(Color::Blue).fg(Color::White)
+                    } else if chat.unread > 0 {
+                        Style::default().fg(Color::Red)
+                    } else {
+                        Style::default()
+                    };
+
+                    if chat.unread > 0 && *chat_idx == self.selected_chat_idx {
+                        style = style.add_modifier(Modifier::BOLD);
+                    }
+
+                    let unread_marker = if chat.unread > 0 {
+                        format!(" ({})", chat.unread)
+                    } else {
+                        String::new()
+                    };
+
+                    let mut spans = vec![];
+                    if self.screen_reader && *chat_idx == self.selected_chat_idx {
+                        // The selection is otherwise only a background color
+                        spans.push(Span::raw("> "));
+                    }
+                    if chat.unread > 0 {
+                        spans.push(Span::styled(
+                            "! ",
+                            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                        ));
+                    } else {
+                        spans.push(Span::raw("  "));
+                    }
+                    spans.push(Span::raw(format!("{}{}", chat.name, unread_marker)));
+                    if chat.mentions > 0 {
+                        spans.push(Span::styled(
+                            format!(" @{}", chat.mentions),
+                            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                        ));
+                    }
+                    if self.huddles.contains_key(&chat.id) {
+                        spans.push(Span::styled(huddle_badge(self.show_emojis), Style::default().fg(Color::Green)));
+                    }
+                    if let Some(badge) = chat.sharing.badge() {
+                        let color = if chat.sharing == Sharing::External { Color::Magenta } else { Color::DarkGray };
+                        spans.push(Span::styled(format!(" {}", badge), Style::default().fg(color)));
+                    }
+
+                    ListItem::new(Line::from(spans)).style(style)
+                }
+            })
+            .collect();
+
+        let list_block = if self.borders_shown() {
+            Block::default()
+                .borders(Borders::ALL)
+                .title(if self.focus_on_chat_list {
+                    "Channels [FOCUSED]"
+                } else {
+                    "Channels"
+                })
+                .border_style(if self.focus_on_chat_list {
+                    Style::default().fg(Color::Cyan)
+                } else {
+                    Style::default()
+                })
+        } else {
+            Block::default()
+        };
+        let list = List::new(items).block(list_block);
+
+        f.render_widget(list, area);
+    }
+
+    fn draw_chat_pane_impl(&self, f: &mut Frame, area: Rect, pane: &ChatPane, is_focused: bool) {
+        let has_reply_preview = pane.reply_preview.is_some();
+        let posting_restricted = self.posting_restricted(pane);
+        let header_height = if !self.borders_shown() || self.compact_mode { 2 } else { 3 };
+        // top margin + 1 line + bottom margin; hidden entirely in read-only mode
+        let input_height: u16 = if self.read_only { 0 } else { 3 };
+        let constraints = if has_reply_preview {
+            vec![
+                Constraint::Length(header_height),
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(input_height),
+            ]
+        } else {
+            vec![
+                Constraint::Length(header_height),
+                Constraint::Min(0),
+                Constraint::Length(input_height),
+            ]
+        };
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        // Header
+        let header_style = if is_focused {
+            if self.focus_on_chat_list {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD)
+            }
+        } else {
+            Style::default().fg(Color::Cyan)
+        };
+
+        let mut header_text = String::new();
+        if is_focused && self.focus_on_chat_list {
+            header_text.push_str("[TARGET] ");
+        } else if is_focused && self.screen_reader {
+            // Focus is otherwise only shown by the header color
+            header_text.push_str("[FOCUSED] ");
+        }
+        if self.read_only {
+            header_text.push_str("[READ-ONLY] ");
+        } else if posting_restricted {
+            header_text.push_str("[POSTING RESTRICTED] ");
+        }
+        let sharing = pane
+            .channel_id_str
+            .as_ref()
+            .and_then(|id| self.chats.iter().find(|c| c.id == *id))
+            .map_or(Sharing::Internal, |c| c.sharing);
+        if let Some(tag) = sharing.header_tag() {
+            header_text.push_str(tag);
+        }
+        let huddle = pane.channel_id_str.as_ref().and_then(|id| self.huddles.get(id));
+        if let (Some(&count), None) = (huddle, &pane.thread_ts) {
+            let people = if count == 1 { "participant" } else { "participants" };
+            let icon = if self.show_emojis { "🎧 " } else { "" };
+            header_text.push_str(&format!("{}huddle in progress ({} {}) ", icon, count, people));
+        }
+        if pane.loading {
+            header_text.push_str("[LOADING] ");
+        }
+        if self.offline {
+            header_text.push_str("[OFFLINE] ");
+        } else if self.is_polling() {
+            header_text.push_str("[POLLING] ");
+        }
+        header_text.push_str(&pane.header_text());
+        if is_focused {
+            header_text.push_str(&self.realtime_status_text());
+        }
+
+        let header = Paragraph::new(header_text)
+            .block(if self.borders_shown() {
+                Block::default().borders(Borders::ALL)
+            } else {
+                Block::default()
+            })
+            .style(header_style);
+        f.render_widget(header, chunks[0]);
+
+        let messages_block = if self.borders_shown() {
+            Block::default().borders(Borders::ALL).title("Messages")
+        } else {
+            Block::default().padding(Padding::left(2))
+        };
+        let msg_inner = messages_block.inner(chunks[1]);
+        let msg_width = msg_inner.width as usize;
+
+        let show_emojis = self.show_emojis;
+        let show_reactions = self.show_reactions;
+        let show_line_numbers = self.show_line_numbers;
+        let show_timestamps = self.show_timestamps;
+        let show_user_colors = self.show_user_colors && !self.screen_reader;
+        let show_system_messages = self.show_system_messages;
+        let show_link_labels = self.show_link_labels;
+        let show_jumbo = self.jumbo_emoji && show_emojis;
+        let highlight_styles = &self.highlight_styles;
+        let user_cache = &self.user_name_cache;
+        let resolve_user = |id: &str| -> String {
+            user_cache
+                .get(id)
+                .cloned()
+                .unwrap_or_else(|| id.to_string())
+        };
+        let now = Local::now();
+        let format_ts = |ts: &str| -> Option<String> {
+            if !show_timestamps {
+                return None;
+            }
+            format_timestamp(ts, self.timestamp_style, &self.timestamp_format, now)
+        };
+
+        // Search hits: every occurrence is marked, the current match stands out
+        let search_query = pane
+            .search
+            .as_ref()
+            .map(|s| s.query.as_str())
+            .filter(|q| !q.is_empty());
+        let current_match = pane.search.as_ref().and_then(|s| s.current_msg());
+        let mut target_line: Option<usize> = None;
+        let first_unread = pane
+            .first_unread_ts
+            .as_ref()
+            .and_then(|ts| pane.msg_data.iter().position(|m| &m.ts == ts));
+        // Mentions (`@Alice`, `#general`, `@here`) and link labels stand out from the text around them
+        let highlight_matches = |text: &str,
+                                 base: Style,
+                                 idx: usize,
+                                 mentions: &[String],
+                                 links: &[(String, String)]|
+         -> Vec<Span<'static>> {
+            let hit_style = if current_match == Some(idx) {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::White).bg(Color::Blue)
+            };
+            let mut marked: Vec<String> = mentions.to_vec();
+            marked.extend(links.iter().map(|(label, _)| label.clone()));
+            let mut spans = Vec::new();
+            for (part, is_marked) in split_labels(text, &marked) {
+                let style = if !is_marked {
+                    base
+                } else if links.iter().any(|(label, _)| label == part) {
+                    base.fg(Color::Blue).add_modifier(Modifier::UNDERLINED)
+                } else {
+                    base.fg(Color::LightBlue).add_modifier(Modifier::BOLD)
+                };
+                match search_query {
+                    Some(query) => spans.extend(split_matches(part, query).into_iter().map(
+                        |(part, hit)| Span::styled(part.to_string(), if hit { hit_style } else { style }),
+                    )),
+                    None => spans.push(Span::styled(part.to_string(), style)),
+                }
+            }
+            spans
+        };
+
+        let filter = PaneFilter::from_pane(pane);
+        let thread_ts = pane.thread_ts.as_deref();
+
+        // Wrapped lines of one message. System notices have no click targets (urls = None).
+        let render_message = |idx: usize, msg: &MessageData| -> CachedMessage {
+            let mut lines: Vec<Line<'static>> = Vec::new();
+            // Join/leave/topic notices: one dimmed line, no sender column
+            if msg.is_system {
+                let system_style = Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC);
+                let mut prefix_spans = Vec::new();
+                if show_line_numbers {
+                    prefix_spans.push(Span::styled(format!("#{} ", idx + 1), system_style));
+                }
+                if let Some(ts_fmt) = format_ts(&msg.ts) {
+                    prefix_spans.push(Span::styled(format!("[{}] ", ts_fmt), system_style));
+                }
+                prefix_spans.push(Span::styled("* ", system_style));
+
+                // "<@U1> has joined the channel" names the user already, older subtypes don't
+                let text = format_message_text(&msg.text, show_emojis, show_link_labels, &resolve_user);
+                let text = if text.starts_with('@') {
+                    text
+                } else {
+                    format!("{} {}", msg.sender_name, text)
+                };
+
+                let prefix_width = spans_width(&prefix_spans);
+                let indent = " ".repeat(prefix_width);
+                let mut wrapped = wrap_spans_hanging(
+                    &highlight_matches(&text, system_style, idx, &[], &[]),
+                    msg_width.saturating_sub(prefix_width),
+                    msg_width.saturating_sub(prefix_width),
+                    indent.as_str(),
+                );
+                let mut first_line = prefix_spans;
+                first_line.extend(wrapped.remove(0));
+                lines.push(Line::from(first_line));
+                lines.extend(wrapped.into_iter().map(Line::from));
+                return CachedMessage { idx, lines, urls: None };
+            }
+
+            let name_style = if msg.is_outgoing {
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD)
+            };
+
+            let formatted_text =
+                format_message_text(&msg.text, show_emojis, show_link_labels, &resolve_user);
+            let mut urls: Vec<(String, String)> = formatted_text
+                .split_whitespace()
+                .filter(|w| w.starts_with("http://") || w.starts_with("https://"))
+                .map(|w| (w.to_string(), w.to_string()))
+                .collect();
+            // Labels are emoji-converted like the text around them
+            let links: Vec<(String, String)> = if show_link_labels {
+                link_labels(&msg.text)
+                    .into_iter()
+                    .map(|(label, url)| {
+                        let label = if show_emojis { convert_slack_emojis(&label) } else { label };
+                        (label, url)
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            urls.extend(links.iter().cloned());
+
+            let mut prefix_spans = Vec::new();
+
+            // Add highlight indicator if message mentions the user or a highlight keyword
+            if msg.mentions_me || msg.matches_keyword {
+                prefix_spans.push(Span::styled(
+                    "@ ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+
+            // Add deleted indicator
+            if msg.is_deleted {
+                prefix_spans.push(Span::styled(
+                    "[DELETED] ",
+                    Style::default()
+                        .fg(Color::Red)
+                        .add_modifier(Modifier::ITALIC),
+                ));
+            }
+
+            if show_line_numbers {
+                prefix_spans.push(Span::styled(
+                    format!("#{} ", idx + 1),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+
+            if let Some(ts_fmt) = format_ts(&msg.ts) {
+                prefix_spans.push(Span::styled(
+                    format!("[{}] ", ts_fmt),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+
+            // Use color-coded username for better visual distinction
+            let username_style = if msg.is_outgoing {
+                name_style  // Keep own messages with original style
+            } else if show_user_colors {
+                Style::default()
+                    .fg(username_color(&msg.sender_name))
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                name_style  // Use default style if colors are disabled
+            };
+            prefix_spans.push(Span::styled(
+                format!("{}: ", msg.sender_name),
+                username_style,
+            ));
+
+            // Emoji-only messages stand out like the desktop client's jumbomoji:
+            // bold, spaced apart, with a blank line above and below
+            let jumbo = if show_jumbo && !msg.is_deleted && msg.forwarded_text.is_none() {
+                jumbo_emoji(&formatted_text).map(|emoji| emoji.join("   "))
+            } else {
+                None
+            };
+            let mut content_spans = match &jumbo {
+                Some(text) => {
+                    lines.push(Line::default());
+                    let style = Style::default().add_modifier(Modifier::BOLD);
+                    highlight_matches(&format!(" {}", text), style, idx, &[], &[])
+                }
+                None => {
+                    // The first of the user's highlight rules that matches a line styles that line
+                    let mentions = mention_labels(&msg.text, &resolve_user);
+                    let mut spans = Vec::new();
+                    for (n, line) in formatted_text.split('\n').enumerate() {
+                        if n > 0 {
+                            spans.push(Span::raw("\n"));
+                        }
+                        let base = highlight_styles
+                            .iter()
+                            .find(|(regex, _)| regex.is_match(line))
+                            .map_or_else(Style::default, |(_, style)| *style);
+                        spans.extend(highlight_matches(line, base, idx, &mentions, &links));
+                    }
+                    spans
+                }
+            };
+            // Add media indicator
+            if let Some(ref media_type) = msg.media_type {
+                let indicator = match media_type.as_str() {
+                    "image" => "[img]",
+                    "video" => "[video]",
+                    _ => "",
+                };
+                if !indicator.is_empty() {
+                    content_spans.push(Span::styled(
+                        format!(" {}", indicator),
+                        Style::default()
+                            .fg(Color::Blue)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                }
+            }
+
+            // Add edited indicator
+            if msg.is_edited && !msg.is_deleted {
+                content_spans.push(Span::styled(
+                    " (edited)",
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::ITALIC),
+                ));
+            }
+
+            // Thread reply indicator; a thread's parent has its count in the pane header
+            if msg.reply_count > 0 && thread_ts != Some(msg.ts.as_str()) {
+                content_spans.push(Span::styled(
+                    format!(" [{} replies]", msg.reply_count),
+                    Style::default()
+                        .fg(Color::Magenta)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+
+            // Reactions inline
+            if show_reactions && !msg.reactions.is_empty() {
+                let reaction_str: String = msg
+                    .reactions
+                    .iter()
+                    .map(|(name, count)| {
+                        let emoji = slack_emoji_to_unicode(name);
+                        if *count > 1 {
+                            format!("{}x{}", count, emoji)
+                        } else {
+                            emoji
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                content_spans.push(Span::styled(
+                    format!("  {}", reaction_str),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+
+            let prefix_width = spans_width(&prefix_spans);
+            let indent = " ".repeat(prefix_width);
+            let indent_width = UnicodeWidthStr::width(indent.as_str());
+            let first_width = msg_width.saturating_sub(prefix_width);
+            let rest_width = msg_width.saturating_sub(indent_width);
+            let mut wrapped =
+                wrap_spans_hanging(&content_spans, first_width, rest_width, indent.as_str());
+            if wrapped.is_empty() {
+                wrapped.push(Vec::new());
+            }
+            let mut first_line = prefix_spans;
+            first_line.extend(wrapped.remove(0));
+            lines.push(Line::from(first_line));
+            for line in wrapped {
+                lines.push(Line::from(line));
+            }
+            if jumbo.is_some() {
+                lines.push(Line::default());
+            }
+
+            // Show quoted/forwarded message as indented block (max 3 lines)
+            if let Some(ref fwd) = msg.forwarded_text {
+                let quote_style = Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC);
+                let quote_prefix = vec![Span::styled("│ ", quote_style)];
+                let quote_prefix_width = spans_width(&quote_prefix);
+                let quote_indent = format!("{}{}", "│ ", " ".repeat(quote_prefix_width.saturating_sub(2)));
+                let quote_first_width = msg_width.saturating_sub(quote_prefix_width);
+                let quote_rest_width =
+                    msg_width.saturating_sub(UnicodeWidthStr::width(quote_indent.as_str()));
+                let quote_spans = vec![Span::styled(fwd.clone(), quote_style)];
+                let mut quote_lines = wrap_spans_hanging(
+                    &quote_spans,
+                    quote_first_width,
+                    quote_rest_width,
+                    quote_indent.as_str(),
+                );
+                if quote_lines.is_empty() {
+                    quote_lines.push(Vec::new());
+                }
+                if quote_lines.len() > 3 {
+                    quote_lines.truncate(3);
+                    quote_lines.push(vec![Span::styled("│ ...", quote_style)]);
+                }
+                let mut first_line = quote_prefix;
+                first_line.extend(quote_lines.remove(0));
+                lines.push(Line::from(first_line));
+                for line in quote_lines {
+                    lines.push(Line::from(line));
+                }
+            }
+
+            // One line per attached file, with the /media reference that downloads it
+            let file_style = Style::default().fg(Color::Blue);
+            for (file_idx, file) in msg.files.iter().enumerate() {
+                let mut details: Vec<String> = Vec::new();
+                if !file.kind.is_empty() {
+                    details.push(file.kind.clone());
+                }
+                if let Some(size) = file.size {
+                    details.push(format_file_size(size));
+                }
+                let reference = if file_idx == 0 {
+                    format!("[#{}]", idx + 1)
+                } else {
+                    format!("[#{} {}]", idx + 1, file_idx + 1)
+                };
+                let icon = if show_emojis { "📎" } else { "[file]" };
+                let text = if details.is_empty() {
+                    format!("{} {} {}", icon, file.name, reference)
+                } else {
+                    format!("{} {} ({}) {}", icon, file.name, details.join(", "), reference)
+                };
+                let wrapped = wrap_spans_hanging(
+                    &[Span::styled(text, file_style)],
+                    msg_width.saturating_sub(indent_width),
+                    msg_width.saturating_sub(indent_width),
+                    indent.as_str(),
+                );
+                for (i, line) in wrapped.into_iter().enumerate() {
+                    let mut spans = Vec::new();
+                    if i == 0 {
+                        spans.push(Span::raw(indent.clone()));
+                    }
+                    spans.extend(line);
+                    lines.push(Line::from(spans));
+                }
+            }
+            CachedMessage { idx, lines, urls: Some(urls) }
+        };
+
+        // Wrapping is the expensive part, so it is cached per message (by ts) and only
+        // redone when the message changes or something that affects every message does
+        let cache_key = {
+            let mut hasher = DefaultHasher::new();
+            msg_width.hash(&mut hasher);
+            (show_emojis, show_reactions, show_line_numbers, show_timestamps, show_user_colors)
+                .hash(&mut hasher);
+            (show_link_labels, show_jumbo).hash(&mut hasher);
+            (self.timestamp_style as u8).hash(&mut hasher);
+            self.timestamp_format.hash(&mut hasher);
+            if self.timestamp_style == TimestampStyle::Relative {
+                (now.timestamp() / 60).hash(&mut hasher);
+            }
+            // Mentions resolve to names as users are looked up
+            user_cache.len().hash(&mut hasher);
+            (search_query, current_match).hash(&mut hasher);
+            hasher.finish()
+        };
+        let mut cache = pane.cached_lines.borrow_mut();
+        if pane.cache_key.get() != Some(cache_key) {
+            cache.clear();
+            pane.cache_key.set(Some(cache_key));
+        }
+
+        // Messages with emojis, reactions, and thread indicators
+        let mut message_lines: Vec<Line> = Vec::new();
+        // Lines and links of each regular message, to map clicks back to it
+        #[allow(clippy::type_complexity)]
+        let mut msg_line_ranges: Vec<(std::ops::Range<usize>, usize, Vec<(String, String)>)> =
+            Vec::new();
+        let thread_root = pane.thread_root();
+        for (idx, msg) in pane.msg_data.iter().enumerate() {
+            // Drawn pinned above the replies instead
+            if idx == 0 && thread_root.is_some() {
+                continue;
+            }
+            if msg.is_bot && pane.hide_bots {
+                continue;
+            }
+            if let Some(ref filter) = filter {
+                let text = format_message_text(&msg.text, show_emojis, false, &resolve_user);
+                if !filter.matches(msg, &text) {
+                    continue;
+                }
+            }
+            if first_unread == Some(idx) {
+                let divider = format!("{:─^width$}", " new ", width = msg_width.max(5));
+                message_lines.push(Line::from(Span::styled(divider, Style::default().fg(Color::Red))));
+            }
+            if msg.is_system && !show_system_messages {
+                continue;
+            }
+            if pane.scroll_to_msg == Some(idx) {
+                target_line = Some(message_lines.len());
+            }
+            // Line numbers and /media references include the index, so a shifted message is redrawn
+            if cache.get(&msg.ts).is_none_or(|cached| cached.idx != idx) {
+                cache.insert(msg.ts.clone(), render_message(idx, msg));
+            }
+            let cached = &cache[&msg.ts];
+            let first_msg_line = message_lines.len();
+            message_lines.extend(cached.lines.iter().cloned());
+            if let Some(urls) = &cached.urls {
+                msg_line_ranges.push((first_msg_line..message_lines.len(), idx, urls.clone()));
+            }
+        }
+
+        // A thread's parent message stays at the top, at most a third of the area
+        let mut pinned_lines: Vec<Line> = Vec::new();
+        if let Some(root) = thread_root {
+            if cache.get(&root.ts).is_none_or(|cached| cached.idx != 0) {
+                cache.insert(root.ts.clone(), render_message(0, root));
+            }
+            let max_lines = (msg_inner.height as usize / 3).max(2);
+            let lines = &cache[&root.ts].lines;
+            if lines.len() < max_lines {
+                pinned_lines.extend(lines.iter().cloned());
+            } else {
+                pinned_lines.extend(lines[..max_lines - 2].iter().cloned());
+                pinned_lines.push(Line::from(Span::styled("...", Style::default().fg(Color::DarkGray))));
+            }
+            let divider = format!("{:─^width$}", " replies ", width = msg_width.max(9));
+            pinned_lines.push(Line::from(Span::styled(divider, Style::default().fg(Color::DarkGray))));
+        }
+        drop(cache);
+        let pinned_height = (pinned_lines.len() as u16).min(msg_inner.height);
+        let list_area = Rect {
+            y: msg_inner.y + pinned_height,
+            height: msg_inner.height - pinned_height,
+            ..msg_inner
+        };
+        let msg_area_height = list_area.height as usize;
+
+        *pane.rendered_lines.borrow_mut() = message_lines.iter().map(line_text).collect();
+        let mut rendered_msgs = vec![None; message_lines.len()];
+        for (range, idx, _) in &msg_line_ranges {
+            rendered_msgs[range.clone()].fill(Some(*idx));
+        }
+        *pane.rendered_msgs.borrow_mut() = rendered_msgs;
+        if let Some(copy) = self.copy_mode.as_ref().filter(|_| is_focused) {
+            highlight_copy_selection(&mut message_lines, copy);
+        }
+
+        // Reply counts and links, as (line, columns, action); placed once the scroll is known
+        let line_targets = find_click_targets(&message_lines, &msg_line_ranges, msg_inner.x);
+
+        let messages = Paragraph::new(message_lines);
+
+        // Use ratatui's own line_count with inner width for accurate wrapping
+        let total_wrapped_lines = messages.line_count(msg_inner.width);
+        let max_scroll = total_wrapped_lines.saturating_sub(msg_area_height);
+        let mut scroll_offset = pane.scroll_offset.min(max_scroll);
+
+        // Bring a requested message into view (roughly centred); App::draw stores the offset
+        if pane.scroll_to_msg.is_some() {
+            if let Some(line) = target_line {
+                if line < scroll_offset || line >= scroll_offset + msg_area_height {
+                    scroll_offset = line.saturating_sub(msg_area_height / 2).min(max_scroll);
+                }
+            }
+            pane.resolved_scroll.set(Some(scroll_offset));
+        }
+
+        let messages = messages.scroll((scroll_offset as u16, 0));
+
+        pane.rendered_view.set((scroll_offset, msg_area_height));
+        let at_bottom = scroll_offset >= max_scroll;
+        pane.at_bottom.set(at_bottom);
+        let mut click_targets: Vec<ClickTarget> = line_targets
+            .into_iter()
+            .filter(|(line, _, _)| *line >= scroll_offset && *line < scroll_offset + msg_area_height)
+            .map(|(line, cols, action)| ClickTarget {
+                row: list_area.y + (line - scroll_offset) as u16,
+                cols,
+                action,
+            })
+            .collect();
+
+        f.render_widget(messages_block, chunks[1]);
+        if pinned_height > 0 {
+            f.render_widget(Paragraph::new(pinned_lines), Rect { height: pinned_height, ..msg_inner });
+        }
+        f.render_widget(messages, list_area);
+
+        // Floating "new messages" badge over the bottom right of the message area
+        if pane.new_below > 0 && !at_bottom && list_area.height > 0 {
+            let badge = format!(
+                " \u{2193} {} new message{} (End) ",
+                pane.new_below,
+                if pane.new_below == 1 { "" } else { "s" }
+            );
+            let width = (badge.chars().count() as u16).min(list_area.width);
+            let rect = Rect {
+                x: list_area.x + list_area.width - width,
+                y: list_area.y + list_area.height - 1,
+                width,
+                height: 1,
+            };
+            f.render_widget(Clear, rect);
+            f.render_widget(
+                Paragraph::new(badge).style(
+                    Style::default()
+                        .bg(Color::Yellow)
+                        .fg(Color::Black)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                rect,
+            );
+            // Drawn over the last message line, so it wins over links underneath
+            click_targets.insert(
+                0,
+                ClickTarget {
+                    row: rect.y,
+                    cols: rect.x..rect.x + rect.width,
+                    action: ClickAction::JumpToLatest,
+                },
+            );
+        }
+        *pane.click_targets.borrow_mut() = click_targets;
+
+        // Reply preview if present
+        if has_reply_preview {
+            if let Some(ref preview) = pane.reply_preview {
+                let reply_bar =
+                    Paragraph::new(preview.as_str()).style(Style::default().fg(Color::Yellow));
+                f.render_widget(reply_bar, chunks[2]);
+            }
+        }
+
+        // Input
+        let input_chunk = if has_reply_preview {
+            chunks[3]
+        } else {
+            chunks[2]
+        };
+        let input_style = if is_focused && !self.focus_on_chat_list {
+            Style::default().fg(Color::Green)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+
+        // Render input with blank line above/below
+        let top_margin: u16 = 1;
+        let bottom_margin: u16 = 1;
+        let input_inner = Rect {
+            x: input_chunk.x,
+            y: input_chunk.y + top_margin,
+            width: input_chunk.width,
+            height: input_chunk.height.saturating_sub(top_margin + bottom_margin),
+        };
+        let (cursor_line, cursor_col) = cursor_visual_pos(
+            pane.input_buffer.as_str(),
+            pane.input_cursor,
+            input_inner.width as usize,
+        );
+        let input_scroll = if input_inner.height > 0 {
+            cursor_line.saturating_sub(input_inner.height as usize - 1)
+        } else {
+            0
+        };
+
+        // An active search takes over the input line
+        if let Some(search) = pane.search.as_ref() {
+            let counter = if search.query.is_empty() {
+                String::new()
+            } else if search.matches.is_empty() {
+                "  (no matches)".to_string()
+            } else {
+                format!("  ({}/{})", search.current + 1, search.matches.len())
+            };
+            let hint = if search.editing { "" } else { "  n/N: older/newer, Esc: close" };
+            let line = Line::from(vec![
+                Span::styled(format!("/{}", search.query), Style::default().fg(Color::Yellow)),
+                Span::styled(format!("{}{}", counter, hint), Style::default().fg(Color::DarkGray)),
+            ]);
+            f.render_widget(Paragraph::new(line), input_inner);
+            if is_focused && search.editing && input_inner.height > 0 {
+                let cursor_x = input_inner.x + 1 + search.query.width() as u16;
+                f.set_cursor_position((cursor_x.min(input_inner.right().saturating_sub(1)), input_inner.y));
+            }
+            return;
+        }
+
+        // Commands still work in a channel we can't post in, so only an empty composer
+        // is replaced by the explanation
+        if posting_restricted && pane.input_buffer.is_empty() {
+            let notice = Paragraph::new(POSTING_RESTRICTED_HINT)
+                .style(Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC));
+            f.render_widget(notice, input_inner);
+        } else {
+            let input_style = if posting_restricted {
+                Style::default().fg(Color::DarkGray)
+            } else {
+                input_style
+            };
+            let input = Paragraph::new(pane.input_buffer.as_str())
+                .style(input_style)
+                .wrap(Wrap { trim: false })
+                .scroll((input_scroll as u16, 0));
+            f.render_widget(input, input_inner);
+        }
+
+        // Character counter in the bottom margin once the draft nears Slack's limit
+        let length = pane.input_buffer.chars().count();
+        if length >= MESSAGE_COUNTER_FROM && input_chunk.height > 0 {
+            let counter = format!("{}/{}", length, MAX_MESSAGE_CHARS);
+            let color = if length > MAX_MESSAGE_CHARS { Color::Red } else { Color::Yellow };
+            let counter_area = Rect {
+                x: input_chunk.x,
+                y: input_chunk.bottom() - 1,
+                width: input_chunk.width,
+                height: 1,
+            };
+            f.render_widget(
+                Paragraph::new(counter).style(Style::default().fg(color)).alignment(Alignment::Right),
+                counter_area,
+            );
+        }
+
+        if is_focused && self.mention_picker_open() {
+            if let Some(picker) = &self.mention_picker {
+                Self::draw_mention_picker(f, area, input_chunk, picker);
+            }
+        }
+
+        // Set cursor position only when input is focused
+        if is_focused && !self.focus_on_chat_list && !self.read_only {
+            let cursor_y = input_inner.y + cursor_line.saturating_sub(input_scroll) as u16;
+            let cursor_x = input_inner.x + cursor_col as u16;
+            f.set_cursor_position((cursor_x, cursor_y));
+        }
+    }
+
+    /// Returns true (and tells the user) if a mutating action is blocked by read-only mode.
+    pub fn reject_if_read_only(&mut self) -> bool {
+        if self.read_only {
+            self.set_status("Read-only mode: press Ctrl+P to leave presentation mode");
+        }
+        self.read_only
+    }
+
+    pub fn set_status(&mut self, message: &str) {
+        self.status_message = Some(message.to_string());
+        self.status_expire = Some(std::time::Instant::now() + std::time::Duration::from_secs(3));
+        self.needs_redraw = true;
+    }
+
+    /// Snapshot of the persisted display/behaviour settings.
+    fn current_settings(&self) -> crate::persistence::AppSettings {
+        crate::persistence::AppSettings {
+            show_reactions: self.show_reactions,
+            show_notifications: self.show_notifications,
+            compact_mode: self.compact_mode,
+            show_emojis: self.show_emojis,
+            show_line_numbers: self.show_line_numbers,
+            show_timestamps: self.show_timestamps,
+            show_chat_list: self.show_chat_list,
+            show_user_colors: self.show_user_colors,
+            show_borders: self.show_borders,
+            screen_reader: self.screen_reader,
+            show_link_labels: self.show_link_labels,
+            quick_reactions: self.quick_reactions.clone(),
+            jumbo_emoji: self.jumbo_emoji,
+            terminal_title: self.terminal_title,
+            osc_notifications: self.osc_notifications,
+            mouse_support: self.mouse_support,
+            auto_away_minutes: self.auto_away_minutes,
+            highlight_keywords: self.highlight_keywords.clone(),
+            highlight_rules: self.highlight_rules.clone(),
+            notify_on_keywords: self.notify_on_keywords,
+            notification_cooldown_secs: self.notification_cooldown_secs,
+            show_system_messages: self.show_system_messages,
+            alias_prefix: self.alias_prefix.clone(),
+            timestamp_style: self.timestamp_style,
+            auto_scroll: self.auto_scroll,
+            timestamp_format: self.timestamp_format.clone(),
+            paste_snippet_lines: self.paste_snippet_lines,
+            recent_chats: self.recent_chats,
+            quiet_hours: self.quiet_hours.clone(),
+            sync_stars: self.sync_stars,
+            sidebar_width: self.sidebar_width,
+            bold_key: self.bold_key.clone(),
+            italic_key: self.italic_key.clone(),
+            code_key: self.code_key.clone(),
+        }
+    }
+
+    pub fn save_state(&self) -> Result<()> {
+        let state = AppState {
+            settings: self.current_settings(),
+            aliases: self.aliases.clone(),
+            snippets: self.snippets.clone(),
+            saved_searches: self.saved_searches.clone(),
+            nicknames: self.nicknames.clone(),
+            followed_threads: self.followed_threads.clone(),
+            starred: self.starred.clone(),
+            recent: self.recent.clone(),
+            sidebar_order: self.sidebar_order.clone(),
+            drafts: self.drafts_with_open_panes(),
+            layout: LayoutData {
+                panes: self
+                    .panes
+                    .iter()
+                    .map(|p| crate::persistence::PaneState {
+                        chat_id: p.chat_id,
+                        channel_id: p.channel_id_str.clone(),
+                        chat_name: p.chat_name.clone(),
+                        username: p.username.clone(),
+                        scroll_offset: p.scroll_offset,
+                        filter_type: p.filter_type,
+                        filter_value: p.filter_value.clone(),
+                        hide_bots: p.hide_bots,
+                        thread_ts: p.thread_ts.clone(),
+                    })
+                    .collect(),
+                focused_pane: self.focused_pane_idx,
+                pane_tree: Some(self.pane_tree.clone()),
+            },
+        };
+
+        state.save(&self.config)
+    }
+
+    // Navigation methods: follow the sidebar's rows, which group chats by section
+    pub fn select_next_chat(&mut self) {
+        let order = self.sidebar_order();
+        if let Some(pos) = order.iter().position(|&i| i == self.selected_chat_idx) {
+            self.selected_chat_idx = order[(pos + 1).min(order.len() - 1)];
+        } else if let Some(&first) = order.first() {
+            self.selected_chat_idx = first;
+        }
+    }
+
+    pub fn select_previous_chat(&mut self) {
+        let order = self.sidebar_order();
+        if let Some(pos) = order.iter().position(|&i| i == self.selected_chat_idx) {
+            self.selected_chat_idx = order[pos.saturating_sub(1)];
+        } else if let Some(&first) = order.first() {
+            self.selected_chat_idx = first;
+        }
+    }
+
+    pub fn next_pane(&mut self) {
+        if self.focus_on_chat_list {
+            self.focus_on_chat_list = false;
+        } else if self.panes.len() > 1 {
+            self.focused_pane_idx = (self.focused_pane_idx + 1) % self.panes.len();
+        }
+        self.clear_unread_for_focused_pane();
+    }
+    
+    /// Terminal focus changed. Coming back clears the counts of the conversations
+    /// on screen, which kept counting while the terminal was in the background.
+    pub fn set_terminal_focused(&mut self, focused: bool) {
+        self.terminal_focused = focused;
+        if !focused {
+            return;
+        }
+        for pane in &self.panes {
+            if let Some(channel_id) = pane.channel_id_str.as_ref() {
+                if let Some(chat) = self.chats.iter_mut().find(|c| &c.id == channel_id) {
+                    chat.unread = 0;
+                    chat.mentions = 0;
+                }
+            }
+        }
+        self.clear_unread_for_focused_pane();
+    }
+
+    fn clear_unread_for_focused_pane(&mut self) {
+        // Clear unread counter for the channel shown in the focused pane
+        if let Some(channel_id) = self.panes.get(self.focused_pane_idx)
+            .and_then(|p| p.channel_id_str.as_ref()) {
+            if let Some(chat) = self.chats.iter_mut().find(|c| &c.id == channel_id) {
+                chat.unread = 0;
+                chat.mentions = 0;
+            }
+        }
+        
+        // Clear mention counter for current workspace
+        let workspace_name = self.config.workspaces
+            .get(self.config.active_workspace)
+            .map(|w| w.name.clone())
+            .unwrap_or_default();
+        self.unread_mentions.insert(workspace_name, 0);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.ensure_valid_pane_idx();
+        self.panes[self.focused_pane_idx].scroll_up();
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.ensure_valid_pane_idx();
+        self.panes[self.focused_pane_idx].scroll_down();
+    }
+
+    pub fn page_up(&mut self) {
+        for _ in 0..10 {
+            self.panes[self.focused_pane_idx].scroll_up();
+        }
+    }
+
+    pub fn page_down(&mut self) {
+        for _ in 0..10 {
+            self.panes[self.focused_pane_idx].scroll_down();
+        }
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.panes[self.focused_pane_idx].scroll_offset = 0;
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.panes[self.focused_pane_idx].scroll_to_bottom();
+    }
+
+    pub fn input_char(&mut self, c: char) {
+        if self.reject_if_read_only() {
+            return;
+        }
+        self.ensure_valid_pane_idx();
+        if c.is_whitespace() {
+            self.expand_snippet_before_cursor();
+        }
+        let pane = &mut self.panes[self.focused_pane_idx];
+        pane.input_buffer.insert(pane.input_cursor, c);
+        pane.input_cursor += c.len_utf8();
+        pane.tab_complete_state = None;
+        self.update_mention_picker();
+    }
+
+    /// Replace a `;name` word ending at the cursor with the snippet's text.
+    fn expand_snippet_before_cursor(&mut self) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        let cursor = pane.input_cursor.min(pane.input_buffer.len());
+        let before = &pane.input_buffer[..cursor];
+        let word_start = before
+            .rfind(char::is_whitespace)
+            .map(|i| i + before[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(0);
+        let Some(name) = before[word_start..].strip_prefix(';') else {
+            return;
+        };
+        let Some(text) = self.snippets.map.get(name) else {
+            return;
+        };
+        pane.input_buffer.replace_range(word_start..cursor, text);
+        pane.input_cursor = word_start + text.len();
+        pane.tab_complete_state = None;
+    }
+
+    /// Apply the formatting shortcut bound to `key`, if any (see `bold_key`).
+    /// Returns false when the key isn't a formatting shortcut.
+    pub fn format_key(&mut self, key: &crossterm::event::KeyEvent) -> bool {
+        if self.focus_on_chat_list {
+            return false;
+        }
+        let marker = if key_matches(&self.bold_key, key) {
+            "*"
+        } else if key_matches(&self.italic_key, key) {
+            "_"
+        } else if key_matches(&self.code_key, key) {
+            "`"
+        } else {
+            return false;
+        };
+        if !self.reject_if_read_only() {
+            self.ensure_valid_pane_idx();
+            self.toggle_word_markup(marker);
+        }
+        true
+    }
+
+    /// Wrap the word at the cursor in `marker` (or unwrap it if it already is).
+    /// Between words an empty pair is inserted with the cursor inside.
+    fn toggle_word_markup(&mut self, marker: &str) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        let buf = &pane.input_buffer;
+        let cursor = pane.input_cursor.min(buf.len());
+        let start = buf[..cursor]
+            .rfind(char::is_whitespace)
+            .map(|i| i + buf[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(0);
+        let end = buf[cursor..]
+            .find(char::is_whitespace)
+            .map(|i| cursor + i)
+            .unwrap_or(buf.len());
+        let word = &buf[start..end];
+
+        if word.len() > 2 * marker.len() && word.starts_with(marker) && word.ends_with(marker) {
+            let inner = word[marker.len()..word.len() - marker.len()].to_string();
+            pane.input_buffer.replace_range(start..end, &inner);
+            pane.input_cursor = start + inner.len();
+        } else if word.is_empty() {
+            pane.input_buffer.insert_str(cursor, &marker.repeat(2));
+            pane.input_cursor = cursor + marker.len();
+        } else {
+            let wrapped = format!("{}{}{}", marker, word, marker);
+            pane.input_buffer.replace_range(start..end, &wrapped);
+            pane.input_cursor = start + wrapped.len();
+        }
+        pane.tab_complete_state = None;
+        self.needs_redraw = true;
+    }
+
+    /// `/code` at the start of the draft: put the rest of it in a fenced block,
+    /// left in the composer to be reviewed and sent with the next Enter.
+    fn wrap_draft_in_code_block(&mut self, pane_idx: usize, code: &str) {
+        let pane = &mut self.panes[pane_idx];
+        let code = code.trim_matches('\n');
+        pane.input_buffer = format!("```\n{}\n```", code);
+        // An empty block puts the cursor on the line inside the fence
+        pane.input_cursor = if code.is_empty() { 4 } else { pane.input_buffer.len() };
+        pane.tab_complete_state = None;
+        self.needs_redraw = true;
+    }
+
+    pub fn backspace(&mut self) {
+        self.ensure_valid_pane_idx();
+        let pane = &mut self.panes[self.focused_pane_idx];
+        if pane.input_cursor == 0 {
+            return;
+        }
+        let prev = prev_char_boundary(&pane.input_buffer, pane.input_cursor);
+        pane.input_buffer.drain(prev..pane.input_cursor);
+        pane.input_cursor = prev;
+        pane.tab_complete_state = None;
+        self.update_mention_picker();
+    }
+
+    pub fn delete_forward(&mut self) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        if pane.input_cursor >= pane.input_buffer.len() {
+            return;
+        }
+        let next = next_char_boundary(&pane.input_buffer, pane.input_cursor);
+        pane.input_buffer.drain(pane.input_cursor..next);
+        pane.tab_complete_state = None;
+    }
+
+    /// Text pasted into the composer (bracketed paste). Pastes longer than
+    /// `paste_snippet_lines` ask first whether to upload them as a snippet.
+    pub fn paste_text(&mut self, text: String) {
+        // Tokens are usually pasted into the /workspace prompt
+        if let Some(prompt) = &mut self.workspace_prompt {
+            prompt.input.push_str(text.trim());
+            self.needs_redraw = true;
+            return;
+        }
+        if self.copy_mode.is_some()
+            || self.pending_paste.is_some()
+            || self.pending_long_message.is_some()
+            || self.pending_slack_command.is_some()
+            || self.info_popup.is_some()
+            || self.file_browser.is_some()
+            || self.user_picker.is_some()
+            || self.reject_if_read_only()
+        {
+            return;
+        }
+        self.ensure_valid_pane_idx();
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        if self.search_editing() {
+            // The search query is a single line
+            let query = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if let Some(search) = self.panes[self.focused_pane_idx].search.as_mut() {
+                search.query.push_str(&query);
+            }
+            self.refresh_search_matches(true);
+            return;
+        }
+        // Pasting while the sidebar has focus goes to the focused pane's composer
+        self.focus_on_chat_list = false;
+        let line_count = text.lines().count();
+        let has_channel = self.panes[self.focused_pane_idx].channel_id_str.is_some();
+        if self.paste_snippet_lines > 0 && line_count > self.paste_snippet_lines && has_channel {
+            self.set_status(&format!(
+                "Pasted {} lines: upload as a snippet? (y = snippet, n = paste as text, Esc = discard)",
+                line_count
+            ));
+            self.status_expire = None; // Stays until answered
+            self.pending_paste = Some(text);
+            return;
+        }
+        self.insert_text(&text);
+    }
+
+    fn insert_text(&mut self, text: &str) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        pane.input_buffer.insert_str(pane.input_cursor, text);
+        pane.input_cursor += text.len();
+        pane.tab_complete_state = None;
+        self.needs_redraw = true;
+    }
+
+    pub fn paste_as_text(&mut self) {
+        if let Some(text) = self.pending_paste.take() {
+            self.insert_text(&text);
+            self.status_message = None;
+        }
+    }
+
+    pub fn discard_paste(&mut self) {
+        if self.pending_paste.take().is_some() {
+            self.set_status("Paste discarded");
+        }
+    }
+
+    /// Share the pending long paste in the focused conversation as a text snippet.
+    pub async fn upload_pending_paste(&mut self) {
+        let Some(text) = self.pending_paste.take() else {
+            return;
+        };
+        self.ensure_valid_pane_idx();
+        let pane = &self.panes[self.focused_pane_idx];
+        let Some(channel_id) = pane.channel_id_str.clone() else {
+            self.insert_text(&text);
+            return;
+        };
+        let thread_ts = pane.thread_ts.clone();
+
+        self.set_status("Uploading snippet...");
+        match self
+            .slack
+            .upload_snippet(&channel_id, &text, "snippet.txt", thread_ts.as_deref())
+            .await
+        {
+            Ok(()) => {
+                let lines = text.lines().count();
+                self.set_status(&format!("Uploaded a {}-line snippet", lines));
+            }
+            Err(e) => {
+                // Keep the text rather than losing it
+                self.insert_text(&text);
+                self.set_status(&format!("Snippet upload failed ({}); pasted as text", e));
+            }
+        }
+    }
+
+    pub fn input_newline(&mut self) {
+        if self.reject_if_read_only() {
+            return;
+        }
+        self.ensure_valid_pane_idx();
+        let pane = &mut self.panes[self.focused_pane_idx];
+        pane.input_buffer.insert(pane.input_cursor, '\n');
+        pane.input_cursor += 1;
+        pane.tab_complete_state = None;
+    }
+
+    pub fn move_cursor_left(&mut self) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        if pane.input_cursor == 0 {
+            return;
+        }
+        pane.input_cursor = prev_char_boundary(&pane.input_buffer, pane.input_cursor);
+        pane.tab_complete_state = None;
+        self.update_mention_picker();
+    }
+
+    pub fn move_cursor_right(&mut self) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        if pane.input_cursor >= pane.input_buffer.len() {
+            return;
+        }
+        pane.input_cursor = next_char_boundary(&pane.input_buffer, pane.input_cursor);
+        pane.tab_complete_state = None;
+        self.update_mention_picker();
+    }
+
+    pub fn move_cursor_home(&mut self) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        let (line_start, _) = line_bounds(&pane.input_buffer, pane.input_cursor);
+        pane.input_cursor = line_start;
+        pane.tab_complete_state = None;
+    }
+
+    pub fn move_cursor_end(&mut self) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        let (_, line_end) = line_bounds(&pane.input_buffer, pane.input_cursor);
+        pane.input_cursor = line_end;
+        pane.tab_complete_state = None;
+    }
+
+    pub fn move_cursor_up(&mut self) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        let (line_start, _) = line_bounds(&pane.input_buffer, pane.input_cursor);
+        if line_start == 0 {
+            return;
+        }
+        let target_col = column_in_line(&pane.input_buffer, line_start, pane.input_cursor);
+        let prev_line_end = line_start.saturating_sub(1);
+        let prev_line_start = pane.input_buffer[..prev_line_end]
+            .rfind('\n')
+            .map(|idx| idx + 1)
+            .unwrap_or(0);
+        let new_cursor = index_from_column(
+            &pane.input_buffer,
+            prev_line_start,
+            prev_line_end,
+            target_col,
+        );
+        pane.input_cursor = new_cursor.min(pane.input_buffer.len());
+        pane.tab_complete_state = None;
+    }
+
+    pub fn move_cursor_down(&mut self) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        let (line_start, line_end) = line_bounds(&pane.input_buffer, pane.input_cursor);
+        if line_end >= pane.input_buffer.len() {
+            return;
+        }
+        let target_col = column_in_line(&pane.input_buffer, line_start, pane.input_cursor);
+        let next_line_start = line_end + 1;
+        let next_line_end = pane.input_buffer[next_line_start..]
+            .find('\n')
+            .map(|idx| next_line_start + idx)
+            .unwrap_or_else(|| pane.input_buffer.len());
+        let new_cursor = index_from_column(
+            &pane.input_buffer,
+            next_line_start,
+            next_line_end,
+            target_col,
+        );
+        pane.input_cursor = new_cursor.min(pane.input_buffer.len());
+        pane.tab_complete_state = None;
+    }
+
+    pub fn tab_complete(&mut self) {
+        use crate::widgets::TabCompleteState;
+
+        self.ensure_valid_pane_idx();
+        if self.panes[self.focused_pane_idx].tab_complete_state.is_none() && self.complete_command_arg() {
+            return;
+        }
+        let pane = &mut self.panes[self.focused_pane_idx];
+
+        if let Some(ref mut state) = pane.tab_complete_state {
+            // Cycle to next candidate
+            if state.candidates.is_empty() {
+                return;
+            }
+            state.index = (state.index + 1) % state.candidates.len();
+            let replacement = &state.candidates[state.index];
+            
+            if state.sigil == '/' {
+                // Command completion
+                pane.input_buffer = format!("/{} {}", replacement, state.after);
+                pane.input_cursor = replacement.len() + 2;
+            } else if state.sigil == ' ' {
+                // Command argument completion
+                pane.input_buffer = format!("{}{} {}", state.before, replacement, state.after);
+                pane.input_cursor = state.before.len() + replacement.len() + 1;
+            } else {
+                // User mention / channel completion
+                pane.input_buffer =
+                    format!("{}{}{} {}", state.before, state.sigil, replacement, state.after);
+                pane.input_cursor = state.before.len() + replacement.len() + 2;
+            }
+        } else {
+            let input = &pane.input_buffer;
+            let cursor = pane.input_cursor.min(input.len());
+            let before_cursor = &input[..cursor];
+            
+            // Check if completing a command
+            if before_cursor.starts_with('/') && !before_cursor.contains(' ') {
+                let prefix = &before_cursor[1..];
+                let prefix_lower = prefix.to_lowercase();
+                
+                let mut candidates: Vec<String> = crate::commands::COMMANDS
+                    .iter()
+                    .map(|cmd| cmd.name)
+                    .filter(|name| name.starts_with(&prefix_lower))
+                    .map(|s| s.to_string())
+                    .chain(
+                        crate::scripting::list_scripts(&self.config.config_dir)
+                            .into_iter()
+                            .filter(|name| name.starts_with(&prefix_lower)),
+                    )
+                    .collect();
+                    
+                if candidates.is_empty() {
+                    return;
+                }
+                
+                candidates.sort();
+                let after = input[cursor..].to_string();
+                let replacement = &candidates[0];
+                
+                pane.input_buffer = format!("/{} {}", replacement, after);
+                pane.input_cursor = replacement.len() + 2;
+                
+                pane.tab_complete_state = Some(TabCompleteState {
+                    before: "/".to_string(),
+                    sigil: '/',
+                    after,
+                    candidates,
+                    index: 0,
+                });
+                return;
+            }
+            
+            // Find @prefix (user mention), #prefix (channel) or :prefix (emoji) at cursor
+            let Some(at_pos) = before_cursor.rfind(['@', '#', ':']) else {
+                return;
+            };
+            let sigil = before_cursor[at_pos..].chars().next().unwrap_or('@');
+            let prefix = &before_cursor[at_pos + 1..];
+            // Don't complete empty @ or if there's a space after @
+            if prefix.is_empty() || prefix.contains(' ') {
+                return;
+            }
+            // ':' only starts an emoji at a word start, not in "12:30" or "http:"
+            if sigil == ':'
+                && (before_cursor[..at_pos]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| !c.is_whitespace())
+                    || !prefix
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '+')))
+            {
+                return;
+            }
+            let prefix_lower = prefix.to_lowercase();
+
+            // Find matching user names from cache, channel names from the sidebar,
+            // or emoji names (completed with the closing ':')
+            let mut candidates: Vec<String> = if sigil == ':' {
+                emoji_names_with_prefix(prefix)
+                    .into_iter()
+                    .map(|name| format!("{}:", name))
+                    .collect()
+            } else if sigil == '#' {
+                self.chats
+                    .iter()
+                    .filter(|c| matches!(c.section, ChatSection::Public | ChatSection::Private))
+                    .map(|c| c.name.clone())
+                    .filter(|name| name.to_lowercase().starts_with(&prefix_lower))
+                    .collect()
+            } else {
+                self.user_name_cache
+                    .values()
+                    .filter(|name| name.to_lowercase().starts_with(&prefix_lower))
+                    .cloned()
+                    .collect()
+            };
+            candidates.sort();
+            candidates.dedup();
+
+            if candidates.is_empty() {
+                return;
+            }
+
+            let replacement = &candidates[0];
+            let before = input[..at_pos].to_string();
+            let after = input[cursor..].to_string();
+            pane.input_buffer = format!("{}{}{} {}", before, sigil, replacement, after);
+            pane.input_cursor = before.len() + replacement.len() + 2;
+
+            pane.tab_complete_state = Some(TabCompleteState {
+                before,
+                sigil,
+                after,
+                candidates,
+                index: 0,
+            });
+        }
+    }
+
+    /// Tab in a command's argument that has a completion spec (`commands::COMMANDS`),
+    /// e.g. `/dm @an` or `/timestamps r`. False if the cursor isn't in one.
+    fn complete_command_arg(&mut self) -> bool {
+        use crate::commands::{arg_completion, ArgCompletion};
+        use crate::widgets::TabCompleteState;
+
+        let pane = &self.panes[self.focused_pane_idx];
+        let input = &pane.input_buffer;
+        let cursor = pane.input_cursor.min(input.len());
+        let before_cursor = &input[..cursor];
+        let Some((name, typed)) = before_cursor.strip_prefix('/').and_then(|s| s.split_once(' ')) else {
+            return false;
+        };
+        if before_cursor.contains('\n') {
+            return false;
+        }
+        // The word being completed, and how many arguments come before it
+        let word_start = typed.rfind(' ').map_or(0, |i| i + 1);
+        let arg = typed[..word_start].split_whitespace().count();
+        let Some(kind) = arg_completion(name, arg) else {
+            return false;
+        };
+        let prefix = typed[word_start..].to_lowercase();
+
+        let mut candidates: Vec<String> = match kind {
+            ArgCompletion::User => self
+                .user_name_cache
+                .values()
+                .filter(|n| n.to_lowercase().starts_with(prefix.trim_start_matches('@')))
+                .map(|n| format!("@{}", n))
+                .collect(),
+            ArgCompletion::Channel => self
+                .chats
+                .iter()
+                .filter(|c| c.name.to_lowercase().starts_with(prefix.trim_start_matches('#')))
+                .map(|c| format!("#{}", c.name))
+                .collect(),
+            ArgCompletion::Workspace => ["add", "rename", "remove"]
+                .into_iter()
+                .map(String::from)
+                .chain(self.config.workspaces.iter().map(|w| w.name.clone()))
+                .filter(|w| w.to_lowercase().starts_with(&prefix))
+                .collect(),
+            ArgCompletion::SavedSearch => ["list", "add", "remove"]
+                .into_iter()
+                .map(String::from)
+                .chain(self.saved_searches.map.keys().cloned())
+                .filter(|s| s.to_lowercase().starts_with(&prefix))
+                .collect(),
+            ArgCompletion::Words(words) => words
+                .iter()
+                .filter(|w| w.starts_with(&prefix))
+                .map(|w| w.to_string())
+                .collect(),
+        };
+        if !matches!(kind, ArgCompletion::Words(_)) {
+            candidates.sort();
+            candidates.dedup();
+        }
+        if candidates.is_empty() {
+            return false;
+        }
+
+        let before = input[..cursor - (typed.len() - word_start)].to_string();
+        let after = input[cursor..].to_string();
+        let pane = &mut self.panes[self.focused_pane_idx];
+        pane.input_buffer = format!("{}{} {}", before, candidates[0], after);
+        pane.input_cursor = before.len() + candidates[0].len() + 1;
+        pane.tab_complete_state = Some(TabCompleteState {
+            before,
+            sigil: ' ',
+            after,
+            candidates,
+            index: 0,
+        });
+        true
+    }
+
+    /// True while the focused pane has a search (typing or browsing matches).
+    pub fn search_active(&self) -> bool {
+        self.panes
+            .get(self.focused_pane_idx)
+            .is_some_and(|p| p.search.is_some())
+    }
+
+    /// True while keys are going into the focused pane's search query.
+    pub fn search_editing(&self) -> bool {
+        self.panes
+            .get(self.focused_pane_idx)
+            .and_then(|p| p.search.as_ref())
+            .is_some_and(|s| s.editing)
+    }
+
+    /// True if nothing has been typed into the focused pane's composer.
+    pub fn focused_input_is_empty(&self) -> bool {
+        self.panes
+            .get(self.focused_pane_idx)
+            .is_none_or(|p| p.input_buffer.is_empty())
+    }
+
+    /// Start an incremental search in the focused pane.
+    pub fn start_search(&mut self) {
+        self.ensure_valid_pane_idx();
+        self.focus_on_chat_list = false;
+        self.panes[self.focused_pane_idx].search = Some(PaneSearch {
+            editing: true,
+            ..Default::default()
+        });
+        self.needs_redraw = true;
+    }
+
+    /// `/search [text]`: start a search, optionally with the query already filled in.
+    pub fn start_search_with(&mut self, query: &str) {
+        self.start_search();
+        if let Some(search) = self.panes[self.focused_pane_idx].search.as_mut() {
+            search.query.push_str(query);
+        }
+        self.refresh_search_matches(true);
+    }
+
+    pub fn search_input_char(&mut self, c: char) {
+        if let Some(search) = self.panes[self.focused_pane_idx].search.as_mut() {
+            search.query.push(c);
+        }
+        self.refresh_search_matches(true);
+    }
+
+    pub fn search_backspace(&mut self) {
+        if let Some(search) = self.panes[self.focused_pane_idx].search.as_mut() {
+            search.query.pop();
+        }
+        self.refresh_search_matches(true);
+    }
+
+    /// Enter: stop editing the query but keep the highlights for n/N.
+    pub fn finish_search_input(&mut self) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        match pane.search.as_mut() {
+            Some(search) if !search.query.is_empty() => search.editing = false,
+            _ => pane.search = None,
+        }
+        self.needs_redraw = true;
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.panes[self.focused_pane_idx].search = None;
+        self.needs_redraw = true;
+    }
+
+    /// n/N: move to the next older (`older = true`) or newer match, wrapping around.
+    pub fn search_step(&mut self, older: bool) {
+        self.refresh_search_matches(false);
+        let Some(search) = self.panes[self.focused_pane_idx].search.as_mut() else {
+            return;
+        };
+        if search.matches.is_empty() {
+            let query = search.query.clone();
+            self.set_status(&format!("No matches for '{}'", query));
+            return;
+        }
+        let len = search.matches.len();
+        search.current = if older {
+            (search.current + len - 1) % len
+        } else {
+            (search.current + 1) % len
+        };
+        let target = search.current_msg();
+        self.panes[self.focused_pane_idx].scroll_to_msg = target;
+        self.needs_redraw = true;
+    }
+
+    /// Re-scan the focused pane. A changed query jumps to the newest match;
+    /// otherwise the current match is kept if it still exists.
+    fn refresh_search_matches(&mut self, query_changed: bool) {
+        let pane = &self.panes[self.focused_pane_idx];
+        let Some(search) = pane.search.as_ref() else {
+            return;
+        };
+        let query = search.query.clone();
+        let previous = search.current_msg();
+        let user_cache = &self.user_name_cache;
+        let resolve_user = |id: &str| -> String {
+            user_cache.get(id).cloned().unwrap_or_else(|| id.to_string())
+        };
+        let filter = PaneFilter::from_pane(pane);
+        let matches: Vec<usize> = if query.is_empty() {
+            Vec::new()
+        } else {
+            pane.msg_data
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| self.show_system_messages || !m.is_system)
+                .filter(|(_, m)| !(m.is_bot && pane.hide_bots))
+                .filter(|(_, m)| {
+                    let text = format_message_text(&m.text, self.show_emojis, self.show_link_labels, &resolve_user);
+                    contains_ignore_case(&text, &query)
+                        && filter.as_ref().is_none_or(|f| f.matches(m, &text))
+                })
+                .map(|(i, _)| i)
+                .collect()
+        };
+
+        let current = match previous.and_then(|p| matches.iter().position(|&m| m == p)) {
+            Some(pos) if !query_changed => pos,
+            _ => matches.len().saturating_sub(1),
+        };
+        let pane = &mut self.panes[self.focused_pane_idx];
+        if let Some(search) = pane.search.as_mut() {
+            search.matches = matches;
+            search.current = current;
+            if query_changed {
+                pane.scroll_to_msg = search.current_msg();
+            }
+        }
+        self.needs_redraw = true;
+    }
+
+    pub fn cancel_reply(&mut self) {
+        let pane = &mut self.panes[self.focused_pane_idx];
+        pane.reply_to_message = None;
+        pane.hide_reply_preview();
+    }
+
+    /// Copy mode `r`: send the next message into the thread of the message under the cursor.
+    pub fn reply_to_selected(&mut self) {
+        if self.reject_if_read_only() {
+            return;
+        }
+        let Some(copy) = self.copy_mode.take() else {
+            return;
+        };
+        self.needs_redraw = true;
+        let pane = &mut self.panes[self.focused_pane_idx];
+        if pane.channel_id_str.is_none() {
+            self.set_status("No channel selected");
+            return;
+        }
+        if pane.thread_ts.is_some() {
+            self.set_status("This pane is a thread; messages sent here already go to it");
+            return;
+        }
+        let msg_idx = pane.rendered_msgs.borrow().get(copy.cursor.0).copied().flatten();
+        // Local echoes have no Slack timestamp yet, and notices have no thread
+        let Some(msg) = msg_idx
+            .and_then(|idx| pane.msg_data.get(idx))
+            .filter(|m| m.local_echo_id.is_none() && !m.is_system)
+        else {
+            self.set_status("No message to reply to");
+            return;
+        };
+        let text: String = msg.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let excerpt: String = text.chars().take(60).collect();
+        let ellipsis = if excerpt.len() < text.len() { "…" } else { "" };
+        let preview = format!("↪ Replying to {}: {}{} (Esc to cancel)", msg.sender_name, excerpt, ellipsis);
+        let ts = msg.ts.clone();
+        pane.set_reply(&ts, preview);
+        self.focus_on_chat_list = false;
+    }
+
+    /// Thread the next message in a pane goes to: the pane's thread, or an armed reply
+    fn reply_thread(&self, pane_idx: usize) -> Option<String> {
+        let pane = &self.panes[pane_idx];
+        pane.thread_ts.clone().or_else(|| pane.reply_to_message.clone())
+    }
+
+    /// The pane would post at the top level of a channel where we aren't allowed to.
+    fn posting_restricted(&self, pane: &ChatPane) -> bool {
+        pane.thread_ts.is_none()
+            && pane.reply_to_message.is_none()
+            && pane
+                .channel_id_str
+                .as_ref()
+                .is_some_and(|id| self.chats.iter().any(|c| c.id == *id && c.posting_restricted))
+    }
+
+    // Split management
+    pub fn split_vertical(&mut self) {
+        let new_idx = self.panes.len();
+        self.panes.push(ChatPane::new());
+        self.pane_zoomed = false;
+        // Split the focused pane, not the root
+        if !self.pane_tree.split_pane(self.focused_pane_idx, SplitDirection::Vertical, new_idx) {
+            // Fallback: split at root if focused pane not found
+            self.pane_tree.split(SplitDirection::Vertical, new_idx);
+        }
+        self.focused_pane_idx = new_idx; // Focus the new pane
+    }
+
+    pub fn split_horizontal(&mut self) {
+        let new_idx = self.panes.len();
+        self.panes.push(ChatPane::new());
+        self.pane_zoomed = false;
+        // Split the focused pane, not the root
+        if !self.pane_tree.split_pane(self.focused_pane_idx, SplitDirection::Horizontal, new_idx) {
+            // Fallback: split at root if focused pane not found
+            self.pane_tree.split(SplitDirection::Horizontal, new_idx);
+        }
+        self.focused_pane_idx = new_idx; // Focus the new pane
+    }
+
+    pub fn toggle_split_direction(&mut self) {
+        self.pane_tree.toggle_direction();
+    }
+
+    /// Swap the focused pane with its previous/next sibling. Focus follows the pane.
+    pub fn swap_pane(&mut self, forward: bool) {
+        if !self.pane_tree.swap_with_neighbor(self.focused_pane_idx, forward) {
+            self.set_status("No pane to swap with");
+        }
+    }
+
+    /// Move the focused pane to the other end of its split.
+    pub fn move_pane_to_other_end(&mut self) {
+        if !self.pane_tree.move_to_other_end(self.focused_pane_idx) {
+            self.set_status("Pane is not in a split");
+        }
+    }
+
+    pub fn close_pane(&mut self) {
+        if self.panes.len() <= 1 {
+            self.set_status("Cannot close the last pane");
+            return;
+        }
+        
+        let pane_idx = self.focused_pane_idx;
+        
+        // Get all pane indices before closing
+        let all_indices = self.pane_tree.get_pane_indices();
+        
+        // Find a new pane to focus on (prefer next pane, or previous if we're at the end)
+        let current_pos = all_indices.iter().position(|&idx| idx == pane_idx).unwrap_or(0);
+        let new_focus_idx = if current_pos + 1 < all_indices.len() {
+            all_indices[current_pos + 1]
+        } else if current_pos > 0 {
+            all_indices[current_pos - 1]
+        } else {
+            0
+        };
+        
+        // Remove the pane from the tree
+        self.pane_tree.close_pane(pane_idx);
+        self.pane_zoomed = false;
+        
+        // Remove the pane from the array
+        self.panes.remove(pane_idx);
+        
+        // Reindex all pane indices in the tree (shift down indices > pane_idx)
+        self.pane_tree.reindex_after_removal(pane_idx);
+        
+        // Update focused pane index (adjust if it was after the removed pane)
+        self.focused_pane_idx = if new_focus_idx > pane_idx {
+            new_focus_idx - 1
+        } else {
+            new_focus_idx
+        };
+        
+        // Ensure focused index is valid
+        if self.focused_pane_idx >= self.panes.len() {
+            self.focused_pane_idx = self.panes.len().saturating_sub(1);
+        }
+    }
+
+    pub fn clear_pane(&mut self) {
+        self.panes[self.focused_pane_idx].clear();
+    }
+
+    // Toggle settings
+    pub fn toggle_pane_zoom(&mut self) {
+        if !self.pane_zoomed && self.panes.len() <= 1 {
+            self.set_status("Only one pane open");
+            return;
+        }
+        self.pane_zoomed = !self.pane_zoomed;
+        if self.pane_zoomed {
+            self.set_status("Pane zoomed (Ctrl+Z to restore layout)");
+        }
+        self.needs_redraw = true;
+    }
+
+    pub fn toggle_chat_list(&mut self) {
+        self.show_chat_list = !self.show_chat_list;
+        self.needs_redraw = true;
+    }
+
+    pub fn toggle_reactions(&mut self) {
+        self.show_reactions = !self.show_reactions;
+        for pane in &mut self.panes {
+            pane.invalidate_cache();
+        }
+        self.needs_redraw = true;
+    }
+
+    pub fn toggle_emojis(&mut self) {
+        self.show_emojis = !self.show_emojis;
+        for pane in &mut self.panes {
+            pane.invalidate_cache();
+        }
+        self.needs_redraw = true;
+    }
+
+    pub fn toggle_timestamps(&mut self) {
+        self.show_timestamps = !self.show_timestamps;
+        for pane in &mut self.panes {
+            pane.invalidate_cache();
+        }
+        self.needs_redraw = true;
+    }
+
+    pub fn toggle_compact_mode(&mut self) {
+        self.compact_mode = !self.compact_mode;
+        for pane in &mut self.panes {
+            pane.invalidate_cache();
+        }
+        self.needs_redraw = true;
+    }
+
+    /// `/timestamps <absolute|full|relative>`
+    pub fn set_timestamp_style(&mut self, style: TimestampStyle) {
+        self.timestamp_style = style;
+        self.show_timestamps = true;
+        for pane in &mut self.panes {
+            pane.invalidate_cache();
+        }
+        self.set_status(&format!("Timestamps: {:?}", style).to_lowercase());
+    }
+
+    /// `/autoscroll <always|bottom|never>`
+    pub fn set_auto_scroll(&mut self, mode: AutoScroll) {
+        self.auto_scroll = mode;
+        if mode == AutoScroll::Always {
+            for pane in &mut self.panes {
+                pane.scroll_to_bottom();
+            }
+        }
+        let name = match mode {
+            AutoScroll::Always => "always",
+            AutoScroll::AtBottom => "only at the bottom",
+            AutoScroll::Never => "never",
+        };
+        self.set_status(&format!("Auto-scroll on new messages: {}", name));
+    }
+
+    pub fn toggle_line_numbers(&mut self) {
+        self.show_line_numbers = !self.show_line_numbers;
+        for pane in &mut self.panes {
+            pane.invalidate_cache();
+        }
+        self.needs_redraw = true;
+    }
+
+    pub fn toggle_user_colors(&mut self) {
+        self.show_user_colors = !self.show_user_colors;
+        for pane in &mut self.panes {
+            pane.invalidate_cache();
+        }
+        self.needs_redraw = true;
+    }
+
+    pub fn toggle_system_messages(&mut self) {
+        self.show_system_messages = !self.show_system_messages;
+        for pane in &mut self.panes {
+            pane.invalidate_cache();
+        }
+        self.set_status(if self.show_system_messages {
+            "System messages shown"
+        } else {
+            "System messages hidden"
+        });
+    }
+
+    pub fn toggle_borders(&mut self) {
+        self.show_borders = !self.show_borders;
+        self.needs_redraw = true;
+    }
+
+    /// Borders are never drawn in screen-reader mode; box lines are read out as noise.
+    fn borders_shown(&self) -> bool {
+        self.show_borders && !self.screen_reader
+    }
+
+    pub fn toggle_screen_reader(&mut self) {
+        self.screen_reader = !self.screen_reader;
+        for pane in &mut self.panes {
+            pane.hide_typing_indicator();
+            pane.invalidate_cache();
+        }
+        self.set_status(if self.screen_reader {
+            "Screen reader mode on"
+        } else {
+            "Screen reader mode off"
+        });
+    }
+
+    pub fn toggle_read_only(&mut self) {
+        self.read_only = !self.read_only;
+        if self.read_only {
+            // Drop any half-typed input so nothing leaks onto the shared screen
+            for pane in &mut self.panes {
+                pane.input_buffer.clear();
+                pane.input_cursor = 0;
+                pane.tab_complete_state = None;
+            }
+        }
+        let status = if self.read_only {
+            "Presentation mode: read-only, input hidden"
+        } else {
+            "Presentation mode off"
+        };
+        self.set_status(status);
+    }
+
+    pub fn toggle_mouse_support(&mut self) {
+        self.mouse_support = !self.mouse_support;
+        let status = if self.mouse_support {
+            "Mouse support enabled (click to focus panes)"
+        } else {
+            "Mouse support disabled (use Shift+drag to select text)"
+        };
+        self.set_status(status);
+    }
+
+    /// Mouse moved: show the URL of a labelled link under the pointer in the status bar.
+    pub fn hover(&mut self, x: u16, y: u16) {
+        if !self.show_link_labels {
+            return;
+        }
+        let url = self
+            .pane_areas
+            .iter()
+            .find(|(_, area)| {
+                x >= area.x && x < area.x + area.width && y >= area.y && y < area.y + area.height
+            })
+            .and_then(|(&idx, _)| self.panes.get(idx))
+            .and_then(|pane| {
+                pane.click_targets
+                    .borrow()
+                    .iter()
+                    .find(|t| t.row == y && t.cols.contains(&x))
+                    .and_then(|t| match &t.action {
+                        ClickAction::OpenUrl(url) => Some(url.clone()),
+                        _ => None,
+                    })
+            });
+        if url == self.hovered_link {
+            return;
+        }
+        if let Some(url) = &url {
+            self.set_status(url);
+        }
+        self.hovered_link = url;
+    }
+
+    pub fn toggle_link_labels(&mut self) {
+        self.show_link_labels = !self.show_link_labels;
+        self.hovered_link = None;
+        self.set_status(if self.show_link_labels {
+            "Links show their text; hover or click one for the URL"
+        } else {
+            "Links show their URL"
+        });
+    }
+
+    pub fn handle_mouse_click(&mut self, x: u16, y: u16) {
+        // Check if click is in chat list
+        if let Some(area) = self.chat_list_area {
+            if x >= area.x && x < area.x + area.width && y >= area.y && y < area.y + area.height {
+                self.focus_on_chat_list = true;
+                // Calculate which chat was clicked (accounting for scroll offset and border)
+                let border_offset = if self.borders_shown() { 1 } else { 0 };
+                let relative_y = y.saturating_sub(area.y + border_offset);
+                let row_idx = relative_y as usize + self.chat_list_scroll_offset;
+                let rows = self.build_chat_list_rows();
+                if let Some(chat_idx) = Self::row_to_chat_idx(&rows, row_idx) {
+                    self.selected_chat_idx = chat_idx;
+                    self.pending_open_chat = true;
+                }
+                return;
+            }
         }
-    }
 
-    pub fn next_pane(&mut self) {
-        if self.focus_on_chat_list {
+        // Check if click is in a pane
+        let clicked_pane = self.pane_areas.iter().find(|(_, area)| {
+            x >= area.x && x < area.x + area.width && y >= area.y && y < area.y + area.height
+        });
+        if let Some((&idx, _)) = clicked_pane {
+            self.focused_pane_idx = idx;
             self.focus_on_chat_list = false;
-        } else if self.panes.len() > 1 {
-            self.focused_pane_idx = (self.focused_pane_idx + 1) % self.panes.len();
+            self.clear_unread_for_focused_pane();
+
+            let action = self.panes[idx]
+                .click_targets
+                .borrow()
+                .iter()
+                .find(|t| t.row == y && t.cols.contains(&x))
+                .map(|t| t.action.clone());
+            match action {
+                Some(ClickAction::OpenThread(msg_idx)) => self.pending_open_thread = Some(msg_idx),
+                Some(ClickAction::OpenUrl(url)) => {
+                    // xdg-open can take a while; keep it off the UI thread
+                    let target = url.clone();
+                    tokio::task::spawn_blocking(move || {
+                        crate::utils::open_with_default_app(std::path::Path::new(&target))
+                    });
+                    self.set_status(&format!("Opened {}", url));
+                }
+                Some(ClickAction::JumpToLatest) => self.panes[idx].scroll_to_bottom(),
+                None => {}
+            }
         }
-        self.clear_unread_for_focused_pane();
     }
-    
-    fn clear_unread_for_focused_pane(&mut self) {
-        // Clear unread counter for the channel shown in the focused pane
-        if let Some(channel_id) = self.panes.get(self.focused_pane_idx)
-            .and_then(|p| p.channel_id_str.as_ref()) {
-            if let Some(chat) = self.chats.iter_mut().find(|c| &c.id == channel_id) {
-                chat.unread = 0;
+
+    /// Start dragging the sidebar border or a split divider under the mouse.
+    /// Returns false if there is none, so the press is a normal click.
+    pub fn start_drag(&mut self, x: u16, y: u16) -> bool {
+        if let Some(area) = self.chat_list_area {
+            let border = area.x + area.width - 1;
+            if (x == border || x == border + 1) && y >= area.y && y < area.y + area.height {
+                self.drag = Some(DragTarget::Sidebar);
+                return true;
             }
         }
-        
-        // Clear mention counter for current workspace
-        let workspace_name = self.config.workspaces
-            .get(self.config.active_workspace)
-            .map(|w| w.name.clone())
-            .unwrap_or_default();
-        self.unread_mentions.insert(workspace_name, 0);
+        if self.pane_zoomed {
+            return false;
+        }
+        let Some(area) = self.pane_tree_area else {
+            return false;
+        };
+        match self.pane_tree.divider_at(area, x, y) {
+            Some((path, divider)) => {
+                self.drag = Some(DragTarget::Divider { path, divider });
+                true
+            }
+            None => false,
+        }
     }
 
-    pub fn scroll_up(&mut self) {
-        self.ensure_valid_pane_idx();
-        self.panes[self.focused_pane_idx].scroll_up();
+    pub fn drag_to(&mut self, x: u16, y: u16) {
+        match &self.drag {
+            Some(DragTarget::Sidebar) => {
+                let left = self.chat_list_area.map_or(0, |a| a.x);
+                self.sidebar_width = (x + 1).saturating_sub(left).max(MIN_SIDEBAR_WIDTH);
+                self.needs_redraw = true;
+            }
+            Some(DragTarget::Divider { path, divider }) => {
+                if let Some(area) = self.pane_tree_area {
+                    if self.pane_tree.resize_divider(area, path, *divider, x, y) {
+                        for pane in &mut self.panes {
+                            pane.invalidate_cache();
+                        }
+                        self.needs_redraw = true;
+                    }
+                }
+            }
+            None => {}
+        }
     }
 
-    pub fn scroll_down(&mut self) {
-        self.ensure_valid_pane_idx();
-        self.panes[self.focused_pane_idx].scroll_down();
+    pub fn end_drag(&mut self) {
+        self.drag = None;
     }
 
-    pub fn page_up(&mut self) {
-        for _ in 0..10 {
-            self.panes[self.focused_pane_idx].scroll_up();
+    pub fn switch_workspace(&mut self, workspace_idx: usize) {
+        if workspace_idx >= self.config.workspaces.len() {
+            self.set_status("Invalid workspace index");
+            return;
         }
-    }
 
-    pub fn page_down(&mut self) {
-        for _ in 0..10 {
-            self.panes[self.focused_pane_idx].scroll_down();
+        if workspace_idx == self.config.active_workspace {
+            self.set_status("Already on this workspace");
+            return;
         }
-    }
 
-    pub fn scroll_to_top(&mut self) {
-        self.panes[self.focused_pane_idx].scroll_offset = 0;
-    }
+        if self.pending_workspace_switch.is_some() {
+            self.set_status("Workspace switch already in progress");
+            return;
+        }
 
-    pub fn scroll_to_bottom(&mut self) {
-        self.panes[self.focused_pane_idx].scroll_offset = usize::MAX;
+        // Save current workspace state
+        let _ = self.save_state();
+        let _ = self.save_cache();
+
+        // Shutdown old WebSocket task
+        let old_slack = self.slack.clone();
+        tokio::spawn(async move { old_slack.shutdown().await });
+
+        // Update active workspace
+        self.config.active_workspace = workspace_idx;
+        let _ = self.config.save();
+
+        let workspace_name = self.config.workspaces[workspace_idx].name.clone();
+
+        // Show the new workspace's last-known sidebar until the refresh arrives
+        let cache = SessionCache::load(&self.config);
+        self.chats = cache.chats;
+        self.outbox = cache.outbox;
+        self.selected_chat_idx = 0;
+
+        // Load saved layout for this workspace
+        let mut app_state = AppState::load(&self.config).unwrap_or_else(|_| AppState {
+            settings: self.current_settings(),
+            aliases: self.aliases.clone(),
+            snippets: self.snippets.clone(),
+            saved_searches: self.saved_searches.clone(),
+            nicknames: self.nicknames.clone(),
+            followed_threads: self.followed_threads.clone(),
+            starred: self.starred.clone(),
+            recent: RecentChats::default(),
+            sidebar_order: SidebarOrder::default(),
+            drafts: Drafts::default(),
+            layout: LayoutData::default(),
+        });
+        self.recent = app_state.recent;
+        self.sidebar_order = app_state.sidebar_order;
+
+        // Restore pane tree
+        let (pane_tree, required_indices) = if let Some(saved_tree) = app_state.layout.pane_tree {
+            let indices = saved_tree.get_pane_indices();
+            (saved_tree, indices)
+        } else {
+            let tree = PaneNode::new_single(0);
+            let indices = tree.get_pane_indices();
+            (tree, indices)
+        };
+        self.pane_tree = pane_tree;
+
+        // Restore panes
+        let max_required_idx = required_indices.iter().max().copied().unwrap_or(0);
+        let total_panes_needed = (max_required_idx + 1)
+            .max(app_state.layout.panes.len())
+            .max(1);
+
+        self.panes.clear();
+        self.pane_zoomed = false;
+        for i in 0..total_panes_needed {
+            if let Some(ps) = app_state.layout.panes.get(i) {
+                self.panes.push(restore_pane(ps, &mut app_state.drafts));
+            } else {
+                self.panes.push(ChatPane::new());
+            }
+        }
+        self.drafts = app_state.drafts;
+
+        if app_state.layout.focused_pane < self.panes.len() {
+            self.focused_pane_idx = app_state.layout.focused_pane;
+        } else {
+            self.focused_pane_idx = 0;
+        }
+
+        if self.offline {
+            self.load_cached_histories();
+        }
+        self.spawn_connect();
+        self.set_status(&format!("Connecting to workspace: {}...", workspace_name));
     }
 
-    pub fn input_char(&mut self, c: char) {
-        self.ensure_valid_pane_idx();
-        let pane = &mut self.panes[self.focused_pane_idx];
-        pane.input_buffer.insert(pane.input_cursor, c);
-        pane.input_cursor += c.len_utf8();
-        pane.tab_complete_state = None;
+    /// Connect to the active workspace in the background; `poll_workspace_switch` picks
+    /// up the result.
+    fn spawn_connect(&mut self) {
+        let workspace = &self.config.workspaces[self.config.active_workspace];
+        let workspace_token = workspace.token.clone();
+        let workspace_app_token = workspace.app_token.clone();
+        let network = self.config.network.clone();
+        let group_members = SessionCache::load(&self.config).group_members;
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let result = async {
+                let slack = SlackClient::new(&workspace_token, &network)
+                    .await
+                    .map_err(ConnectError::from_error)?;
+                slack.add_group_members(group_members);
+                let my_user_id = slack.get_my_user_id().await.map_err(ConnectError::from_error)?;
+                let my_usergroups = slack.get_my_usergroups().await;
+                slack
+                    .start_event_listener(workspace_app_token)
+                    .await
+                    .map_err(ConnectError::from_error)?;
+                Ok((slack, my_user_id, my_usergroups))
+            }
+            .await;
+            let _ = tx.send(result);
+        });
+        self.pending_workspace_switch = Some(rx);
     }
 
-    pub fn backspace(&mut self) {
-        self.ensure_valid_pane_idx();
-        let pane = &mut self.panes[self.focused_pane_idx];
-        if pane.input_cursor == 0 {
+    /// Offline mode: try to reach Slack again every `OFFLINE_RETRY_SECS`. Online, messages
+    /// handed back unsent are tried again at the same pace.
+    pub fn maybe_go_online(&mut self) {
+        // Online but messages came back unsent: try them again at the same pace
+        if !self.offline {
+            if !self.outbox.is_empty()
+                && self.slack.auth_failure().is_none()
+                && self.pending_workspace_switch.is_none()
+                && self.last_online_attempt.elapsed().as_secs() >= OFFLINE_RETRY_SECS
+            {
+                self.last_online_attempt = std::time::Instant::now();
+                self.flush_outbox();
+            }
             return;
         }
-        let prev = prev_char_boundary(&pane.input_buffer, pane.input_cursor);
-        pane.input_buffer.drain(prev..pane.input_cursor);
-        pane.input_cursor = prev;
-        pane.tab_complete_state = None;
-    }
-
-    pub fn delete_forward(&mut self) {
-        let pane = &mut self.panes[self.focused_pane_idx];
-        if pane.input_cursor >= pane.input_buffer.len() {
+        if self.pending_workspace_switch.is_some()
+            || self.last_online_attempt.elapsed().as_secs() < OFFLINE_RETRY_SECS
+        {
             return;
         }
-        let next = next_char_boundary(&pane.input_buffer, pane.input_cursor);
-        pane.input_buffer.drain(pane.input_cursor..next);
-        pane.tab_complete_state = None;
+        self.last_online_attempt = std::time::Instant::now();
+        self.spawn_connect();
     }
 
-    pub fn input_newline(&mut self) {
-        self.ensure_valid_pane_idx();
-        let pane = &mut self.panes[self.focused_pane_idx];
-        pane.input_buffer.insert(pane.input_cursor, '\n');
-        pane.input_cursor += 1;
-        pane.tab_complete_state = None;
+    /// `/reconnect`: try to connect now instead of waiting for the next retry.
+    pub fn reconnect(&mut self) {
+        if self.offline {
+            if self.pending_workspace_switch.is_some() {
+                self.set_status("Already trying to reach Slack...");
+                return;
+            }
+            self.set_status("Trying to reach Slack...");
+            self.last_online_attempt = std::time::Instant::now();
+            self.spawn_connect();
+        } else if self.slack.reconnect() {
+            self.set_status("Reconnecting Socket Mode...");
+        } else {
+            self.set_status("Socket Mode isn't running (no app token); polling for new messages");
+        }
     }
 
-    pub fn move_cursor_left(&mut self) {
-        let pane = &mut self.panes[self.focused_pane_idx];
-        if pane.input_cursor == 0 {
+    /// Send the messages queued while offline or unsent because Slack was unreachable, in order.
+    fn flush_outbox(&mut self) {
+        if self.outbox.is_empty() {
             return;
         }
-        pane.input_cursor = prev_char_boundary(&pane.input_buffer, pane.input_cursor);
-        pane.tab_complete_state = None;
+        let outbox = std::mem::take(&mut self.outbox);
+        self.send_in_order(outbox);
     }
 
-    pub fn move_cursor_right(&mut self) {
-        let pane = &mut self.panes[self.focused_pane_idx];
-        if pane.input_cursor >= pane.input_buffer.len() {
-            return;
+    /// Queue messages for the sender task, which posts them one after another so they
+    /// keep their order, also behind messages of earlier calls.
+    fn send_in_order(&mut self, messages: Vec<OutboxMessage>) {
+        let queue = match &self.send_queue {
+            Some(queue) if !queue.is_closed() => queue.clone(),
+            _ => self.send_queue.insert(self.spawn_sender()).clone(),
+        };
+        for message in messages {
+            let _ = queue.send(message);
         }
-        pane.input_cursor = next_char_boundary(&pane.input_buffer, pane.input_cursor);
-        pane.tab_complete_state = None;
     }
 
-    pub fn move_cursor_home(&mut self) {
-        let pane = &mut self.panes[self.focused_pane_idx];
-        let (line_start, _) = line_bounds(&pane.input_buffer, pane.input_cursor);
-        pane.input_cursor = line_start;
-        pane.tab_complete_state = None;
+    /// Start the sender task for the current client. It ends once its queue is dropped
+    /// and drained.
+    fn spawn_sender(&self) -> tokio::sync::mpsc::UnboundedSender<OutboxMessage> {
+        let (queue, mut messages) = tokio::sync::mpsc::unbounded_channel::<OutboxMessage>();
+        let slack = self.slack.clone();
+        let tx = self.api_tx.clone();
+        tokio::spawn(async move {
+            // After a message couldn't go out, the ones behind it are handed back unsent
+            // too, so the outbox keeps them in order
+            let mut held: Option<String> = None;
+            while let Some(entry) = messages.recv().await {
+                if let Some(reason) = &held {
+                    let result = Err(reason.clone());
+                    let _ = tx.send(ApiEvent::Sent { message: entry, result, restricted: false, retry: true });
+                    continue;
+                }
+                let result = slack
+                    .send_message(&entry.channel_id, &entry.text, entry.thread_ts.as_deref(), entry.broadcast)
+                    .await;
+                let slack_error = result.as_ref().err().and_then(|e| e.downcast_ref::<SlackError>());
+                let restricted = entry.thread_ts.is_none()
+                    && slack_error.is_some_and(|e| e.code().starts_with("restricted_action"));
+                // Only when the request never reached Slack: a timeout may still have posted it
+                let retry = matches!(slack_error, Some(SlackError::InvalidAuth(_)))
+                    || result.as_ref().is_err_and(|e| {
+                        e.chain()
+                            .any(|c| c.downcast_ref::<reqwest::Error>().is_some_and(|e| e.is_connect()))
+                    });
+                let result = result.map_err(|e| e.to_string());
+                if retry {
+                    held = result.clone().err();
+                }
+                let _ = tx.send(ApiEvent::Sent { message: entry, result, restricted, retry });
+            }
+        });
+        queue
+    }
+
+    /// Called from the event loop to check if a background workspace switch completed.
+    pub fn poll_workspace_switch(&mut self) -> bool {
+        let rx = match self.pending_workspace_switch.as_mut() {
+            Some(rx) => rx,
+            None => return false,
+        };
+
+        match rx.try_recv() {
+            Ok(Ok((slack, my_user_id, my_usergroups))) => {
+                self.connection_state = slack.connection_state();
+                self.slack = slack;
+                self.send_queue = None; // Messages still queued go out with the old client
+                self.my_user_id = my_user_id;
+                self.my_usergroups = my_usergroups;
+                self.member_profiles.clear();
+                self.request_users();
+                self.mention_picker = None;
+                self.app_start_instant = std::time::Instant::now();
+                self.last_realtime_event_instant = None;
+                self.last_realtime_event_at = None;
+                self.last_fallback_refresh_instant = std::time::Instant::now();
+                self.last_fallback_refresh_at = None;
+                self.realtime_was_stale = false;
+                self.pending_workspace_switch = None;
+                self.pending_refresh_chats = true;
+                self.pending_reload_panes = true;
+                self.reauth_asked = false;
+                let name = self.config.workspaces[self.config.active_workspace].name.clone();
+                if std::mem::take(&mut self.reauthenticating) {
+                    self.flush_outbox();
+                    self.set_status(&format!("Signed in to {} again", name));
+                } else if std::mem::take(&mut self.starting) {
+                    let queued = self.outbox.len();
+                    self.flush_outbox();
+                    self.set_status(&match queued {
+                        0 => format!("Connected to {}", name),
+                        n => format!("Connected to {}, sending {} queued messages", name, n),
+                    });
+                } else if self.offline {
+                    self.offline = false;
+                    let queued = self.outbox.len();
+                    self.flush_outbox();
+                    self.set_status(&match queued {
+                        0 => "Back online".to_string(),
+                        n => format!("Back online, sending {} queued messages", n),
+                    });
+                } else {
+                    self.set_status(&format!("Switched to workspace: {}", name));
+                }
+                true
+            }
+            // A refused token asks for a new one; otherwise (e.g. Slack unreachable, or a
+            // rotating token that's renewed meanwhile) offline mode keeps the cached messages
+            Ok(Err(e)) if std::mem::take(&mut self.starting) => {
+                self.pending_workspace_switch = None;
+                let workspace = &self.config.workspaces[self.config.active_workspace];
+                match e {
+                    ConnectError::Rejected(_) if !workspace.rotates_token() => {
+                        self.start_workspace_reauth(Some(e.to_string()));
+                    }
+                    _ => {
+                        warn!("Starting offline: {}", e);
+                        self.offline = true;
+                        self.last_online_attempt = std::time::Instant::now();
+                        self.show_offline_status();
+                    }
+                }
+                false
+            }
+            Ok(Err(e)) if self.offline => {
+                self.pending_workspace_switch = None;
+                debug!("Still offline: {}", e);
+                false
+            }
+            Ok(Err(e)) if std::mem::take(&mut self.reauthenticating) => {
+                self.pending_workspace_switch = None;
+                self.set_status(&format!("Signing in again failed: {} (/workspace reauth to retry)", e));
+                false
+            }
+            Ok(Err(e)) => {
+                self.pending_workspace_switch = None;
+                self.set_status(&format!("Workspace switch failed: {}", e));
+                false
+            }
+            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => false,
+            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
+                self.pending_workspace_switch = None;
+                self.set_status("Workspace switch failed: task dropped");
+                false
+            }
+        }
     }
 
-    pub fn move_cursor_end(&mut self) {
+    /// Scroll the focused pane to the first message that arrived unseen.
+    pub fn jump_to_first_unread(&mut self) {
+        self.ensure_valid_pane_idx();
         let pane = &mut self.panes[self.focused_pane_idx];
-        let (_, line_end) = line_bounds(&pane.input_buffer, pane.input_cursor);
-        pane.input_cursor = line_end;
-        pane.tab_complete_state = None;
+        let idx = pane
+            .first_unread_ts
+            .as_ref()
+            .and_then(|ts| pane.msg_data.iter().position(|m| &m.ts == ts));
+        match idx {
+            Some(idx) => {
+                pane.scroll_to_msg = Some(idx);
+                self.needs_redraw = true;
+            }
+            None => self.set_status("No unread messages in this pane"),
+        }
     }
 
-    pub fn move_cursor_up(&mut self) {
-        let pane = &mut self.panes[self.focused_pane_idx];
-        let (line_start, _) = line_bounds(&pane.input_buffer, pane.input_cursor);
-        if line_start == 0 {
+    /// `/markallread`: zero every local unread counter and move Slack's read cursor
+    /// to "now" for all conversations in the background.
+    pub fn mark_all_read(&mut self) {
+        if self.pending_mark_all_read.is_some() {
+            self.set_status("Already marking conversations as read");
             return;
         }
-        let target_col = column_in_line(&pane.input_buffer, line_start, pane.input_cursor);
-        let prev_line_end = line_start.saturating_sub(1);
-        let prev_line_start = pane.input_buffer[..prev_line_end]
-            .rfind('\n')
-            .map(|idx| idx + 1)
-            .unwrap_or(0);
-        let new_cursor = index_from_column(
-            &pane.input_buffer,
-            prev_line_start,
-            prev_line_end,
-            target_col,
-        );
-        pane.input_cursor = new_cursor.min(pane.input_buffer.len());
-        pane.tab_complete_state = None;
+
+        for chat in &mut self.chats {
+            chat.unread = 0;
+            chat.mentions = 0;
+        }
+        for pane in &mut self.panes {
+            pane.first_unread_ts = None;
+        }
+        let workspace_name = self
+            .config
+            .workspaces
+            .get(self.config.active_workspace)
+            .map(|w| w.name.clone())
+            .unwrap_or_default();
+        self.unread_mentions.insert(workspace_name, 0);
+
+        let slack = self.slack.clone();
+        let channel_ids: Vec<String> = self.chats.iter().map(|c| c.id.clone()).collect();
+        let total = channel_ids.len();
+        let ts = format!("{}.000000", chrono::Local::now().timestamp());
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (mut marked, mut failed) = (0, 0);
+            for channel_id in channel_ids {
+                let mut attempts = 0;
+                loop {
+                    attempts += 1;
+                    match slack.mark_conversation(&channel_id, &ts).await {
+                        Ok(()) => marked += 1,
+                        Err(e) => {
+                            // conversations.mark is rate limited; wait it out a few times
+                            if let Some(SlackError::RateLimited { retry_after }) = e.downcast_ref() {
+                                if attempts < 4 {
+                                    let secs = retry_after.unwrap_or(5);
+                                    tokio::time::sleep(std::time::Duration::from_secs(secs)).await;
+                                    continue;
+                                }
+                            }
+                            warn!("conversations.mark {} failed: {}", channel_id, e);
+                            failed += 1;
+                        }
+                    }
+                    break;
+                }
+            }
+            let _ = tx.send((marked, failed));
+        });
+        self.pending_mark_all_read = Some(rx);
+        self.set_status(&format!("Marking {} conversation(s) as read...", total));
     }
 
-    pub fn move_cursor_down(&mut self) {
-        let pane = &mut self.panes[self.focused_pane_idx];
-        let (line_start, line_end) = line_bounds(&pane.input_buffer, pane.input_cursor);
-        if line_end >= pane.input_buffer.len() {
-            return;
+    /// Called from the event loop to report when `/markallread` finished.
+    pub fn poll_mark_all_read(&mut self) -> bool {
+        let Some(rx) = self.pending_mark_all_read.as_mut() else {
+            return false;
+        };
+        let (marked, failed) = match rx.try_recv() {
+            Ok(result) => result,
+            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return false,
+            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
+                self.pending_mark_all_read = None;
+                self.set_status("Marking as read failed: task dropped");
+                return true;
+            }
+        };
+        self.pending_mark_all_read = None;
+        if failed == 0 {
+            self.set_status(&format!("Marked {} conversation(s) as read", marked));
+        } else {
+            self.set_status(&format!(
+                "Marked {} conversation(s) as read, {} failed (see log)",
+                marked, failed
+            ));
         }
-        let target_col = column_in_line(&pane.input_buffer, line_start, pane.input_cursor);
-        let next_line_start = line_end + 1;
-        let next_line_end = pane.input_buffer[next_line_start..]
-            .find('\n')
-            .map(|idx| next_line_start + idx)
-            .unwrap_or_else(|| pane.input_buffer.len());
-        let new_cursor = index_from_column(
-            &pane.input_buffer,
-            next_line_start,
-            next_line_end,
-            target_col,
-        );
-        pane.input_cursor = new_cursor.min(pane.input_buffer.len());
-        pane.tab_complete_state = None;
+        true
     }
 
-    pub fn tab_complete(&mut self) {
-        use crate::widgets::TabCompleteState;
+    /// Fetch unread conversations from every configured workspace in the background.
+    /// The result is shown by `poll_unreads` in the "All unreads" pane.
+    pub fn open_unified_unreads(&mut self) {
+        if self.pending_unreads.is_some() {
+            self.set_status("Unreads are already loading");
+            return;
+        }
 
-        self.ensure_valid_pane_idx();
-        let pane = &mut self.panes[self.focused_pane_idx];
+        // Reuse the live client for the active workspace, authenticate the others on demand
+        let active_idx = self.config.active_workspace;
+        let targets: Vec<(String, String, Option<SlackClient>)> = self
+            .config
+            .workspaces
+            .iter()
+            .enumerate()
+            .map(|(idx, ws)| {
+                let client = (idx == active_idx).then(|| self.slack.clone());
+                (ws.name.clone(), ws.token.clone(), client)
+            })
+            .collect();
 
-        if let Some(ref mut state) = pane.tab_complete_state {
-            // Cycle to next candidate
-            if state.candidates.is_empty() {
-                return;
-            }
-            state.index = (state.index + 1) % state.candidates.len();
-            let replacement = &state.candidates[state.index];
-            
-            if state.before.starts_with('/') {
-                // Command completion
-                pane.input_buffer = format!("/{} {}", replacement, state.after);
-                pane.input_cursor = replacement.len() + 2;
-            } else {
-                // User mention completion
-                pane.input_buffer = format!("{}@{} {}", state.before, replacement, state.after);
-                pane.input_cursor = state.before.len() + replacement.len() + 2;
-            }
-        } else {
-            let input = &pane.input_buffer;
-            let cursor = pane.input_cursor.min(input.len());
-            let before_cursor = &input[..cursor];
-            
-            // Check if completing a command
-            if before_cursor.starts_with('/') && !before_cursor.contains(' ') {
-                let prefix = &before_cursor[1..];
-                let prefix_lower = prefix.to_lowercase();
-                
-                // All available commands
-                let commands = vec![
-                    "thread", "t", "react", "filter", "alias", "unalias",
-                    "workspace", "ws", "leave", "help", "h"
-                ];
-                
-                let mut candidates: Vec<String> = commands
-                    .into_iter()
-                    .filter(|cmd| cmd.starts_with(&prefix_lower))
-                    .map(|s| s.to_string())
-                    .collect();
-                    
-                if candidates.is_empty() {
-                    return;
+        let network = self.config.network.clone();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let mut results = Vec::new();
+            for (name, token, client) in targets {
+                let chats = async {
+                    let slack = match client {
+                        Some(slack) => slack,
+                        None => SlackClient::new(&token, &network).await?,
+                    };
+                    slack.get_conversations().await
                 }
-                
-                candidates.sort();
-                let after = input[cursor..].to_string();
-                let replacement = &candidates[0];
-                
-                pane.input_buffer = format!("/{} {}", replacement, after);
-                pane.input_cursor = replacement.len() + 2;
-                
-                pane.tab_complete_state = Some(TabCompleteState {
-                    before: "/".to_string(),
-                    after,
-                    candidates,
-                    index: 0,
-                });
-                return;
-            }
-            
-            // Find @prefix at cursor for user mentions
-            let at_pos = before_cursor.rfind('@');
-            if at_pos.is_none() {
-                return;
-            }
-            let at_pos = at_pos.unwrap();
-            let prefix = &before_cursor[at_pos + 1..];
-            // Don't complete empty @ or if there's a space after @
-            if prefix.is_empty() || prefix.contains(' ') {
-                return;
+                .await
+                .map(|chats| chats.into_iter().filter(|c| c.unread > 0).collect())
+                .map_err(|e| e.to_string());
+                results.push((name, chats));
             }
-            let prefix_lower = prefix.to_lowercase();
+            let _ = tx.send(results);
+        });
+        self.pending_unreads = Some(rx);
 
-            // Find matching names from cache
-            let mut candidates: Vec<String> = self
-                .user_name_cache
-                .values()
-                .filter(|name| name.to_lowercase().starts_with(&prefix_lower))
-                .cloned()
-                .collect();
-            candidates.sort();
-            candidates.dedup();
+        self.set_status(&format!(
+            "Loading unreads from {} workspace(s)...",
+            self.config.workspaces.len()
+        ));
+    }
 
-            if candidates.is_empty() {
-                return;
+    /// Called from the event loop to check if the unreads fetch completed.
+    pub fn poll_unreads(&mut self) -> bool {
+        let rx = match self.pending_unreads.as_mut() {
+            Some(rx) => rx,
+            None => return false,
+        };
+
+        let results = match rx.try_recv() {
+            Ok(results) => results,
+            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return false,
+            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
+                self.pending_unreads = None;
+                self.set_status("Loading unreads failed: task dropped");
+                return false;
             }
+        };
+        self.pending_unreads = None;
 
-            let replacement = &candidates[0];
-            let before = input[..at_pos].to_string();
-            let after = input[cursor..].to_string();
-            pane.input_buffer = format!("{}@{} {}", before, replacement, after);
-            pane.input_cursor = before.len() + replacement.len() + 2;
+        let row = |workspace: &str, text: String| list_row(workspace, text, String::new());
 
-            pane.tab_complete_state = Some(TabCompleteState {
-                before,
-                after,
-                candidates,
-                index: 0,
-            });
+        // Interleave all workspaces, busiest conversations first
+        let mut unread: Vec<(String, ChatInfo)> = Vec::new();
+        let mut rows = Vec::new();
+        for (workspace, chats) in results {
+            match chats {
+                Ok(chats) => unread.extend(chats.into_iter().map(|c| (workspace.clone(), c))),
+                Err(e) => rows.push(row(&workspace, format!("(failed to load: {})", e))),
+            }
         }
-    }
-
-    pub fn cancel_reply(&mut self) {
-        let pane = &mut self.panes[self.focused_pane_idx];
-        pane.reply_to_message = None;
-        pane.hide_reply_preview();
-    }
+        unread.sort_by(|(wa, a), (wb, b)| {
+            b.unread
+                .cmp(&a.unread)
+                .then(a.section.cmp(&b.section))
+                .then_with(|| wa.cmp(wb))
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
 
-    // Split management
-    pub fn split_vertical(&mut self) {
-        let new_idx = self.panes.len();
-        self.panes.push(ChatPane::new());
-        // Split the focused pane, not the root
-        if !self.pane_tree.split_pane(self.focused_pane_idx, SplitDirection::Vertical, new_idx) {
-            // Fallback: split at root if focused pane not found
-            self.pane_tree.split(SplitDirection::Vertical, new_idx);
+        let total: u32 = unread.iter().map(|(_, c)| c.unread).sum();
+        let conversations = unread.len();
+        for (workspace, chat) in unread {
+            let prefix = match chat.section {
+                ChatSection::Public | ChatSection::Private => "#",
+                _ => "",
+            };
+            rows.push(row(
+                &workspace,
+                format!("{}{} ({} unread)", prefix, chat.name, chat.unread),
+            ));
         }
-        self.focused_pane_idx = new_idx; // Focus the new pane
-    }
-
-    pub fn split_horizontal(&mut self) {
-        let new_idx = self.panes.len();
-        self.panes.push(ChatPane::new());
-        // Split the focused pane, not the root
-        if !self.pane_tree.split_pane(self.focused_pane_idx, SplitDirection::Horizontal, new_idx) {
-            // Fallback: split at root if focused pane not found
-            self.pane_tree.split(SplitDirection::Horizontal, new_idx);
+        if rows.is_empty() {
+            rows.push(row(UNREADS_PANE_NAME, "Nothing unread".to_string()));
         }
-        self.focused_pane_idx = new_idx; // Focus the new pane
+
+        self.show_list_pane(UNREADS_PANE_NAME, UNREADS_PANE_NAME, rows);
+
+        self.set_status(&format!(
+            "{} unread message(s) in {} conversation(s)",
+            total, conversations
+        ));
+        true
     }
 
-    pub fn toggle_split_direction(&mut self) {
-        self.pane_tree.toggle_direction();
+    /// Show `rows` in the list pane whose title starts with `reuse_prefix`, otherwise
+    /// in a new one split off the focused pane, and focus it.
+    fn show_list_pane(&mut self, reuse_prefix: &str, name: &str, rows: Vec<MessageData>) {
+        let pane_idx = match self
+            .panes
+            .iter()
+            .position(|p| p.channel_id_str.is_none() && p.chat_name.starts_with(reuse_prefix))
+        {
+            Some(idx) => idx,
+            None => {
+                let new_idx = self.panes.len();
+                self.panes.push(ChatPane::new());
+                self.pane_zoomed = false;
+                if !self.pane_tree.split_pane(self.focused_pane_idx, SplitDirection::Vertical, new_idx) {
+                    self.pane_tree.split(SplitDirection::Vertical, new_idx);
+                }
+                new_idx
+            }
+        };
+
+        let pane = &mut self.panes[pane_idx];
+        pane.chat_name = name.to_string();
+        pane.msg_data = rows;
+        pane.scroll_offset = 0;
+        pane.search = None;
+        pane.invalidate_cache();
+        self.focused_pane_idx = pane_idx;
     }
 
-    pub fn close_pane(&mut self) {
-        if self.panes.len() <= 1 {
-            self.set_status("Cannot close the last pane");
+    /// `/search` with Slack operators: search.messages, shown in a results pane. Tokens
+    /// that can't search (bot tokens, no search:read) and offline mode search the
+    /// messages loaded in the open panes instead.
+    pub async fn search_slack(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            self.set_status("Usage: /search <text with from:@name in:#channel before:/after:/on:DATE has:link>");
             return;
         }
-        
-        let pane_idx = self.focused_pane_idx;
-        
-        // Get all pane indices before closing
-        let all_indices = self.pane_tree.get_pane_indices();
-        
-        // Find a new pane to focus on (prefer next pane, or previous if we're at the end)
-        let current_pos = all_indices.iter().position(|&idx| idx == pane_idx).unwrap_or(0);
-        let new_focus_idx = if current_pos + 1 < all_indices.len() {
-            all_indices[current_pos + 1]
-        } else if current_pos > 0 {
-            all_indices[current_pos - 1]
-        } else {
-            0
-        };
-        
-        // Remove the pane from the tree
-        self.pane_tree.close_pane(pane_idx);
-        
-        // Remove the pane from the array
-        self.panes.remove(pane_idx);
-        
-        // Reindex all pane indices in the tree (shift down indices > pane_idx)
-        self.pane_tree.reindex_after_removal(pane_idx);
-        
-        // Update focused pane index (adjust if it was after the removed pane)
-        self.focused_pane_idx = if new_focus_idx > pane_idx {
-            new_focus_idx - 1
-        } else {
-            new_focus_idx
-        };
-        
-        // Ensure focused index is valid
-        if self.focused_pane_idx >= self.panes.len() {
-            self.focused_pane_idx = self.panes.len().saturating_sub(1);
+        if self.offline {
+            self.search_loaded_messages(query, "offline");
+            return;
+        }
+
+        self.set_status("Searching...");
+        match self.slack.search_messages(query, SEARCH_RESULTS).await {
+            Ok((matches, total)) => {
+                let rows: Vec<MessageData> = matches
+                    .into_iter()
+                    .map(|m| {
+                        let sender = m
+                            .user
+                            .as_ref()
+                            .and_then(|id| self.nicknames.map.get(id).or_else(|| self.user_name_cache.get(id)))
+                            .cloned()
+                            .unwrap_or(m.username);
+                        let channel = if m.channel.is_im {
+                            let name = self.user_name_cache.get(&m.channel.name).unwrap_or(&m.channel.name);
+                            format!("@{}", name)
+                        } else {
+                            format!("#{}", m.channel.name)
+                        };
+                        list_row(&format!("{} in {}", sender, channel), m.text, m.ts)
+                    })
+                    .collect();
+                let shown = rows.len();
+                self.show_search_results(query, rows);
+                self.set_status(&format!("{} result(s), showing the newest {}", total, shown));
+            }
+            Err(e) => {
+                let code = e.downcast_ref::<SlackError>().map(|e| e.code().to_string());
+                match code.as_deref() {
+                    Some("not_allowed_token_type" | "missing_scope") => {
+                        self.search_loaded_messages(query, "search.messages needs a user token with search:read");
+                    }
+                    _ => self.set_status(&format!("Search failed: {}", e)),
+                }
+            }
         }
     }
 
-    pub fn clear_pane(&mut self) {
-        self.panes[self.focused_pane_idx].clear();
+    /// Match the query against every message loaded in the open panes.
+    fn search_loaded_messages(&mut self, query: &str, why: &str) {
+        let parsed = SearchQuery::parse(query);
+        let resolve_user = |id: &str| self.user_name_cache.get(id).cloned().unwrap_or_else(|| id.to_string());
+        let mut found: Vec<(String, MessageData)> = Vec::new();
+        for pane in self.panes.iter().filter(|p| p.channel_id_str.is_some()) {
+            for msg in pane.msg_data.iter().filter(|m| !m.is_system && !m.is_deleted) {
+                let text = format_message_text(&msg.text, self.show_emojis, false, &resolve_user);
+                let has_files = !msg.files.is_empty() || !msg.file_ids.is_empty();
+                if !parsed.matches(&pane.chat_name, &msg.sender_name, &text, has_files, ts_date(&msg.ts)) {
+                    continue;
+                }
+                let key = format!("{:?}{}", pane.channel_id_str, msg.ts);
+                if found.iter().all(|(k, _)| *k != key) {
+                    let sender = format!("{} in {}", msg.sender_name, pane.chat_name);
+                    found.push((key, list_row(&sender, msg.text.clone(), msg.ts.clone())));
+                }
+            }
+        }
+        // Newest first, like search.messages
+        found.sort_by(|(_, a), (_, b)| b.ts.cmp(&a.ts));
+        let rows: Vec<MessageData> = found.into_iter().map(|(_, row)| row).take(SEARCH_RESULTS).collect();
+        let shown = rows.len();
+        self.show_search_results(query, rows);
+        self.set_status(&format!("{} result(s) in loaded messages only ({})", shown, why));
     }
 
-    // Toggle settings
-    pub fn toggle_chat_list(&mut self) {
-        self.show_chat_list = !self.show_chat_list;
-        self.needs_redraw = true;
+    fn show_search_results(&mut self, query: &str, mut rows: Vec<MessageData>) {
+        if rows.is_empty() {
+            rows.push(list_row("Search", "No results".to_string(), String::new()));
+        }
+        // Oldest at the top, like a conversation
+        rows.reverse();
+        let name = format!("{}{}", SEARCH_PANE_PREFIX, query);
+        self.show_list_pane(SEARCH_PANE_PREFIX, &name, rows);
     }
 
-    pub fn toggle_reactions(&mut self) {
-        self.show_reactions = !self.show_reactions;
-        for pane in &mut self.panes {
-            pane.invalidate_cache();
-        }
-        self.needs_redraw = true;
+    /// Index into `chats` by channel ID or (case-insensitive) name, '#' optional
+    fn find_chat_idx(&self, wanted: &str) -> Option<usize> {
+        let name = wanted.trim_start_matches('#');
+        self.chats
+            .iter()
+            .position(|c| c.id == wanted)
+            .or_else(|| self.chats.iter().position(|c| c.name.eq_ignore_ascii_case(name)))
     }
 
-    pub fn toggle_emojis(&mut self) {
-        self.show_emojis = !self.show_emojis;
-        for pane in &mut self.panes {
-            pane.invalidate_cache();
+    /// Answer all commands queued on the control socket.
+    /// Wait for the next control socket request (never resolves without a socket).
+    pub async fn next_ipc_request(
+        requests: &mut Option<tokio::sync::mpsc::UnboundedReceiver<IpcRequest>>,
+    ) -> Option<IpcRequest> {
+        match requests.as_mut() {
+            Some(rx) => rx.recv().await,
+            None => std::future::pending().await,
         }
-        self.needs_redraw = true;
     }
 
-    pub fn toggle_timestamps(&mut self) {
-        self.show_timestamps = !self.show_timestamps;
-        for pane in &mut self.panes {
-            pane.invalidate_cache();
-        }
+    pub async fn handle_ipc_request(&mut self, request: IpcRequest) {
+        let IpcRequest { command, reply } = request;
+        let response = self.handle_ipc_command(command).await;
+        let _ = reply.send(response);
         self.needs_redraw = true;
     }
 
-    pub fn toggle_compact_mode(&mut self) {
-        self.compact_mode = !self.compact_mode;
-        for pane in &mut self.panes {
-            pane.invalidate_cache();
-        }
-        self.needs_redraw = true;
+    /// Results of background tasks are polled, so the event loop wakes up more often while one runs.
+    pub fn has_background_work(&self) -> bool {
+        self.pending_workspace_switch.is_some()
+            || self.pending_unreads.is_some()
+            || self.pending_mark_all_read.is_some()
+            || self
+                .downloads
+                .iter()
+                .any(|d| matches!(d.state, DownloadState::InProgress))
     }
 
-    pub fn toggle_line_numbers(&mut self) {
-        self.show_line_numbers = !self.show_line_numbers;
-        for pane in &mut self.panes {
-            pane.invalidate_cache();
+    async fn handle_ipc_command(&mut self, command: IpcCommand) -> serde_json::Value {
+        match command {
+            IpcCommand::Send { channel, text, thread_ts } => {
+                if self.read_only {
+                    return error_reply("client is in read-only mode");
+                }
+                let Some(idx) = self.find_chat_idx(&channel) else {
+                    return error_reply(&format!("channel '{}' not found", channel));
+                };
+                let channel_id = self.chats[idx].id.clone();
+                let text = self.convert_mentions_to_ids(&text);
+                match self.slack.send_message(&channel_id, &text, thread_ts.as_deref(), false).await {
+                    Ok(ts) => serde_json::json!({ "ok": true, "ts": ts }),
+                    Err(e) => error_reply(&e.to_string()),
+                }
+            }
+            IpcCommand::OpenChannel { channel } => {
+                let Some(idx) = self.find_chat_idx(&channel) else {
+                    return error_reply(&format!("channel '{}' not found", channel));
+                };
+                self.selected_chat_idx = idx;
+                self.open_selected_chat();
+                self.needs_redraw = true;
+                serde_json::json!({ "ok": true })
+            }
+            IpcCommand::NotifyState => {
+                let workspace = self
+                    .config
+                    .workspaces
+                    .get(self.config.active_workspace)
+                    .map(|w| w.name.clone())
+                    .unwrap_or_default();
+                let unread_chats = self.chats.iter().filter(|c| c.unread > 0).count();
+                let unread: u32 = self.chats.iter().map(|c| c.unread).sum();
+                serde_json::json!({
+                    "ok": true,
+                    "workspace": workspace,
+                    "mentions": self.unread_mentions,
+                    "unread": unread,
+                    "unread_chats": unread_chats,
+                    "read_only": self.read_only,
+                })
+            }
         }
-        self.needs_redraw = true;
     }
 
-    pub fn toggle_user_colors(&mut self) {
-        self.show_user_colors = !self.show_user_colors;
-        for pane in &mut self.panes {
-            pane.invalidate_cache();
+    pub fn get_workspace_list(&self) -> Vec<(usize, String, bool)> {
+        self.config.workspaces
+            .iter()
+            .enumerate()
+            .map(|(idx, ws)| (idx, ws.name.clone(), idx == self.config.active_workspace))
+            .collect()
+    }
+
+    /// Workspace by 1-based number or (case-insensitive) name
+    pub fn find_workspace(&self, arg: &str) -> Option<usize> {
+        match arg.parse::<usize>() {
+            Ok(n) => (n >= 1 && n <= self.config.workspaces.len()).then(|| n - 1),
+            Err(_) => self
+                .config
+                .workspaces
+                .iter()
+                .position(|ws| ws.name.eq_ignore_ascii_case(arg)),
         }
-        self.needs_redraw = true;
     }
 
-    pub fn toggle_borders(&mut self) {
-        self.show_borders = !self.show_borders;
+    /// `/workspace add`: ask for a name and the tokens, then save the new workspace.
+    pub fn start_workspace_add(&mut self) {
+        if self.reject_if_read_only() {
+            return;
+        }
+        self.workspace_prompt = Some(WorkspacePrompt::new(WorkspaceStep::Name, String::new()));
         self.needs_redraw = true;
     }
 
-    pub fn toggle_mouse_support(&mut self) {
-        self.mouse_support = !self.mouse_support;
-        let status = if self.mouse_support {
-            "Mouse support enabled (click to focus panes)"
-        } else {
-            "Mouse support disabled (use Shift+drag to select text)"
+    /// `/workspace rename [name|N]`: rename a workspace (the active one by default).
+    pub fn start_workspace_rename(&mut self, arg: Option<&str>) {
+        if self.reject_if_read_only() {
+            return;
+        }
+        let idx = match arg {
+            Some(arg) => match self.find_workspace(arg) {
+                Some(idx) => idx,
+                None => {
+                    self.set_status(&format!("Workspace '{}' not found", arg));
+                    return;
+                }
+            },
+            None => self.config.active_workspace,
         };
-        self.set_status(status);
+        let Some(workspace) = self.config.workspaces.get(idx) else {
+            self.set_status("No workspace configured");
+            return;
+        };
+        self.workspace_prompt = Some(WorkspacePrompt::new(WorkspaceStep::Rename(idx), workspace.name.clone()));
+        self.needs_redraw = true;
     }
 
-    pub fn handle_mouse_click(&mut self, x: u16, y: u16) {
-        // Check if click is in chat list
-        if let Some(area) = self.chat_list_area {
-            if x >= area.x && x < area.x + area.width && y >= area.y && y < area.y + area.height {
-                self.focus_on_chat_list = true;
-                // Calculate which chat was clicked (accounting for scroll offset and border)
-                let border_offset = if self.show_borders { 1 } else { 0 };
-                let relative_y = y.saturating_sub(area.y + border_offset);
-                let row_idx = relative_y as usize + self.chat_list_scroll_offset;
-                let rows = self.build_chat_list_rows();
-                if let Some(chat_idx) = Self::row_to_chat_idx(&rows, row_idx) {
-                    self.selected_chat_idx = chat_idx;
-                    self.pending_open_chat = true;
-                }
-                return;
-            }
+    /// `/workspace reauth`: enter a new token for the active workspace.
+    pub fn start_workspace_reauth(&mut self, reason: Option<String>) {
+        if self.reject_if_read_only() {
+            return;
+        }
+        if self.config.workspaces.is_empty() {
+            self.set_status("No workspace configured");
+            return;
         }
+        let mut prompt = WorkspacePrompt::new(WorkspaceStep::Reauth(self.config.active_workspace), String::new());
+        prompt.error = reason;
+        self.workspace_prompt = Some(prompt);
+        self.needs_redraw = true;
+    }
 
-        // Check if click is in a pane
-        for (idx, area) in &self.pane_areas {
-            if x >= area.x && x < area.x + area.width && y >= area.y && y < area.y + area.height {
-                self.focused_pane_idx = *idx;
-                self.focus_on_chat_list = false;
-                self.clear_unread_for_focused_pane();
-                return;
+    /// Renew rotating tokens (`refresh_token` in the workspace config) shortly before they
+    /// expire, for every workspace so switching to one works too. The active workspace's
+    /// token is also renewed as soon as Slack rejects it.
+    pub fn maybe_refresh_tokens(&mut self) {
+        let now = chrono::Utc::now().timestamp();
+        let rejected = self.slack.auth_failure().is_some();
+        for (idx, workspace) in self.config.workspaces.iter().enumerate() {
+            let due = workspace.token_refresh_due(now) || (rejected && idx == self.config.active_workspace);
+            let tried_lately = self
+                .token_refreshes
+                .get(&workspace.name)
+                .is_some_and(|at| at.elapsed().as_secs() < TOKEN_REFRESH_RETRY_SECS);
+            if !workspace.rotates_token() || !due || tried_lately {
+                continue;
             }
+            self.token_refreshes.insert(workspace.name.clone(), std::time::Instant::now());
+            let name = workspace.name.clone();
+            let (refresh_token, client_id, client_secret) = (
+                workspace.refresh_token.clone(),
+                workspace.client_id.clone(),
+                workspace.client_secret.clone(),
+            );
+            let network = self.config.network.clone();
+            let tx = self.api_tx.clone();
+            tokio::spawn(async move {
+                let result =
+                    SlackClient::refresh_access_token(&refresh_token, &client_id, &client_secret, &network).await;
+                let _ = tx.send(ApiEvent::TokenRefreshed {
+                    workspace: name,
+                    result: result.map_err(|e| e.to_string()),
+                });
+            });
         }
     }
 
-    pub fn switch_workspace(&mut self, workspace_idx: usize) {
-        if workspace_idx >= self.config.workspaces.len() {
-            self.set_status("Invalid workspace index");
+    /// Slack stopped accepting the token: ask for a new one, once per rejected token.
+    /// Until then Web API calls fail right away and the panes keep what they show.
+    /// Workspaces with token rotation are only asked once renewing the token failed.
+    pub fn maybe_prompt_reauth(&mut self) {
+        if self.reauth_asked || self.offline || self.pending_workspace_switch.is_some() {
             return;
         }
-
-        if workspace_idx == self.config.active_workspace {
-            self.set_status("Already on this workspace");
+        let Some(code) = self.slack.auth_failure() else {
+            return;
+        };
+        let workspace = &self.config.workspaces[self.config.active_workspace];
+        if workspace.rotates_token() && !self.token_refresh_failed.contains(&workspace.name) {
             return;
         }
-
-        if self.pending_workspace_switch.is_some() {
-            self.set_status("Workspace switch already in progress");
+        self.reauth_asked = true;
+        let name = workspace.name.clone();
+        warn!("Slack rejected the token of {} ({}), asking for a new one", name, code);
+        if self.workspace_prompt.is_some() || self.read_only {
+            self.set_status(&format!("Slack rejected the token ({}); /workspace reauth to sign in again", code));
             return;
         }
+        self.start_workspace_reauth(Some(format!("Slack no longer accepts the token of {} ({})", name, code)));
+    }
 
-        // Save current workspace state
-        let _ = self.save_state();
-
-        // Shutdown old WebSocket task
+    /// Connect again with the active workspace's (new) token. The sidebar and panes
+    /// are reloaded once connected, like after a workspace switch.
+    fn sign_in_again(&mut self) {
         let old_slack = self.slack.clone();
         tokio::spawn(async move { old_slack.shutdown().await });
+        self.reauthenticating = true;
+        self.set_status("Signing in again...");
+        self.spawn_connect();
+    }
 
-        // Update active workspace
-        self.config.active_workspace = workspace_idx;
-        let _ = self.config.save();
-
-        let workspace_name = self.config.workspaces[workspace_idx].name.clone();
-        let workspace_token = self.config.workspaces[workspace_idx].token.clone();
-        let workspace_app_token = self.config.workspaces[workspace_idx].app_token.clone();
-
-        // Clear old chats and restore layout synchronously
-        self.chats.clear();
-
-        // Load saved layout for this workspace
-        let app_state = AppState::load(&self.config).unwrap_or_else(|_| AppState {
-            settings: crate::persistence::AppSettings {
-                show_reactions: self.show_reactions,
-                show_notifications: self.show_notifications,
-                compact_mode: self.compact_mode,
-                show_emojis: self.show_emojis,
-                show_line_numbers: self.show_line_numbers,
-                show_timestamps: self.show_timestamps,
-                show_chat_list: self.show_chat_list,
-                show_user_colors: self.show_user_colors,
-                show_borders: self.show_borders,
-                mouse_support: self.mouse_support,
-            },
-            aliases: self.aliases.clone(),
-            layout: LayoutData::default(),
-        });
-
-        // Restore pane tree
-        let (pane_tree, required_indices) = if let Some(saved_tree) = app_state.layout.pane_tree {
-            let indices = saved_tree.get_pane_indices();
-            (saved_tree, indices)
-        } else {
-            let tree = PaneNode::new_single(0);
-            let indices = tree.get_pane_indices();
-            (tree, indices)
+    /// `/workspace remove <name|N>`: drop a workspace other than the active one.
+    pub fn remove_workspace(&mut self, arg: &str) {
+        if self.reject_if_read_only() {
+            return;
+        }
+        let Some(idx) = self.find_workspace(arg) else {
+            self.set_status(&format!("Workspace '{}' not found", arg));
+            return;
         };
-        self.pane_tree = pane_tree;
-
-        // Restore panes
-        let max_required_idx = required_indices.iter().max().copied().unwrap_or(0);
-        let total_panes_needed = (max_required_idx + 1)
-            .max(app_state.layout.panes.len())
-            .max(1);
-
-        self.panes.clear();
-        for i in 0..total_panes_needed {
-            if let Some(ps) = app_state.layout.panes.get(i) {
-                let mut pane = ChatPane::new();
-                pane.chat_id = ps.chat_id;
-                pane.channel_id_str = ps.channel_id.clone();
-                pane.chat_name = ps.chat_name.clone();
-                pane.scroll_offset = ps.scroll_offset;
-                pane.thread_ts = ps.thread_ts.clone();
-                self.panes.push(pane);
-            } else {
-                self.panes.push(ChatPane::new());
-            }
+        if idx == self.config.active_workspace {
+            self.set_status("Switch to another workspace before removing this one");
+            return;
         }
-
-        if app_state.layout.focused_pane < self.panes.len() {
-            self.focused_pane_idx = app_state.layout.focused_pane;
-        } else {
-            self.focused_pane_idx = 0;
+        if self.pending_workspace_switch.is_some() {
+            self.set_status("Workspace switch in progress, try again in a moment");
+            return;
         }
+        match self.config.remove_workspace(idx) {
+            Ok(removed) => {
+                self.unread_mentions.remove(&removed.name);
+                self.set_status(&format!("Removed workspace {}", removed.name));
+            }
+            Err(e) => self.set_status(&format!("Failed to remove workspace: {}", e)),
+        }
+    }
 
-        // Spawn async connection work in background
-        let (tx, rx) = tokio::sync::oneshot::channel();
-        tokio::spawn(async move {
-            let result = async {
-                let slack = SlackClient::new(&workspace_token, &workspace_app_token)
-                    .await
-                    .map_err(|e| e.to_string())?;
-                let my_user_id = slack.get_my_user_id().await.map_err(|e| e.to_string())?;
-                slack
-                    .start_event_listener(workspace_app_token)
-                    .await
-                    .map_err(|e| e.to_string())?;
-                Ok((slack, my_user_id))
+    /// Typing in the /workspace prompt (`None` = Backspace).
+    pub fn workspace_prompt_key(&mut self, c: Option<char>) {
+        let Some(prompt) = &mut self.workspace_prompt else {
+            return;
+        };
+        match c {
+            Some(c) => prompt.input.push(c),
+            None => {
+                prompt.input.pop();
             }
-            .await;
-            let _ = tx.send(result);
-        });
-        self.pending_workspace_switch = Some(rx);
+        }
+        self.needs_redraw = true;
+    }
 
-        self.set_status(&format!("Connecting to workspace: {}...", workspace_name));
+    pub fn cancel_workspace_prompt(&mut self) {
+        self.workspace_prompt = None;
+        self.set_status("Cancelled");
     }
 
-    /// Called from the event loop to check if a background workspace switch completed.
-    pub fn poll_workspace_switch(&mut self) -> bool {
-        let rx = match self.pending_workspace_switch.as_mut() {
-            Some(rx) => rx,
-            None => return false,
+    /// Enter in the /workspace prompt: check the answer and go to the next step.
+    pub async fn workspace_prompt_submit(&mut self) {
+        let Some(mut prompt) = self.workspace_prompt.take() else {
+            return;
         };
-
-        match rx.try_recv() {
-            Ok(Ok((slack, my_user_id))) => {
-                self.slack = slack;
-                self.my_user_id = my_user_id;
-                self.app_start_instant = std::time::Instant::now();
-                self.last_realtime_event_instant = None;
-                self.last_realtime_event_at = None;
-                self.last_fallback_refresh_instant = std::time::Instant::now();
-                self.last_fallback_refresh_at = None;
-                self.realtime_was_stale = false;
-                self.pending_workspace_switch = None;
-                self.pending_refresh_chats = true;
-                self.pending_reload_panes = true;
-                let name = self.config.workspaces[self.config.active_workspace].name.clone();
-                self.set_status(&format!("Switched to workspace: {}", name));
-                true
+        self.needs_redraw = true;
+        let input = prompt.input.trim().to_string();
+        let names: Vec<&str> = self.config.workspaces.iter().map(|ws| ws.name.as_str()).collect();
+        if let Some(error) = prompt.invalid_answer(&names) {
+            prompt.error = Some(error);
+            self.workspace_prompt = Some(prompt);
+            return;
+        }
+        prompt.error = None;
+
+        match prompt.step {
+            WorkspaceStep::Name => {
+                prompt.name = input;
+                prompt.input.clear();
+                prompt.step = WorkspaceStep::Token;
+                self.workspace_prompt = Some(prompt);
             }
-            Ok(Err(e)) => {
-                self.pending_workspace_switch = None;
-                self.set_status(&format!("Workspace switch failed: {}", e));
-                false
+            WorkspaceStep::Token => {
+                // Catch a mistyped token now rather than at the next workspace switch
+                match SlackClient::verify_token(&input, &self.config.network).await {
+                    Ok(_) => {
+                        prompt.token = input;
+                        prompt.input.clear();
+                        prompt.step = WorkspaceStep::AppToken;
+                    }
+                    Err(e) => prompt.error = Some(format!("Slack rejected the token: {}", e)),
+                }
+                self.workspace_prompt = Some(prompt);
             }
-            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => false,
-            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
-                self.pending_workspace_switch = None;
-                self.set_status("Workspace switch failed: task dropped");
-                false
+            WorkspaceStep::AppToken => {
+                let workspace = crate::config::Workspace {
+                    name: prompt.name.clone(),
+                    token: prompt.token.clone(),
+                    app_token: input,
+                    ..Default::default()
+                };
+                match self.config.add_workspace(workspace) {
+                    Ok(()) => {
+                        let number = self.config.workspaces.len();
+                        self.set_status(&format!(
+                            "Added workspace {} as #{} (/workspace {} to switch)",
+                            prompt.name, number, number
+                        ));
+                    }
+                    Err(e) => self.set_status(&format!("Failed to save the config: {}", e)),
+                }
+            }
+            WorkspaceStep::Reauth(idx) => {
+                if let Err(e) = SlackClient::verify_token(&input, &self.config.network).await {
+                    prompt.error = Some(format!("Slack rejected the token: {}", e));
+                    self.workspace_prompt = Some(prompt);
+                    return;
+                }
+                if let Err(e) = self.config.set_workspace_token(idx, &input) {
+                    warn!("Saving the new token failed: {}", e);
+                }
+                if idx == self.config.active_workspace && self.pending_workspace_switch.is_none() {
+                    self.sign_in_again();
+                } else {
+                    self.set_status("Token saved");
+                }
+            }
+            WorkspaceStep::Rename(idx) => {
+                let old_name = self.config.workspaces[idx].name.clone();
+                if idx == self.config.active_workspace {
+                    // Written under the new name once renamed
+                    let _ = self.save_state();
+                    let _ = self.save_cache();
+                }
+                match self.config.rename_workspace(idx, &input) {
+                    Ok(()) => {
+                        if let Some(count) = self.unread_mentions.remove(&old_name) {
+                            self.unread_mentions.insert(input.clone(), count);
+                        }
+                        self.set_status(&format!("Renamed workspace {} to {}", old_name, input));
+                    }
+                    Err(e) => self.set_status(&format!("Failed to rename workspace: {}", e)),
+                }
             }
         }
     }
 
-    pub fn get_workspace_list(&self) -> Vec<(usize, String, bool)> {
-        self.config.workspaces
-            .iter()
-            .enumerate()
-            .map(|(idx, ws)| (idx, ws.name.clone(), idx == self.config.active_workspace))
-            .collect()
-    }
-
     pub fn show_workspace_list(&mut self) {
         let workspaces = self.get_workspace_list();
         let mut msg = String::from("Workspaces (Ctrl+1-9 to switch):\n");
@@ -2908,12 +7875,10 @@ fn column_in_line(s: &str, line_start: usize, cursor: usize) -> usize {
 }
 
 fn index_from_column(s: &str, line_start: usize, line_end: usize, target_col: usize) -> usize {
-    let mut col = 0;
-    for (byte_idx, _) in s[line_start..line_end].char_indices() {
+    for (col, (byte_idx, _)) in s[line_start..line_end].char_indices().enumerate() {
         if col >= target_col {
             return line_start + byte_idx;
         }
-        col += 1;
     }
     line_end
 }
@@ -0,0 +1,223 @@
+use anyhow::{anyhow, Result};
+use chrono::{Local, TimeZone};
+use clap::{Parser, Subcommand};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::app::{ChatInfo, ChatSection};
+use crate::config::Config;
+use crate::doctor;
+use crate::formatting::format_message_text;
+use crate::slack::SlackClient;
+
+/// Terminal Slack client. Without a subcommand the interactive TUI is started.
+#[derive(Parser)]
+#[command(name = "slack_client_rs", version, about)]
+pub struct Cli {
+    /// Config file to use instead of config/slack_config.json
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Start the TUI in read-only presentation mode
+    #[arg(long, visible_alias = "present")]
+    pub read_only: bool,
+
+    /// Do not open the control socket for external scripts
+    #[arg(long)]
+    pub no_ipc: bool,
+
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Subcommand)]
+pub enum CliCommand {
+    /// Send a message to a channel or DM
+    Send {
+        /// Channel ID, channel name (#general) or DM user name
+        channel: String,
+        /// Message text (remaining arguments are joined with spaces)
+        #[arg(required = true, num_args = 1..)]
+        text: Vec<String>,
+    },
+    /// List the conversations you are a member of
+    Channels {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print recent messages of a channel, oldest first
+    History {
+        /// Channel ID, channel name (#general) or DM user name
+        channel: String,
+        /// Number of messages to fetch
+        #[arg(short = 'n', long = "limit", default_value_t = 50)]
+        limit: usize,
+        /// Print JSON (one array) instead of text lines
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check the tokens' scopes and Socket Mode, and explain what to fix
+    Doctor,
+}
+
+/// Run a non-interactive subcommand with the active workspace's credentials.
+/// A rotating token that is about to expire is renewed (and saved) first.
+pub async fn run(command: CliCommand, config: &mut Config) -> Result<()> {
+    let idx = config.active_workspace;
+    let workspace = config
+        .workspaces
+        .get(idx)
+        .ok_or_else(|| anyhow!("No workspaces configured; run without a subcommand to set one up"))?;
+    if workspace.token_refresh_due(chrono::Utc::now().timestamp()) {
+        let rotated = SlackClient::refresh_access_token(
+            &workspace.refresh_token,
+            &workspace.client_id,
+            &workspace.client_secret,
+            &config.network,
+        )
+        .await
+        .map_err(|e| anyhow!("Refreshing the token of {} failed: {}", workspace.name, e))?;
+        if let Err(e) = config.set_rotated_token(idx, &rotated.access_token, &rotated.refresh_token, rotated.expires_at)
+        {
+            eprintln!("Warning: Could not save the refreshed token: {}", e);
+        }
+    }
+    let workspace = &config.workspaces[idx];
+    let slack = match SlackClient::new(&workspace.token, &config.network).await {
+        Ok(slack) => slack,
+        Err(e) if matches!(command, CliCommand::Doctor) => {
+            return print_doctor_report(&workspace.name, doctor::auth_failure(&e));
+        }
+        Err(e) => return Err(e),
+    };
+
+    match command {
+        CliCommand::Doctor => {
+            let report = doctor::check(&slack, &workspace.app_token, None).await;
+            return print_doctor_report(&workspace.name, report);
+        }
+        CliCommand::Send { channel, text } => {
+            let chat = resolve_channel(&slack, &channel).await?;
+            slack.send_message(&chat.id, &text.join(" "), None, false).await?;
+        }
+        CliCommand::Channels { json } => {
+            let mut chats = slack.get_conversations().await?;
+            chats.sort_by_key(|c| (c.section as u8, c.name.to_lowercase()));
+            if json {
+                let list: Vec<serde_json::Value> = chats
+                    .iter()
+                    .map(|c| {
+                        serde_json::json!({
+                            "id": c.id,
+                            "name": c.name,
+                            "section": section_key(c.section),
+                            "unread": c.unread,
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&list)?);
+            } else {
+                for c in &chats {
+                    println!("{}\t{}\t{}", c.id, section_key(c.section), display_name(c));
+                }
+            }
+        }
+        CliCommand::History { channel, limit, json } => {
+            let chat = resolve_channel(&slack, &channel).await?;
+            let mut messages = slack.get_conversation_history(&chat.id, limit).await?;
+            messages.reverse(); // API returns newest first
+
+            let mut names: HashMap<String, String> = HashMap::new();
+            for msg in &messages {
+                if let Some(ref uid) = msg.user {
+                    if !names.contains_key(uid) {
+                        names.insert(uid.clone(), slack.resolve_user_name(uid).await);
+                    }
+                }
+            }
+            let author = |msg: &crate::slack::SlackMessage| -> String {
+                if let Some(ref uid) = msg.user {
+                    names.get(uid).cloned().unwrap_or_else(|| uid.clone())
+                } else if let Some(ref bot) = msg.bot_profile {
+                    bot.name.clone().unwrap_or_else(|| "Bot".to_string())
+                } else {
+                    msg.username.clone().unwrap_or_else(|| "Unknown".to_string())
+                }
+            };
+
+            if json {
+                let list: Vec<serde_json::Value> = messages
+                    .iter()
+                    .map(|m| {
+                        serde_json::json!({
+                            "ts": m.ts,
+                            "user": m.user,
+                            "author": author(m),
+                            "text": m.text,
+                            "thread_ts": m.thread_ts,
+                            "reply_count": m.reply_count,
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&list)?);
+            } else {
+                let resolve = |id: &str| names.get(id).cloned().unwrap_or_else(|| id.to_string());
+                for m in &messages {
+                    let time = m
+                        .ts
+                        .split('.')
+                        .next()
+                        .and_then(|s| s.parse::<i64>().ok())
+                        .and_then(|secs| Local.timestamp_opt(secs, 0).single())
+                        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_default();
+                    let text = format_message_text(&m.text, true, false, &resolve);
+                    println!("[{}] {}: {}", time, author(m), text);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_doctor_report(workspace: &str, report: doctor::Report) -> Result<()> {
+    println!("Workspace: {}", workspace);
+    for line in &report.lines {
+        println!("  {}", line);
+    }
+    match report.problems {
+        0 => Ok(()),
+        n => Err(anyhow!("{} problem(s) found", n)),
+    }
+}
+
+/// Find a conversation by ID or (case-insensitive) name, with or without a leading '#'.
+async fn resolve_channel(slack: &SlackClient, wanted: &str) -> Result<ChatInfo> {
+    let chats = slack.get_conversations().await?;
+    let name = wanted.trim_start_matches('#');
+    chats
+        .iter()
+        .find(|c| c.id == wanted)
+        .or_else(|| chats.iter().find(|c| c.name.eq_ignore_ascii_case(name)))
+        .cloned()
+        .ok_or_else(|| anyhow!("Channel '{}' not found", wanted))
+}
+
+fn section_key(section: ChatSection) -> &'static str {
+    match section {
+        ChatSection::Public => "public",
+        ChatSection::Private => "private",
+        ChatSection::Group => "group",
+        ChatSection::DirectMessage => "dm",
+        ChatSection::Bot => "bot",
+    }
+}
+
+fn display_name(chat: &ChatInfo) -> String {
+    match chat.section {
+        ChatSection::Public | ChatSection::Private => format!("#{}", chat.name),
+        _ => chat.name.clone(),
+    }
+}
@@ -1,6 +1,11 @@
 use anyhow::Result;
+use tracing::level_filters::LevelFilter;
+use tracing::{debug, trace, warn};
 
-use crate::app::App;
+use crate::app::{parse_filter_date, App};
+use crate::formatting::TimestampStyle;
+use crate::widgets::AutoScroll;
+use crate::slack::SlackError;
 use crate::widgets::FilterType;
 
 pub struct Command {
@@ -26,6 +31,102 @@ impl Command {
     }
 }
 
+/// What Tab completes an argument of a command to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgCompletion {
+    /// "@Display Name" of a known user
+    User,
+    /// "#name" of a conversation in the sidebar
+    Channel,
+    /// A workspace name, or `add`/`rename`/`remove`
+    Workspace,
+    /// A saved search name, or `list`/`add`/`remove`
+    SavedSearch,
+    /// One of a fixed set of words (none: the argument isn't completed)
+    Words(&'static [&'static str]),
+}
+
+/// A command offered by Tab, with what each of its arguments completes to
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub args: &'static [ArgCompletion],
+}
+
+const fn command(name: &'static str) -> CommandSpec {
+    CommandSpec { name, args: &[] }
+}
+
+const fn command_with(name: &'static str, args: &'static [ArgCompletion]) -> CommandSpec {
+    CommandSpec { name, args }
+}
+
+/// Built-in commands for Tab completion; arguments without a spec fall back to the
+/// usual @user, #channel and :emoji: completion.
+pub const COMMANDS: &[CommandSpec] = &[
+    command("thread"),
+    command("t"),
+    command("react"),
+    command_with(
+        "filter",
+        &[ArgCompletion::Words(&["sender", "media", "link", "regex", "since", "until", "bots"])],
+    ),
+    command("alias"),
+    command("aliases"),
+    command("unalias"),
+    command_with("workspace", &[ArgCompletion::Workspace]),
+    command_with("ws", &[ArgCompletion::Workspace]),
+    command("unreads"),
+    command_with("snippet", &[ArgCompletion::Words(&["add", "remove", "list"])]),
+    command("nick"),
+    command_with("dm", &[ArgCompletion::User]),
+    command_with("join", &[ArgCompletion::Channel]),
+    command_with("forward", &[ArgCompletion::Words(&[]), ArgCompletion::Channel]),
+    command("finduser"),
+    command_with("invite", &[ArgCompletion::User]),
+    command_with("kick", &[ArgCompletion::User]),
+    command("reply"),
+    command("fav"),
+    command_with("move", &[ArgCompletion::Words(&["up", "down"])]),
+    command("follow"),
+    command("unfollow"),
+    command("info"),
+    command("msginfo"),
+    command("files"),
+    command("media"),
+    command("downloads"),
+    command("doctor"),
+    command("diagnose"),
+    command("stats"),
+    command("reconnect"),
+    command("slack"),
+    command("scripts"),
+    command("search"),
+    command_with("searches", &[ArgCompletion::SavedSearch]),
+    command_with("drafts", &[ArgCompletion::Words(&["discard"])]),
+    command("markallread"),
+    command("leave"),
+    command("away"),
+    command("active"),
+    command_with("dnd", &[ArgCompletion::Words(&["off", "30m", "1h", "2h"])]),
+    command("system"),
+    command("screenreader"),
+    command("linklabels"),
+    command("code"),
+    command_with("timestamps", &[ArgCompletion::Words(&["absolute", "full", "relative"])]),
+    command_with("autoscroll", &[ArgCompletion::Words(&["always", "bottom", "never"])]),
+    command_with("debug", &[ArgCompletion::Words(&["on", "off"])]),
+    command("help"),
+    command("h"),
+];
+
+/// What argument `arg` (0-based) of `/name` completes to, if it has a spec.
+pub fn arg_completion(name: &str, arg: usize) -> Option<ArgCompletion> {
+    COMMANDS
+        .iter()
+        .find(|c| c.name == name)
+        .and_then(|c| c.args.get(arg).copied())
+}
+
 pub struct CommandHandler;
 
 impl CommandHandler {
@@ -52,15 +153,156 @@ impl CommandHandler {
             "alias" => {
                 Self::handle_alias(app, &cmd).await?;
             }
+            "aliases" => {
+                Self::handle_aliases(app);
+            }
             "unalias" => {
                 Self::handle_unalias(app, &cmd).await?;
             }
+            "nick" => {
+                Self::handle_nick(app, &cmd).await;
+            }
+            "snippet" => {
+                Self::handle_snippet(app, &cmd);
+            }
             "workspace" | "ws" => {
                 Self::handle_workspace(app, &cmd).await?;
             }
+            "unreads" => {
+                app.open_unified_unreads();
+            }
+            "markallread" => {
+                app.mark_all_read();
+            }
+            "dm" => {
+                Self::handle_dm(app, &cmd).await?;
+            }
+            "join" => match cmd.args.first() {
+                Some(channel) => app.join_chat(channel),
+                None => app.set_status("Usage: /join #channel"),
+            },
+            "forward" => match (cmd.args.first().map(|a| a.trim_start_matches('#').parse::<usize>()), cmd.args.get(1)) {
+                (Some(Ok(n)), Some(channel)) => app.forward_message(n, channel).await,
+                _ => app.set_status("Usage: /forward #N #channel (share message N there)"),
+            },
+            "finduser" => {
+                if cmd.args.is_empty() {
+                    app.set_status("Usage: /finduser <name, title or email>");
+                } else {
+                    app.find_users(&cmd.args.join(" ")).await;
+                }
+            }
+            "invite" => {
+                Self::handle_membership(app, &cmd, true).await?;
+            }
+            "kick" => {
+                Self::handle_membership(app, &cmd, false).await?;
+            }
+            "search" => {
+                let query = cmd.args.join(" ");
+                if crate::search::has_operators(&query) {
+                    app.search_slack(&query).await;
+                } else {
+                    app.start_search_with(&query);
+                }
+            }
+            "searches" => {
+                Self::handle_searches(app, &cmd).await;
+            }
+            "drafts" => {
+                let args: Vec<&str> = cmd.args.iter().map(|a| a.as_str()).collect();
+                let n = args.last().and_then(|n| n.parse::<usize>().ok());
+                match (args.as_slice(), n) {
+                    ([], _) => app.show_drafts(),
+                    ([_], Some(n)) => app.open_draft(n),
+                    (["discard" | "rm", _], Some(n)) => app.discard_draft(n),
+                    _ => app.set_status("Usage: /drafts | /drafts N | /drafts discard N"),
+                }
+            }
+            "reply" => {
+                Self::handle_reply(app, &cmd).await;
+            }
+            "fav" => {
+                app.toggle_favorite().await;
+            }
+            "move" => match cmd.args.first().map(|a| a.to_lowercase()).as_deref() {
+                Some("up") => app.move_selected_chat(true),
+                Some("down") => app.move_selected_chat(false),
+                _ => app.set_status("Usage: /move up|down"),
+            },
+            "follow" => {
+                app.set_thread_followed(true);
+            }
+            "unfollow" => {
+                app.set_thread_followed(false);
+            }
+            "info" => {
+                app.show_channel_info().await;
+            }
+            "msginfo" => {
+                match cmd.args.first().map(|a| a.trim_start_matches('#').parse::<usize>()) {
+                    Some(Ok(n)) => app.show_message_info(n).await,
+                    _ => app.set_status("Usage: /msginfo #N (details of message N)"),
+                }
+            }
+            "files" => {
+                app.show_channel_files().await;
+            }
+            "downloads" => {
+                app.show_downloads();
+            }
+            "doctor" => {
+                app.run_doctor().await;
+            }
+            "reconnect" => {
+                app.reconnect();
+            }
+            "stats" => {
+                app.show_api_stats();
+            }
+            "diagnose" => {
+                match cmd.args.first().map(|a| a.parse::<usize>()) {
+                    None => app.write_diagnostics(crate::diagnostics::DEFAULT_LOG_LINES),
+                    Some(Ok(n)) => app.write_diagnostics(n),
+                    Some(Err(_)) => app.set_status("Usage: /diagnose [number of log lines]"),
+                }
+            }
             "leave" => {
                 Self::handle_leave(app).await?;
             }
+            "away" => {
+                app.set_presence(true).await;
+            }
+            "active" => {
+                app.set_presence(false).await;
+            }
+            "dnd" => {
+                app.set_dnd(cmd.args.first().map(String::as_str)).await;
+            }
+            "system" => {
+                app.toggle_system_messages();
+            }
+            "screenreader" => {
+                app.toggle_screen_reader();
+            }
+            "linklabels" => {
+                app.toggle_link_labels();
+            }
+            "timestamps" => {
+                match cmd.args.first().and_then(|a| TimestampStyle::parse(a)) {
+                    Some(style) => app.set_timestamp_style(style),
+                    None => app.set_status("Usage: /timestamps absolute|full|relative"),
+                }
+            }
+            "autoscroll" => {
+                match cmd.args.first().and_then(|a| AutoScroll::parse(a)) {
+                    Some(mode) => app.set_auto_scroll(mode),
+                    None => app.set_status("Usage: /autoscroll always|bottom|never"),
+                }
+            }
+            "debug" => {
+                Self::handle_debug(app, &cmd);
+            }
             "help" | "h" => {
                 Self::handle_help(app).await?;
             }
@@ -79,8 +321,16 @@ impl CommandHandler {
                     app.set_status(&format!("Unknown command: /{}", cmd.name));
                 }
             }
+            "scripts" => {
+                Self::handle_scripts(app);
+            }
+            "slack" => {
+                Self::handle_slack(app, &cmd);
+            }
             _ => {
-                app.set_status(&format!("Unknown command: /{}", cmd.name));
+                if !app.run_script(&cmd.name, &cmd.args).await {
+                    app.ask_send_slack_command(text);
+                }
             }
         }
 
@@ -117,27 +367,20 @@ impl CommandHandler {
             return Ok(());
         }
 
-        let msg = &pane.msg_data[num - 1];
-        let thread_ts = msg.ts.clone();
-        let parent_user = msg.sender_name.clone();
-        let channel_id_str = match &pane.channel_id_str {
-            Some(id) => id.clone(),
-            None => {
-                app.set_status("No channel selected");
-                return Ok(());
-            }
-        };
-
-        app.open_thread(&channel_id_str, &thread_ts, &parent_user)
-            .await?;
-        Ok(())
+        app.open_message_thread(num - 1).await
     }
 
     async fn handle_react(app: &mut App, cmd: &Command) -> Result<()> {
+        if app.reject_if_read_only() {
+            return Ok(());
+        }
         if cmd.args.is_empty() {
             app.set_status("Usage: /react <emoji> [message_number]");
             return Ok(());
         }
+        if app.scope_unavailable("reactions.add", "Reacting") {
+            return Ok(());
+        }
 
         let pane = &app.panes[app.focused_pane_idx];
         if let Some(channel_id) = &pane.channel_id_str {
@@ -162,23 +405,51 @@ impl CommandHandler {
         Ok(())
     }
 
+    /// `/reply [-b] <text>` in a thread pane; `-b` also posts the reply to the channel.
+    async fn handle_reply(app: &mut App, cmd: &Command) {
+        if app.reject_if_read_only() {
+            return;
+        }
+        let pane_idx = app.focused_pane_idx;
+        if app.panes[pane_idx].thread_ts.is_none() {
+            app.set_status("/reply works in a thread pane (open one with /thread N)");
+            return;
+        }
+        let broadcast = cmd.args.first().is_some_and(|a| a == "-b");
+        let words = if broadcast { &cmd.args[1..] } else { &cmd.args[..] };
+        let text = words.join(" ");
+        if text.is_empty() {
+            app.set_status("Usage: /reply [-b] <text> (-b also sends to the channel)");
+            return;
+        }
+        app.send_text(pane_idx, &text, broadcast);
+    }
+
     async fn handle_filter(app: &mut App, cmd: &Command) -> Result<()> {
         if cmd.args.is_empty() {
             let pane = &mut app.panes[app.focused_pane_idx];
             pane.filter_type = None;
             pane.filter_value = None;
+            pane.hide_bots = false;
             pane.invalidate_cache();
             app.set_status("Filter cleared");
             return Ok(());
         }
 
         let filter_str = &cmd.args[0].to_lowercase();
+        if filter_str == "bots" {
+            Self::handle_filter_bots(app, cmd.args.get(1).map(|a| a.to_lowercase()));
+            return Ok(());
+        }
         let filter_type = match filter_str.as_str() {
             "sender" => FilterType::Sender,
             "media" => FilterType::Media,
             "link" => FilterType::Link,
+            "regex" => FilterType::Regex,
+            "since" => FilterType::Since,
+            "until" => FilterType::Until,
             _ => {
-                app.set_status("Usage: /filter [sender|media|link] [value]");
+                app.set_status("Usage: /filter [sender|media|link|regex|since|until] [value] | /filter bots [on|off]");
                 return Ok(());
             }
         };
@@ -189,6 +460,30 @@ impl CommandHandler {
             None
         };
 
+        // These need a value that parses, otherwise the filter would silently show nothing
+        match (filter_type, filter_value.as_deref()) {
+            (FilterType::Regex, None) => {
+                app.set_status("Usage: /filter regex <pattern>");
+                return Ok(());
+            }
+            (FilterType::Regex, Some(pattern)) => {
+                if let Err(e) = regex::Regex::new(pattern) {
+                    app.set_status(&format!("Invalid regex: {}", e));
+                    return Ok(());
+                }
+            }
+            (FilterType::Since | FilterType::Until, value)
+                if value.and_then(parse_filter_date).is_none() =>
+            {
+                app.set_status(&format!(
+                    "Usage: /filter {} <YYYY-MM-DD|today|yesterday>",
+                    filter_str
+                ));
+                return Ok(());
+            }
+            _ => {}
+        }
+
         let pane = &mut app.panes[app.focused_pane_idx];
         pane.filter_type = Some(filter_type);
         pane.filter_value = filter_value.clone();
@@ -204,6 +499,28 @@ impl CommandHandler {
         Ok(())
     }
 
+    /// `/filter bots [on|off]`: hide bot and app messages in the focused pane (toggles
+    /// without an argument). Kept apart from the filter so both can apply.
+    fn handle_filter_bots(app: &mut App, arg: Option<String>) {
+        let pane = &mut app.panes[app.focused_pane_idx];
+        let hide = match arg.as_deref() {
+            None => !pane.hide_bots,
+            Some("on") => true,
+            Some("off") => false,
+            Some(_) => {
+                app.set_status("Usage: /filter bots [on|off]");
+                return;
+            }
+        };
+        pane.hide_bots = hide;
+        pane.invalidate_cache();
+        app.set_status(if hide {
+            "Bot and app messages hidden in this pane (/filter bots off to show them)"
+        } else {
+            "Bot and app messages shown"
+        });
+    }
+
     async fn handle_alias(app: &mut App, cmd: &Command) -> Result<()> {
         if cmd.args.len() < 2 {
             app.set_status("Usage: /alias <name> <value>");
@@ -214,11 +531,27 @@ impl CommandHandler {
         let alias_value = cmd.args[1..].join(" ");
 
         app.aliases.insert(alias_name.clone(), alias_value.clone());
-        app.set_status(&format!("Alias '{}' = '{}'", alias_name, alias_value));
+        let msg = format!("Alias {}{} = '{}'", app.alias_prefix, alias_name, alias_value);
+        app.set_status(&msg);
 
         Ok(())
     }
 
+    fn handle_aliases(app: &mut App) {
+        if app.aliases.map.is_empty() {
+            app.set_status("No aliases. Add one with /alias <name> <value>");
+            return;
+        }
+        let mut entries: Vec<(&String, &String)> = app.aliases.map.iter().collect();
+        entries.sort();
+        let list: Vec<String> = entries
+            .iter()
+            .map(|(name, value)| format!("{}{} = {}", app.alias_prefix, name, value))
+            .collect();
+        let msg = format!("Aliases: {}", list.join(" | "));
+        app.set_status(&msg);
+    }
+
     async fn handle_unalias(app: &mut App, cmd: &Command) -> Result<()> {
         if cmd.args.is_empty() {
             app.set_status("Usage: /unalias <name>");
@@ -235,7 +568,197 @@ impl CommandHandler {
         Ok(())
     }
 
+    async fn handle_dm(app: &mut App, cmd: &Command) -> Result<()> {
+        if cmd.args.is_empty() {
+            app.set_status("Usage: /dm @name");
+            return Ok(());
+        }
+
+        // Display names may contain spaces ("@Ana Lima", as Tab completes them)
+        app.open_dm(&cmd.args.join(" ")).await
+    }
+
+    /// `/invite @name` (invite = true) or `/kick @name` for the focused channel.
+    async fn handle_membership(app: &mut App, cmd: &Command, invite: bool) -> Result<()> {
+        if app.reject_if_read_only() {
+            return Ok(());
+        }
+        let verb = if invite { "invite" } else { "kick" };
+        if cmd.args.is_empty() {
+            app.set_status(&format!("Usage: /{} @name", verb));
+            return Ok(());
+        }
+        let method = if invite { "conversations.invite" } else { "conversations.kick" };
+        if app.scope_unavailable(method, &format!("/{}", verb)) {
+            return Ok(());
+        }
+
+        let pane = &app.panes[app.focused_pane_idx];
+        let channel_id = match &pane.channel_id_str {
+            Some(id) => id.clone(),
+            None => {
+                app.set_status("No channel selected");
+                return Ok(());
+            }
+        };
+        let channel_name = pane.chat_name.clone();
+        let name = cmd.args.join(" ");
+        let Some(user_id) = app.find_user_id(&name) else {
+            app.set_status(&format!("Unknown user: {}", name));
+            return Ok(());
+        };
+        let user_name = app
+            .user_name_cache
+            .get(&user_id)
+            .cloned()
+            .unwrap_or_else(|| user_id.clone());
+
+        let result = if invite {
+            app.slack.invite_to_conversation(&channel_id, &user_id).await
+        } else {
+            app.slack.kick_from_conversation(&channel_id, &user_id).await
+        };
+
+        let msg = match result {
+            Ok(()) if invite => format!("Invited {} to {}", user_name, channel_name),
+            Ok(()) => format!("Removed {} from {}", user_name, channel_name),
+            Err(e) => match e.downcast_ref::<SlackError>().map(|e| e.code()) {
+                Some("already_in_channel") => format!("{} is already in {}", user_name, channel_name),
+                Some("not_in_channel") if !invite => format!("{} is not in {}", user_name, channel_name),
+                Some("cant_invite_self") | Some("cant_kick_self") => {
+                    format!("You can't {} yourself", verb)
+                }
+                _ => format!("Failed to {} {}: {}", verb, user_name, e),
+            },
+        };
+        app.set_status(&msg);
+
+        Ok(())
+    }
+
+    async fn handle_nick(app: &mut App, cmd: &Command) {
+        match cmd.args.as_slice() {
+            [] => {
+                if app.nicknames.map.is_empty() {
+                    app.set_status("No nicknames. Set one with /nick @name <nickname>");
+                    return;
+                }
+                let mut nicks: Vec<&String> = app.nicknames.map.values().collect();
+                nicks.sort();
+                let msg = format!(
+                    "Nicknames: {}",
+                    nicks.iter().map(|n| n.as_str()).collect::<Vec<_>>().join(", ")
+                );
+                app.set_status(&msg);
+            }
+            [who] => app.set_nickname(who, None).await,
+            [who, nick @ ..] => app.set_nickname(who, Some(&nick.join(" "))).await,
+        }
+    }
+
+    /// `/slack <command> [args]`: post `/command args` as a message, so workspace slash
+    /// commands and workflow triggers get it instead of the local command handler.
+    fn handle_slack(app: &mut App, cmd: &Command) {
+        if cmd.args.is_empty() {
+            app.set_status("Usage: /slack <command> [args] (e.g. /slack standup done)");
+            return;
+        }
+        let text = cmd.args.join(" ");
+        let text = if text.starts_with('/') { text } else { format!("/{}", text) };
+        app.send_text(app.focused_pane_idx, &text, false);
+    }
+
+    /// `/snippet add <name> <text>`, `/snippet remove <name>`, `/snippet [list]`
+    fn handle_snippet(app: &mut App, cmd: &Command) {
+        let args: Vec<&str> = cmd.args.iter().map(|a| a.as_str()).collect();
+        let msg = match args.as_slice() {
+            [] | ["list"] => {
+                if app.snippets.map.is_empty() {
+                    "No snippets. Add one with /snippet add <name> <text>".to_string()
+                } else {
+                    let mut names: Vec<&String> = app.snippets.map.keys().collect();
+                    names.sort();
+                    let names: Vec<String> = names.iter().map(|n| format!(";{}", n)).collect();
+                    format!("Snippets: {}", names.join(" "))
+                }
+            }
+            ["add", name, text @ ..] if !text.is_empty() => {
+                let name = name.trim_start_matches(';');
+                app.snippets.map.insert(name.to_string(), text.join(" "));
+                format!("Snippet ;{} saved", name)
+            }
+            ["remove" | "rm", name] => {
+                let name = name.trim_start_matches(';');
+                if app.snippets.map.remove(name).is_some() {
+                    format!("Removed snippet ;{}", name)
+                } else {
+                    format!("Snippet ;{} not found", name)
+                }
+            }
+            _ => {
+                app.set_status("Usage: /snippet add <name> <text> | /snippet remove <name> | /snippet list");
+                return;
+            }
+        };
+
+        let msg = match app.snippets.save(&app.config) {
+            Ok(()) => msg,
+            Err(e) => format!("{} (not saved: {})", msg, e),
+        };
+        app.set_status(&msg);
+    }
+
+    /// `/searches add <name> <query>`, `/searches remove <name>`, `/searches [list]`,
+    /// `/searches <name>` runs one on Slack.
+    async fn handle_searches(app: &mut App, cmd: &Command) {
+        let args: Vec<&str> = cmd.args.iter().map(|a| a.as_str()).collect();
+        let msg = match args.as_slice() {
+            [] | ["list"] => {
+                if app.saved_searches.map.is_empty() {
+                    app.set_status("No saved searches. Add one with /searches add <name> <query>");
+                } else {
+                    let mut saved: Vec<(&String, &String)> = app.saved_searches.map.iter().collect();
+                    saved.sort();
+                    let saved: Vec<String> = saved.iter().map(|(name, query)| format!("{}: {}", name, query)).collect();
+                    app.set_status(&format!("Saved searches: {}", saved.join(" | ")));
+                }
+                return;
+            }
+            ["add", name, query @ ..] if !query.is_empty() => {
+                app.saved_searches.map.insert(name.to_string(), query.join(" "));
+                format!("Saved search {} (run it with /searches {})", name, name)
+            }
+            ["remove" | "rm", name] => {
+                if app.saved_searches.map.remove(*name).is_some() {
+                    format!("Removed saved search {}", name)
+                } else {
+                    format!("Saved search {} not found", name)
+                }
+            }
+            [name] => {
+                match app.saved_searches.map.get(*name).cloned() {
+                    Some(query) => app.search_slack(&query).await,
+                    None => app.set_status(&format!("Saved search {} not found (/searches lists them)", name)),
+                }
+                return;
+            }
+            _ => {
+                app.set_status("Usage: /searches add <name> <query> | /searches remove <name> | /searches [<name>]");
+                return;
+            }
+        };
+
+        let msg = match app.saved_searches.save(&app.config) {
+            Ok(()) => msg,
+            Err(e) => format!("{} (not saved: {})", msg, e),
+        };
+        app.set_status(&msg);
+    }
+
     async fn handle_leave(app: &mut App) -> Result<()> {
+        if app.reject_if_read_only() {
+            return Ok(());
+        }
         let pane = &app.panes[app.focused_pane_idx];
         let channel_id = match &pane.channel_id_str {
             Some(id) => id.clone(),
@@ -269,8 +792,33 @@ impl CommandHandler {
             return Ok(());
         }
 
-        // Switch to workspace by number or name
         let arg = &cmd.args[0];
+        match arg.as_str() {
+            "add" => {
+                app.start_workspace_add();
+                return Ok(());
+            }
+            "rename" => {
+                let name = cmd.args[1..].join(" ");
+                app.start_workspace_rename((!name.is_empty()).then_some(name.as_str()));
+                return Ok(());
+            }
+            "reauth" => {
+                app.start_workspace_reauth(None);
+                return Ok(());
+            }
+            "remove" => {
+                if cmd.args.len() < 2 {
+                    app.set_status("Usage: /workspace remove <name|N>");
+                } else {
+                    app.remove_workspace(&cmd.args[1..].join(" "));
+                }
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        // Switch to workspace by number or name
         if let Ok(idx) = arg.parse::<usize>() {
             // Switch by number (1-indexed)
             if idx > 0 && idx <= app.config.workspaces.len() {
@@ -295,38 +843,24 @@ impl CommandHandler {
     }
 
     async fn handle_media(app: &mut App, cmd: &Command) -> Result<()> {
-        use std::fs::OpenOptions;
-        use std::io::Write;
-        
-        let log_to_file = |msg: &str| {
-            if let Ok(mut file) = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open("/tmp/slack_rust_debug.log")
-            {
-                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                let _ = writeln!(file, "[{}] {}", timestamp, msg);
-            }
-        };
-        
-        log_to_file("=== HANDLE MEDIA COMMAND DEBUG ===");
-        log_to_file(&format!("Command args: {:?}", cmd.args));
+        debug!("=== HANDLE MEDIA COMMAND DEBUG ===");
+        debug!("Command args: {:?}", cmd.args);
         
         if cmd.args.is_empty() {
-            app.set_status("Usage: /media #N (download and open media from message N)");
+            app.set_status("Usage: /media #N [file] (download and open a file from message N)");
             return Ok(());
         }
 
         let num_str = cmd.args[0].trim_start_matches('#');
-        log_to_file(&format!("Parsing message number from: {}", num_str));
+        debug!("Parsing message number from: {}", num_str);
         
         let msg_num: usize = match num_str.parse() {
             Ok(n) => {
-                log_to_file(&format!("Parsed message number: {}", n));
+                debug!("Parsed message number: {}", n);
                 n
             }
             Err(e) => {
-                log_to_file(&format!("Failed to parse message number: {}", e));
+                warn!("Failed to parse message number: {}", e);
                 app.set_status("Invalid message number");
                 return Ok(());
             }
@@ -334,95 +868,89 @@ impl CommandHandler {
 
         // Get the focused pane
         let pane = &app.panes[app.focused_pane_idx];
-        log_to_file(&format!("Focused pane has {} messages", pane.msg_data.len()));
-        log_to_file(&format!("Channel ID: {:?}", pane.channel_id_str));
+        debug!("Focused pane has {} messages", pane.msg_data.len());
+        debug!("Channel ID: {:?}", pane.channel_id_str);
         
         if msg_num == 0 || msg_num > pane.msg_data.len() {
-            log_to_file(&format!("Message #{} not found (valid range: 1-{})", msg_num, pane.msg_data.len()));
+            debug!("Message #{} not found (valid range: 1-{})", msg_num, pane.msg_data.len());
             app.set_status(&format!("Message #{} not found", msg_num));
             return Ok(());
         }
 
         let msg = &pane.msg_data[msg_num - 1];
-        log_to_file(&format!("Message #{}: media_type={:?}, file_urls={:?}, file_names={:?}", 
-            msg_num, msg.media_type, msg.file_urls, msg.file_names));
-        log_to_file(&format!("Message text: {}", msg.text));
+        debug!("Message #{}: media_type={:?}, file_urls={:?}, file_names={:?}", 
+            msg_num, msg.media_type, msg.file_urls, msg.file_names);
+        trace!("Message text: {}", msg.text);
         
         if msg.file_ids.is_empty() {
-            log_to_file(&format!("Message #{} has no file_ids", msg_num));
+            debug!("Message #{} has no file_ids", msg_num);
             app.set_status(&format!("Message #{} has no media", msg_num));
             return Ok(());
         }
 
-        let file_id = &msg.file_ids[0];
-        let file_name = msg.file_names.get(0).cloned().unwrap_or_else(|| "file".to_string());
-        
-        log_to_file(&format!("Downloading file_id: {}, file_name: {}", file_id, file_name));
-        
-        // Try to get a shareable public URL using files.sharedPublicURL API
-        // This gives us a direct download URL that works without HTML redirects
-        match app.slack.get_shared_public_url(file_id, &file_name).await {
-            Ok(file_path) => {
-                log_to_file(&format!("File downloaded successfully to: {:?}", file_path));
-                // Open file with system default application
-                #[cfg(target_os = "macos")]
-                {
-                    use std::process::Command;
-                    log_to_file("Opening file with 'open' command");
-                    let output = Command::new("open").arg(&file_path).output();
-                    log_to_file(&format!("Open command result: {:?}", output));
-                }
-                #[cfg(target_os = "linux")]
-                {
-                    use std::process::Command;
-                    log_to_file("Opening file with 'xdg-open' command");
-                    let output = Command::new("xdg-open").arg(&file_path).output();
-                    log_to_file(&format!("Xdg-open command result: {:?}", output));
-                }
-                #[cfg(not(any(target_os = "macos", target_os = "linux")))]
-                {
-                    app.set_status(&format!("Downloaded to: {}", file_path.display()));
-                }
-                app.set_status(&format!("Opened media from message #{}", msg_num));
-            }
-            Err(e) => {
-                log_to_file(&format!("Failed to get shared public URL: {}. Trying fallback...", e));
-                // Fallback: try direct download from file_urls if available
-                if !msg.file_urls.is_empty() {
-                    let file_url = &msg.file_urls[0];
-                    log_to_file(&format!("Trying direct download from URL: {}", file_url));
-                    match app.slack.download_file_from_url(file_url, &file_name).await {
-                        Ok(file_path) => {
-                            log_to_file(&format!("File downloaded successfully via fallback to: {:?}", file_path));
-                            #[cfg(target_os = "macos")]
-                            {
-                                use std::process::Command;
-                                let _ = Command::new("open").arg(&file_path).output();
-                            }
-                            #[cfg(target_os = "linux")]
-                            {
-                                use std::process::Command;
-                                let _ = Command::new("xdg-open").arg(&file_path).output();
-                            }
-                            app.set_status(&format!("Opened media from message #{}", msg_num));
-                        }
-                        Err(fallback_err) => {
-                            log_to_file(&format!("Fallback also failed: {}", fallback_err));
-                            app.set_status(&format!("Failed to download media: {}", fallback_err));
-                        }
-                    }
-                } else {
-                    log_to_file(&format!("No file_urls available for fallback"));
-                    app.set_status(&format!("Failed to download media: {}", e));
-                }
+        // Second argument picks one of several attachments ("[#N 2]" in the pane)
+        let file_idx = match cmd.args.get(1).map(|a| a.parse::<usize>()) {
+            None => 0,
+            Some(Ok(n)) if n >= 1 && n <= msg.file_ids.len() => n - 1,
+            Some(_) => {
+                let count = msg.file_ids.len();
+                app.set_status(&format!("Message #{} has {} file(s)", msg_num, count));
+                return Ok(());
             }
-        }
+        };
+
+        let file_id = Some(msg.file_ids[file_idx].clone()).filter(|id| !id.is_empty());
+        let file_name = msg.file_names.get(file_idx).cloned().unwrap_or_else(|| "file".to_string());
+        let file_url = msg.file_urls.get(file_idx).filter(|u| !u.is_empty()).cloned();
+        debug!("Downloading file_id: {:?}, file_name: {}", file_id, file_name);
+
+        // Runs in the background; the file is opened once it is saved
+        app.start_download(file_name, file_id, file_url);
 
         Ok(())
     }
 
+    fn handle_debug(app: &mut App, cmd: &Command) {
+        let Some(handle) = app.log_handle.as_mut() else {
+            app.set_status("Logging is not available");
+            return;
+        };
+
+        let level = match cmd.args.first().map(|a| a.as_str()) {
+            Some("on") => LevelFilter::DEBUG,
+            Some("off") => LevelFilter::OFF,
+            Some(_) => {
+                app.set_status("Usage: /debug on|off");
+                return;
+            }
+            None => {
+                let msg = format!("Log level: {} ({})", handle.level(), handle.path.display());
+                app.set_status(&msg);
+                return;
+            }
+        };
+
+        let msg = match handle.set_level(level) {
+            Ok(()) if level == LevelFilter::OFF => "Debug logging off".to_string(),
+            Ok(()) => format!("Debug logging on: {}", handle.path.display()),
+            Err(e) => e.to_string(),
+        };
+        app.set_status(&msg);
+    }
+
+    fn handle_scripts(app: &mut App) {
+        let names = crate::scripting::list_scripts(&app.config.config_dir);
+        if names.is_empty() {
+            let dir = crate::scripting::scripts_dir(&app.config.config_dir);
+            app.set_status(&format!("No scripts; add <name>.rhai files to {}", dir.display()));
+        } else {
+            let list: Vec<String> = names.iter().map(|n| format!("/{}", n)).collect();
+            app.set_status(&format!("Scripts: {}", list.join(", ")));
+        }
+    }
+
     async fn handle_help(app: &mut App) -> Result<()> {
-        app.set_status("Commands: /thread N | /react <emoji> | /filter | /workspace | /leave | /alias | /media #N | /help");
+        app.set_status("Commands: /thread N | /reply [-b] <text> | /fav | /move up|down | /follow | /unfollow | /react <emoji> | /filter | /workspace [add|rename|reauth|remove] | /unreads | /markallread | /dm @name | /join #channel | /forward #N #channel | /finduser <query> | /invite @name | /kick @name | /info | /msginfo #N | /files | /downloads | /doctor | /diagnose [lines] | /stats | /reconnect | /slack <command> | /scripts | /search [text|from: in: before: after: has:] | /searches [add|remove] | /drafts [N|discard N] | /leave | /alias | /aliases | /snippet | /nick @name [nickname] | /media #N [file] | /away | /active | /dnd [duration|off] | /system | /screenreader | /linklabels | /code [text] | /timestamps absolute|full|relative | /autoscroll always|bottom|never | /debug on|off | /help");
         Ok(())
     }
 }
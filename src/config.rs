@@ -1,15 +1,112 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::formatting::{HighlightRule, TimestampStyle};
+use crate::widgets::AutoScroll;
+
+/// Rotating tokens are renewed this long before they expire
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 600;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Workspace {
     pub name: String,
     // Support both 'token' (from Python client) and 'bot_token' (legacy)
-    #[serde(alias = "bot_token")]
+    #[serde(alias = "bot_token", default, skip_serializing_if = "String::is_empty")]
     pub token: String,
-    pub app_token: String, // For Socket Mode
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub app_token: String, // For Socket Mode (empty = poll for new messages)
+
+    /// Token rotation: with a refresh token and the Slack app's client ID and secret,
+    /// the access token (valid for 12 hours) is renewed before it expires
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub refresh_token: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub client_secret: String,
+    /// When `token` expires, in Unix seconds (0 = unknown, renewed right away)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub token_expires_at: i64,
+
+    /// Channels whose incoming messages are mirrored to a local file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tee: Vec<TeeRule>,
+}
+
+impl Workspace {
+    /// Whether the access token is renewed with `refresh_token`
+    pub fn rotates_token(&self) -> bool {
+        !self.refresh_token.is_empty() && !self.client_id.is_empty() && !self.client_secret.is_empty()
+    }
+
+    /// Whether a rotating token expires (or expired) within the refresh margin of `now`
+    pub fn token_refresh_due(&self, now: i64) -> bool {
+        self.rotates_token() && self.token_expires_at - now <= TOKEN_REFRESH_MARGIN_SECS
+    }
+}
+
+fn is_zero(n: &i64) -> bool {
+    *n == 0
+}
+
+/// Mirror every incoming message of one channel to a local file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeeRule {
+    /// Channel ID (C0123...) or channel name (with or without '#')
+    pub channel: String,
+    pub path: PathBuf,
+    #[serde(default)]
+    pub format: TeeFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TeeFormat {
+    /// `[YYYY-MM-DD HH:MM:SS] author: text`
+    #[default]
+    Text,
+    /// One JSON object per line: {"ts", "time", "channel", "author", "text"}
+    Jsonl,
+}
+
+/// Run a shell command for incoming messages. The message is passed as JSON on
+/// stdin and in `SLACK_*` environment variables.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hook {
+    pub on: HookEvent,
+    /// Only messages in this channel (ID or name, with or without '#'); empty = all
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub channel: String,
+    pub command: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookEvent {
+    /// You were mentioned, or a highlight keyword matched
+    Mention,
+    /// A direct message (1:1 or group DM)
+    Dm,
+    /// Any message
+    Message,
+}
+
+impl Hook {
+    pub fn matches(&self, channel_id: &str, channel_name: &str) -> bool {
+        let wanted = self.channel.trim_start_matches('#');
+        wanted.is_empty() || wanted == channel_id || wanted.eq_ignore_ascii_case(channel_name)
+    }
+}
+
+impl TeeRule {
+    pub fn matches(&self, channel_id: &str, channel_name: &str) -> bool {
+        let wanted = self.channel.trim_start_matches('#');
+        wanted == channel_id || wanted.eq_ignore_ascii_case(channel_name)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,8 +117,91 @@ pub struct Config {
     #[serde(default)]
     pub settings: Settings,
 
+    #[serde(default)]
+    pub logging: LoggingConfig,
+
+    #[serde(default)]
+    pub network: NetworkConfig,
+
+    /// Where downloaded files are saved (default: ~/Downloads/slack_rust)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_dir: Option<PathBuf>,
+
+    /// Command used to open downloads, keyed by mimetype (`image/png`), mimetype
+    /// family (`image/*`) or extension (`pdf`); others use the system default
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub openers: HashMap<String, String>,
+
+    /// Shell commands run when a mention, DM or channel message arrives
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<Hook>,
+
     #[serde(skip)]
     pub config_dir: PathBuf,
+
+    /// File this config is saved to (`--config`, or `slack_config.json` in `config_dir`)
+    #[serde(skip)]
+    pub config_file: PathBuf,
+
+    /// On-disk token and app token of the workspace whose tokens came from the
+    /// environment (None: it only exists there), restored on save so env tokens are
+    /// never written to the config file
+    #[serde(skip)]
+    file_credentials: Option<(usize, Option<(String, String)>)>,
+}
+
+/// Debug log written through `tracing`; off unless enabled here or with `/debug on`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// off, error, warn, info, debug or trace
+    #[serde(default = "default_log_level")]
+    pub level: String,
+
+    /// Log file (default: slack_rust.log in the config directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: default_log_level(),
+            file: None,
+        }
+    }
+}
+
+fn default_log_level() -> String {
+    "off".to_string()
+}
+
+/// HTTP and WebSocket timeouts, so a hung network fails instead of blocking forever
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Whole Web API request, including reading the response
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+
+    /// Establishing a TCP/TLS connection (Web API and Socket Mode)
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout_secs: default_request_timeout_secs(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+        }
+    }
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +235,75 @@ pub struct Settings {
 
     #[serde(default = "default_true")]
     pub mouse_support: bool,
+
+    #[serde(default)]
+    pub auto_away_minutes: u64,
+
+    #[serde(default)]
+    pub highlight_keywords: Vec<String>,
+
+    #[serde(default)]
+    pub highlight_rules: Vec<HighlightRule>,
+
+    #[serde(default = "default_true")]
+    pub notify_on_keywords: bool,
+
+    #[serde(default = "default_notification_cooldown_secs")]
+    pub notification_cooldown_secs: u64,
+
+    #[serde(default = "default_true")]
+    pub show_system_messages: bool,
+    #[serde(default = "default_alias_prefix")]
+    pub alias_prefix: String,
+    #[serde(default)]
+    pub timestamp_style: TimestampStyle,
+    #[serde(default)]
+    pub auto_scroll: AutoScroll,
+
+    #[serde(default = "default_timestamp_format")]
+    pub timestamp_format: String,
+
+    #[serde(default = "default_paste_snippet_lines")]
+    pub paste_snippet_lines: usize,
+
+    #[serde(default = "default_recent_chats")]
+    pub recent_chats: usize,
+
+    #[serde(default)]
+    pub quiet_hours: String,
+
+    #[serde(default)]
+    pub sync_stars: bool,
+
+    #[serde(default)]
+    pub sidebar_width: u16,
+
+    #[serde(default)]
+    pub screen_reader: bool,
+
+    #[serde(default)]
+    pub show_link_labels: bool,
+
+    #[serde(default = "default_true")]
+    pub jumbo_emoji: bool,
+
+    #[serde(default = "default_quick_reactions")]
+    pub quick_reactions: Vec<String>,
+
+    #[serde(default = "default_true")]
+    pub terminal_title: bool,
+
+    #[serde(default)]
+    pub osc_notifications: bool,
+
+    #[serde(default = "default_bold_key")]
+    pub bold_key: String,
+
+    #[serde(default = "default_italic_key")]
+    pub italic_key: String,
+
+    #[serde(default = "default_code_key")]
+    pub code_key: String,
 }
 
 impl Default for Settings {
@@ -70,6 +319,30 @@ impl Default for Settings {
             show_user_colors: true,
             show_borders: true,
             mouse_support: true,
+            auto_away_minutes: 0,
+            highlight_keywords: Vec::new(),
+            highlight_rules: Vec::new(),
+            notify_on_keywords: true,
+            notification_cooldown_secs: default_notification_cooldown_secs(),
+            show_system_messages: true,
+            alias_prefix: default_alias_prefix(),
+            timestamp_style: TimestampStyle::default(),
+            auto_scroll: AutoScroll::default(),
+            timestamp_format: default_timestamp_format(),
+            paste_snippet_lines: default_paste_snippet_lines(),
+            recent_chats: default_recent_chats(),
+            quiet_hours: String::new(),
+            sync_stars: false,
+            sidebar_width: 0,
+            screen_reader: false,
+            show_link_labels: false,
+            jumbo_emoji: true,
+            quick_reactions: default_quick_reactions(),
+            terminal_title: true,
+            osc_notifications: false,
+            bold_key: default_bold_key(),
+            italic_key: default_italic_key(),
+            code_key: default_code_key(),
         }
     }
 }
@@ -78,10 +351,73 @@ fn default_true() -> bool {
     true
 }
 
+fn default_alias_prefix() -> String {
+    "$".to_string()
+}
+
+fn default_timestamp_format() -> String {
+    "%H:%M".to_string()
+}
+
+fn default_paste_snippet_lines() -> usize {
+    10
+}
+
+fn default_recent_chats() -> usize {
+    5
+}
+
+fn default_notification_cooldown_secs() -> u64 {
+    10
+}
+
+fn default_quick_reactions() -> Vec<String> {
+    crate::persistence::default_quick_reactions()
+}
+
+fn default_bold_key() -> String {
+    "alt+b".to_string()
+}
+
+fn default_italic_key() -> String {
+    "alt+i".to_string()
+}
+
+fn default_code_key() -> String {
+    "alt+e".to_string()
+}
+
+/// Non-empty environment variable
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
 impl Config {
-    pub fn load() -> Result<Self> {
-        let config_dir = Self::get_config_dir();
-        let config_path = config_dir.join("slack_config.json");
+    /// Load the config from `path` (the `--config` flag), or from the default location.
+    /// `SLACK_TOKEN` / `SLACK_APP_TOKEN` override the active workspace's credentials.
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        let mut config = Self::load_file(path)?;
+        config.apply_env_overrides();
+        Ok(config)
+    }
+
+    fn load_file(path: Option<PathBuf>) -> Result<Self> {
+        let explicit_path = path.is_some();
+        let (config_dir, config_path) = match path {
+            Some(path) => {
+                let dir = path
+                    .parent()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| PathBuf::from("."));
+                (dir, path)
+            }
+            None => {
+                let dir = Self::get_config_dir();
+                let file = dir.join("slack_config.json");
+                (dir, file)
+            }
+        };
 
         // If Rust config exists, use it
         if config_path.exists() {
@@ -90,6 +426,7 @@ impl Config {
             // Try to load as new multi-workspace format first
             if let Ok(mut config) = serde_json::from_str::<Config>(&content) {
                 config.config_dir = config_dir;
+                config.config_file = config_path;
                 // Ensure active_workspace is within bounds
                 if config.active_workspace >= config.workspaces.len() {
                     config.active_workspace = 0;
@@ -113,13 +450,21 @@ impl Config {
                     name: old_config.workspace_name.unwrap_or_else(|| "Default".to_string()),
                     token: old_config.token,
                     app_token: old_config.app_token,
+                    ..Default::default()
                 };
                 
                 let config = Config {
                     workspaces: vec![workspace],
                     active_workspace: 0,
                     settings: old_config.settings,
+                    logging: LoggingConfig::default(),
+                    network: NetworkConfig::default(),
+                    download_dir: None,
+                    openers: HashMap::new(),
+                    hooks: Vec::new(),
                     config_dir: config_dir.clone(),
+                    config_file: config_path.clone(),
+                    file_credentials: None,
                 };
                 
                 // Save in new format
@@ -128,8 +473,14 @@ impl Config {
             }
         }
 
+        // Headless: credentials supplied entirely through the environment
+        if let Some(config) = Self::from_env(&config_dir, &config_path)? {
+            return Ok(config);
+        }
+
         // If Rust config doesn't exist, try to copy from Python client's config
-        if let Some(python_config_path) = Self::find_python_config() {
+        let python_config = if explicit_path { None } else { Self::find_python_config() };
+        if let Some(python_config_path) = python_config {
             if python_config_path.exists() {
                 match fs::read_to_string(&python_config_path) {
                     Ok(content) => {
@@ -166,12 +517,20 @@ impl Config {
                                         name: "Default".to_string(),
                                         token,
                                         app_token,
+                                        ..Default::default()
                                     };
                                     let config = Config {
                                         workspaces: vec![workspace],
                                         active_workspace: 0,
                                         settings: Settings::default(),
+                                        logging: LoggingConfig::default(),
+                                        network: NetworkConfig::default(),
+                                        download_dir: None,
+                                        openers: HashMap::new(),
+                                        hooks: Vec::new(),
                                         config_dir: config_dir.clone(),
+                                        config_file: config_path.clone(),
+                                        file_credentials: None,
                                     };
 
                                     // Save to Rust config location
@@ -197,9 +556,69 @@ impl Config {
             }
         }
 
-        // No config found anywhere, create new one
-        let config = Self::create_new(config_dir)?;
-        Ok(config)
+        // No config found anywhere: the TUI asks for the first workspace
+        if !std::io::stdin().is_terminal() {
+            return Err(anyhow!(
+                "No config found at {} and stdin is not a terminal; set SLACK_TOKEN and SLACK_APP_TOKEN or pass --config <path>",
+                config_path.display()
+            ));
+        }
+        Self::empty(config_dir, config_path)
+    }
+
+    /// Build a single-workspace config from `SLACK_TOKEN` (and optionally `SLACK_APP_TOKEN`).
+    fn from_env(config_dir: &Path, config_path: &Path) -> Result<Option<Self>> {
+        let Some(token) = env_var("SLACK_TOKEN") else {
+            return Ok(None);
+        };
+        let app_token = env_var("SLACK_APP_TOKEN").unwrap_or_default();
+
+        // Settings, layout and aliases are still persisted next to the config file
+        fs::create_dir_all(config_dir)?;
+
+        let workspace = Workspace {
+            name: env_var("SLACK_WORKSPACE_NAME").unwrap_or_else(|| "Default".to_string()),
+            token,
+            app_token,
+            ..Default::default()
+        };
+        Ok(Some(Config {
+            workspaces: vec![workspace],
+            active_workspace: 0,
+            settings: Settings::default(),
+            logging: LoggingConfig::default(),
+            network: NetworkConfig::default(),
+            download_dir: None,
+            openers: HashMap::new(),
+            hooks: Vec::new(),
+            config_dir: config_dir.to_path_buf(),
+            config_file: config_path.to_path_buf(),
+            file_credentials: Some((0, None)),
+        }))
+    }
+
+    /// Replace the active workspace's tokens with `SLACK_TOKEN` / `SLACK_APP_TOKEN` when set.
+    fn apply_env_overrides(&mut self) {
+        let token = env_var("SLACK_TOKEN");
+        let app_token = env_var("SLACK_APP_TOKEN");
+        if token.is_none() && app_token.is_none() {
+            return;
+        }
+        let idx = self.active_workspace;
+        let Some(workspace) = self.workspaces.get_mut(idx) else {
+            return;
+        };
+
+        if self.file_credentials.is_none() {
+            self.file_credentials =
+                Some((idx, Some((workspace.token.clone(), workspace.app_token.clone()))));
+        }
+        if let Some(token) = token {
+            workspace.token = token;
+        }
+        if let Some(app_token) = app_token {
+            workspace.app_token = app_token;
+        }
     }
 
     fn find_python_config() -> Option<PathBuf> {
@@ -227,71 +646,43 @@ impl Config {
                 .join("slack_config.json"),
         ];
 
-        for path in paths_to_try {
-            if path.exists() {
-                return Some(path);
-            }
-        }
-
-        None
+        paths_to_try.into_iter().find(|path| path.exists())
     }
 
     pub fn save(&self) -> Result<()> {
-        let config_path = self.config_dir.join("slack_config.json");
-        let content = serde_json::to_string_pretty(&self)?;
-        fs::write(config_path, content)?;
+        let content = match self.file_credentials {
+            Some((idx, ref credentials)) => {
+                // Without on-disk credentials the tokens are left out (empty fields aren't written)
+                let mut on_disk = self.clone();
+                if let Some(workspace) = on_disk.workspaces.get_mut(idx) {
+                    let (token, app_token) = credentials.clone().unwrap_or_default();
+                    workspace.token = token;
+                    workspace.app_token = app_token;
+                }
+                serde_json::to_string_pretty(&on_disk)?
+            }
+            None => serde_json::to_string_pretty(&self)?,
+        };
+        fs::write(&self.config_file, content)?;
         Ok(())
     }
 
-    fn create_new(config_dir: PathBuf) -> Result<Self> {
+    /// A config without workspaces, saved by the first-run setup once one is added.
+    fn empty(config_dir: PathBuf, config_path: PathBuf) -> Result<Self> {
         fs::create_dir_all(&config_dir)?;
-
-        println!("=== Slack Client Setup ===");
-        println!("Get your Token from https://api.slack.com/apps");
-        println!("You need:");
-        println!("  1. User OAuth Token (xoxp-...) or Bot User OAuth Token (xoxb-...)");
-        println!("  2. App-Level Token for Socket Mode (xapp-...)");
-        println!();
-
-        print!("Workspace name: ");
-        use std::io::{self, Write};
-        io::stdout().flush()?;
-        let mut workspace_name = String::new();
-        io::stdin().read_line(&mut workspace_name)?;
-        let workspace_name = if workspace_name.trim().is_empty() {
-            "Default".to_string()
-        } else {
-            workspace_name.trim().to_string()
-        };
-
-        print!("Enter Token (xoxp-... or xoxb-...): ");
-        io::stdout().flush()?;
-        let mut token = String::new();
-        io::stdin().read_line(&mut token)?;
-        let token = token.trim().to_string();
-
-        print!("Enter App Token (xapp-...): ");
-        io::stdout().flush()?;
-        let mut app_token = String::new();
-        io::stdin().read_line(&mut app_token)?;
-        let app_token = app_token.trim().to_string();
-
-        let workspace = Workspace {
-            name: workspace_name,
-            token,
-            app_token,
-        };
-
-        let config = Config {
-            workspaces: vec![workspace],
+        Ok(Config {
+            workspaces: Vec::new(),
             active_workspace: 0,
             settings: Settings::default(),
-            config_dir: config_dir.clone(),
-        };
-
-        config.save()?;
-        println!("\nConfiguration saved to: {}", config_dir.display());
-        Ok(config)
+            logging: LoggingConfig::default(),
+            network: NetworkConfig::default(),
+            download_dir: None,
+            openers: HashMap::new(),
+            hooks: Vec::new(),
+            config_dir,
+            config_file: config_path,
+            file_credentials: None,
+        })
     }
 
     fn get_config_dir() -> PathBuf {
@@ -319,21 +710,154 @@ impl Config {
         home.join(".config").join("slack_client_rs")
     }
 
-    pub fn layout_path(&self) -> PathBuf {
-        // Use workspace-specific layout files
-        let workspace_name = if self.workspaces.is_empty() {
+    /// Append a workspace and save the config.
+    pub fn add_workspace(&mut self, workspace: Workspace) -> Result<()> {
+        self.workspaces.push(workspace);
+        self.save()
+    }
+
+    /// Rename workspace `idx`, moving its layout and cache files along, and save.
+    pub fn rename_workspace(&mut self, idx: usize, new_name: &str) -> Result<()> {
+        let old_name = self.workspaces[idx].name.clone();
+        for prefix in ["layout", "cache", "sidebar_order", "drafts", "recent_chats"] {
+            let old_path = self.config_dir.join(format!("{}_{}.json", prefix, old_name));
+            if old_path.exists() {
+                fs::rename(&old_path, self.config_dir.join(format!("{}_{}.json", prefix, new_name)))?;
+            }
+        }
+        self.workspaces[idx].name = new_name.to_string();
+        self.save()
+    }
+
+    /// Replace the token of workspace `idx` and save. A token from the environment
+    /// is only replaced for this run. A pasted token does not rotate, so the refresh
+    /// token is dropped with the old one.
+    pub fn set_workspace_token(&mut self, idx: usize, token: &str) -> Result<()> {
+        self.workspaces[idx].token = token.to_string();
+        self.workspaces[idx].refresh_token.clear();
+        self.workspaces[idx].token_expires_at = 0;
+        self.save()
+    }
+
+    /// Store a refreshed token pair of workspace `idx` (token rotation) and save.
+    pub fn set_rotated_token(&mut self, idx: usize, token: &str, refresh_token: &str, expires_at: i64) -> Result<()> {
+        let workspace = &mut self.workspaces[idx];
+        workspace.token = token.to_string();
+        workspace.refresh_token = refresh_token.to_string();
+        workspace.token_expires_at = expires_at;
+        self.save()
+    }
+
+    /// Remove workspace `idx` (not the active one) and save.
+    pub fn remove_workspace(&mut self, idx: usize) -> Result<Workspace> {
+        if idx == self.active_workspace {
+            return Err(anyhow!("can't remove the active workspace"));
+        }
+        let removed = self.workspaces.remove(idx);
+        if self.active_workspace > idx {
+            self.active_workspace -= 1;
+        }
+        // Environment tokens stay attached to the workspace they were loaded for
+        self.file_credentials = match self.file_credentials.take() {
+            Some((i, _)) if i == idx => None,
+            Some((i, credentials)) if i > idx => Some((i - 1, credentials)),
+            other => other,
+        };
+        self.save()?;
+        Ok(removed)
+    }
+
+    /// Name used in per-workspace file names
+    fn active_workspace_name(&self) -> String {
+        if self.workspaces.is_empty() {
             "default".to_string()
         } else {
             let idx = self.active_workspace.min(self.workspaces.len().saturating_sub(1));
             self.workspaces[idx].name.clone()
-        };
-        self.config_dir.join(format!("layout_{}.json", workspace_name))
+        }
+    }
+
+    pub fn layout_path(&self) -> PathBuf {
+        // Use workspace-specific layout files
+        self.config_dir.join(format!("layout_{}.json", self.active_workspace_name()))
+    }
+
+    pub fn sidebar_order_path(&self) -> PathBuf {
+        self.config_dir.join(format!("sidebar_order_{}.json", self.active_workspace_name()))
+    }
+
+    pub fn drafts_path(&self) -> PathBuf {
+        self.config_dir.join(format!("drafts_{}.json", self.active_workspace_name()))
+    }
+
+    pub fn cache_path(&self) -> PathBuf {
+        self.config_dir.join(format!("cache_{}.json", self.active_workspace_name()))
     }
 
     pub fn aliases_path(&self) -> PathBuf {
         self.config_dir.join("aliases.json")
     }
     
+    pub fn snippets_path(&self) -> PathBuf {
+        self.config_dir.join("snippets.json")
+    }
+
+    pub fn saved_searches_path(&self) -> PathBuf {
+        self.config_dir.join("saved_searches.json")
+    }
+
+    pub fn nicknames_path(&self) -> PathBuf {
+        self.config_dir.join("nicknames.json")
+    }
+
+    pub fn recent_chats_path(&self) -> PathBuf {
+        self.config_dir.join(format!("recent_chats_{}.json", self.active_workspace_name()))
+    }
+
+    pub fn starred_path(&self) -> PathBuf {
+        self.config_dir.join("starred.json")
+    }
+
+    pub fn followed_threads_path(&self) -> PathBuf {
+        self.config_dir.join("followed_threads.json")
+    }
+
+    /// Configured download directory with a leading `~/` expanded, or ~/Downloads/slack_rust.
+    pub fn download_dir(&self) -> PathBuf {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        match &self.download_dir {
+            Some(dir) => match dir.strip_prefix("~") {
+                Ok(rest) => home.join(rest),
+                Err(_) => dir.clone(),
+            },
+            None => home.join("Downloads").join("slack_rust"),
+        }
+    }
+
+    /// Configured opener for a downloaded file: exact mimetype, then `type/*`, then extension.
+    pub fn opener_for(&self, path: &Path) -> Option<&str> {
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let mimetype = crate::utils::guess_mimetype(&ext);
+        let family = mimetype.and_then(|m| m.split('/').next()).map(|t| format!("{}/*", t));
+        let dotted = format!(".{}", ext);
+
+        let keys = mimetype
+            .map(str::to_string)
+            .into_iter()
+            .chain(family)
+            .chain((!ext.is_empty()).then(|| ext.clone()))
+            .chain((!ext.is_empty()).then_some(dotted));
+        for key in keys {
+            if let Some((_, cmd)) = self.openers.iter().find(|(k, _)| k.eq_ignore_ascii_case(&key)) {
+                return Some(cmd.as_str());
+            }
+        }
+        None
+    }
+
     pub fn settings_path(&self) -> PathBuf {
         self.config_dir.join("settings.json")
     }
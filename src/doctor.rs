@@ -0,0 +1,178 @@
+//! `doctor` / `/doctor`: check a workspace's tokens and turn missing scopes and
+//! Socket Mode problems into setup steps.
+
+use crate::slack::{ConnectionState, SlackClient, SlackError};
+
+/// OAuth scopes the client uses: (scope, needed for basic use, what doesn't work without it)
+const SCOPES: &[(&str, bool, &str)] = &[
+    ("channels:read", true, "public channels are missing from the sidebar"),
+    ("channels:history", true, "messages of public channels can't be loaded"),
+    ("groups:read", true, "private channels are missing from the sidebar"),
+    ("groups:history", true, "messages of private channels can't be loaded"),
+    ("im:read", true, "direct messages are missing from the sidebar"),
+    ("im:history", true, "direct messages can't be loaded"),
+    ("mpim:read", true, "group DMs are missing from the sidebar"),
+    ("mpim:history", true, "group DMs can't be loaded"),
+    ("chat:write", true, "sending, editing and deleting messages"),
+    ("users:read", true, "user names (authors show as user IDs)"),
+    ("reactions:write", false, "/react and quick reactions"),
+    ("im:write", false, "/dm to start a conversation"),
+    ("users:write", false, "/away and /active"),
+    ("users:read.email", false, "/finduser by email address"),
+    ("usergroups:read", false, "highlighting mentions of your user groups"),
+    ("channels:write", false, "/invite, /kick and /markallread in public channels"),
+    ("groups:write", false, "/invite, /kick and /markallread in private channels"),
+    ("mpim:write", false, "/markallread for group DMs"),
+    ("dnd:read", false, "showing your Do Not Disturb state"),
+    ("dnd:write", false, "/dnd"),
+    ("stars:write", false, "sync_stars (starring channels on Slack with /fav)"),
+    ("files:read", false, "/files"),
+    ("search:read", false, "/search with Slack operators (user tokens only)"),
+    ("files:write", false, "uploading long pastes as snippets"),
+    ("pins:read", false, "live pin notices (pin_added / pin_removed events)"),
+    ("stars:read", false, "stars from other clients (star_added / star_removed events)"),
+];
+
+const SCOPES_HINT: &str =
+    "Add them under OAuth & Permissions at https://api.slack.com/apps, then reinstall the app.";
+
+/// Result of checking one workspace
+pub struct Report {
+    pub lines: Vec<String>,
+    pub problems: usize, // Missing basic scopes, bad tokens, Socket Mode failures
+}
+
+/// Check `slack`'s token and the Socket Mode `app_token`. A `live` connection that is
+/// up counts as working; opening a second one would take events away from it.
+pub async fn check(slack: &SlackClient, app_token: &str, live: Option<&ConnectionState>) -> Report {
+    let mut report = Report {
+        lines: Vec::new(),
+        problems: 0,
+    };
+
+    match slack.token_info().await {
+        Ok(info) => {
+            report.lines.push(format!(
+                "Token: {} as {} in {}",
+                token_kind(slack.token_prefix()),
+                info.user,
+                info.team
+            ));
+            match info.scopes {
+                Some(granted) => check_scopes(&mut report, &granted),
+                None => report
+                    .lines
+                    .push("Scopes: not reported by Slack for this token, skipping the check".to_string()),
+            }
+        }
+        Err(e) => {
+            report.problems += 1;
+            report.lines.push(format!("Token: auth.test failed: {}", e));
+            report.lines.push(auth_hint(&e).to_string());
+        }
+    }
+
+    // Calls that already failed with missing_scope in this session
+    let disabled = slack.missing_scopes();
+    if !disabled.is_empty() {
+        report.lines.push("Disabled this session:".to_string());
+        for (method, scope) in disabled {
+            report.lines.push(format!("  {} (needs {})", method, scope));
+        }
+    }
+
+    if app_token.is_empty() {
+        report.lines.push(
+            "Socket Mode: no app_token configured, new messages are polled (slower)".to_string(),
+        );
+    } else if let Some(state @ ConnectionState::Connected { .. }) = live {
+        report.lines.push(format!("Socket Mode: {}", state.label()));
+    } else {
+        match slack.check_socket_mode(app_token).await {
+            Ok(()) => report.lines.push("Socket Mode: connected".to_string()),
+            Err(e) => {
+                report.problems += 1;
+                report.lines.push(format!("Socket Mode: failed: {}", e));
+                report.lines.push(socket_hint(&e).to_string());
+            }
+        }
+    }
+
+    report
+}
+
+/// Lines for a token that could not even create a client (auth.test failed at startup)
+pub fn auth_failure(error: &anyhow::Error) -> Report {
+    Report {
+        lines: vec![
+            format!("Token: auth.test failed: {}", error),
+            auth_hint(error).to_string(),
+        ],
+        problems: 1,
+    }
+}
+
+fn check_scopes(report: &mut Report, granted: &[String]) {
+    let missing: Vec<&(&str, bool, &str)> = SCOPES
+        .iter()
+        .filter(|(scope, _, _)| !granted.iter().any(|g| g == scope))
+        .collect();
+    report.lines.push(format!(
+        "Scopes: {} granted, {} of the {} used here missing",
+        granted.len(),
+        missing.len(),
+        SCOPES.len()
+    ));
+    if missing.is_empty() {
+        return;
+    }
+
+    for (basic, heading) in [(true, "Missing scopes:"), (false, "Missing optional scopes:")] {
+        let group: Vec<_> = missing.iter().filter(|(_, b, _)| *b == basic).collect();
+        if group.is_empty() {
+            continue;
+        }
+        if basic {
+            report.problems += group.len();
+        }
+        report.lines.push(heading.to_string());
+        for (scope, _, feature) in group {
+            report.lines.push(format!("  {} - {}", scope, feature));
+        }
+    }
+    report.lines.push(SCOPES_HINT.to_string());
+}
+
+fn token_kind(prefix: &str) -> &'static str {
+    match prefix {
+        "xoxp" => "user token",
+        "xoxb" => "bot token",
+        _ => "token",
+    }
+}
+
+fn auth_hint(error: &anyhow::Error) -> &'static str {
+    match error.downcast_ref::<SlackError>() {
+        Some(SlackError::InvalidAuth(_)) => {
+            "Copy a fresh Bot (xoxb-) or User (xoxp-) token from OAuth & Permissions into the config."
+        }
+        Some(_) => "Slack rejected the token; check that the app is installed to this workspace.",
+        None => "Could not reach Slack; check the network connection and proxy settings.",
+    }
+}
+
+fn socket_hint(error: &anyhow::Error) -> &'static str {
+    match error.downcast_ref::<SlackError>() {
+        Some(SlackError::MissingScope { .. }) => {
+            "The app_token needs the connections:write scope (Basic Information > App-Level Tokens)."
+        }
+        Some(SlackError::InvalidAuth(_)) => {
+            "The app_token is invalid; generate an App-Level Token (xapp-) under Basic Information."
+        }
+        Some(SlackError::Api(code)) if code == "not_allowed_token_type" => {
+            "app_token must be an App-Level Token (xapp-), not a bot or user token."
+        }
+        Some(_) => "Enable Socket Mode under Settings > Socket Mode and reinstall the app.",
+        None => "Could not open the WebSocket; a firewall or proxy may be blocking wss://wss-primary.slack.com.",
+    }
+}
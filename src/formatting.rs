@@ -1,123 +1,88 @@
+use chrono::{DateTime, Datelike, Local, TimeZone};
 use once_cell::sync::Lazy;
+use ratatui::style::{Color, Modifier, Style};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-static SLACK_EMOJI: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+/// Slack-only names that the gemoji table (used by the `emojis` crate) spells differently.
+static SLACK_ALIASES: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
     let mut m = HashMap::new();
-    m.insert("+1", "\u{1F44D}");
-    m.insert("thumbsup", "\u{1F44D}");
-    m.insert("-1", "\u{1F44E}");
-    m.insert("thumbsdown", "\u{1F44E}");
-    m.insert("heart", "\u{2764}\u{FE0F}");
-    m.insert("heart_eyes", "\u{1F60D}");
-    m.insert("joy", "\u{1F602}");
-    m.insert("rofl", "\u{1F923}");
-    m.insert("smile", "\u{1F604}");
-    m.insert("grinning", "\u{1F600}");
-    m.insert("smiley", "\u{1F603}");
-    m.insert("laughing", "\u{1F606}");
-    m.insert("wink", "\u{1F609}");
-    m.insert("blush", "\u{1F60A}");
-    m.insert("yum", "\u{1F60B}");
-    m.insert("sunglasses", "\u{1F60E}");
     m.insert("thinking_face", "\u{1F914}");
-    m.insert("thinking", "\u{1F914}");
-    m.insert("raised_hands", "\u{1F64C}");
-    m.insert("clap", "\u{1F44F}");
-    m.insert("fire", "\u{1F525}");
-    m.insert("100", "\u{1F4AF}");
-    m.insert("tada", "\u{1F389}");
     m.insert("party_popper", "\u{1F389}");
-    m.insert("rocket", "\u{1F680}");
-    m.insert("star", "\u{2B50}");
-    m.insert("eyes", "\u{1F440}");
-    m.insert("wave", "\u{1F44B}");
-    m.insert("pray", "\u{1F64F}");
-    m.insert("muscle", "\u{1F4AA}");
-    m.insert("ok_hand", "\u{1F44C}");
-    m.insert("v", "\u{270C}\u{FE0F}");
-    m.insert("point_up", "\u{261D}\u{FE0F}");
-    m.insert("point_down", "\u{1F447}");
-    m.insert("point_left", "\u{1F448}");
-    m.insert("point_right", "\u{1F449}");
-    m.insert("sob", "\u{1F62D}");
-    m.insert("cry", "\u{1F622}");
-    m.insert("angry", "\u{1F620}");
-    m.insert("rage", "\u{1F621}");
-    m.insert("scream", "\u{1F631}");
-    m.insert("fearful", "\u{1F628}");
-    m.insert("sweat", "\u{1F613}");
-    m.insert("disappointed", "\u{1F61E}");
-    m.insert("confused", "\u{1F615}");
-    m.insert("neutral_face", "\u{1F610}");
-    m.insert("expressionless", "\u{1F611}");
-    m.insert("unamused", "\u{1F612}");
     m.insert("rolling_eyes", "\u{1F644}");
-    m.insert("grimacing", "\u{1F62C}");
-    m.insert("relieved", "\u{1F60C}");
-    m.insert("pensive", "\u{1F614}");
-    m.insert("sleepy", "\u{1F62A}");
-    m.insert("sleeping", "\u{1F634}");
-    m.insert("mask", "\u{1F637}");
-    m.insert("nerd_face", "\u{1F913}");
-    m.insert("worried", "\u{1F61F}");
-    m.insert("flushed", "\u{1F633}");
-    m.insert("hugs", "\u{1F917}");
     m.insert("hugging_face", "\u{1F917}");
-    m.insert("cowboy_hat_face", "\u{1F920}");
-    m.insert("clown_face", "\u{1F921}");
-    m.insert("shushing_face", "\u{1F92B}");
-    m.insert("exploding_head", "\u{1F92F}");
-    m.insert("partying_face", "\u{1F973}");
-    m.insert("star_struck", "\u{1F929}");
-    m.insert("money_mouth_face", "\u{1F911}");
-    m.insert("zany_face", "\u{1F92A}");
-    m.insert("skull", "\u{1F480}");
-    m.insert("ghost", "\u{1F47B}");
-    m.insert("alien", "\u{1F47D}");
     m.insert("robot_face", "\u{1F916}");
-    m.insert("poop", "\u{1F4A9}");
-    m.insert("hankey", "\u{1F4A9}");
-    m.insert("see_no_evil", "\u{1F648}");
-    m.insert("hear_no_evil", "\u{1F649}");
-    m.insert("speak_no_evil", "\u{1F64A}");
-    m.insert("kiss", "\u{1F48B}");
-    m.insert("cupid", "\u{1F498}");
-    m.insert("sparkling_heart", "\u{1F496}");
-    m.insert("broken_heart", "\u{1F494}");
-    m.insert("orange_heart", "\u{1F9E1}");
-    m.insert("yellow_heart", "\u{1F49B}");
-    m.insert("green_heart", "\u{1F49A}");
-    m.insert("blue_heart", "\u{1F499}");
-    m.insert("purple_heart", "\u{1F49C}");
-    m.insert("black_heart", "\u{1F5A4}");
-    m.insert("white_heart", "\u{1F90D}");
-    m.insert("two_hearts", "\u{1F495}");
-    m.insert("revolving_hearts", "\u{1F49E}");
     m.insert("check", "\u{2705}");
-    m.insert("white_check_mark", "\u{2705}");
-    m.insert("x", "\u{274C}");
-    m.insert("heavy_check_mark", "\u{2714}\u{FE0F}");
-    m.insert("warning", "\u{26A0}\u{FE0F}");
-    m.insert("no_entry", "\u{26D4}");
-    m.insert("question", "\u{2753}");
-    m.insert("exclamation", "\u{2757}");
     m
 });
 
+/// Slack's own short names mostly follow the Unicode (CLDR) names, snake-cased:
+/// "face with monocle" -> `face_with_monocle`, where gemoji has `monocle_face`.
+static CLDR_NAMES: Lazy<HashMap<String, &'static emojis::Emoji>> = Lazy::new(|| {
+    emojis::iter()
+        .map(|emoji| {
+            let name: String = emoji
+                .name()
+                .to_lowercase()
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join("_");
+            (name, emoji)
+        })
+        .collect()
+});
+
+/// Map Slack's `skin-tone-2`..`skin-tone-6` suffix to the Fitzpatrick modifier.
+fn skin_tone(tone: &str) -> Option<emojis::SkinTone> {
+    use emojis::SkinTone;
+    match tone {
+        "2" => Some(SkinTone::Light),
+        "3" => Some(SkinTone::MediumLight),
+        "4" => Some(SkinTone::Medium),
+        "5" => Some(SkinTone::MediumDark),
+        "6" => Some(SkinTone::Dark),
+        _ => None,
+    }
+}
+
 /// Convert a Slack emoji name to its Unicode character.
 pub fn slack_emoji_to_unicode(name: &str) -> String {
     // Handle skin tone modifiers
-    let base_name = if let Some(idx) = name.find("::skin-tone-") {
-        &name[..idx]
-    } else {
-        name
+    let (base_name, tone) = match name.split_once("::skin-tone-") {
+        Some((base, tone)) => (base, skin_tone(tone)),
+        None => (name, None),
     };
 
-    if let Some(&emoji) = SLACK_EMOJI.get(base_name) {
-        emoji.to_string()
-    } else {
-        format!(":{}:", name)
+    if let Some(&emoji) = SLACK_ALIASES.get(base_name) {
+        return emoji.to_string();
     }
+    let found =
+        emojis::get_by_shortcode(base_name).or_else(|| CLDR_NAMES.get(base_name).copied());
+    match found {
+        Some(emoji) => tone
+            .and_then(|tone| emoji.with_skin_tone(tone))
+            .unwrap_or(emoji)
+            .as_str()
+            .to_string(),
+        None => format!(":{}:", name),
+    }
+}
+
+/// Known emoji names starting with `prefix` (case-insensitive), sorted.
+pub fn emoji_names_with_prefix(prefix: &str) -> Vec<String> {
+    let prefix = prefix.to_lowercase();
+    let mut names: Vec<String> = emojis::iter()
+        .flat_map(|emoji| emoji.shortcodes())
+        .chain(SLACK_ALIASES.keys().copied())
+        .chain(CLDR_NAMES.keys().map(String::as_str))
+        .filter(|name| name.starts_with(&prefix))
+        .map(|name| name.to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
 }
 
 /// Replace :emoji_name: patterns in text with Unicode characters.
@@ -158,6 +123,31 @@ pub fn convert_slack_emojis(text: &str) -> String {
     result
 }
 
+/// The emoji of a message that is nothing but 1-3 emoji (spaces allowed between them),
+/// for showing it larger. `text` must already have its `:name:` codes converted.
+pub fn jumbo_emoji(text: &str) -> Option<Vec<&str>> {
+    let mut found = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        // Longest known emoji at the start; ZWJ sequences run to about ten chars
+        let mut len = rest
+            .char_indices()
+            .take(12)
+            .map(|(i, c)| i + c.len_utf8())
+            .filter(|&end| emojis::get(&rest[..end]).is_some())
+            .last()?;
+        if rest[len..].starts_with('\u{fe0f}') {
+            len += '\u{fe0f}'.len_utf8();
+        }
+        found.push(&rest[..len]);
+        if found.len() > 3 {
+            return None;
+        }
+        rest = rest[len..].trim_start();
+    }
+    (!found.is_empty()).then_some(found)
+}
+
 /// Convert Slack user mentions <@U12345> to @name.
 pub fn convert_slack_mentions(text: &str, resolve_user: &impl Fn(&str) -> String) -> String {
     let mut result = String::with_capacity(text.len());
@@ -187,8 +177,9 @@ pub fn convert_slack_mentions(text: &str, resolve_user: &impl Fn(&str) -> String
     result
 }
 
-/// Convert Slack link format <URL|text> and <URL> to just the URL.
-pub fn convert_slack_links(text: &str) -> String {
+/// Convert Slack link format <URL|text> and <URL> to just the URL, or with
+/// `show_labels` to the text (links without one still show the URL).
+pub fn convert_slack_links(text: &str, show_labels: bool) -> String {
     let mut result = String::with_capacity(text.len());
     let mut rest = text;
 
@@ -198,18 +189,21 @@ pub fn convert_slack_links(text: &str) -> String {
         if let Some(end) = after.find('>') {
             let inner = &after[..end];
             if inner.starts_with("http://") || inner.starts_with("https://") {
-                // <URL|text> -> URL, <URL> -> URL
-                let url = if let Some(pipe) = inner.find('|') {
-                    &inner[..pipe]
-                } else {
-                    inner
-                };
-                result.push_str(url);
+                // <URL|text> -> URL (or text), <URL> -> URL
+                match inner.split_once('|') {
+                    Some((_, label)) if show_labels && !label.is_empty() => result.push_str(label),
+                    Some((url, _)) => result.push_str(url),
+                    None => result.push_str(inner),
+                }
             } else if inner.starts_with('@') {
                 // User mention - keep as-is with angle brackets for convert_slack_mentions
                 result.push('<');
                 result.push_str(inner);
                 result.push('>');
+            } else if let Some(channel) = inner.strip_prefix('#') {
+                result.push_str(&channel_mention_text(channel));
+            } else if let Some(special) = inner.strip_prefix('!') {
+                result.push_str(&special_mention_text(special, Local::now()));
             } else {
                 result.push_str(inner);
             }
@@ -223,6 +217,159 @@ pub fn convert_slack_links(text: &str) -> String {
     result
 }
 
+/// The `<URL|text>` links of a raw message as (text, URL); links without text are left out.
+pub fn link_labels(text: &str) -> Vec<(String, String)> {
+    let mut links = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('>') else {
+            break;
+        };
+        let inner = &after[..end];
+        if inner.starts_with("http://") || inner.starts_with("https://") {
+            if let Some((url, label)) = inner.split_once('|').filter(|(_, l)| !l.is_empty()) {
+                links.push((label.to_string(), url.to_string()));
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    links
+}
+
+/// `<#C123|general>` -> `#general`; without a name the channel ID is shown.
+fn channel_mention_text(inner: &str) -> String {
+    match inner.split_once('|') {
+        Some((_, name)) if !name.is_empty() => format!("#{}", name),
+        Some((id, _)) => format!("#{}", id),
+        None => format!("#{}", inner),
+    }
+}
+
+/// Readable text of a `<!...>` token: `@here`/`@channel`/`@everyone`, user group
+/// handles and `<!date^...>` times. Anything else shows its label (or its name).
+fn special_mention_text(inner: &str, now: DateTime<Local>) -> String {
+    let (command, label) = match inner.split_once('|') {
+        Some((command, label)) => (command, Some(label).filter(|l| !l.is_empty())),
+        None => (inner, None),
+    };
+    match command {
+        "here" | "channel" | "everyone" => format!("@{}", command),
+        _ if command.starts_with("subteam^") => {
+            let handle = label.unwrap_or(&command["subteam^".len()..]);
+            format!("@{}", handle.trim_start_matches('@'))
+        }
+        _ if command.starts_with("date^") => format_slack_date(command, now)
+            .or_else(|| label.map(str::to_string))
+            .unwrap_or_default(),
+        _ => label.unwrap_or(command).to_string(),
+    }
+}
+
+/// `date^1392734382^{date_short} at {time}[^link]` in local time, or None if malformed.
+fn format_slack_date(command: &str, now: DateTime<Local>) -> Option<String> {
+    let mut parts = command.splitn(4, '^').skip(1);
+    let secs: i64 = parts.next()?.parse().ok()?;
+    let template = parts.next()?;
+    let dt = Local.timestamp_opt(secs, 0).single()?;
+
+    let day = dt.day();
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    let date = format!("{} {}{}, {}", dt.format("%B"), day, suffix, dt.year());
+    let date_short = dt.format("%b %-d, %Y").to_string();
+    let date_long = format!("{}, {}", dt.format("%A"), date);
+    // "_pretty" variants say Today/Yesterday/Tomorrow when they apply
+    let pretty = |fallback: &str| match (dt.date_naive() - now.date_naive()).num_days() {
+        0 => "Today".to_string(),
+        -1 => "Yesterday".to_string(),
+        1 => "Tomorrow".to_string(),
+        _ => fallback.to_string(),
+    };
+
+    let text = template
+        .replace("{date_num}", &dt.format("%Y-%m-%d").to_string())
+        .replace("{date_slash}", &dt.format("%d/%m/%Y").to_string())
+        .replace("{date_long_pretty}", &pretty(&date_long))
+        .replace("{date_short_pretty}", &pretty(&date_short))
+        .replace("{date_pretty}", &pretty(&date))
+        .replace("{date_long_full}", &dt.format("%A, %B %-d, %Y").to_string())
+        .replace("{date_long}", &date_long)
+        .replace("{date_short}", &date_short)
+        .replace("{date}", &date)
+        .replace("{time_secs}", &dt.format("%-I:%M:%S %p").to_string())
+        .replace("{time}", &dt.format("%-I:%M %p").to_string());
+    Some(text)
+}
+
+/// The readable form of each user, channel and group mention in a raw message
+/// (`@Alice`, `#general`, `@here`), so the renderer can style them.
+pub fn mention_labels(text: &str, resolve_user: &impl Fn(&str) -> String) -> Vec<String> {
+    let mut labels = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('>') else {
+            break;
+        };
+        let inner = &after[..end];
+        if let Some(user) = inner.strip_prefix('@') {
+            let user_id = user.split('|').next().unwrap_or(user);
+            labels.push(format!("@{}", resolve_user(user_id)));
+        } else if let Some(channel) = inner.strip_prefix('#') {
+            labels.push(channel_mention_text(channel));
+        } else if let Some(special) = inner.strip_prefix('!') {
+            if !special.starts_with("date^") {
+                labels.push(special_mention_text(special, Local::now()));
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    labels.sort();
+    labels.dedup();
+    labels
+}
+
+/// Split `text` into runs, flagging occurrences of any of `labels` that end at a
+/// word boundary (so `@al` doesn't match inside `@alice`).
+pub fn split_labels<'a>(text: &'a str, labels: &[String]) -> Vec<(&'a str, bool)> {
+    let mut parts = Vec::new();
+    let mut plain_start = 0;
+    let mut pos = 0;
+    while pos < text.len() {
+        let hit = labels
+            .iter()
+            .filter(|label| !label.is_empty() && text[pos..].starts_with(label.as_str()))
+            .filter(|label| {
+                !text[pos + label.len()..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-')
+            })
+            .map(String::len)
+            .max();
+        if let Some(len) = hit {
+            if plain_start < pos {
+                parts.push((&text[plain_start..pos], false));
+            }
+            parts.push((&text[pos..pos + len], true));
+            pos += len;
+            plain_start = pos;
+        } else {
+            pos += text[pos..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    if plain_start < text.len() {
+        parts.push((&text[plain_start..], false));
+    }
+    parts
+}
+
 /// Remove skin-tone modifiers like :skin-tone-6: from text
 fn remove_skin_tone_modifiers(text: &str) -> String {
     // Pattern: :skin-tone-X: where X is a digit
@@ -236,12 +383,14 @@ fn remove_skin_tone_modifiers(text: &str) -> String {
 }
 
 /// Format message text: convert links, mentions, and emojis.
+/// `show_link_labels` shows the text of `<URL|text>` links instead of the URL.
 pub fn format_message_text(
     text: &str,
     show_emojis: bool,
+    show_link_labels: bool,
     resolve_user: &impl Fn(&str) -> String,
 ) -> String {
-    let mut out = convert_slack_links(text);
+    let mut out = convert_slack_links(text, show_link_labels);
     out = remove_skin_tone_modifiers(&out);
     out = convert_slack_mentions(&out, resolve_user);
     if show_emojis {
@@ -250,10 +399,244 @@ pub fn format_message_text(
     out
 }
 
+/// Styles message lines that match `pattern` (`highlight_rules` setting), e.g.
+/// `{"pattern": "FAILED|ERROR", "style": "red bold"}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighlightRule {
+    pub pattern: String,
+    /// Foreground color, `on <color>` for the background, and bold, italic,
+    /// underlined, dim or reversed. Colors are names ("lightred") or "#rrggbb".
+    pub style: String,
+}
+
+impl HighlightRule {
+    pub fn compile(&self) -> Result<(Regex, Style), String> {
+        let regex = Regex::new(&self.pattern).map_err(|e| format!("{}: {}", self.pattern, e))?;
+        let mut style = Style::default();
+        let mut words = self.style.split_whitespace();
+        while let Some(word) = words.next() {
+            let color = |name: Option<&str>| -> Result<Color, String> {
+                let name = name.ok_or_else(|| format!("{}: missing color", self.style))?;
+                name.parse().map_err(|_| format!("{}: unknown color {}", self.style, name))
+            };
+            style = match word.to_lowercase().as_str() {
+                "bold" => style.add_modifier(Modifier::BOLD),
+                "italic" => style.add_modifier(Modifier::ITALIC),
+                "underlined" | "underline" => style.add_modifier(Modifier::UNDERLINED),
+                "dim" => style.add_modifier(Modifier::DIM),
+                "reversed" => style.add_modifier(Modifier::REVERSED),
+                "on" => style.bg(color(words.next())?),
+                _ => style.fg(color(Some(word))?),
+            };
+        }
+        Ok((regex, style))
+    }
+}
+
+/// Return the first keyword that appears in `text` as a whole word (case-insensitive).
+pub fn find_keyword<'a>(text: &str, keywords: &'a [String]) -> Option<&'a str> {
+    let haystack = text.to_lowercase();
+    keywords.iter().map(|k| k.as_str()).find(|keyword| {
+        let needle = keyword.trim().to_lowercase();
+        if needle.is_empty() {
+            return false;
+        }
+        haystack.match_indices(&needle).any(|(pos, _)| {
+            let before = haystack[..pos].chars().next_back();
+            let after = haystack[pos + needle.len()..].chars().next();
+            !before.is_some_and(|c| c.is_alphanumeric() || c == '_')
+                && !after.is_some_and(|c| c.is_alphanumeric() || c == '_')
+        })
+    })
+}
+
+/// How message timestamps are shown (`timestamp_style` setting).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampStyle {
+    /// `timestamp_format` (strftime, default `%H:%M`)
+    #[default]
+    Absolute,
+    /// Date and time, `%Y-%m-%d %H:%M`
+    Full,
+    /// "5m ago", falling back to the date after a week
+    Relative,
+}
+
+impl TimestampStyle {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "absolute" | "time" => Some(TimestampStyle::Absolute),
+            "full" | "date" => Some(TimestampStyle::Full),
+            "relative" => Some(TimestampStyle::Relative),
+            _ => None,
+        }
+    }
+}
+
+/// Human-readable file size ("512 B", "1.4 KB", "23.0 MB").
+pub fn format_file_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Format a Slack `ts` (seconds since the epoch, optionally with a fraction)
+/// for display. `now` is passed in so relative times are testable.
+pub fn format_timestamp(
+    ts: &str,
+    style: TimestampStyle,
+    format: &str,
+    now: DateTime<Local>,
+) -> Option<String> {
+    let secs: i64 = ts.split('.').next()?.parse().ok()?;
+    let dt = Local.timestamp_opt(secs, 0).single()?;
+    let text = match style {
+        TimestampStyle::Absolute => dt.format(format).to_string(),
+        TimestampStyle::Full => dt.format("%Y-%m-%d %H:%M").to_string(),
+        TimestampStyle::Relative => {
+            let ago = (now - dt).num_seconds().max(0);
+            match ago {
+                0..60 => "now".to_string(),
+                60..3600 => format!("{}m ago", ago / 60),
+                3600..86400 => format!("{}h ago", ago / 3600),
+                86400..604800 => format!("{}d ago", ago / 86400),
+                _ => dt.format("%Y-%m-%d").to_string(),
+            }
+        }
+    };
+    Some(text)
+}
+
+/// Whether `format` is a strftime string chrono can format (`%Q` is not); formatting
+/// with an invalid one panics.
+pub fn valid_time_format(format: &str) -> bool {
+    !chrono::format::StrftimeItems::new(format).any(|item| matches!(item, chrono::format::Item::Error))
+}
+
+/// Byte length of the case-insensitive match of `needle` at the start of `hay`, if any.
+fn match_len_at(hay: &str, needle: &str) -> Option<usize> {
+    let mut hay_chars = hay.char_indices();
+    for n in needle.chars() {
+        let (_, h) = hay_chars.next()?;
+        if !h.to_lowercase().eq(n.to_lowercase()) {
+            return None;
+        }
+    }
+    Some(hay_chars.next().map(|(i, _)| i).unwrap_or(hay.len()))
+}
+
+/// Split `text` into runs, flagging the case-insensitive occurrences of `query`.
+pub fn split_matches<'a>(text: &'a str, query: &str) -> Vec<(&'a str, bool)> {
+    if query.is_empty() {
+        return vec![(text, false)];
+    }
+    let mut parts = Vec::new();
+    let mut plain_start = 0;
+    let mut pos = 0;
+    while pos < text.len() {
+        if let Some(len) = match_len_at(&text[pos..], query) {
+            if plain_start < pos {
+                parts.push((&text[plain_start..pos], false));
+            }
+            parts.push((&text[pos..pos + len], true));
+            pos += len;
+            plain_start = pos;
+        } else {
+            pos += text[pos..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    if plain_start < text.len() {
+        parts.push((&text[plain_start..], false));
+    }
+    parts
+}
+
+/// True if `text` contains `query`, ignoring case.
+pub fn contains_ignore_case(text: &str, query: &str) -> bool {
+    split_matches(text, query).iter().any(|(_, hit)| *hit)
+}
+
+/// Split a message that is too long for one post into parts of at most `max_chars`
+/// characters, breaking at a paragraph, line or word where possible. A code block
+/// that gets cut is closed at the end of one part and reopened in the next.
+pub fn split_message(text: &str, max_chars: usize) -> Vec<String> {
+    const FENCE: &str = "```";
+    // Leaves room for the fences around a cut code block
+    let budget = max_chars.saturating_sub(8).max(1);
+    let mut parts = Vec::new();
+    let mut rest = text.trim();
+    let mut in_code = false;
+    while !rest.is_empty() {
+        let prefix = if in_code { "```\n" } else { "" };
+        if prefix.len() + rest.chars().count() <= max_chars {
+            parts.push(format!("{}{}", prefix, rest));
+            break;
+        }
+        let limit = rest.char_indices().nth(budget).map_or(rest.len(), |(i, _)| i);
+        let window = &rest[..limit];
+        let (cut, skip) = if let Some(i) = window.rfind("\n\n").filter(|&i| i > 0) {
+            (i, 2)
+        } else if let Some(i) = window.rfind('\n').filter(|&i| i > 0) {
+            (i, 1)
+        } else if let Some(i) = window.rfind(' ').filter(|&i| i > 0) {
+            (i, 1)
+        } else {
+            (limit, 0)
+        };
+        let chunk = rest[..cut].trim_end();
+        let mut part = format!("{}{}", prefix, chunk);
+        in_code ^= chunk.matches(FENCE).count() % 2 == 1;
+        if in_code {
+            part.push_str("\n```");
+        }
+        parts.push(part);
+        rest = &rest[cut + skip..];
+    }
+    parts
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_highlight_rule() {
+        let rule = |pattern: &str, style: &str| HighlightRule {
+            pattern: pattern.to_string(),
+            style: style.to_string(),
+        };
+        let (regex, style) = rule("FAILED|ERROR", "red bold").compile().unwrap();
+        assert!(regex.is_match("Build #412 FAILED"));
+        assert_eq!(style, Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
+        let (_, style) = rule("DEPLOYED", "black on #00ff00").compile().unwrap();
+        assert_eq!(style, Style::default().fg(Color::Black).bg(Color::Rgb(0, 255, 0)));
+        assert!(rule("(", "red").compile().is_err());
+        assert!(rule("x", "reddish").compile().is_err());
+        assert!(rule("x", "white on").compile().is_err());
+    }
+
+    #[test]
+    fn test_jumbo_emoji() {
+        assert_eq!(jumbo_emoji("🎉"), Some(vec!["🎉"]));
+        assert_eq!(jumbo_emoji(" 🔥🔥 ❤️ "), Some(vec!["🔥", "🔥", "❤️"]));
+        assert_eq!(jumbo_emoji("👍🏽 👩‍💻"), Some(vec!["👍🏽", "👩‍💻"]));
+        assert_eq!(jumbo_emoji(&convert_slack_emojis(":fire::tada:")), Some(vec!["🔥", "🎉"]));
+        assert_eq!(jumbo_emoji("🎉🎉🎉🎉"), None);
+        assert_eq!(jumbo_emoji("nice 🎉"), None);
+        assert_eq!(jumbo_emoji(":custom_emoji:"), None);
+        assert_eq!(jumbo_emoji("42"), None);
+        assert_eq!(jumbo_emoji("  "), None);
+    }
+
     #[test]
     fn test_emoji_conversion() {
         assert_eq!(
@@ -261,6 +644,8 @@ mod tests {
             "hello \u{1F525} world"
         );
         assert_eq!(convert_slack_emojis(":thumbsup:"), "\u{1F44D}");
+        assert_eq!(convert_slack_emojis(":face_with_monocle:"), "\u{1F9D0}");
+        assert_eq!(slack_emoji_to_unicode("wave::skin-tone-3"), "\u{1F44B}\u{1F3FC}");
         assert_eq!(convert_slack_emojis(":unknown_emoji:"), ":unknown_emoji:");
         assert_eq!(convert_slack_emojis("no emojis here"), "no emojis here");
     }
@@ -268,13 +653,21 @@ mod tests {
     #[test]
     fn test_slack_links() {
         assert_eq!(
-            convert_slack_links("<https://example.com|click>"),
+            convert_slack_links("<https://example.com|click>", false),
             "https://example.com"
         );
         assert_eq!(
-            convert_slack_links("<https://example.com>"),
+            convert_slack_links("<https://example.com>", false),
             "https://example.com"
         );
+        assert_eq!(
+            convert_slack_links("<https://example.com|click> <https://a.io>", true),
+            "click https://a.io"
+        );
+        assert_eq!(
+            link_labels("<https://example.com|click> <https://a.io>"),
+            vec![("click".to_string(), "https://example.com".to_string())]
+        );
     }
 
     #[test]
@@ -292,4 +685,112 @@ mod tests {
             "hi @Alice"
         );
     }
+
+    #[test]
+    fn test_channel_and_special_mentions() {
+        assert_eq!(convert_slack_links("see <#C123|general>", false), "see #general");
+        assert_eq!(convert_slack_links("<#C123>", false), "#C123");
+        assert_eq!(convert_slack_links("<!here> <!channel|channel>", false), "@here @channel");
+        assert_eq!(convert_slack_links("<!subteam^S1|@oncall> help", false), "@oncall help");
+        assert_eq!(convert_slack_links("<!date^bad^{date}|Feb 18>", false), "Feb 18");
+
+        let now = Local.with_ymd_and_hms(2014, 2, 18, 12, 0, 0).unwrap();
+        let ts = now.timestamp();
+        assert_eq!(
+            special_mention_text(&format!("date^{}^{{date_num}} {{time}}|x", ts), now),
+            "2014-02-18 12:00 PM"
+        );
+        assert_eq!(
+            special_mention_text(&format!("date^{}^{{date_pretty}}, {{date_long}}", ts), now),
+            "Today, Tuesday, February 18th, 2014"
+        );
+
+        let resolve = |_: &str| "Alice".to_string();
+        let labels = mention_labels("<@U1> in <#C1|general> <!here> <!date^1^{date}>", &resolve);
+        assert_eq!(labels, vec!["#general", "@Alice", "@here"]);
+        assert_eq!(
+            split_labels("@Alice @Alicex #general", &labels),
+            vec![("@Alice", true), (" @Alicex ", false), ("#general", true)]
+        );
+    }
+
+    #[test]
+    fn test_format_file_size() {
+        assert_eq!(format_file_size(512), "512 B");
+        assert_eq!(format_file_size(1536), "1.5 KB");
+        assert_eq!(format_file_size(23 * 1024 * 1024), "23.0 MB");
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        let now = Local.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+        let at = |secs_ago: i64| format!("{}.000100", now.timestamp() - secs_ago);
+        let relative = |secs_ago| format_timestamp(&at(secs_ago), TimestampStyle::Relative, "%H:%M", now);
+
+        assert_eq!(relative(30).as_deref(), Some("now"));
+        assert_eq!(relative(5 * 60).as_deref(), Some("5m ago"));
+        assert_eq!(relative(3 * 3600).as_deref(), Some("3h ago"));
+        assert_eq!(relative(2 * 86400).as_deref(), Some("2d ago"));
+        assert_eq!(relative(30 * 86400).as_deref(), Some("2024-04-10"));
+
+        let abs = format_timestamp(&at(3600), TimestampStyle::Absolute, "%H:%M", now);
+        assert_eq!(abs.as_deref(), Some("11:00"));
+        let full = format_timestamp(&at(0), TimestampStyle::Full, "%H:%M", now);
+        assert_eq!(full.as_deref(), Some("2024-05-10 12:00"));
+        assert_eq!(format_timestamp("garbage", TimestampStyle::Full, "%H:%M", now), None);
+
+        assert!(valid_time_format("%a %H:%M:%S"));
+        assert!(!valid_time_format("%Q"));
+        assert!(!valid_time_format("%H:%"));
+    }
+
+    #[test]
+    fn test_emoji_names_with_prefix() {
+        let names = emoji_names_with_prefix("THUMBS");
+        assert!(names.contains(&"thumbsup".to_string()));
+        assert!(names.iter().all(|n| n.starts_with("thumbs")));
+        assert!(names.windows(2).all(|w| w[0] <= w[1]));
+        assert!(emoji_names_with_prefix("no_such_emoji_zzz").is_empty());
+    }
+
+    #[test]
+    fn test_split_matches() {
+        assert_eq!(
+            split_matches("Deploy done, DEPLOY again", "deploy"),
+            vec![("Deploy", true), (" done, ", false), ("DEPLOY", true), (" again", false)]
+        );
+        assert_eq!(split_matches("Ärger über ärger", "ÄRGER"), vec![("Ärger", true), (" über ", false), ("ärger", true)]);
+        assert_eq!(split_matches("nothing", "xyz"), vec![("nothing", false)]);
+        assert_eq!(split_matches("text", ""), vec![("text", false)]);
+        assert!(contains_ignore_case("Hello World", "o w"));
+    }
+
+    #[test]
+    fn test_find_keyword() {
+        let keywords = vec!["Andersson".to_string(), "phoenix".to_string()];
+        assert_eq!(find_keyword("ping andersson please", &keywords), Some("Andersson"));
+        assert_eq!(find_keyword("Project PHOENIX is live", &keywords), Some("phoenix"));
+        assert_eq!(find_keyword("phoenixes everywhere", &keywords), None);
+        assert_eq!(find_keyword("nothing here", &keywords), None);
+        assert_eq!(find_keyword("anything", &[String::new()]), None);
+    }
+
+    #[test]
+    fn test_split_message() {
+        let text = format!("{}\n\n{}", "a ".repeat(20).trim(), "b".repeat(30));
+        let parts = split_message(&text, 50);
+        assert_eq!(parts, vec!["a ".repeat(20).trim().to_string(), "b".repeat(30)]);
+
+        // A cut code block is closed and reopened; code indentation is kept
+        let code = format!("intro\n```\n{}```", "    line\n".repeat(10));
+        let parts = split_message(&code, 60);
+        assert!(parts.len() > 1);
+        assert!(parts.iter().all(|p| p.chars().count() <= 60 && p.matches("```").count() % 2 == 0), "{:?}", parts);
+        assert!(parts[1].starts_with("```\n    line"));
+
+        // No break at all: hard cut on a char boundary
+        let parts = split_message(&"é".repeat(25), 10);
+        assert!(parts.iter().all(|p| p.chars().count() <= 10));
+        assert_eq!(parts.concat(), "é".repeat(25));
+    }
 }
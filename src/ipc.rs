@@ -0,0 +1,159 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, oneshot};
+
+/// Loopback port used where Unix domain sockets are unavailable
+#[cfg(not(unix))]
+pub const TCP_PORT: u16 = 47113;
+
+/// Commands accepted on the control socket, one JSON object per line:
+/// `{"cmd": "send", "channel": "#general", "text": "hi"}`,
+/// `{"cmd": "open-channel", "channel": "general"}`, `{"cmd": "notify-state"}`.
+/// Each line is answered with one JSON line.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "cmd", rename_all = "kebab-case")]
+pub enum IpcCommand {
+    Send {
+        channel: String,
+        text: String,
+        #[serde(default)]
+        thread_ts: Option<String>,
+    },
+    OpenChannel {
+        channel: String,
+    },
+    NotifyState,
+}
+
+/// A parsed command waiting for the main loop, plus where to send its JSON reply
+pub struct IpcRequest {
+    pub command: IpcCommand,
+    pub reply: oneshot::Sender<serde_json::Value>,
+}
+
+pub fn socket_path(config_dir: &Path) -> PathBuf {
+    config_dir.join("control.sock")
+}
+
+/// Start listening in the background. Requests are delivered through the returned receiver.
+#[cfg(unix)]
+pub fn start(config_dir: &Path) -> std::io::Result<mpsc::UnboundedReceiver<IpcRequest>> {
+    let path = socket_path(config_dir);
+    // Another instance is still listening: leave its socket alone
+    if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AddrInUse,
+            format!("{} is in use by another instance", path.display()),
+        ));
+    }
+    // A socket left behind by a crashed instance would make bind fail
+    let _ = std::fs::remove_file(&path);
+    // Created owner-only from the start rather than chmod-ed after bind
+    // SAFETY: umask only swaps the process file mode mask
+    let old_mask = unsafe { libc::umask(0o177) };
+    let listener = tokio::net::UnixListener::bind(&path);
+    unsafe { libc::umask(old_mask) };
+    let listener = listener?;
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_connection(stream, tx.clone()));
+        }
+    });
+    Ok(rx)
+}
+
+/// Start listening in the background. Requests are delivered through the returned receiver.
+#[cfg(not(unix))]
+pub fn start(_config_dir: &Path) -> std::io::Result<mpsc::UnboundedReceiver<IpcRequest>> {
+    let std_listener = std::net::TcpListener::bind(("127.0.0.1", TCP_PORT))?;
+    std_listener.set_nonblocking(true)?;
+    let listener = tokio::net::TcpListener::from_std(std_listener)?;
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_connection(stream, tx.clone()));
+        }
+    });
+    Ok(rx)
+}
+
+/// Remove the socket file on exit (only call it after a successful `start`).
+pub fn cleanup(config_dir: &Path) {
+    #[cfg(unix)]
+    let _ = std::fs::remove_file(socket_path(config_dir));
+    #[cfg(not(unix))]
+    let _ = config_dir;
+}
+
+async fn serve_connection<S>(stream: S, tx: mpsc::UnboundedSender<IpcRequest>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<IpcCommand>(&line) {
+            Ok(command) => {
+                let (reply_tx, reply_rx) = oneshot::channel();
+                if tx.send(IpcRequest { command, reply: reply_tx }).is_err() {
+                    break; // App is shutting down
+                }
+                reply_rx
+                    .await
+                    .unwrap_or_else(|_| error_reply("request dropped"))
+            }
+            Err(e) => error_reply(&format!("invalid command: {}", e)),
+        };
+
+        let mut out = response.to_string();
+        out.push('\n');
+        if writer.write_all(out.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+pub fn error_reply(message: &str) -> serde_json::Value {
+    serde_json::json!({ "ok": false, "error": message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_connection_round_trip() {
+        let (client, server) = tokio::io::duplex(1024);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tokio::spawn(serve_connection(server, tx));
+
+        // Answer requests like the main loop would
+        tokio::spawn(async move {
+            while let Some(request) = rx.recv().await {
+                let ok = matches!(request.command, IpcCommand::OpenChannel { ref channel } if channel == "general");
+                let _ = request.reply.send(serde_json::json!({ "ok": ok }));
+            }
+        });
+
+        let (reader, mut writer) = tokio::io::split(client);
+        let mut lines = BufReader::new(reader).lines();
+
+        writer
+            .write_all(b"{\"cmd\": \"open-channel\", \"channel\": \"general\"}\n{\"cmd\": \"bogus\"}\n")
+            .await
+            .unwrap();
+
+        let first: serde_json::Value = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(first["ok"], true);
+        let second: serde_json::Value = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(second["ok"], false);
+    }
+}
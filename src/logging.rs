@@ -0,0 +1,67 @@
+use anyhow::{anyhow, Result};
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload, Registry};
+
+use crate::config::LoggingConfig;
+
+/// Runtime control over the log level (used by `/debug on|off`)
+pub struct LogHandle {
+    reload: reload::Handle<Targets, Registry>,
+    level: LevelFilter,
+    pub path: PathBuf,
+}
+
+impl LogHandle {
+    pub fn level(&self) -> LevelFilter {
+        self.level
+    }
+
+    pub fn set_level(&mut self, level: LevelFilter) -> Result<()> {
+        self.reload
+            .reload(targets(level))
+            .map_err(|e| anyhow!("Failed to change log level: {}", e))?;
+        self.level = level;
+        Ok(())
+    }
+}
+
+/// Install the global subscriber. Only this crate's events are written, to
+/// `logging.file` (default: `slack_rust.log` in the config directory).
+pub fn init(logging: &LoggingConfig, config_dir: &Path) -> Result<LogHandle> {
+    let level = parse_level(&logging.level)?;
+    let path = logging
+        .file
+        .clone()
+        .unwrap_or_else(|| config_dir.join("slack_rust.log"));
+
+    // The file is opened per event so nothing is created while logging is off
+    let file_path = path.clone();
+    let writer = move || -> Box<dyn std::io::Write> {
+        match OpenOptions::new().create(true).append(true).open(&file_path) {
+            Ok(file) => Box::new(file),
+            Err(_) => Box::new(std::io::sink()),
+        }
+    };
+
+    let (filter, reload) = reload::Layer::new(targets(level));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(writer).with_ansi(false).with_target(false))
+        .try_init()?;
+
+    Ok(LogHandle { reload, level, path })
+}
+
+pub fn parse_level(level: &str) -> Result<LevelFilter> {
+    level
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("Invalid log level '{}' (use off, error, warn, info, debug or trace)", level))
+}
+
+fn targets(level: LevelFilter) -> Targets {
+    Targets::new().with_target(env!("CARGO_CRATE_NAME"), level)
+}
@@ -1,103 +1,238 @@
 use anyhow::Result;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{
+        EnableBracketedPaste, EnableFocusChange, EnableMouseCapture, Event, EventStream, KeyCode,
+        KeyModifiers,
+    },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{enable_raw_mode, EnterAlternateScreen},
 };
+use futures::{FutureExt, StreamExt};
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
+use std::panic::AssertUnwindSafe;
+use std::time::Duration;
 
 mod app;
+mod cli;
 mod commands;
 mod config;
+mod diagnostics;
+mod doctor;
 mod formatting;
+mod ipc;
+mod logging;
+mod notifications;
 mod persistence;
+mod scripting;
+mod search;
+mod setup;
 mod slack;
 mod split_view;
 mod utils;
 mod widgets;
 
-use app::App;
+use app::{ApiEvent, App};
+use clap::Parser;
+use cli::Cli;
+use config::Config;
+use ipc::IpcRequest;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Create app BEFORE entering TUI mode (so authentication can work)
-    let mut app = App::new().await?;
-    
-    // Load chat history for saved panes
-    let _ = app.load_all_pane_histories().await;
+    let cli = Cli::parse();
+
+    // Load config BEFORE entering TUI mode (so authentication can work)
+    let mut config = Config::load(cli.config)?;
+    let log_handle = match logging::init(&config.logging, &config.config_dir) {
+        Ok(handle) => Some(handle),
+        Err(e) => {
+            eprintln!("Warning: Could not set up logging: {}", e);
+            None
+        }
+    };
+
+    // Non-interactive subcommands reuse the config and client, then exit
+    if let Some(command) = cli.command {
+        return cli::run(command, &mut config).await;
+    }
+
+    // First run: ask for a workspace before connecting
+    if config.workspaces.is_empty() {
+        setup::first_run(&mut config).await?;
+    }
+
+    let mut app = App::new(config).await?;
+    app.read_only = cli.read_only;
+    app.log_handle = log_handle;
+
+    // Control socket for external scripts (window manager bars etc.)
+    if !cli.no_ipc {
+        match ipc::start(&app.config.config_dir) {
+            Ok(rx) => app.ipc_requests = Some(rx),
+            Err(e) => eprintln!("Warning: Could not start control socket: {}", e),
+        }
+    }
 
     // Setup terminal
+    install_panic_hook();
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste,
+        EnableFocusChange
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
     terminal.hide_cursor()?; // Cursor shown only when input is focused
+    utils::save_terminal_title();
 
-    // Run app
-    let _res = run_app(&mut terminal, &mut app).await;
+    // Run app; a panic in the UI loop is caught here so the state still gets saved
+    let result = AssertUnwindSafe(run_app(&mut terminal, &mut app)).catch_unwind().await;
 
     // Save state before exiting (even if there was an error)
     let _ = app.save_state();
+    let _ = app.save_cache();
     
     // Shutdown WebSocket connection
     app.slack.shutdown().await;
+    if app.ipc_requests.is_some() {
+        ipc::cleanup(&app.config.config_dir);
+    }
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    utils::restore_terminal();
+    if let Err(panic) = result {
+        std::panic::resume_unwind(panic);
+    }
 
     Ok(())
 }
 
+/// Restore the terminal before a panic message is printed, so it's readable and the
+/// shell isn't left in raw mode. Panics in background tasks don't end the app (tokio
+/// catches them), so those are only logged and the screen is left alone.
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().name() == Some("main") {
+            utils::restore_terminal();
+            default_hook(info);
+        } else {
+            tracing::error!("Background task panicked: {}", info);
+        }
+    }));
+}
+
+/// Resolves on SIGINT, SIGTERM or SIGHUP (terminal closed), so the app can exit
+/// through the normal path: save state, close the socket, restore the terminal.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match (signal(SignalKind::terminate()), signal(SignalKind::hangup())) {
+            (Ok(mut term), Ok(mut hup)) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                    _ = hup.recv() => {}
+                }
+            }
+            _ => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// How long the loop sleeps without input or Slack updates; clocks, expiring
+/// messages and periodic work (fallback refresh, auto-away) are checked this often
+const IDLE_TICK: Duration = Duration::from_secs(1);
+/// Background task results (workspace switch, downloads...) are polled at this rate
+const BUSY_TICK: Duration = Duration::from_millis(100);
+
+/// What ended the event loop's wait
+enum Wake {
+    Input(Event),
+    Api(ApiEvent),   // Result of a spawned Slack call
+    Ipc(IpcRequest), // Control socket request
+    Tick,            // Timer or queued Slack updates; handled at the top of the loop
+}
+
 async fn run_app<B: ratatui::backend::Backend + std::io::Write>(
     terminal: &mut Terminal<B>,
     app: &mut App,
 ) -> Result<()> {
+    let mut events = EventStream::new();
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
     loop {
         // Ensure pane indices are valid (workspace switch may have changed pane count)
         app.ensure_valid_pane_idx();
 
         // Process Slack events
         app.process_slack_events().await?;
-        app.maybe_run_fallback_refresh().await?;
+        app.poll_connection_state();
+        app.maybe_run_fallback_refresh();
+        app.maybe_go_online();
+        app.maybe_refresh_tokens();
+        app.maybe_prompt_reauth();
+        app.maybe_auto_away().await;
+        app.flush_notifications();
 
-        // Poll for workspace switch completion
+        // Poll for the background connect (startup, workspace switch, going online)
         if app.poll_workspace_switch() {
-            let _ = app.load_all_pane_histories().await;
             app.needs_redraw = true;
         }
 
-        // Handle pending chat refresh (from workspace switch)
+        // Poll for the cross-workspace unreads fetch
+        if app.poll_unreads() {
+            app.needs_redraw = true;
+        }
+
+        if app.poll_mark_all_read() {
+            app.needs_redraw = true;
+        }
+
+        if app.poll_downloads() {
+            app.needs_redraw = true;
+        }
+
+        // Handle pending chat refresh (after connecting)
         if app.pending_refresh_chats {
             app.pending_refresh_chats = false;
-            let _ = app.refresh_chats().await;
+            app.request_chats();
+            app.refresh_dnd().await;
             app.needs_redraw = true;
         }
         
-        // Handle pending pane reload (from workspace switch)
+        // Handle pending pane reload (after connecting)
         if app.pending_reload_panes {
             app.pending_reload_panes = false;
-            let _ = app.reload_pane_contents().await;
+            app.reload_pane_contents();
+            app.needs_redraw = true;
+        }
+
+        // Handle pending thread open (click on "[N replies]")
+        if let Some(msg_idx) = app.pending_open_thread.take() {
+            app.open_message_thread(msg_idx).await?;
             app.needs_redraw = true;
         }
 
         // Handle pending chat open (from mouse click)
         if app.pending_open_chat {
             app.pending_open_chat = false;
-            app.open_selected_chat().await?;
+            app.open_selected_chat();
             app.needs_redraw = true;
         }
 
         // Check expiry timers
         let now = std::time::Instant::now();
-        let mut next_wake = std::time::Duration::from_millis(50);
+        let mut next_wake = if app.has_background_work() { BUSY_TICK } else { IDLE_TICK };
 
         for pane in &mut app.panes {
             if let Some(expire) = pane.typing_expire {
@@ -133,15 +268,228 @@ async fn run_app<B: ratatui::backend::Backend + std::io::Write>(
 
         // Draw ONLY if something changed
         if app.needs_redraw {
+            app.update_terminal_title();
             terminal.draw(|f| app.draw(f))?;
             app.needs_redraw = false;
         }
 
-        if event::poll(next_wake)? {
-            let event = event::read()?;
+        // Sleep until input, a Slack update, a control socket request or the next timer
+        let wake = tokio::select! {
+            event = events.next() => match event {
+                Some(event) => Wake::Input(event?),
+                None => return Ok(()), // Input closed
+            },
+            _ = app.slack.updates_ready() => Wake::Tick,
+            Some(event) = app.api_rx.recv() => Wake::Api(event),
+            Some(request) = App::next_ipc_request(&mut app.ipc_requests) => Wake::Ipc(request),
+            _ = tokio::time::sleep(next_wake) => Wake::Tick,
+            _ = &mut shutdown => {
+                tracing::info!("Received a termination signal, exiting");
+                return Ok(());
+            }
+        };
+        let event = match wake {
+            Wake::Input(event) => Some(event),
+            Wake::Api(event) => {
+                app.handle_api_event(event);
+                None
+            }
+            Wake::Ipc(request) => {
+                app.handle_ipc_request(request).await;
+                None
+            }
+            Wake::Tick => None,
+        };
+
+        if let Some(event) = event {
             match event {
                 Event::Key(key) => {
+                    app.note_user_activity().await;
                     match key.code {
+                        // /workspace add|rename prompt: typing, Enter next step, Esc cancels
+                        KeyCode::Enter if app.workspace_prompt.is_some() => {
+                            app.workspace_prompt_submit().await;
+                        }
+                        KeyCode::Esc if app.workspace_prompt.is_some() => {
+                            app.cancel_workspace_prompt();
+                        }
+                        KeyCode::Backspace if app.workspace_prompt.is_some() => {
+                            app.workspace_prompt_key(None);
+                        }
+                        KeyCode::Char(c)
+                            if app.workspace_prompt.is_some()
+                                && !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                        {
+                            app.workspace_prompt_key(Some(c));
+                        }
+                        _ if app.workspace_prompt.is_some() => {}
+                        // Long paste prompt: y uploads a snippet, n pastes as text, Esc discards
+                        KeyCode::Char('y') if app.pending_paste.is_some() => {
+                            app.upload_pending_paste().await;
+                        }
+                        KeyCode::Char('n') if app.pending_paste.is_some() => {
+                            app.paste_as_text();
+                        }
+                        KeyCode::Esc if app.pending_paste.is_some() => {
+                            app.discard_paste();
+                        }
+                        _ if app.pending_paste.is_some() => {}
+                        // Message over Slack's limit: s splits it, u uploads a snippet, Esc goes back
+                        KeyCode::Char('s') if app.pending_long_message.is_some() => {
+                            app.send_long_message_split();
+                        }
+                        KeyCode::Char('u') if app.pending_long_message.is_some() => {
+                            app.upload_long_message().await;
+                        }
+                        KeyCode::Esc if app.pending_long_message.is_some() => {
+                            app.cancel_long_message();
+                        }
+                        _ if app.pending_long_message.is_some() => {}
+                        // Unknown command: y posts it to the conversation, n/Esc puts it back
+                        KeyCode::Char('y') if app.pending_slack_command.is_some() => {
+                            app.send_pending_slack_command();
+                        }
+                        KeyCode::Char('n') | KeyCode::Esc if app.pending_slack_command.is_some() => {
+                            app.cancel_slack_command();
+                        }
+                        _ if app.pending_slack_command.is_some() => {}
+                        // Copy mode: move the cursor, v selects, y copies, Esc/q leaves
+                        KeyCode::Up | KeyCode::Char('k') if app.copy_mode.is_some() => {
+                            app.copy_mode_move(Some(-1), Some(0));
+                        }
+                        KeyCode::Down | KeyCode::Char('j') if app.copy_mode.is_some() => {
+                            app.copy_mode_move(Some(1), Some(0));
+                        }
+                        KeyCode::Left | KeyCode::Char('h') if app.copy_mode.is_some() => {
+                            app.copy_mode_move(Some(0), Some(-1));
+                        }
+                        KeyCode::Right | KeyCode::Char('l') if app.copy_mode.is_some() => {
+                            app.copy_mode_move(Some(0), Some(1));
+                        }
+                        KeyCode::PageUp if app.copy_mode.is_some() => {
+                            app.copy_mode_move(Some(-10), Some(0));
+                        }
+                        KeyCode::PageDown if app.copy_mode.is_some() => {
+                            app.copy_mode_move(Some(10), Some(0));
+                        }
+                        KeyCode::Char('0') | KeyCode::Home if app.copy_mode.is_some() => {
+                            app.copy_mode_move(Some(0), Some(isize::MIN));
+                        }
+                        KeyCode::Char('$') | KeyCode::End if app.copy_mode.is_some() => {
+                            app.copy_mode_move(Some(0), None);
+                        }
+                        KeyCode::Char('g') if app.copy_mode.is_some() => {
+                            app.copy_mode_move(Some(isize::MIN), Some(0));
+                        }
+                        KeyCode::Char('G') if app.copy_mode.is_some() => {
+                            app.copy_mode_move(None, Some(0));
+                        }
+                        KeyCode::Char('v') | KeyCode::Char(' ') if app.copy_mode.is_some() => {
+                            app.copy_mode_toggle_selection();
+                        }
+                        KeyCode::Char('y') | KeyCode::Enter if app.copy_mode.is_some() => {
+                            app.copy_mode_yank();
+                        }
+                        KeyCode::Esc | KeyCode::Char('q') if app.copy_mode.is_some() => {
+                            app.exit_copy_mode();
+                        }
+                        // r: reply in the thread of the message under the cursor
+                        KeyCode::Char('r') if app.copy_mode.is_some() => {
+                            app.reply_to_selected();
+                        }
+                        // 1-9: quick reaction to the message under the cursor
+                        KeyCode::Char(c @ '1'..='9') if app.copy_mode.is_some() => {
+                            app.quick_react((c as u8 - b'1') as usize).await;
+                        }
+                        _ if app.copy_mode.is_some() => {}
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::ALT) => {
+                            app.enter_copy_mode();
+                        }
+                        // Alt+1-9: quick reaction to the latest message
+                        KeyCode::Char(c @ '1'..='9') if key.modifiers.contains(KeyModifiers::ALT) => {
+                            app.quick_react((c as u8 - b'1') as usize).await;
+                        }
+                        // Esc/Enter: Dismiss the info popup
+                        KeyCode::Esc | KeyCode::Enter if app.info_popup.is_some() => {
+                            app.info_popup = None;
+                            app.needs_redraw = true;
+                        }
+                        // /files browser: Up/Down select, Enter downloads and opens, Esc closes
+                        KeyCode::Up if app.file_browser.is_some() => {
+                            app.file_browser_move(-1);
+                        }
+                        KeyCode::Down if app.file_browser.is_some() => {
+                            app.file_browser_move(1);
+                        }
+                        KeyCode::Enter if app.file_browser.is_some() => {
+                            app.open_selected_file();
+                        }
+                        KeyCode::Esc if app.file_browser.is_some() => {
+                            app.file_browser = None;
+                            app.needs_redraw = true;
+                        }
+                        // /finduser list: Up/Down select, Enter opens a DM, Esc closes
+                        KeyCode::Up if app.user_picker.is_some() => {
+                            app.user_picker_move(-1);
+                        }
+                        KeyCode::Down if app.user_picker.is_some() => {
+                            app.user_picker_move(1);
+                        }
+                        KeyCode::Enter if app.user_picker.is_some() => {
+                            app.open_selected_user_dm().await?;
+                        }
+                        KeyCode::Esc if app.user_picker.is_some() => {
+                            app.user_picker = None;
+                            app.needs_redraw = true;
+                        }
+                        // @mention picker: Up/Down select, Enter/Tab insert, Esc closes;
+                        // typing keeps filtering
+                        KeyCode::Up if app.mention_picker_open() => {
+                            app.mention_picker_move(-1);
+                        }
+                        KeyCode::Down if app.mention_picker_open() => {
+                            app.mention_picker_move(1);
+                        }
+                        KeyCode::Enter | KeyCode::Tab if app.mention_picker_open() => {
+                            app.insert_selected_mention();
+                        }
+                        KeyCode::Esc if app.mention_picker_open() => {
+                            app.mention_picker = None;
+                            app.needs_redraw = true;
+                        }
+                        // Search mode: typing edits the query, Enter keeps the matches, Esc closes
+                        KeyCode::Char(c) if app.search_editing() && !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.search_input_char(c);
+                        }
+                        KeyCode::Backspace if app.search_editing() => {
+                            app.search_backspace();
+                        }
+                        KeyCode::Enter if app.search_editing() => {
+                            app.finish_search_input();
+                        }
+                        KeyCode::Esc if app.search_active() => {
+                            app.cancel_search();
+                        }
+                        // n/N: Older/newer search match
+                        KeyCode::Char(c @ ('n' | 'N'))
+                            if app.search_active() && !app.focus_on_chat_list && app.focused_input_is_empty() =>
+                        {
+                            app.search_step(c == 'n');
+                        }
+                        // Alt+B/Alt+I/Alt+E (configurable): *bold*, _italic_, `code` around the word
+                        KeyCode::Char(_) if app.format_key(&key) => {}
+                        // Ctrl+F: Search the focused pane ('/' alone does it in read-only mode,
+                        // where there is no composer to type commands into)
+                        KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.start_search();
+                        }
+                        KeyCode::Char('/') if app.read_only && !app.focus_on_chat_list => {
+                            app.start_search();
+                        }
+                        // Ctrl+A: Jump to the first unread message in the focused pane
+                        KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.jump_to_first_unread();
+                        }
                         // Ctrl+Q: Quit
                         KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                             app.save_state()?;
@@ -163,6 +511,21 @@ async fn run_app<B: ratatui::backend::Backend + std::io::Write>(
                         KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                             app.toggle_split_direction();
                         }
+                        // Alt+Left/Right: Swap focused pane with its neighbor
+                        KeyCode::Left if key.modifiers.contains(KeyModifiers::ALT) => {
+                            app.swap_pane(false);
+                        }
+                        KeyCode::Right if key.modifiers.contains(KeyModifiers::ALT) => {
+                            app.swap_pane(true);
+                        }
+                        // Alt+M: Move focused pane to the other end of its split
+                        KeyCode::Char('m') if key.modifiers.contains(KeyModifiers::ALT) => {
+                            app.move_pane_to_other_end();
+                        }
+                        // Ctrl+Z: Zoom the focused pane / restore the split layout
+                        KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.toggle_pane_zoom();
+                        }
                         // Ctrl+W: Close pane
                         KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                             app.close_pane();
@@ -203,6 +566,10 @@ async fn run_app<B: ratatui::backend::Backend + std::io::Write>(
                         KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                             app.toggle_borders();
                         }
+                        // Ctrl+P: Toggle read-only presentation mode
+                        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.toggle_read_only();
+                        }
                         // Ctrl+M: Toggle mouse support
                         KeyCode::Char('m') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                             app.toggle_mouse_support();
@@ -237,7 +604,7 @@ async fn run_app<B: ratatui::backend::Backend + std::io::Write>(
                         }
                         // Enter: Open chat (when focus on chat list)
                         KeyCode::Enter if app.focus_on_chat_list => {
-                            app.open_selected_chat().await?;
+                            app.open_selected_chat();
                         }
                         // Shift+Up/Down: Always scroll messages
                         KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => {
@@ -270,35 +637,32 @@ async fn run_app<B: ratatui::backend::Backend + std::io::Write>(
                             }
                         }
                         // Page Up/Down: Scroll faster
-                        KeyCode::PageUp => {
-                            if !app.focus_on_chat_list {
+                        KeyCode::PageUp
+                            if !app.focus_on_chat_list => {
                                 app.page_up();
                             }
-                        }
-                        KeyCode::PageDown => {
-                            if !app.focus_on_chat_list {
+                        KeyCode::PageDown
+                            if !app.focus_on_chat_list => {
                                 app.page_down();
                             }
-                        }
                         // Home/End: Move cursor to line start/end or jump to top/bottom (Ctrl)
-                        KeyCode::Home => {
-                            if !app.focus_on_chat_list {
+                        KeyCode::Home
+                            if !app.focus_on_chat_list => {
                                 if key.modifiers.contains(KeyModifiers::CONTROL) {
                                     app.scroll_to_top();
                                 } else {
                                     app.move_cursor_home();
                                 }
                             }
-                        }
-                        KeyCode::End => {
-                            if !app.focus_on_chat_list {
-                                if key.modifiers.contains(KeyModifiers::CONTROL) {
+                        KeyCode::End
+                            if !app.focus_on_chat_list => {
+                                // There is no cursor to move in an empty input, so End jumps to the latest message
+                                if key.modifiers.contains(KeyModifiers::CONTROL) || app.focused_input_is_empty() {
                                     app.scroll_to_bottom();
                                 } else {
                                     app.move_cursor_end();
                                 }
                             }
-                        }
                         // Backspace: Delete character
                         KeyCode::Backspace if !app.focus_on_chat_list => {
                             app.backspace();
@@ -331,13 +695,24 @@ async fn run_app<B: ratatui::backend::Backend + std::io::Write>(
                         continue;
                     }
                     
-                    use crossterm::event::MouseEventKind;
+                    use crossterm::event::{MouseButton, MouseEventKind};
                     match mouse_event.kind {
+                        MouseEventKind::Down(MouseButton::Left)
+                            if app.start_drag(mouse_event.column, mouse_event.row) => {}
                         MouseEventKind::Down(_) => {
                             app.handle_mouse_click(mouse_event.column, mouse_event.row);
                         }
+                        MouseEventKind::Drag(MouseButton::Left) => {
+                            app.drag_to(mouse_event.column, mouse_event.row);
+                        }
+                        MouseEventKind::Up(_) => {
+                            app.end_drag();
+                        }
+                        MouseEventKind::Moved => {
+                            app.hover(mouse_event.column, mouse_event.row);
+                        }
                         MouseEventKind::ScrollUp => {
-                            let in_chat_list = app.chat_list_area.map_or(false, |area| {
+                            let in_chat_list = app.chat_list_area.is_some_and(|area| {
                                 mouse_event.column >= area.x
                                     && mouse_event.column < area.x + area.width
                                     && mouse_event.row >= area.y
@@ -350,7 +725,7 @@ async fn run_app<B: ratatui::backend::Backend + std::io::Write>(
                             }
                         }
                         MouseEventKind::ScrollDown => {
-                            let in_chat_list = app.chat_list_area.map_or(false, |area| {
+                            let in_chat_list = app.chat_list_area.is_some_and(|area| {
                                 mouse_event.column >= area.x
                                     && mouse_event.column < area.x + area.width
                                     && mouse_event.row >= area.y
@@ -365,6 +740,11 @@ async fn run_app<B: ratatui::backend::Backend + std::io::Write>(
                         _ => {}
                     }
                 }
+                Event::Paste(text) => {
+                    app.paste_text(text);
+                }
+                Event::FocusGained => app.set_terminal_focused(true),
+                Event::FocusLost => app.set_terminal_focused(false),
                 _ => {}
             }
             app.needs_redraw = true;
@@ -0,0 +1,162 @@
+//! Desktop notification throttling. The first notification from a conversation goes
+//! out right away; more from it during the cooldown are held and sent as one summary
+//! when the cooldown ends ("10 new mentions in incident"), so a busy channel can't
+//! flood the desktop.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationKind {
+    Mention,
+    Keyword,
+    Reply, // In a followed thread
+}
+
+impl NotificationKind {
+    fn plural(self) -> &'static str {
+        match self {
+            NotificationKind::Mention => "mentions",
+            NotificationKind::Keyword => "keyword matches",
+            NotificationKind::Reply => "thread replies",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+}
+
+/// Notifications of one conversation since its last one was shown
+struct Held {
+    label: String, // Conversation name for the summary
+    kinds: Vec<NotificationKind>,
+    last: Option<Notification>,
+}
+
+pub struct Notifier {
+    cooldown: Duration, // Zero: every notification is shown
+    channels: HashMap<String, (Instant, Held)>, // channel ID -> (last shown, held since)
+}
+
+impl Notifier {
+    pub fn new(cooldown_secs: u64) -> Self {
+        Self {
+            cooldown: Duration::from_secs(cooldown_secs),
+            channels: HashMap::new(),
+        }
+    }
+
+    /// The notification to show now, or None if it is held for `flush`.
+    pub fn push(
+        &mut self,
+        channel_id: &str,
+        label: &str,
+        kind: NotificationKind,
+        notification: Notification,
+        now: Instant,
+    ) -> Option<Notification> {
+        if self.cooldown.is_zero() {
+            return Some(notification);
+        }
+        match self.channels.get_mut(channel_id) {
+            Some((shown, held)) if now.duration_since(*shown) < self.cooldown => {
+                held.label = label.to_string();
+                held.kinds.push(kind);
+                held.last = Some(notification);
+                None
+            }
+            _ => {
+                let held = Held {
+                    label: label.to_string(),
+                    kinds: Vec::new(),
+                    last: None,
+                };
+                self.channels.insert(channel_id.to_string(), (now, held));
+                Some(notification)
+            }
+        }
+    }
+
+    /// Held notifications of the conversations whose cooldown has ended: a single one
+    /// as it was, several as a summary. Showing a summary starts a new cooldown.
+    pub fn flush(&mut self, now: Instant) -> Vec<Notification> {
+        let cooldown = self.cooldown;
+        let mut ready = Vec::new();
+        self.channels.retain(|_, (shown, held)| {
+            if now.duration_since(*shown) < cooldown {
+                return true;
+            }
+            let Some(last) = held.last.take() else {
+                return false; // Quiet for a whole cooldown
+            };
+            let kinds = std::mem::take(&mut held.kinds);
+            ready.push(if kinds.len() == 1 {
+                last
+            } else {
+                let what = if kinds.iter().all(|k| *k == kinds[0]) {
+                    kinds[0].plural()
+                } else {
+                    "notifications"
+                };
+                Notification {
+                    title: format!("Slack: {}", held.label),
+                    body: format!("{} new {} in {}. Latest: {}", kinds.len(), what, held.label, last.body),
+                }
+            });
+            *shown = now;
+            true
+        });
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(body: &str) -> Notification {
+        Notification {
+            title: "Slack: incident".to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_notifier_batches_bursts() {
+        let mut notifier = Notifier::new(10);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mention = NotificationKind::Mention;
+
+        assert_eq!(notifier.push("C1", "incident", mention, note("a"), at(0)), Some(note("a")));
+        for (i, body) in ["b", "c", "d"].iter().enumerate() {
+            assert_eq!(notifier.push("C1", "incident", mention, note(body), at(i as u64 + 1)), None);
+        }
+        // Other conversations have their own cooldown
+        assert!(notifier.push("C2", "general", mention, note("x"), at(2)).is_some());
+        assert!(notifier.flush(at(5)).is_empty());
+
+        let ready = notifier.flush(at(10));
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].body, "3 new mentions in incident. Latest: d");
+
+        // A single held notification is shown as it was
+        assert_eq!(notifier.push("C1", "incident", NotificationKind::Reply, note("e"), at(12)), None);
+        assert_eq!(notifier.flush(at(20)), vec![note("e")]);
+        assert!(notifier.flush(at(40)).is_empty());
+        assert!(notifier.push("C1", "incident", mention, note("f"), at(41)).is_some());
+    }
+
+    #[test]
+    fn test_notifier_without_cooldown() {
+        let mut notifier = Notifier::new(0);
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(notifier.push("C1", "x", NotificationKind::Keyword, note("a"), now).is_some());
+        }
+        assert!(notifier.flush(now).is_empty());
+    }
+}
@@ -3,8 +3,12 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 
+use crate::app::ChatInfo;
 use crate::config::Config;
+use crate::formatting::{HighlightRule, TimestampStyle};
+use crate::widgets::AutoScroll;
 use crate::split_view::PaneNode;
+use crate::widgets::{FilterType, MessageData};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LayoutData {
@@ -20,12 +24,16 @@ pub struct PaneState {
     #[serde(default)]
     pub channel_id: Option<String>,
     pub chat_name: String,
+    #[serde(default)]
+    pub username: Option<String>,
     pub scroll_offset: usize,
     #[serde(default)]
-    pub filter_type: Option<String>,
+    pub filter_type: Option<FilterType>,
     #[serde(default)]
     pub filter_value: Option<String>,
     #[serde(default)]
+    pub hide_bots: bool,
+    #[serde(default)]
     pub thread_ts: Option<String>,
 }
 
@@ -36,9 +44,11 @@ impl LayoutData {
                 chat_id: None,
                 channel_id: None,
                 chat_name: "No chat selected".to_string(),
+                username: None,
                 scroll_offset: 0,
                 filter_type: None,
                 filter_value: None,
+                hide_bots: false,
                 thread_ts: None,
             }],
             focused_pane: 0,
@@ -109,6 +119,33 @@ impl Aliases {
     pub fn remove(&mut self, name: &str) -> Option<String> {
         self.map.remove(name)
     }
+
+    /// Replace every `<prefix><name>` word in `text` with the alias value.
+    /// Values are inserted as-is and not expanded again.
+    pub fn expand(&self, text: &str, prefix: &str) -> String {
+        if self.map.is_empty() {
+            return text.to_string();
+        }
+        let is_name_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            let at_word_start = out.chars().next_back().is_none_or(|p| !is_name_char(p));
+            if at_word_start {
+                if let Some(after) = rest.strip_prefix(prefix) {
+                    let end = after.find(|c: char| !is_name_char(c)).unwrap_or(after.len());
+                    if let Some(value) = (end > 0).then(|| self.map.get(&after[..end])).flatten() {
+                        out.push_str(value);
+                        rest = &after[end..];
+                        continue;
+                    }
+                }
+            }
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        out
+    }
 }
 
 impl Default for Aliases {
@@ -117,10 +154,383 @@ impl Default for Aliases {
     }
 }
 
+/// Canned responses: `;name` in the composer expands to the stored text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippets {
+    #[serde(flatten)]
+    pub map: HashMap<String, String>, // name -> text
+}
+
+impl Snippets {
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+
+    pub fn load(config: &Config) -> Result<Self> {
+        let path = config.snippets_path();
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            let snippets: Snippets = serde_json::from_str(&content)?;
+            Ok(snippets)
+        } else {
+            Ok(Self::new())
+        }
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let path = config.snippets_path();
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+impl Default for Snippets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Named Slack searches (`/searches`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SavedSearches {
+    #[serde(flatten)]
+    pub map: HashMap<String, String>, // name -> query
+}
+
+impl SavedSearches {
+    pub fn load(config: &Config) -> Result<Self> {
+        let path = config.saved_searches_path();
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            Ok(serde_json::from_str(&content)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(config.saved_searches_path(), content)?;
+        Ok(())
+    }
+}
+
+/// Local nicknames that replace Slack display names (`/nick`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Nicknames {
+    #[serde(flatten)]
+    pub map: HashMap<String, String>, // user_id -> nickname
+}
+
+impl Nicknames {
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+
+    pub fn load(config: &Config) -> Result<Self> {
+        let path = config.nicknames_path();
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            let nicknames: Nicknames = serde_json::from_str(&content)?;
+            Ok(nicknames)
+        } else {
+            Ok(Self::new())
+        }
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let path = config.nicknames_path();
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+impl Default for Nicknames {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Channels starred with `/fav`, listed in the sidebar's "Starred" section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StarredChannels {
+    #[serde(default)]
+    pub channels: Vec<String>, // Channel IDs
+}
+
+impl StarredChannels {
+    pub fn load(config: &Config) -> Result<Self> {
+        let path = config.starred_path();
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            Ok(serde_json::from_str(&content)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let path = config.starred_path();
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn contains(&self, channel_id: &str) -> bool {
+        self.channels.iter().any(|c| c == channel_id)
+    }
+}
+
+/// Conversations opened or written to, newest first, for the sidebar's "Recent" section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentChats {
+    #[serde(default)]
+    pub channels: Vec<String>, // Channel IDs
+}
+
+impl RecentChats {
+    /// More than the sidebar shows, so chats that left the list don't shrink the section
+    const KEPT: usize = 50;
+
+    pub fn load(config: &Config) -> Result<Self> {
+        let path = config.recent_chats_path();
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            Ok(serde_json::from_str(&content)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(config.recent_chats_path(), content)?;
+        Ok(())
+    }
+
+    /// Move `channel_id` to the front.
+    pub fn touch(&mut self, channel_id: &str) {
+        self.channels.retain(|c| c != channel_id);
+        self.channels.insert(0, channel_id.to_string());
+        self.channels.truncate(Self::KEPT);
+    }
+}
+
+/// Chat order set with `/move`, per workspace. Listed chats come first in their sidebar
+/// section, in this order; the others follow in the usual order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SidebarOrder {
+    #[serde(default)]
+    pub channels: Vec<String>, // Channel IDs
+}
+
+impl SidebarOrder {
+    pub fn load(config: &Config) -> Result<Self> {
+        let path = config.sidebar_order_path();
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            Ok(serde_json::from_str(&content)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(config.sidebar_order_path(), content)?;
+        Ok(())
+    }
+
+    /// Sort one section's chat indices: listed chats first, the rest keep their order.
+    pub fn sort(&self, chat_idxs: &mut [usize], chats: &[ChatInfo]) {
+        chat_idxs.sort_by_key(|&i| {
+            self.channels
+                .iter()
+                .position(|c| *c == chats[i].id)
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    /// Pin the chats of one section (`channel_ids`) in the given order.
+    pub fn arrange(&mut self, channel_ids: &[String]) {
+        self.channels.retain(|c| !channel_ids.contains(c));
+        self.channels.extend(channel_ids.iter().cloned());
+    }
+}
+
+/// Unsent composer text of conversations not open in a pane, per workspace, newest
+/// first. A draft is stashed when its pane switches to another conversation and put
+/// back in the composer when the conversation is opened again (`/drafts`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Drafts {
+    #[serde(default)]
+    pub drafts: Vec<Draft>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Draft {
+    pub channel_id: String,
+    pub text: String,
+}
+
+impl Drafts {
+    pub fn load(config: &Config) -> Result<Self> {
+        let path = config.drafts_path();
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            Ok(serde_json::from_str(&content)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(config.drafts_path(), content)?;
+        Ok(())
+    }
+
+    /// Keep `text` as the conversation's draft; blank text removes it.
+    pub fn set(&mut self, channel_id: &str, text: &str) {
+        self.drafts.retain(|d| d.channel_id != channel_id);
+        if !text.trim().is_empty() {
+            self.drafts.insert(
+                0,
+                Draft {
+                    channel_id: channel_id.to_string(),
+                    text: text.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Remove and return the conversation's draft.
+    pub fn take(&mut self, channel_id: &str) -> Option<String> {
+        let pos = self.drafts.iter().position(|d| d.channel_id == channel_id)?;
+        Some(self.drafts.remove(pos).text)
+    }
+}
+
+/// Last-known sidebar and user names of a workspace, shown at startup until Slack answers.
+/// Also what offline mode shows: the open panes' last messages and the unsent outbox.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionCache {
+    #[serde(default)]
+    pub chats: Vec<ChatInfo>,
+    #[serde(default)]
+    pub user_names: HashMap<String, String>,
+    #[serde(default)]
+    pub my_user_id: String,
+    /// Members of each group DM, which make up its name
+    #[serde(default)]
+    pub group_members: HashMap<String, Vec<String>>,
+    /// Newest messages of each open pane, keyed by `SessionCache::key`
+    #[serde(default)]
+    pub messages: HashMap<String, Vec<MessageData>>,
+    #[serde(default)]
+    pub outbox: Vec<OutboxMessage>,
+}
+
+/// A message written while offline, sent once Slack is reachable again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxMessage {
+    pub channel_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_ts: Option<String>,
+    pub text: String, // Mentions already converted to <@U...>
+    #[serde(default)]
+    pub broadcast: bool,
+    #[serde(skip)]
+    pub local_echo_id: Option<u64>, // Its local echo in a pane, this session
+}
+
+impl SessionCache {
+    /// Key of a channel's (or thread's) messages in `messages`
+    pub fn key(channel_id: &str, thread_ts: Option<&str>) -> String {
+        match thread_ts {
+            Some(ts) => format!("{}/{}", channel_id, ts),
+            None => channel_id.to_string(),
+        }
+    }
+
+    /// A missing or unreadable cache is simply empty.
+    pub fn load(config: &Config) -> Self {
+        fs::read_to_string(config.cache_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let content = serde_json::to_string(self)?;
+        fs::write(config.cache_path(), content)?;
+        Ok(())
+    }
+}
+
+/// Threads followed with `/follow`; new replies notify even when no pane shows them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FollowedThreads {
+    #[serde(default)]
+    pub threads: Vec<FollowedThread>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowedThread {
+    pub channel_id: String,
+    pub thread_ts: String,
+    /// Pane title when followed ("Thread: alice"), used in notifications
+    #[serde(default)]
+    pub label: String,
+}
+
+impl FollowedThreads {
+    pub fn load(config: &Config) -> Result<Self> {
+        let path = config.followed_threads_path();
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            Ok(serde_json::from_str(&content)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let path = config.followed_threads_path();
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn find(&self, channel_id: &str, thread_ts: &str) -> Option<&FollowedThread> {
+        self.threads
+            .iter()
+            .find(|t| t.channel_id == channel_id && t.thread_ts == thread_ts)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppState {
     pub settings: AppSettings,
     pub aliases: Aliases,
+    pub snippets: Snippets,
+    #[serde(default)]
+    pub saved_searches: SavedSearches,
+    pub nicknames: Nicknames,
+    pub followed_threads: FollowedThreads,
+    pub starred: StarredChannels,
+    #[serde(default)]
+    pub recent: RecentChats,
+    #[serde(default)]
+    pub sidebar_order: SidebarOrder,
+    #[serde(default)]
+    pub drafts: Drafts,
     pub layout: LayoutData,
 }
 
@@ -155,6 +565,98 @@ pub struct AppSettings {
 
     #[serde(default = "default_true")]
     pub mouse_support: bool,
+
+    /// Minutes without keyboard input before presence is set to away (0 = off)
+    #[serde(default)]
+    pub auto_away_minutes: u64,
+
+    /// Words that highlight a message like a direct mention (case-insensitive)
+    #[serde(default)]
+    pub highlight_keywords: Vec<String>,
+
+    /// Message text matching a rule's regex is drawn in its style, e.g. CI failures in red
+    #[serde(default)]
+    pub highlight_rules: Vec<HighlightRule>,
+
+    #[serde(default = "default_true")]
+    pub notify_on_keywords: bool,
+
+    /// Seconds after a notification during which more from the same conversation are
+    /// collected into one summary (0 = notify for every message)
+    #[serde(default = "default_notification_cooldown_secs")]
+    pub notification_cooldown_secs: u64,
+
+    /// Show join/leave/topic notices as dimmed lines (toggle with `/system`)
+    #[serde(default = "default_true")]
+    pub show_system_messages: bool,
+    /// Marks an alias in outgoing messages, e.g. `$brb` (empty = bare words)
+    #[serde(default = "default_alias_prefix")]
+    pub alias_prefix: String,
+    /// absolute (uses `timestamp_format`), full, or relative
+    #[serde(default)]
+    pub timestamp_style: TimestampStyle,
+
+    /// Follow new messages always, only when at the bottom (at_bottom), or never
+    #[serde(default)]
+    pub auto_scroll: AutoScroll,
+
+    /// strftime format for absolute timestamps
+    #[serde(default = "default_timestamp_format")]
+    pub timestamp_format: String,
+
+    /// Pastes longer than this many lines offer a snippet upload (0 = never)
+    #[serde(default = "default_paste_snippet_lines")]
+    pub paste_snippet_lines: usize,
+
+    /// Conversations listed in the sidebar's "Recent" section (0 = hide it)
+    #[serde(default = "default_recent_chats")]
+    pub recent_chats: usize,
+
+    /// Local time range without desktop notifications, e.g. "22:00-07:00" (empty = off)
+    #[serde(default)]
+    pub quiet_hours: String,
+
+    /// Also star channels on Slack (stars.add / stars.remove) when using /fav
+    #[serde(default)]
+    pub sync_stars: bool,
+
+    /// Sidebar width in columns, set by dragging its border (0 = fit the names)
+    #[serde(default)]
+    pub sidebar_width: u16,
+
+    /// Screen-reader friendly output: no borders or color-only cues, no ticking
+    /// status text, and new messages announced in the status bar (`/screenreader`)
+    #[serde(default)]
+    pub screen_reader: bool,
+
+    /// Show the text of `<URL|text>` links instead of the URL (`/linklabels`)
+    #[serde(default)]
+    pub show_link_labels: bool,
+
+    /// Show messages of only 1-3 emoji larger, spaced out on their own lines
+    #[serde(default = "default_true")]
+    pub jumbo_emoji: bool,
+
+    /// Emoji names for Alt+1..Alt+9 (and 1-9 in copy mode), in key order
+    #[serde(default = "default_quick_reactions")]
+    pub quick_reactions: Vec<String>,
+
+    /// Show the unread and mention counts in the terminal window title
+    #[serde(default = "default_true")]
+    pub terminal_title: bool,
+
+    /// Also notify through the terminal (OSC 9), e.g. over SSH or in tmux
+    #[serde(default)]
+    pub osc_notifications: bool,
+
+    /// Composer keys that wrap the word at the cursor in `*bold*`, `_italic_`
+    /// and `` `code` ``, e.g. "alt+b" or "ctrl+shift+c"
+    #[serde(default = "default_bold_key")]
+    pub bold_key: String,
+    #[serde(default = "default_italic_key")]
+    pub italic_key: String,
+    #[serde(default = "default_code_key")]
+    pub code_key: String,
 }
 
 impl Default for AppSettings {
@@ -170,6 +672,30 @@ impl Default for AppSettings {
             show_user_colors: true,
             show_borders: true,
             mouse_support: true,
+            auto_away_minutes: 0,
+            highlight_keywords: Vec::new(),
+            highlight_rules: Vec::new(),
+            notify_on_keywords: true,
+            notification_cooldown_secs: default_notification_cooldown_secs(),
+            show_system_messages: true,
+            alias_prefix: default_alias_prefix(),
+            timestamp_style: TimestampStyle::default(),
+            auto_scroll: AutoScroll::default(),
+            timestamp_format: default_timestamp_format(),
+            paste_snippet_lines: default_paste_snippet_lines(),
+            recent_chats: default_recent_chats(),
+            quiet_hours: String::new(),
+            sync_stars: false,
+            sidebar_width: 0,
+            screen_reader: false,
+            show_link_labels: false,
+            jumbo_emoji: true,
+            quick_reactions: default_quick_reactions(),
+            terminal_title: true,
+            osc_notifications: false,
+            bold_key: default_bold_key(),
+            italic_key: default_italic_key(),
+            code_key: default_code_key(),
         }
     }
 }
@@ -198,10 +724,49 @@ fn default_true() -> bool {
     true
 }
 
+fn default_alias_prefix() -> String {
+    "$".to_string()
+}
+
+fn default_timestamp_format() -> String {
+    "%H:%M".to_string()
+}
+
+fn default_paste_snippet_lines() -> usize {
+    10
+}
+
+fn default_recent_chats() -> usize {
+    5
+}
+
+fn default_notification_cooldown_secs() -> u64 {
+    10
+}
+
+pub fn default_quick_reactions() -> Vec<String> {
+    ["+1", "heart", "joy", "eyes", "tada", "white_check_mark", "pray", "fire", "raised_hands"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect()
+}
+
+fn default_bold_key() -> String {
+    "alt+b".to_string()
+}
+
+fn default_italic_key() -> String {
+    "alt+i".to_string()
+}
+
+fn default_code_key() -> String {
+    "alt+e".to_string()
+}
+
 impl AppState {
     pub fn load(config: &Config) -> Result<Self> {
         // Try to load settings from a separate file, fallback to config
-        let settings = AppSettings::load(config).unwrap_or_else(|_| AppSettings {
+        let settings = AppSettings::load(config).unwrap_or(AppSettings {
             show_reactions: config.settings.show_reactions,
             show_notifications: config.settings.show_notifications,
             compact_mode: config.settings.compact_mode,
@@ -212,11 +777,43 @@ impl AppState {
             show_user_colors: config.settings.show_user_colors,
             show_borders: config.settings.show_borders,
             mouse_support: config.settings.mouse_support,
+            auto_away_minutes: config.settings.auto_away_minutes,
+            highlight_keywords: config.settings.highlight_keywords.clone(),
+            highlight_rules: config.settings.highlight_rules.clone(),
+            notify_on_keywords: config.settings.notify_on_keywords,
+            notification_cooldown_secs: config.settings.notification_cooldown_secs,
+            show_system_messages: config.settings.show_system_messages,
+            alias_prefix: config.settings.alias_prefix.clone(),
+            timestamp_style: config.settings.timestamp_style,
+            auto_scroll: config.settings.auto_scroll,
+            timestamp_format: config.settings.timestamp_format.clone(),
+            paste_snippet_lines: config.settings.paste_snippet_lines,
+            recent_chats: config.settings.recent_chats,
+            quiet_hours: config.settings.quiet_hours.clone(),
+            sync_stars: config.settings.sync_stars,
+            sidebar_width: config.settings.sidebar_width,
+            screen_reader: config.settings.screen_reader,
+            show_link_labels: config.settings.show_link_labels,
+            jumbo_emoji: config.settings.jumbo_emoji,
+            quick_reactions: config.settings.quick_reactions.clone(),
+            terminal_title: config.settings.terminal_title,
+            osc_notifications: config.settings.osc_notifications,
+            bold_key: config.settings.bold_key.clone(),
+            italic_key: config.settings.italic_key.clone(),
+            code_key: config.settings.code_key.clone(),
         });
         
         Ok(Self {
             settings,
             aliases: Aliases::load(config)?,
+            snippets: Snippets::load(config)?,
+            saved_searches: SavedSearches::load(config)?,
+            nicknames: Nicknames::load(config)?,
+            followed_threads: FollowedThreads::load(config)?,
+            starred: StarredChannels::load(config)?,
+            recent: RecentChats::load(config)?,
+            sidebar_order: SidebarOrder::load(config)?,
+            drafts: Drafts::load(config)?,
             layout: LayoutData::load(config)?,
         })
     }
@@ -224,6 +821,14 @@ impl AppState {
     pub fn save(&self, config: &Config) -> Result<()> {
         self.settings.save(config)?;
         self.aliases.save(config)?;
+        self.snippets.save(config)?;
+        self.saved_searches.save(config)?;
+        self.nicknames.save(config)?;
+        self.followed_threads.save(config)?;
+        self.starred.save(config)?;
+        self.recent.save(config)?;
+        self.sidebar_order.save(config)?;
+        self.drafts.save(config)?;
         self.layout.save(config)?;
         Ok(())
     }
@@ -0,0 +1,187 @@
+//! Custom slash commands written in Rhai: `scripts/<name>.rhai` in the config
+//! directory becomes `/<name>`. Scripts see a snapshot of the focused pane and can
+//! only queue a few actions (status text, send, react) that the app carries out after
+//! the script returns, so they never hold the UI or the Slack client.
+
+use anyhow::{anyhow, Result};
+use rhai::{Array, Dynamic, Engine, Map, Scope};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Keeps a runaway loop from freezing the UI
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Something a script asked the app to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptAction {
+    Status(String),
+    /// Send to the focused pane (`channel` None) or a channel by ID or name
+    Send { channel: Option<String>, text: String },
+    /// React to a message by ts, or the latest one in the focused pane
+    React { ts: Option<String>, emoji: String },
+}
+
+/// What a script can read
+#[derive(Debug, Clone, Default)]
+pub struct ScriptContext {
+    pub args: Vec<String>,
+    pub workspace: String,
+    pub channel: String, // Focused pane's conversation name ("" if empty)
+    pub channel_id: String,
+    pub thread_ts: String, // "" outside thread panes
+    pub messages: Vec<(String, String, String)>, // (user, text, ts), oldest first
+}
+
+pub fn scripts_dir(config_dir: &Path) -> PathBuf {
+    config_dir.join("scripts")
+}
+
+/// Script file for `/name`, if there is one. Names are plain words so a command
+/// can't point outside the scripts directory.
+pub fn find_script(config_dir: &Path, name: &str) -> Option<PathBuf> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return None;
+    }
+    let path = scripts_dir(config_dir).join(format!("{}.rhai", name));
+    path.is_file().then_some(path)
+}
+
+/// Names of the available script commands, sorted
+pub fn list_scripts(config_dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(scripts_dir(config_dir)) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "rhai"))
+        .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Run a script file and return the actions it queued.
+pub fn run_script(path: &Path, context: &ScriptContext) -> Result<Vec<ScriptAction>> {
+    let source = std::fs::read_to_string(path)?;
+    run_source(&source, context)
+}
+
+fn run_source(source: &str, context: &ScriptContext) -> Result<Vec<ScriptAction>> {
+    let actions = Rc::new(RefCell::new(Vec::new()));
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_string_size(100_000);
+    engine.set_max_array_size(10_000);
+
+    let queue = actions.clone();
+    engine.register_fn("status", move |text: &str| {
+        queue.borrow_mut().push(ScriptAction::Status(text.to_string()));
+    });
+    let queue = actions.clone();
+    engine.on_print(move |text| {
+        queue.borrow_mut().push(ScriptAction::Status(text.to_string()));
+    });
+    let queue = actions.clone();
+    engine.register_fn("send", move |text: &str| {
+        queue.borrow_mut().push(ScriptAction::Send {
+            channel: None,
+            text: text.to_string(),
+        });
+    });
+    let queue = actions.clone();
+    engine.register_fn("send_to", move |channel: &str, text: &str| {
+        queue.borrow_mut().push(ScriptAction::Send {
+            channel: Some(channel.to_string()),
+            text: text.to_string(),
+        });
+    });
+    let queue = actions.clone();
+    engine.register_fn("react", move |emoji: &str| {
+        queue.borrow_mut().push(ScriptAction::React {
+            ts: None,
+            emoji: emoji.trim_matches(':').to_string(),
+        });
+    });
+    let queue = actions.clone();
+    engine.register_fn("react_to", move |ts: &str, emoji: &str| {
+        queue.borrow_mut().push(ScriptAction::React {
+            ts: Some(ts.to_string()),
+            emoji: emoji.trim_matches(':').to_string(),
+        });
+    });
+
+    let mut scope = Scope::new();
+    let args: Array = context.args.iter().cloned().map(Dynamic::from).collect();
+    scope.push_constant("args", args);
+    scope.push_constant("workspace", context.workspace.clone());
+
+    let mut pane = Map::new();
+    pane.insert("channel".into(), context.channel.clone().into());
+    pane.insert("channel_id".into(), context.channel_id.clone().into());
+    pane.insert("thread_ts".into(), context.thread_ts.clone().into());
+    let messages: Array = context
+        .messages
+        .iter()
+        .map(|(user, text, ts)| {
+            let mut msg = Map::new();
+            msg.insert("user".into(), user.clone().into());
+            msg.insert("text".into(), text.clone().into());
+            msg.insert("ts".into(), ts.clone().into());
+            Dynamic::from_map(msg)
+        })
+        .collect();
+    pane.insert("messages".into(), messages.into());
+    scope.push_constant("pane", pane);
+
+    engine
+        .run_with_scope(&mut scope, source)
+        .map_err(|e| anyhow!("{}", e))?;
+    drop(engine); // Releases the other references to `actions`
+    Ok(Rc::try_unwrap(actions)
+        .map(RefCell::into_inner)
+        .unwrap_or_else(|shared| shared.borrow().clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_reads_pane_and_queues_actions() {
+        let context = ScriptContext {
+            args: vec!["deploy".to_string()],
+            channel: "ops".to_string(),
+            messages: vec![("alice".to_string(), "ship it".to_string(), "1.2".to_string())],
+            ..Default::default()
+        };
+        let source = r##"
+            let last = pane.messages[pane.messages.len() - 1];
+            status(`${args[0]} in #${pane.channel}: ${last.user} said ${last.text}`);
+            send_to("#general", "done");
+            react_to(last.ts, ":tada:");
+        "##;
+        assert_eq!(
+            run_source(source, &context).unwrap(),
+            vec![
+                ScriptAction::Status("deploy in #ops: alice said ship it".to_string()),
+                ScriptAction::Send {
+                    channel: Some("#general".to_string()),
+                    text: "done".to_string(),
+                },
+                ScriptAction::React {
+                    ts: Some("1.2".to_string()),
+                    emoji: "tada".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_script_limits_and_names() {
+        let err = run_source("loop {}", &ScriptContext::default()).unwrap_err();
+        assert!(err.to_string().contains("operations"), "{}", err);
+        assert!(find_script(Path::new("/tmp"), "../etc/passwd").is_none());
+    }
+}
@@ -0,0 +1,129 @@
+//! Slack search syntax for `/search`. Queries are passed to search.messages as typed;
+//! the operators are also parsed here so messages can be matched locally, for the
+//! results pane and for tokens that can't use search.messages (bot tokens).
+
+use chrono::NaiveDate;
+
+use crate::app::parse_filter_date;
+
+/// Operators that make `/search` ask Slack instead of searching the focused pane
+const OPERATORS: &[&str] = &["from:", "in:", "before:", "after:", "on:", "during:", "has:", "is:", "to:", "with:"];
+
+/// True if `query` uses a Slack search operator such as `from:@ana` or `has:link`.
+pub fn has_operators(query: &str) -> bool {
+    query
+        .split_whitespace()
+        .any(|word| operator(word).is_some_and(|(_, value)| !value.is_empty()))
+}
+
+fn operator(word: &str) -> Option<(&'static str, &str)> {
+    let lower = word.to_lowercase();
+    let op = OPERATORS.iter().find(|op| lower.starts_with(*op))?;
+    Some((op, &word[op.len()..]))
+}
+
+/// A parsed query. Operators Slack knows but that can't be checked locally
+/// (`is:`, `to:`, `with:`, `during:`) are ignored when matching here.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    pub terms: Vec<String>,    // Words and "quoted phrases", lowercased
+    pub from: Vec<String>,     // Without the @, lowercased
+    pub channels: Vec<String>, // `in:`, without the #, lowercased
+    pub before: Option<NaiveDate>,
+    pub after: Option<NaiveDate>,
+    pub on: Option<NaiveDate>,
+    pub has_link: bool,
+    pub has_file: bool,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut parsed = Self::default();
+        for token in tokenize(query) {
+            let Some((op, value)) = operator(&token).filter(|(_, v)| !v.is_empty()) else {
+                parsed.terms.push(token.to_lowercase());
+                continue;
+            };
+            let value = value.trim_matches('"').to_lowercase();
+            match op {
+                "from:" => parsed.from.push(value.trim_start_matches('@').to_string()),
+                "in:" => parsed.channels.push(value.trim_start_matches(['#', '@']).to_string()),
+                "before:" => parsed.before = parse_filter_date(&value),
+                "after:" => parsed.after = parse_filter_date(&value),
+                "on:" => parsed.on = parse_filter_date(&value),
+                "has:" if value == "link" => parsed.has_link = true,
+                "has:" if value == "file" => parsed.has_file = true,
+                _ => {}
+            }
+        }
+        parsed
+    }
+
+    /// `channel` is the conversation's name, `text` the message as displayed (links as
+    /// URLs) and `date` its local date. Slack's before:/after: exclude the day itself.
+    pub fn matches(&self, channel: &str, sender: &str, text: &str, has_files: bool, date: Option<NaiveDate>) -> bool {
+        let text = text.to_lowercase();
+        let sender = sender.to_lowercase();
+        let channel = channel.trim_start_matches('#').to_lowercase();
+        let dated = |check: &dyn Fn(NaiveDate) -> bool| date.is_some_and(check);
+        self.terms.iter().all(|t| text.contains(t.as_str()))
+            && (self.from.is_empty() || self.from.iter().any(|f| sender.contains(f.as_str())))
+            && (self.channels.is_empty() || self.channels.contains(&channel))
+            && self.before.is_none_or(|b| dated(&|d| d < b))
+            && self.after.is_none_or(|a| dated(&|d| d > a))
+            && self.on.is_none_or(|o| dated(&|d| d == o))
+            && (!self.has_link || text.contains("http://") || text.contains("https://"))
+            && (!self.has_file || has_files)
+    }
+}
+
+/// Split on whitespace, keeping "quoted phrases" (and `op:"quoted values"`) together.
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_search_query() {
+        let query = SearchQuery::parse(r#"deploy "went wrong" from:@Ana in:#ops after:2024-05-01 has:link is:thread"#);
+        assert_eq!(query.terms, vec!["deploy", "went wrong"]);
+        assert_eq!(query.from, vec!["ana"]);
+        assert_eq!(query.channels, vec!["ops"]);
+        assert_eq!(query.after, NaiveDate::from_ymd_opt(2024, 5, 1));
+        assert!(query.has_link && !query.has_file);
+        assert!(has_operators("in:#ops"));
+        assert!(!has_operators("plain words in: the text"));
+    }
+
+    #[test]
+    fn test_search_query_matches() {
+        let query = SearchQuery::parse("deploy from:@ana in:#ops before:2024-05-02");
+        let day = NaiveDate::from_ymd_opt(2024, 5, 1);
+        assert!(query.matches("ops", "Ana Lima", "Deploy done", false, day));
+        assert!(!query.matches("general", "Ana Lima", "Deploy done", false, day));
+        assert!(!query.matches("ops", "Bo", "Deploy done", false, day));
+        assert!(!query.matches("ops", "Ana", "Deploy done", false, NaiveDate::from_ymd_opt(2024, 5, 2)));
+        assert!(!query.matches("ops", "Ana", "Deploy done", false, None));
+        assert!(SearchQuery::parse("has:file").matches("x", "y", "z", true, None));
+    }
+}
@@ -0,0 +1,147 @@
+//! First-run setup: ask for the first workspace inside the TUI before the client starts.
+
+use anyhow::{anyhow, Result};
+use crossterm::{
+    event::{DisableBracketedPaste, EnableBracketedPaste, Event, EventStream, KeyCode, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use futures::StreamExt;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+    Terminal,
+};
+use std::io::{self, IsTerminal};
+
+use crate::app::App;
+use crate::config::{Config, Workspace};
+use crate::slack::SlackClient;
+use crate::widgets::{WorkspacePrompt, WorkspaceStep};
+
+const INTRO: [&str; 4] = [
+    "Welcome! Set up your first Slack workspace.",
+    "Create an app at https://api.slack.com/apps (see the README for the scopes) and copy:",
+    "  1. the User OAuth Token (xoxp-...) or Bot User OAuth Token (xoxb-...)",
+    "  2. an App-Level Token for Socket Mode (xapp-...), optional",
+];
+
+/// Ask for a workspace name and tokens, check the token with Slack and save the config.
+pub async fn first_run(config: &mut Config) -> Result<()> {
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "No workspace in {}; set SLACK_TOKEN and SLACK_APP_TOKEN or run in a terminal",
+            config.config_file.display()
+        ));
+    }
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let result = run_wizard(&mut terminal, config).await;
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableBracketedPaste)?;
+    terminal.show_cursor()?;
+    result
+}
+
+async fn run_wizard<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    config: &mut Config,
+) -> Result<()> {
+    let mut prompt = WorkspacePrompt::new(WorkspaceStep::Name, String::new());
+    let mut events = EventStream::new();
+
+    loop {
+        terminal.draw(|f| draw(f, &prompt))?;
+
+        let Some(event) = events.next().await else {
+            return Err(anyhow!("Setup cancelled"));
+        };
+        match event? {
+            Event::Key(key) => match key.code {
+                KeyCode::Esc => return Err(anyhow!("Setup cancelled")),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Err(anyhow!("Setup cancelled"));
+                }
+                KeyCode::Backspace => {
+                    prompt.input.pop();
+                }
+                KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                    prompt.input.push(c);
+                }
+                KeyCode::Enter => {
+                    if let Some(error) = prompt.invalid_answer(&[]) {
+                        prompt.error = Some(error);
+                        continue;
+                    }
+                    prompt.error = None;
+                    let input = prompt.input.trim().to_string();
+                    match prompt.step {
+                        WorkspaceStep::Name => {
+                            prompt.name = input;
+                            prompt.input.clear();
+                            prompt.step = WorkspaceStep::Token;
+                        }
+                        WorkspaceStep::Token => {
+                            prompt.error = Some("Checking the token with Slack...".to_string());
+                            terminal.draw(|f| draw(f, &prompt))?;
+                            match SlackClient::verify_token(&input, &config.network).await {
+                                Ok(_) => {
+                                    prompt.error = None;
+                                    prompt.token = input;
+                                    prompt.input.clear();
+                                    prompt.step = WorkspaceStep::AppToken;
+                                }
+                                Err(e) => {
+                                    prompt.error = Some(format!("Slack rejected the token: {}", e))
+                                }
+                            }
+                        }
+                        WorkspaceStep::AppToken | WorkspaceStep::Rename(_) | WorkspaceStep::Reauth(_) => {
+                            let workspace = Workspace {
+                                name: prompt.name.clone(),
+                                token: prompt.token.clone(),
+                                app_token: input,
+                                ..Default::default()
+                            };
+                            config.active_workspace = 0;
+                            return config.add_workspace(workspace);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Event::Paste(text) => prompt.input.push_str(text.trim()),
+            _ => {}
+        }
+    }
+}
+
+fn draw(f: &mut ratatui::Frame, prompt: &WorkspacePrompt) {
+    let area = f.area();
+    let mut lines: Vec<Line> = INTRO.iter().map(|l| Line::from(*l)).collect();
+    lines[0] = Line::from(Span::styled(INTRO[0], Style::default().add_modifier(Modifier::BOLD)));
+    lines.push(Line::from(Span::styled(
+        "Tokens are masked while you type or paste them; Esc quits.",
+        Style::default().fg(Color::DarkGray),
+    )));
+    let intro_height = (lines.len() as u16 + 1).min(area.height);
+    f.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: false }),
+        Rect { x: area.x + 2, y: area.y + 1, width: area.width.saturating_sub(4), height: intro_height },
+    );
+
+    let below = Rect {
+        y: area.y + intro_height,
+        height: area.height.saturating_sub(intro_height),
+        ..area
+    };
+    App::draw_workspace_prompt(f, below, prompt);
+}
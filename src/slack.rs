@@ -2,21 +2,22 @@ use anyhow::{anyhow, Result};
 use futures::{SinkExt, StreamExt};
 use regex::Regex;
 use reqwest::Client as HttpClient;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, watch, Mutex, Notify};
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-use std::fs::OpenOptions;
-use std::io::Write;
+use tracing::{debug, trace, warn};
 use tokio::sync::broadcast;
 
-use crate::app::{ChatInfo, ChatSection};
+use crate::app::{ChatInfo, ChatSection, Sharing};
+use crate::config::NetworkConfig;
 
 /// Updates received from Slack
 #[derive(Debug, Clone)]
 pub enum SlackUpdate {
     NewMessage {
         channel_id: String,
+        user_id: Option<String>, // None for bot/app messages without a user
         user_name: String,
         text: String,
         ts: String,
@@ -26,6 +27,7 @@ pub enum SlackUpdate {
         forwarded: Option<String>,
         mentions_me: bool,
         files: Vec<SlackFile>,
+        is_system: bool, // Join/leave/topic/... notice, see `is_system_subtype`
     },
     MessageChanged {
         channel_id: String,
@@ -40,23 +42,336 @@ pub enum SlackUpdate {
         channel_id: String,
         user_name: String,
     },
+    /// A conversation the user can now see (created it, joined it, or a new DM)
+    ChannelAdded(ChatInfo),
+    ChannelRenamed {
+        channel_id: String,
+        name: String,
+    },
+    /// A huddle started, changed participants (Some(count)) or ended (None)
+    HuddleChanged {
+        channel_id: String,
+        participants: Option<usize>,
+    },
+    /// Someone else joined or left a conversation (renames group DMs)
+    MemberChanged {
+        channel_id: String,
+        user_id: String,
+        joined: bool,
+    },
+    /// Archived, or the user left it
+    ChannelRemoved {
+        channel_id: String,
+    },
+    /// Someone pinned (or unpinned) a message
+    MessagePinned {
+        channel_id: String,
+        user_name: String,
+        ts: String,       // The pinned message
+        text: String,
+        event_ts: String, // When it was pinned
+        pinned: bool,
+    },
+    /// The user starred or unstarred a conversation in another client
+    ChannelStarred {
+        channel_id: String,
+        starred: bool,
+    },
+}
+
+/// Longest message text chat.postMessage takes; longer drafts fail with msg_too_long
+pub const MAX_MESSAGE_CHARS: usize = 40_000;
+
+/// How often the Socket Mode task pings Slack
+const WS_PING_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+/// Reconnect when nothing (events, pings, pongs) arrived for this long
+const WS_STALE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(90);
+/// File downloads may legitimately take longer than the configured request timeout
+const DOWNLOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(600);
+/// Report download progress at most once per this many bytes
+const PROGRESS_STEP_BYTES: u64 = 256 * 1024;
+/// apps.connections.open failures in a row before switching to HTTP polling
+const OPEN_FAILURES_BEFORE_POLLING: u32 = 3;
+/// Shortest retry interval for Socket Mode while polling
+const POLLING_RECONNECT_SECS: u64 = 30;
+/// Socket Mode reconnect backoff: first retry after about this long, doubling up to the max
+const RECONNECT_BASE_DELAY_MS: u64 = 1000;
+const RECONNECT_MAX_DELAY_MS: u64 = 60_000;
+
+/// Attempts per Web API call before a network error or 5xx answer is returned
+const MAX_API_ATTEMPTS: u32 = 4;
+const RETRY_BASE_DELAY_MS: u64 = 250;
+const RETRY_MAX_DELAY_MS: u64 = 4000;
+
+/// Backoff before retry number `attempt` (1-based): exponential, capped, with
+/// jitter between half and the full step so parallel calls don't retry in lockstep.
+fn retry_delay(attempt: u32) -> std::time::Duration {
+    backoff_delay(attempt, RETRY_BASE_DELAY_MS, RETRY_MAX_DELAY_MS)
+}
+
+/// Methods that only read, so sending them twice after a timeout or 5xx is harmless.
+/// Writes are retried only when the connection failed before the request went out.
+fn is_read_method(method: &str) -> bool {
+    let action = method.rsplit('.').next().unwrap_or(method);
+    method.starts_with("search.")
+        || matches!(
+            action,
+            "list" | "info" | "history" | "replies" | "members" | "test" | "getPresence" | "getPermalink" | "lookupByEmail"
+        )
+}
+
+/// Key of the missing_scope cache: the method, plus the conversation for calls on one,
+/// since the scope they need depends on its type (channels:history vs groups:history).
+fn scope_key(method: &str, channel: Option<&str>) -> String {
+    match channel {
+        Some(channel) => format!("{} in {}", method, channel),
+        None => method.to_string(),
+    }
+}
+
+/// The `channel` a Web API request is about, from its query or JSON body
+fn request_channel(request: &reqwest::Request) -> Option<String> {
+    if let Some((_, channel)) = request.url().query_pairs().find(|(k, _)| k == "channel") {
+        return Some(channel.into_owned());
+    }
+    let body: serde_json::Value = serde_json::from_slice(request.body()?.as_bytes()?).ok()?;
+    body.get("channel")?.as_str().map(str::to_string)
+}
+
+/// Wait before Socket Mode reconnect attempt number `attempt` (1-based)
+fn reconnect_delay(attempt: u32) -> std::time::Duration {
+    backoff_delay(attempt, RECONNECT_BASE_DELAY_MS, RECONNECT_MAX_DELAY_MS)
+}
+
+fn read_token(token: &std::sync::RwLock<String>) -> String {
+    token.read().map(|t| t.clone()).unwrap_or_default()
+}
+
+fn backoff_delay(attempt: u32, base_ms: u64, max_ms: u64) -> std::time::Duration {
+    let step = base_ms
+        .saturating_mul(1 << (attempt.saturating_sub(1)).min(16))
+        .min(max_ms);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    std::time::Duration::from_millis(step / 2 + nanos % (step / 2 + 1))
+}
+
+/// Error reported by the Slack Web API (`"ok": false`), keyed by Slack's `error` code.
+/// Messages are phrased so they can be appended to "Failed to ...: " in the status bar.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SlackError {
+    #[error("missing scope '{needed}' (add it to your Slack app's OAuth scopes and reinstall the app)")]
+    MissingScope { needed: String },
+    #[error("channel not found")]
+    ChannelNotFound,
+    #[error("not a member of this channel")]
+    NotInChannel,
+    #[error("not permitted for your account or this channel ({0})")]
+    NotAllowed(String),
+    #[error("rate limited by Slack{}", retry_after.map(|s| format!(", retry in {}s", s)).unwrap_or_default())]
+    RateLimited { retry_after: Option<u64> },
+    #[error("token is invalid or has been revoked ({0})")]
+    InvalidAuth(String),
+    #[error("Slack API error: {0}")]
+    Api(String),
+}
+
+impl SlackError {
+    /// Build from an `"ok": false` response body (and the HTTP `Retry-After` header, if any).
+    pub fn from_response(body: &serde_json::Value, retry_after: Option<u64>) -> Self {
+        let code = body
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown_error");
+        match code {
+            "missing_scope" => SlackError::MissingScope {
+                needed: body
+                    .get("needed")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+            },
+            "channel_not_found" => SlackError::ChannelNotFound,
+            "not_in_channel" => SlackError::NotInChannel,
+            "restricted_action" | "cant_invite" | "cant_kick_from_general" | "user_is_restricted"
+            | "method_not_supported_for_channel_type" => SlackError::NotAllowed(code.to_string()),
+            code if code.starts_with("restricted_action_") => SlackError::NotAllowed(code.to_string()),
+            "ratelimited" | "rate_limited" => SlackError::RateLimited { retry_after },
+            "invalid_auth" | "not_authed" | "token_revoked" | "token_expired" | "account_inactive"
+            | "invalid_refresh_token" => {
+                SlackError::InvalidAuth(code.to_string())
+            }
+            _ => SlackError::Api(code.to_string()),
+        }
+    }
+
+    /// Slack's error code, e.g. `channel_not_found`
+    pub fn code(&self) -> &str {
+        match self {
+            SlackError::MissingScope { .. } => "missing_scope",
+            SlackError::ChannelNotFound => "channel_not_found",
+            SlackError::NotInChannel => "not_in_channel",
+            SlackError::RateLimited { .. } => "ratelimited",
+            SlackError::InvalidAuth(code) | SlackError::NotAllowed(code) | SlackError::Api(code) => code,
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct SlackClient {
     http: HttpClient,
-    token: String, // Can be either User Token (xoxp-) or Bot Token (xoxb-)
+    // User (xoxp-) or bot (xoxb-) token; replaced in place when a rotating token is refreshed
+    token: Arc<std::sync::RwLock<String>>,
     user_id: Arc<Mutex<Option<String>>>,
     pending_updates: Arc<Mutex<Vec<SlackUpdate>>>,
+    updates_ready: Arc<Notify>, // Signalled after the WebSocket task queues updates
     ws_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
     ws_shutdown: Arc<Mutex<Option<broadcast::Sender<()>>>>,
+    reconnect_now: Arc<Notify>, // `/reconnect`: skip the backoff wait or drop the current connection
     user_name_cache: Arc<Mutex<std::collections::HashMap<String, String>>>,
+    user_flags: Arc<Mutex<std::collections::HashMap<String, UserFlags>>>, // From users.list prefetch
+    user_profiles: Arc<Mutex<Vec<UserSummary>>>, // Live users from the prefetch, for /finduser
+    usergroups: Arc<Mutex<Vec<String>>>, // IDs of user groups the current user belongs to
+    // Group DM -> member IDs, which make up its name. Saved with the session cache so a
+    // restart or workspace switch doesn't call conversations.members for every group.
+    group_members: Arc<std::sync::Mutex<std::collections::HashMap<String, Vec<String>>>>,
+    connection: Arc<watch::Sender<ConnectionState>>, // Published by the WebSocket task
+    connect_timeout: std::time::Duration, // For the Socket Mode WebSocket handshake
+    // API method (plus the conversation, for calls on one) -> scope it failed with
+    // (missing_scope); not called again this session. A private channel lacking
+    // groups:history doesn't block history elsewhere. A std Mutex so the UI can check
+    // it without awaiting; never held across an await.
+    missing_scopes: Arc<std::sync::Mutex<std::collections::HashMap<String, String>>>,
+    recent_errors: Arc<std::sync::Mutex<std::collections::VecDeque<ApiError>>>, // Newest last, for /diagnose
+    // Error code once Slack rejects the token (invalid_auth, token_revoked, ...). Web API
+    // calls then fail without a request until the app signs in again with a new client.
+    auth_failure: Arc<std::sync::Mutex<Option<String>>>,
+    stats: ApiStats, // Shared with the WebSocket task's lookups
+}
+
+/// Web API calls of one method this session
+#[derive(Debug, Clone, Default)]
+pub struct MethodStats {
+    pub calls: u32,
+    pub failures: u32, // Including rate limits
+    pub rate_limited: u32,
+    pub total_time: std::time::Duration,
+}
+
+/// How a Web API call ended, for `ApiStats`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CallOutcome {
+    Ok,
+    Failed,
+    RateLimited,
+}
+
+/// Per-method call counters, for /stats
+#[derive(Debug, Clone, Default)]
+pub struct ApiStats(Arc<std::sync::Mutex<std::collections::HashMap<String, MethodStats>>>);
+
+impl ApiStats {
+    fn record(&self, method: &str, started: std::time::Instant, outcome: CallOutcome) {
+        let Ok(mut stats) = self.0.lock() else {
+            return;
+        };
+        let entry = stats.entry(method.to_string()).or_default();
+        entry.calls += 1;
+        entry.total_time += started.elapsed();
+        if outcome != CallOutcome::Ok {
+            entry.failures += 1;
+        }
+        if outcome == CallOutcome::RateLimited {
+            entry.rate_limited += 1;
+        }
+    }
+
+    /// Record a call made without `api_call`, from its HTTP status and `ok` field
+    fn record_response(&self, method: &str, started: std::time::Instant, status: reqwest::StatusCode, ok: bool) {
+        let outcome = match (status, ok) {
+            (reqwest::StatusCode::TOO_MANY_REQUESTS, _) => CallOutcome::RateLimited,
+            (_, true) => CallOutcome::Ok,
+            (_, false) => CallOutcome::Failed,
+        };
+        self.record(method, started, outcome);
+    }
+
+    /// All methods called so far, most called first
+    pub fn snapshot(&self) -> Vec<(String, MethodStats)> {
+        let mut list: Vec<(String, MethodStats)> = self
+            .0
+            .lock()
+            .map(|s| s.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default();
+        list.sort_by(|a, b| b.1.calls.cmp(&a.1.calls).then_with(|| a.0.cmp(&b.0)));
+        list
+    }
+}
+
+/// A failed Web API call
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub time: chrono::DateTime<chrono::Local>,
+    pub method: String,
+    pub code: String,
+}
+
+/// API errors kept for /diagnose
+const RECENT_ERRORS: usize = 50;
+
+/// Socket Mode connection state, published by the WebSocket task
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    Connecting,
+    Connected {
+        since: chrono::DateTime<chrono::Local>,
+    },
+    Reconnecting {
+        since: chrono::DateTime<chrono::Local>, // When the connection was lost
+        reason: String,
+        attempt: u32, // Failed attempts so far (0: reconnecting right away)
+        retry_at: chrono::DateTime<chrono::Local>,
+    },
+    Disconnected {
+        since: chrono::DateTime<chrono::Local>,
+    },
+    /// Socket Mode is unavailable; the app polls conversations.history instead
+    Polling {
+        since: chrono::DateTime<chrono::Local>,
+        reason: String,
+    },
+}
+
+impl ConnectionState {
+    pub fn label(&self) -> String {
+        match self {
+            ConnectionState::Connecting => "connecting".to_string(),
+            ConnectionState::Connected { .. } => "connected".to_string(),
+            ConnectionState::Reconnecting { since, reason, attempt: 0, .. } => {
+                format!("reconnecting since {} ({})", since.format("%H:%M:%S"), reason)
+            }
+            ConnectionState::Reconnecting { since, reason, attempt, retry_at } => format!(
+                "reconnecting since {} ({}, {} failed, next try {})",
+                since.format("%H:%M:%S"),
+                reason,
+                attempt,
+                retry_at.format("%H:%M:%S")
+            ),
+            ConnectionState::Disconnected { since } => {
+                format!("disconnected since {}", since.format("%H:%M:%S"))
+            }
+            ConnectionState::Polling { since, reason } => {
+                format!("polling since {} ({})", since.format("%H:%M:%S"), reason)
+            }
+        }
+    }
 }
 
 #[derive(Deserialize)]
 #[allow(dead_code)]
 struct AuthTestResponse {
-    ok: bool,
     user_id: String,
     team: String,
     team_id: String,
@@ -64,7 +379,6 @@ struct AuthTestResponse {
 
 #[derive(Deserialize)]
 struct ConversationsListResponse {
-    ok: bool,
     channels: Vec<Channel>,
 }
 
@@ -88,19 +402,87 @@ struct Channel {
     #[serde(default)]
     is_member: bool,
     #[serde(default)]
+    is_read_only: bool, // Announcement channels: only some members may post
+    #[serde(default)]
+    is_thread_only: bool,
+    #[serde(default)]
+    is_ext_shared: bool, // Slack Connect: members of other organizations can see it
+    #[serde(default)]
+    is_pending_ext_shared: bool,
+    #[serde(default)]
+    is_org_shared: bool, // Shared between workspaces of our Enterprise Grid org
+    #[serde(default)]
+    is_shared: bool,
+    #[serde(default)]
     unread_count: Option<u32>,
 }
 
+impl Channel {
+    fn sharing(&self) -> Sharing {
+        if self.is_ext_shared || self.is_pending_ext_shared {
+            Sharing::External
+        } else if self.is_org_shared || self.is_shared {
+            Sharing::Organization
+        } else {
+            Sharing::Internal
+        }
+    }
+}
+
 #[derive(Deserialize)]
-struct ConversationMembersResponse {
+struct ConversationInfoResponse {
     ok: bool,
+    channel: Option<Channel>,
+}
+
+/// Channel metadata shown by `/info` (conversations.info).
+#[derive(Debug, Deserialize)]
+pub struct ChannelDetails {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub created: i64,
+    #[serde(default)]
+    pub num_members: Option<u32>,
+    #[serde(default)]
+    pub topic: Option<ChannelText>,
+    #[serde(default)]
+    pub purpose: Option<ChannelText>,
+    #[serde(default)]
+    pub is_shared: bool,
+    #[serde(default)]
+    pub is_ext_shared: bool,
+    #[serde(default)]
+    pub is_org_shared: bool,
+    #[serde(default)]
+    pub is_archived: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelText {
+    #[serde(default)]
+    pub value: String,
+}
+
+#[derive(Deserialize)]
+struct ChannelDetailsResponse {
+    channel: ChannelDetails,
+}
+
+#[derive(Deserialize)]
+struct ConversationOpenResponse {
+    channel: Channel,
+}
+
+#[derive(Deserialize)]
+struct ConversationMembersResponse {
     #[serde(default)]
     members: Vec<String>,
 }
 
 #[derive(Deserialize)]
 struct ConversationHistoryResponse {
-    ok: bool,
     messages: Vec<SlackMessage>,
     #[serde(default)]
     response_metadata: Option<ResponseMetadata>,
@@ -125,6 +507,8 @@ pub struct SlackMessage {
     pub ts: String,
     pub user: Option<String>,
     #[serde(default)]
+    pub subtype: Option<String>,
+    #[serde(default)]
     pub text: String,
     #[serde(default)]
     pub bot_id: Option<String>,
@@ -144,6 +528,24 @@ pub struct SlackMessage {
     pub attachments: Vec<SlackAttachment>,
     #[serde(default)]
     pub files: Vec<SlackFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room: Option<HuddleRoom>, // Set on `huddle_thread` messages
+}
+
+/// The call behind a `huddle_thread` message; edited as people join and leave
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct HuddleRoom {
+    #[serde(default)]
+    pub has_ended: bool,
+    #[serde(default)]
+    pub participants: Vec<String>, // User IDs of the people in the call now
+}
+
+impl HuddleRoom {
+    /// Number of people in the call, or None once it has ended
+    pub fn live_participants(&self) -> Option<usize> {
+        (!self.has_ended).then_some(self.participants.len())
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -167,6 +569,8 @@ pub struct SlackFile {
     #[serde(default)]
     pub filetype: Option<String>,
     #[serde(default)]
+    pub pretty_type: Option<String>,
+    #[serde(default)]
     pub url_private: Option<String>,
     #[serde(default)]
     pub url_private_download: Option<String>,
@@ -202,6 +606,101 @@ pub struct SlackAttachment {
     pub title: Option<String>,
 }
 
+/// Publish `Reconnecting` after `attempt` failed attempts, keeping the time of the first
+/// failure across retries. Returns how long to wait before the next attempt.
+fn mark_connection_down(
+    connection: &watch::Sender<ConnectionState>,
+    down_since: &mut Option<chrono::DateTime<chrono::Local>>,
+    reason: &str,
+    attempt: u32,
+) -> std::time::Duration {
+    let since = *down_since.get_or_insert_with(chrono::Local::now);
+    let wait = if attempt == 0 { std::time::Duration::ZERO } else { reconnect_delay(attempt) };
+    connection.send_replace(ConnectionState::Reconnecting {
+        since,
+        reason: reason.to_string(),
+        attempt,
+        retry_at: chrono::Local::now() + wait,
+    });
+    wait
+}
+
+/// Record a failed apps.connections.open call and return how long to wait before
+/// retrying. After a few failures in a row, fall back to `Polling` and retry slowly.
+fn mark_open_failed(
+    connection: &watch::Sender<ConnectionState>,
+    down_since: &mut Option<chrono::DateTime<chrono::Local>>,
+    failures: &mut u32,
+    attempt: u32,
+    reason: &str,
+) -> std::time::Duration {
+    *failures += 1;
+    if *failures < OPEN_FAILURES_BEFORE_POLLING {
+        return mark_connection_down(connection, down_since, reason, attempt);
+    }
+    let since = *down_since.get_or_insert_with(chrono::Local::now);
+    connection.send_replace(ConnectionState::Polling {
+        since,
+        reason: reason.to_string(),
+    });
+    reconnect_delay(attempt).max(std::time::Duration::from_secs(POLLING_RECONNECT_SECS))
+}
+
+/// Sleep before the next connection attempt; `/reconnect` cuts it short.
+/// False if the client is shutting down instead.
+async fn wait_to_reconnect(
+    wait: std::time::Duration,
+    reconnect_now: &Notify,
+    shutdown_rx: &mut broadcast::Receiver<()>,
+) -> bool {
+    tokio::select! {
+        _ = tokio::time::sleep(wait) => true,
+        _ = reconnect_now.notified() => true,
+        _ = shutdown_rx.recv() => false,
+    }
+}
+
+/// Prefer display_name > name (username)
+fn user_display_name(user: User) -> String {
+    user.profile
+        .and_then(|p| p.display_name)
+        .filter(|n| !n.is_empty())
+        .unwrap_or(user.name)
+}
+
+/// True if Slack couldn't be reached at all (DNS, connect or timeout), as opposed
+/// to Slack answering with an error.
+pub fn is_network_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect() || e.is_timeout())
+    })
+}
+
+/// Message subtypes that are channel notices (joins, topic changes, ...) rather than chat.
+pub fn is_system_subtype(subtype: &str) -> bool {
+    matches!(
+        subtype,
+        "channel_join"
+            | "channel_leave"
+            | "channel_topic"
+            | "channel_purpose"
+            | "channel_name"
+            | "channel_archive"
+            | "channel_unarchive"
+            | "group_join"
+            | "group_leave"
+            | "group_topic"
+            | "group_purpose"
+            | "group_name"
+            | "group_archive"
+            | "group_unarchive"
+            | "pinned_item"
+            | "unpinned_item"
+    )
+}
+
 fn extract_forwarded_text(attachments: &[SlackAttachment]) -> Option<String> {
     for att in attachments {
         if let Some(text) = att.text.as_ref().filter(|t| !t.is_empty()) {
@@ -217,18 +716,43 @@ fn extract_forwarded_text(attachments: &[SlackAttachment]) -> Option<String> {
     None
 }
 
-/// Check if the text contains a mention of the specified user ID
-/// Looks for patterns like <@U12345> or <@U12345|name>
-fn text_mentions_user(text: &str, user_id: &str) -> bool {
+/// Check if the text mentions the current user, either directly (<@U12345>,
+/// <@U12345|name>), via a broadcast (<!here>, <!channel>, <!everyone>) or via
+/// a user group the user belongs to (<!subteam^S12345>, <!subteam^S12345|@team>).
+pub fn text_mentions_me(text: &str, user_id: &str, usergroups: &[String]) -> bool {
     if user_id.is_empty() {
         return false;
     }
-    
+
     // Look for <@USER_ID> or <@USER_ID|...>
     let pattern1 = format!("<@{}>", user_id);
     let pattern2 = format!("<@{}|", user_id);
-    
-    text.contains(&pattern1) || text.contains(&pattern2)
+    if text.contains(&pattern1) || text.contains(&pattern2) {
+        return true;
+    }
+
+    let mut rest = text;
+    while let Some(start) = rest.find("<!") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('>') else {
+            break;
+        };
+        let inner = &after[..end];
+        let target = inner.split('|').next().unwrap_or(inner);
+        match target {
+            "here" | "channel" | "everyone" => return true,
+            _ => {
+                if let Some(group_id) = target.strip_prefix("subteam^") {
+                    if usergroups.iter().any(|g| g == group_id) {
+                        return true;
+                    }
+                }
+            }
+        }
+        rest = &after[end + 1..];
+    }
+
+    false
 }
 
 #[derive(Deserialize)]
@@ -237,10 +761,76 @@ struct UserInfoResponse {
     user: User,
 }
 
+#[derive(Deserialize)]
+struct UsersListResponse {
+    #[serde(default)]
+    members: Vec<User>,
+    #[serde(default)]
+    response_metadata: Option<ResponseMetadata>,
+}
+
+/// Searchable profile fields of a user (/finduser)
+#[derive(Debug, Clone)]
+pub struct UserSummary {
+    pub id: String,
+    pub name: String, // Display name, as in the name cache
+    pub handle: String,
+    pub real_name: String,
+    pub title: String,
+    pub email: String,
+}
+
+impl UserSummary {
+    fn from_user(user: &User) -> Self {
+        let profile = user.profile.as_ref();
+        let field = |f: Option<&Option<String>>| f.and_then(|v| v.clone()).unwrap_or_default();
+        Self {
+            id: user.id.clone(),
+            name: profile
+                .and_then(|p| p.display_name.clone())
+                .filter(|n| !n.is_empty())
+                .unwrap_or_else(|| user.name.clone()),
+            handle: user.name.clone(),
+            real_name: user.real_name.clone().unwrap_or_default(),
+            title: field(profile.map(|p| &p.title)),
+            email: field(profile.map(|p| &p.email)),
+        }
+    }
+
+    /// Case-insensitive substring match on any name field, the title or the email.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [&self.name, &self.handle, &self.real_name, &self.title, &self.email]
+            .iter()
+            .any(|f| f.to_lowercase().contains(&query))
+    }
+}
+
+#[derive(Deserialize)]
+struct UserLookupResponse {
+    user: User,
+}
+
+#[derive(Deserialize)]
+struct PresenceResponse {
+    presence: String,
+}
+
+/// Per-user facts kept from the users.list prefetch
+#[derive(Debug, Clone, Copy)]
+struct UserFlags {
+    is_bot: bool,
+    deleted: bool,
+}
+
 #[derive(Deserialize)]
 struct UserProfile {
     #[serde(default)]
     display_name: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -257,6 +847,152 @@ struct User {
     deleted: bool,
 }
 
+#[derive(Deserialize)]
+struct UsergroupsListResponse {
+    #[serde(default)]
+    usergroups: Vec<Usergroup>,
+}
+
+#[derive(Deserialize)]
+struct Usergroup {
+    id: String,
+    #[serde(default)]
+    users: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct PostMessageResponse {
+    ts: String, // Timestamp of the posted message, as it will appear in events
+}
+
+/// Download progress callback: bytes received so far and the total size, if known
+pub type DownloadProgress = dyn Fn(u64, Option<u64>) + Send + Sync;
+
+/// A file shared in a conversation, as listed by files.list
+#[derive(Debug, Clone, Deserialize)]
+pub struct SharedFile {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub pretty_type: Option<String>,
+    #[serde(default)]
+    pub filetype: Option<String>,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub created: i64,
+    #[serde(default)]
+    pub url_private_download: Option<String>,
+    #[serde(default)]
+    pub url_private: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FilesListResponse {
+    #[serde(default)]
+    files: Vec<SharedFile>,
+}
+
+/// Do Not Disturb snooze state (dnd.info / dnd.setSnooze)
+#[derive(Debug, Clone, Deserialize)]
+pub struct DndInfo {
+    #[serde(default)]
+    pub snooze_enabled: bool,
+    #[serde(default)]
+    pub snooze_endtime: Option<i64>,
+}
+
+/// A message found by search.messages
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchMatch {
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub username: String,
+    pub ts: String,
+    #[serde(default)]
+    pub text: String,
+    pub channel: SearchChannel,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchChannel {
+    #[serde(default)]
+    pub name: String, // A user ID for DMs
+    #[serde(default)]
+    pub is_im: bool,
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    messages: SearchMessages,
+}
+
+#[derive(Deserialize)]
+struct SearchMessages {
+    #[serde(default)]
+    total: u32,
+    #[serde(default)]
+    matches: Vec<SearchMatch>,
+}
+
+#[derive(Deserialize)]
+struct PermalinkResponse {
+    permalink: String,
+}
+
+#[derive(Deserialize)]
+struct UploadUrlResponse {
+    upload_url: String,
+    file_id: String,
+}
+
+/// Who a token belongs to and what it may do (auth.test, for `doctor`)
+#[derive(Debug, Clone)]
+pub struct TokenInfo {
+    pub user: String,
+    pub team: String,
+    pub scopes: Option<Vec<String>>, // From the x-oauth-scopes header; None if Slack didn't send it
+}
+
+/// A renewed access token of a workspace with token rotation (oauth.v2.access)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotatedToken {
+    pub access_token: String,
+    pub refresh_token: String, // Slack hands out a new one with every refresh
+    pub expires_at: i64,       // Unix seconds
+}
+
+impl RotatedToken {
+    /// From an oauth.v2.access answer received at `now` (Unix seconds). User tokens
+    /// come under `authed_user`, bot tokens at the top level.
+    fn from_response(body: &serde_json::Value, now: i64) -> Result<Self> {
+        if !body.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+            return Err(SlackError::from_response(body, None).into());
+        }
+        let tokens = match body.get("authed_user") {
+            Some(user) if user.get("access_token").is_some() => user,
+            _ => body,
+        };
+        let field = |name: &str| tokens.get(name).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let (access_token, refresh_token) = (field("access_token"), field("refresh_token"));
+        if access_token.is_empty() || refresh_token.is_empty() {
+            return Err(anyhow!("oauth.v2.access returned no token"));
+        }
+        let expires_in = tokens.get("expires_in").and_then(|v| v.as_i64()).unwrap_or(0);
+        Ok(Self {
+            access_token,
+            refresh_token,
+            expires_at: now + expires_in,
+        })
+    }
+}
+
 #[derive(Deserialize)]
 struct SocketModeConnectResponse {
     ok: bool,
@@ -264,37 +1000,373 @@ struct SocketModeConnectResponse {
 }
 
 impl SlackClient {
-    pub async fn new(token: &str, _app_token: &str) -> Result<Self> {
-        let http = HttpClient::new();
-        let token = token.to_string();
+    /// A client that hasn't talked to Slack yet, for offline mode: every call fails
+    /// until the app connects again with `new`.
+    pub fn offline(token: &str, network: &NetworkConfig) -> Result<Self> {
+        let client = Self::build(token, network)?;
+        client.connection.send_replace(ConnectionState::Disconnected {
+            since: chrono::Local::now(),
+        });
+        Ok(client)
+    }
 
-        let client = Self {
+    fn build(token: &str, network: &NetworkConfig) -> Result<Self> {
+        let connect_timeout = std::time::Duration::from_secs(network.connect_timeout_secs);
+        let http = HttpClient::builder()
+            .timeout(std::time::Duration::from_secs(network.request_timeout_secs))
+            .connect_timeout(connect_timeout)
+            .build()?;
+        let token = Arc::new(std::sync::RwLock::new(token.to_string()));
+
+        Ok(Self {
             http,
             token,
             user_id: Arc::new(Mutex::new(None)),
             pending_updates: Arc::new(Mutex::new(Vec::new())),
+            updates_ready: Arc::new(Notify::new()),
             ws_handle: Arc::new(Mutex::new(None)),
             ws_shutdown: Arc::new(Mutex::new(None)),
+            reconnect_now: Arc::new(Notify::new()),
             user_name_cache: Arc::new(Mutex::new(std::collections::HashMap::new())),
-        };
+            user_flags: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            user_profiles: Arc::new(Mutex::new(Vec::new())),
+            usergroups: Arc::new(Mutex::new(Vec::new())),
+            group_members: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
+            connection: Arc::new(watch::Sender::new(ConnectionState::Connecting)),
+            connect_timeout,
+            missing_scopes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
+            recent_errors: Arc::new(std::sync::Mutex::new(std::collections::VecDeque::new())),
+            auth_failure: Arc::new(std::sync::Mutex::new(None)),
+            stats: ApiStats::default(),
+        })
+    }
+
+    /// A client whose token passed auth.test. The TUI loads all users afterwards with
+    /// `prefetch_users`; one-shot commands look up the few they need.
+    pub async fn new(token: &str, network: &NetworkConfig) -> Result<Self> {
+        let client = Self::build(token, network)?;
 
         // Test authentication
         let auth_response: AuthTestResponse = client
+            .api_call(client.http.get("https://slack.com/api/auth.test"))
+            .await?;
+
+        *client.user_id.lock().await = Some(auth_response.user_id.clone());
+
+        // Best effort: needs usergroups:read, group mentions are simply not detected without it
+        if let Ok(groups) = client.fetch_my_usergroups(&auth_response.user_id).await {
+            *client.usergroups.lock().await = groups;
+        }
+
+        Ok(client)
+    }
+
+    /// Send a Web API request with the client's token and decode the JSON body.
+    /// An `"ok": false` answer (or HTTP 429) becomes a `SlackError`.
+    async fn api_call<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T> {
+        let request = request.bearer_auth(self.token()).build()?;
+        let method = request.url().path().trim_start_matches("/api/").to_string();
+        let scope_key = scope_key(&method, request_channel(&request).as_deref());
+        let missing = self.missing_scopes.lock().ok().and_then(|m| m.get(&scope_key).cloned());
+        if let Some(needed) = missing {
+            debug!("{} skipped, token lacks {}", scope_key, needed);
+            return Err(SlackError::MissingScope { needed }.into());
+        }
+        if let Some(code) = self.auth_failure() {
+            return Err(SlackError::InvalidAuth(code).into());
+        }
+
+        let started = std::time::Instant::now();
+        let result = self.send_api_request(request, &method).await;
+        let outcome = match &result {
+            Ok(_) => CallOutcome::Ok,
+            Err(e) if matches!(e.downcast_ref(), Some(SlackError::RateLimited { .. })) => CallOutcome::RateLimited,
+            Err(_) => CallOutcome::Failed,
+        };
+        self.stats.record(&method, started, outcome);
+        if let Some(SlackError::MissingScope { needed }) = result.as_ref().err().and_then(|e| e.downcast_ref()) {
+            if let Ok(mut missing) = self.missing_scopes.lock() {
+                missing.insert(scope_key, needed.clone());
+            }
+        }
+        result
+    }
+
+    async fn send_api_request<T: DeserializeOwned>(&self, request: reqwest::Request, method: &str) -> Result<T> {
+        // Retry network errors and 5xx answers with jittered exponential backoff. Slack may
+        // have handled a write that timed out or got a 5xx, so those are not sent again.
+        let is_read = is_read_method(method);
+        let mut attempt = 0;
+        let response = loop {
+            attempt += 1;
+            let can_retry = attempt < MAX_API_ATTEMPTS;
+            let Some(this_try) = request.try_clone() else {
+                break self.http.execute(request).await?;
+            };
+            match self.http.execute(this_try).await {
+                Ok(resp) if resp.status().is_server_error() && can_retry && is_read => {
+                    warn!("{} returned HTTP {} (attempt {})", method, resp.status(), attempt);
+                }
+                Ok(resp) => break resp,
+                Err(e) if can_retry && (e.is_connect() || (is_read && e.is_timeout())) => {
+                    warn!("{} request failed: {} (attempt {})", method, e, attempt);
+                }
+                Err(e) => return Err(e.into()),
+            }
+            tokio::time::sleep(retry_delay(attempt)).await;
+        };
+
+        if response.status().is_server_error() {
+            self.record_error(method, &format!("http_{}", response.status().as_u16()));
+            return Err(anyhow!("Slack is unavailable (HTTP {})", response.status()));
+        }
+
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            warn!("{} rate limited (retry after {:?}s)", method, retry_after);
+            self.record_error(method, "ratelimited");
+            return Err(SlackError::RateLimited { retry_after }.into());
+        }
+
+        let body: serde_json::Value = response.json().await?;
+        if !body.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+            let err = SlackError::from_response(&body, retry_after);
+            warn!("{} failed: {}", method, err.code());
+            self.record_error(method, err.code());
+            if let SlackError::InvalidAuth(code) = &err {
+                if let Ok(mut failure) = self.auth_failure.lock() {
+                    failure.get_or_insert_with(|| code.clone());
+                }
+            }
+            return Err(err.into());
+        }
+        Ok(serde_json::from_value(body)?)
+    }
+
+    async fn fetch_my_usergroups(&self, my_user_id: &str) -> Result<Vec<String>> {
+        let response: UsergroupsListResponse = self
+            .api_call(self.http.get("https://slack.com/api/usergroups.list?include_users=true"))
+            .await?;
+
+        Ok(response
+            .usergroups
+            .into_iter()
+            .filter(|g| g.users.iter().any(|u| u == my_user_id))
+            .map(|g| g.id)
+            .collect())
+    }
+
+    /// IDs of the user groups the current user is a member of.
+    pub async fn get_my_usergroups(&self) -> Vec<String> {
+        self.usergroups.lock().await.clone()
+    }
+
+    /// auth.test, keeping the granted scopes that Slack lists in the `x-oauth-scopes` header.
+    pub async fn token_info(&self) -> Result<TokenInfo> {
+        let response = self
             .http
             .get("https://slack.com/api/auth.test")
-            .bearer_auth(&client.token)
+            .bearer_auth(self.token())
+            .send()
+            .await?;
+        let scopes = response
+            .headers()
+            .get("x-oauth-scopes")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            });
+        let body: serde_json::Value = response.json().await?;
+        if !body.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+            return Err(SlackError::from_response(&body, None).into());
+        }
+        let field = |name: &str| body.get(name).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        Ok(TokenInfo {
+            user: field("user"),
+            team: field("team"),
+            scopes,
+        })
+    }
+
+    /// auth.test for a token that isn't in use yet (`/workspace add`); returns the team name.
+    pub async fn verify_token(token: &str, network: &NetworkConfig) -> Result<String> {
+        let http = HttpClient::builder()
+            .timeout(std::time::Duration::from_secs(network.request_timeout_secs))
+            .connect_timeout(std::time::Duration::from_secs(network.connect_timeout_secs))
+            .build()?;
+        let body: serde_json::Value = http
+            .get("https://slack.com/api/auth.test")
+            .bearer_auth(token)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if !body.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+            return Err(SlackError::from_response(&body, None).into());
+        }
+        Ok(body.get("team").and_then(|v| v.as_str()).unwrap_or("").to_string())
+    }
+
+    /// Exchange a rotation refresh token for a new access token (oauth.v2.access).
+    pub async fn refresh_access_token(
+        refresh_token: &str,
+        client_id: &str,
+        client_secret: &str,
+        network: &NetworkConfig,
+    ) -> Result<RotatedToken> {
+        let http = HttpClient::builder()
+            .timeout(std::time::Duration::from_secs(network.request_timeout_secs))
+            .connect_timeout(std::time::Duration::from_secs(network.connect_timeout_secs))
+            .build()?;
+        let body: serde_json::Value = http
+            .post("https://slack.com/api/oauth.v2.access")
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token),
+                ("client_id", client_id),
+                ("client_secret", client_secret),
+            ])
             .send()
             .await?
             .json()
             .await?;
+        RotatedToken::from_response(&body, chrono::Utc::now().timestamp())
+    }
 
-        if !auth_response.ok {
-            return Err(anyhow!("Slack authentication failed"));
+    /// Open a Socket Mode connection with `app_token`, wait for Slack's hello and close it again.
+    pub async fn check_socket_mode(&self, app_token: &str) -> Result<()> {
+        let body: serde_json::Value = self
+            .http
+            .post("https://slack.com/api/apps.connections.open")
+            .bearer_auth(app_token)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if !body.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+            return Err(SlackError::from_response(&body, None).into());
         }
+        let url = body
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("apps.connections.open returned no URL"))?;
+
+        let hello = async {
+            let (mut ws_stream, _) = connect_async(url).await?;
+            while let Some(msg) = ws_stream.next().await {
+                if let Message::Text(text) = msg? {
+                    if text.contains("\"hello\"") {
+                        let _ = ws_stream.close(None).await;
+                        return Ok(());
+                    }
+                }
+            }
+            Err(anyhow!("connection closed before Slack's hello"))
+        };
+        tokio::time::timeout(self.connect_timeout * 2, hello)
+            .await
+            .map_err(|_| anyhow!("no hello from Slack within {:?}", self.connect_timeout * 2))?
+    }
 
-        *client.user_id.lock().await = Some(auth_response.user_id);
+    /// The scope `method` (e.g. "files.list") failed for with missing_scope earlier this
+    /// session. Calls on one conversation are tracked per conversation and not reported here.
+    pub fn missing_scope(&self, method: &str) -> Option<String> {
+        self.missing_scopes.lock().ok()?.get(method).cloned()
+    }
 
-        Ok(client)
+    fn record_error(&self, method: &str, code: &str) {
+        if let Ok(mut errors) = self.recent_errors.lock() {
+            if errors.len() == RECENT_ERRORS {
+                errors.pop_front();
+            }
+            errors.push_back(ApiError {
+                time: chrono::Local::now(),
+                method: method.to_string(),
+                code: code.to_string(),
+            });
+        }
+    }
+
+    /// Per-method call counts of this session
+    pub fn api_stats(&self) -> Vec<(String, MethodStats)> {
+        self.stats.snapshot()
+    }
+
+    /// The last failed Web API calls, oldest first
+    pub fn recent_errors(&self) -> Vec<ApiError> {
+        self.recent_errors
+            .lock()
+            .map(|e| e.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Methods disabled this session by missing_scope, with the scope each needs
+    pub fn missing_scopes(&self) -> Vec<(String, String)> {
+        let mut list: Vec<(String, String)> = self
+            .missing_scopes
+            .lock()
+            .map(|m| m.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default();
+        list.sort();
+        list
+    }
+
+    /// Why Slack stopped accepting the token, once it has (e.g. "token_revoked").
+    pub fn auth_failure(&self) -> Option<String> {
+        self.auth_failure.lock().ok().and_then(|f| f.clone())
+    }
+
+    fn token(&self) -> String {
+        read_token(&self.token)
+    }
+
+    /// Use `token` from now on, e.g. after it was refreshed (token rotation). Calls
+    /// paused because Slack rejected the old token go through again.
+    pub fn set_token(&self, token: &str) {
+        if let Ok(mut current) = self.token.write() {
+            *current = token.to_string();
+        }
+        if let Ok(mut failure) = self.auth_failure.lock() {
+            *failure = None;
+        }
+    }
+
+    /// "xoxp" or "xoxb" (user or bot token)
+    pub fn token_prefix(&self) -> &'static str {
+        // Rotating tokens start with "xoxe.", e.g. xoxe.xoxp-1-...
+        let token = self.token();
+        match token.trim_start_matches("xoxe.").split('-').next() {
+            Some("xoxp") => "xoxp",
+            Some("xoxb") => "xoxb",
+            _ => "",
+        }
+    }
+
+    /// Subscribe to Socket Mode connection state changes.
+    pub fn connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.connection.subscribe()
+    }
+
+    /// Make the Socket Mode task try to connect now: skips a pending backoff wait, or
+    /// drops the current connection and opens a new one. False if Socket Mode isn't running.
+    pub fn reconnect(&self) -> bool {
+        let running = self
+            .ws_handle
+            .try_lock()
+            .map(|handle| handle.as_ref().is_some_and(|h| !h.is_finished()))
+            .unwrap_or(true);
+        if running {
+            self.reconnect_now.notify_one();
+        }
+        running
     }
 
     pub async fn get_my_user_id(&self) -> Result<String> {
@@ -304,20 +1376,27 @@ impl SlackClient {
 
     pub async fn start_event_listener(&self, app_token: String) -> Result<()> {
         // Log that we're starting a new listener
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("/tmp/slack_rust_debug.log")
-            .and_then(|mut f| {
-                use std::io::Write;
-                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                writeln!(f, "[{}] start_event_listener called", timestamp)
+        debug!("start_event_listener called");
+
+        if app_token.trim().is_empty() {
+            warn!("No app-level token configured; polling for new messages instead of Socket Mode");
+            self.connection.send_replace(ConnectionState::Polling {
+                since: chrono::Local::now(),
+                reason: "no app token".to_string(),
             });
+            return Ok(());
+        }
 
         let pending_updates = self.pending_updates.clone();
+        let updates_ready = self.updates_ready.clone();
         let http = self.http.clone();
         let token = self.token.clone();
         let user_id = self.user_id.clone();
+        let usergroups = self.usergroups.clone();
+        let stats = self.stats.clone();
+        let connection = self.connection.clone();
+        let connect_timeout = self.connect_timeout;
+        let reconnect_now = self.reconnect_now.clone();
 
         // Create shutdown channel
         let (shutdown_tx, mut shutdown_rx) = broadcast::channel::<()>(1);
@@ -327,82 +1406,149 @@ impl SlackClient {
         let (proactive_tx, mut proactive_rx) = mpsc::channel::<()>(1);
 
         let handle = tokio::spawn(async move {
-            let log_to_file = |msg: &str| {
-                if let Ok(mut file) = OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open("/tmp/slack_rust_debug.log")
-                {
-                    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                    let _ = writeln!(file, "[{}] {}", timestamp, msg);
-                }
-            };
-
             let envelope_id_regex = Regex::new(r#""envelope_id"\s*:\s*"([^"]+)""#).expect("valid regex");
 
-            log_to_file("WebSocket task starting...");
+            debug!("WebSocket task starting...");
+            connection.send_replace(ConnectionState::Connecting);
+
+            // When the connection was lost, kept across failed reconnect attempts
+            let mut down_since: Option<chrono::DateTime<chrono::Local>> = None;
+            // Consecutive apps.connections.open failures
+            let mut open_failures = 0u32;
+            // Failed attempts since the last working connection, for the backoff
+            let mut failed_attempts = 0u32;
 
             // Reconnection loop
             'reconnect: loop {
                 // Get fresh WebSocket URL (Slack rotates these periodically)
+                let started = std::time::Instant::now();
                 let ws_url = match http
                     .post("https://slack.com/api/apps.connections.open")
                     .bearer_auth(&app_token)
                     .send()
                     .await
                 {
-                    Ok(resp) => match resp.json::<SocketModeConnectResponse>().await {
-                        Ok(r) if r.ok => r.url,
-                        Ok(_) => {
-                            log_to_file("apps.connections.open returned ok=false");
-                            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    Ok(resp) => match (resp.status(), resp.json::<SocketModeConnectResponse>().await) {
+                        (status, Ok(r)) if r.ok => {
+                            stats.record_response("apps.connections.open", started, status, true);
+                            r.url
+                        }
+                        (status, Ok(_)) => {
+                            stats.record_response("apps.connections.open", started, status, false);
+                            debug!("apps.connections.open returned ok=false");
+                            failed_attempts += 1;
+                            let wait = mark_open_failed(&connection, &mut down_since, &mut open_failures, failed_attempts, "connection refused");
+                            if !wait_to_reconnect(wait, &reconnect_now, &mut shutdown_rx).await {
+                                break 'reconnect;
+                            }
                             continue 'reconnect;
                         }
-                        Err(e) => {
-                            log_to_file(&format!("apps.connections.open parse error: {}", e));
-                            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                        (status, Err(e)) => {
+                            stats.record_response("apps.connections.open", started, status, false);
+                            warn!("apps.connections.open parse error: {}", e);
+                            failed_attempts += 1;
+                            let wait = mark_open_failed(&connection, &mut down_since, &mut open_failures, failed_attempts, "bad response");
+                            if !wait_to_reconnect(wait, &reconnect_now, &mut shutdown_rx).await {
+                                break 'reconnect;
+                            }
                             continue 'reconnect;
                         }
                     },
                     Err(e) => {
-                        log_to_file(&format!("apps.connections.open request failed: {}", e));
-                        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                        stats.record("apps.connections.open", started, CallOutcome::Failed);
+                        warn!("apps.connections.open request failed: {}", e);
+                        failed_attempts += 1;
+                        let wait = mark_open_failed(&connection, &mut down_since, &mut open_failures, failed_attempts, "network error");
+                        if !wait_to_reconnect(wait, &reconnect_now, &mut shutdown_rx).await {
+                            break 'reconnect;
+                        }
                         continue 'reconnect;
                     }
                 };
-
-                let (mut ws_stream, _) = match connect_async(&ws_url).await {
-                    Ok(conn) => conn,
-                    Err(e) => {
-                        log_to_file(&format!("WebSocket connect failed: {}", e));
-                        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                open_failures = 0;
+
+                let (mut ws_stream, _) = match tokio::time::timeout(connect_timeout, connect_async(&ws_url)).await {
+                    Ok(Ok(conn)) => conn,
+                    Ok(Err(e)) => {
+                        warn!("WebSocket connect failed: {}", e);
+                        failed_attempts += 1;
+                        let wait = mark_connection_down(&connection, &mut down_since, "connect failed", failed_attempts);
+                        if !wait_to_reconnect(wait, &reconnect_now, &mut shutdown_rx).await {
+                            break 'reconnect;
+                        }
+                        continue 'reconnect;
+                    }
+                    Err(_) => {
+                        warn!("WebSocket connect timed out after {:?}", connect_timeout);
+                        failed_attempts += 1;
+                        let wait = mark_connection_down(&connection, &mut down_since, "connect timed out", failed_attempts);
+                        if !wait_to_reconnect(wait, &reconnect_now, &mut shutdown_rx).await {
+                            break 'reconnect;
+                        }
                         continue 'reconnect;
                     }
                 };
 
-                log_to_file("WebSocket connected successfully");
+                debug!("WebSocket connected successfully");
+                down_since = None;
+                failed_attempts = 0;
+                connection.send_replace(ConnectionState::Connected {
+                    since: chrono::Local::now(),
+                });
+
+                // Liveness: ping periodically, give up on a connection that went quiet
+                let mut last_traffic = std::time::Instant::now();
+                let mut ping_timer = tokio::time::interval_at(
+                    tokio::time::Instant::now() + WS_PING_INTERVAL,
+                    WS_PING_INTERVAL,
+                );
 
-                // Process messages until disconnect, stream end, or shutdown
-                loop {
+                // Process messages until disconnect, stream end, or shutdown.
+                // Evaluates to why the connection dropped (None for a planned refresh).
+                let reason = loop {
                     tokio::select! {
                         biased;
 
                         _ = shutdown_rx.recv() => {
-                            log_to_file("Received shutdown signal, closing WebSocket gracefully");
+                            debug!("Received shutdown signal, closing WebSocket gracefully");
                             let _ = ws_stream.close(None).await;
                             break 'reconnect;
                         }
 
                         Some(()) = proactive_rx.recv() => {
-                            log_to_file("Proactive reconnect triggered (before connection timeout)");
+                            debug!("Proactive reconnect triggered (before connection timeout)");
+                            let _ = ws_stream.close(None).await;
+                            break None; // Planned handover, not an outage
+                        }
+
+                        _ = reconnect_now.notified() => {
+                            debug!("Reconnect requested");
                             let _ = ws_stream.close(None).await;
-                            break;
+                            break Some("reconnect requested");
+                        }
+
+                        _ = ping_timer.tick() => {
+                            if last_traffic.elapsed() >= WS_STALE_TIMEOUT {
+                                warn!("No WebSocket traffic for {}s, forcing reconnect", last_traffic.elapsed().as_secs());
+                                let _ = ws_stream.close(None).await;
+                                break Some("stale connection");
+                            }
+                            if let Err(e) = ws_stream.send(Message::Ping(Vec::new())).await {
+                                warn!("WebSocket ping failed: {}", e);
+                                break Some("ping failed");
+                            }
                         }
 
                         msg = ws_stream.next() => {
+                            if matches!(msg, Some(Ok(_))) {
+                                last_traffic = std::time::Instant::now();
+                            }
                             match msg {
+                                Some(Ok(Message::Ping(data))) => {
+                                    let _ = ws_stream.send(Message::Pong(data)).await;
+                                }
                                 Some(Ok(Message::Text(text))) => {
-                                    log_to_file(&format!("Received WebSocket message: {}", &text[..text.len().min(200)]));
+                                    trace!("Received WebSocket message: {}", &text[..text.len().min(200)]);
 
                                     // Robust ack: extract envelope_id even if full parse fails
                                     let envelope_id = serde_json::from_str::<serde_json::Value>(&text)
@@ -418,12 +1564,12 @@ impl SlackClient {
                                     if let Some(ref eid) = envelope_id {
                                         let ack = serde_json::json!({ "envelope_id": eid });
                                         let _ = ws_stream.send(Message::Text(ack.to_string())).await;
-                                        log_to_file(&format!("Acknowledged envelope: {}", eid));
+                                        debug!("Acknowledged envelope: {}", eid);
                                     }
 
                                     if let Ok(envelope) = serde_json::from_str::<serde_json::Value>(&text) {
                                         if let Some(event_type) = envelope.get("type").and_then(|v| v.as_str()) {
-                                            log_to_file(&format!("Event type: {}", event_type));
+                                            debug!("Event type: {}", event_type);
 
                                             if event_type == "hello" {
                                                 if let Some(debug) = envelope.get("debug_info") {
@@ -434,51 +1580,61 @@ impl SlackClient {
                                                             tokio::time::sleep(std::time::Duration::from_secs(delay_secs)).await;
                                                             let _ = proactive_tx_clone.send(()).await;
                                                         });
-                                                        log_to_file(&format!("Scheduled proactive reconnect in {} seconds", delay_secs));
+                                                        debug!("Scheduled proactive reconnect in {} seconds", delay_secs);
                                                     }
                                                 }
                                             } else if event_type == "disconnect" {
                                                 let reason = envelope.get("reason").and_then(|v| v.as_str()).unwrap_or("unknown");
-                                                log_to_file(&format!("Received disconnect (reason: {}), reconnecting", reason));
+                                                debug!("Received disconnect (reason: {}), reconnecting", reason);
                                                 let _ = ws_stream.close(None).await;
-                                                break;
+                                                break Some("disconnected by Slack");
                                             } else if event_type == "events_api" {
                                                 if let Some(event) = envelope.get("payload").and_then(|p| p.get("event")) {
-                                                    log_to_file(&format!("Processing event: {:?}", event));
+                                                    trace!("Processing event: {:?}", event);
                                                     Self::process_event(
                                                         event,
                                                         &pending_updates,
                                                         &http,
-                                                        &token,
+                                                        &read_token(&token),
                                                         &user_id,
+                                                        &usergroups,
+                                                        &stats,
                                                     )
                                                     .await;
-                                                    log_to_file("Event processed, added to pending_updates");
+                                                    updates_ready.notify_one();
+                                                    debug!("Event processed, added to pending_updates");
                                                 }
                                             }
                                         }
                                     }
                                 }
                                 Some(Ok(Message::Close(_))) => {
-                                    log_to_file("WebSocket received Close frame, reconnecting");
-                                    break;
+                                    debug!("WebSocket received Close frame, reconnecting");
+                                    break Some("closed by Slack");
                                 }
                                 Some(Err(e)) => {
-                                    log_to_file(&format!("WebSocket stream error: {}", e));
-                                    break;
+                                    warn!("WebSocket stream error: {}", e);
+                                    break Some("stream error");
                                 }
                                 None => {
-                                    log_to_file("WebSocket stream ended, reconnecting");
-                                    break;
+                                    debug!("WebSocket stream ended, reconnecting");
+                                    break Some("stream ended");
                                 }
                                 _ => {}
                             }
                         }
                     }
+                };
+                if let Some(reason) = reason {
+                    // First retry is immediate; the backoff starts if it fails
+                    mark_connection_down(&connection, &mut down_since, reason, 0);
                 }
             }
 
-            log_to_file("WebSocket task exiting");
+            debug!("WebSocket task exiting");
+            connection.send_replace(ConnectionState::Disconnected {
+                since: chrono::Local::now(),
+            });
         });
 
         *self.ws_handle.lock().await = Some(handle);
@@ -491,26 +1647,32 @@ impl SlackClient {
         http: &HttpClient,
         token: &str,
         user_id: &Arc<Mutex<Option<String>>>,
+        usergroups: &Arc<Mutex<Vec<String>>>,
+        stats: &ApiStats,
     ) {
-        // Local logging function
-        let log_to_file = |msg: &str| {
-            use std::fs::OpenOptions;
-            use std::io::Write;
-            if let Ok(mut file) = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open("/tmp/slack_rust_debug.log")
-            {
-                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                let _ = writeln!(file, "[{}] {}", timestamp, msg);
-            }
-        };
-
         if let Some(event_type) = event.get("type").and_then(|v| v.as_str()) {
             match event_type {
                 "message" => {
                     // Check for message subtypes (edited, deleted)
                     let subtype = event.get("subtype").and_then(|v| v.as_str());
+
+                    // A huddle's state lives in the `room` of its huddle_thread message,
+                    // which is edited as people join and leave
+                    let message = match subtype {
+                        Some("message_changed") => event.get("message"),
+                        _ => Some(event),
+                    };
+                    if let (Some(channel_id), Some(room)) = (
+                        event.get("channel").and_then(|v| v.as_str()),
+                        message.and_then(|m| m.get("room")),
+                    ) {
+                        if let Ok(room) = serde_json::from_value::<HuddleRoom>(room.clone()) {
+                            pending_updates.lock().await.push(SlackUpdate::HuddleChanged {
+                                channel_id: channel_id.to_string(),
+                                participants: room.live_participants(),
+                            });
+                        }
+                    }
                     
                     match subtype {
                         Some("message_changed") => {
@@ -571,27 +1733,29 @@ impl SlackClient {
 
                         let my_id = user_id.lock().await.clone().unwrap_or_default();
                         let is_self = !my_id.is_empty() && user_id_event == my_id;
-                        
-                        // Check if the message mentions the current user
-                        let mentions_me = !my_id.is_empty() && text_mentions_user(text, &my_id);
+                        let is_system = subtype.is_some_and(is_system_subtype);
+
+                        // Check if the message mentions the current user ("<@me> has joined" doesn't count)
+                        let my_groups = usergroups.lock().await.clone();
+                        let mentions_me = !is_system && text_mentions_me(text, &my_id, &my_groups);
 
                         // DEBUG: Log the entire event to see what fields we have
-                        log_to_file("=== MESSAGE EVENT DEBUG ===");
-                        log_to_file(&format!("Full event: {}", serde_json::to_string_pretty(event).unwrap_or_default()));
-                        log_to_file(&format!("user field: {:?}", event.get("user")));
-                        log_to_file(&format!("username field: {:?}", event.get("username")));
-                        log_to_file(&format!("bot_id field: {:?}", event.get("bot_id")));
-                        log_to_file(&format!("bot_profile field: {:?}", event.get("bot_profile")));
-                        log_to_file(&format!("app_id field: {:?}", event.get("app_id")));
+                        debug!("=== MESSAGE EVENT DEBUG ===");
+                        trace!("Full event: {}", serde_json::to_string_pretty(event).unwrap_or_default());
+                        debug!("user field: {:?}", event.get("user"));
+                        debug!("username field: {:?}", event.get("username"));
+                        debug!("bot_id field: {:?}", event.get("bot_id"));
+                        debug!("bot_profile field: {:?}", event.get("bot_profile"));
+                        debug!("app_id field: {:?}", event.get("app_id"));
 
                         // Fetch user name - prioritize user field first (real users), then bot_profile, username, bot_id
                         let user_name = if event.get("user").is_some() && user_id_event != "unknown" {
                             // Regular user - fetch from API (prioritize this over bot_profile)
-                            if let Ok(user_info) = Self::fetch_user_info(http, token, user_id_event).await {
-                                log_to_file(&format!("Using fetched user info: {}", user_info));
+                            if let Ok(user_info) = Self::fetch_user_info(http, token, stats, user_id_event).await {
+                                debug!("Using fetched user info: {}", user_info);
                                 user_info
                             } else {
-                                log_to_file(&format!("Failed to fetch user info, using user_id: {}", user_id_event));
+                                warn!("Failed to fetch user info, using user_id: {}", user_id_event);
                                 user_id_event.to_string()
                             }
                         } else if let Some(bot_profile) = event.get("bot_profile") {
@@ -601,50 +1765,63 @@ impl SlackClient {
                                 .and_then(|n| n.as_str())
                                 .unwrap_or("Bot")
                                 .to_string();
-                            log_to_file(&format!("Using bot_profile.name: {}", name));
+                            debug!("Using bot_profile.name: {}", name);
                             name
                         } else if let Some(username) = event.get("username").and_then(|u| u.as_str()) {
                             // Bot with username field
-                            log_to_file(&format!("Using username field: {}", username));
+                            debug!("Using username field: {}", username);
                             username.to_string()
                         } else if let Some(bot_id) = event.get("bot_id").and_then(|b| b.as_str()) {
                             // Bot message - fetch bot info
-                            log_to_file(&format!("Fetching bot info for bot_id: {}", bot_id));
+                            debug!("Fetching bot info for bot_id: {}", bot_id);
                             let client = SlackClient {
                                 http: http.clone(),
-                                token: token.to_string(),
+                                token: Arc::new(std::sync::RwLock::new(token.to_string())),
                                 user_id: user_id.clone(),
                                 pending_updates: pending_updates.clone(),
+                                updates_ready: Arc::new(Notify::new()),
                                 ws_handle: Arc::new(Mutex::new(None)),
                                 ws_shutdown: Arc::new(Mutex::new(None)),
+                                reconnect_now: Arc::new(Notify::new()),
                                 user_name_cache: Arc::new(Mutex::new(std::collections::HashMap::new())),
+                                user_flags: Arc::new(Mutex::new(std::collections::HashMap::new())),
+                                user_profiles: Arc::new(Mutex::new(Vec::new())),
+                                usergroups: usergroups.clone(),
+                                group_members: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
+                                connection: Arc::new(watch::Sender::new(ConnectionState::Connecting)),
+                                connect_timeout: std::time::Duration::from_secs(NetworkConfig::default().connect_timeout_secs),
+                                missing_scopes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
+                                recent_errors: Arc::new(std::sync::Mutex::new(std::collections::VecDeque::new())),
+                                auth_failure: Arc::new(std::sync::Mutex::new(None)),
+                                stats: stats.clone(),
                             };
                             let bot_name = client.resolve_bot_name(bot_id).await;
-                            log_to_file(&format!("Got bot name: {}", bot_name));
+                            debug!("Got bot name: {}", bot_name);
                             bot_name
                         } else {
-                            log_to_file(&format!("No user info available, using user_id_event: {}", user_id_event));
+                            debug!("No user info available, using user_id_event: {}", user_id_event);
                             user_id_event.to_string()
                         };
-                        log_to_file(&format!("Final user_name: {}", user_name));
+                        debug!("Final user_name: {}", user_name);
 
                         // Extract files from event
-                        log_to_file(&format!("Files field in event: {:?}", event.get("files")));
+                        trace!("Files field in event: {:?}", event.get("files"));
                         let files: Vec<SlackFile> = event
                             .get("files")
                             .and_then(|f| {
-                                log_to_file(&format!("Files JSON: {}", serde_json::to_string_pretty(f).unwrap_or_default()));
+                                trace!("Files JSON: {}", serde_json::to_string_pretty(f).unwrap_or_default());
                                 serde_json::from_value(f.clone()).ok()
                             })
                             .unwrap_or_default();
-                        log_to_file(&format!("Parsed {} files", files.len()));
+                        debug!("Parsed {} files", files.len());
                         for (idx, file) in files.iter().enumerate() {
-                            log_to_file(&format!("  File {}: id={:?}, mimetype={:?}, filetype={:?}, name={:?}", 
-                                idx, file.id, file.mimetype, file.filetype, file.name));
+                            debug!("  File {}: id={:?}, mimetype={:?}, filetype={:?}, name={:?}", 
+                                idx, file.id, file.mimetype, file.filetype, file.name);
                         }
 
                         pending_updates.lock().await.push(SlackUpdate::NewMessage {
                             channel_id: channel_id.to_string(),
+                            user_id: event.get("user").and_then(|v| v.as_str()).map(str::to_string),
                             user_name,
                             text: text.to_string(),
                             ts: ts.to_string(),
@@ -654,6 +1831,148 @@ impl SlackClient {
                             forwarded,
                             mentions_me,
                             files,
+                            is_system,
+                        });
+                    }
+                }
+                "channel_created" => {
+                    // Only the creator is a member of a brand-new channel
+                    let channel = event.get("channel");
+                    let creator = channel.and_then(|c| c.get("creator")).and_then(|v| v.as_str());
+                    let my_id = user_id.lock().await.clone().unwrap_or_default();
+                    if let (Some(id), Some(name), true) = (
+                        channel.and_then(|c| c.get("id")).and_then(|v| v.as_str()),
+                        channel.and_then(|c| c.get("name")).and_then(|v| v.as_str()),
+                        creator == Some(my_id.as_str()),
+                    ) {
+                        pending_updates.lock().await.push(SlackUpdate::ChannelAdded(ChatInfo {
+                            id: id.to_string(),
+                            name: name.to_string(),
+                            username: Some(id.to_string()),
+                            unread: 0,
+                            mentions: 0,
+                            posting_restricted: false,
+                            sharing: Sharing::Internal,
+                            section: ChatSection::Public,
+                        }));
+                    }
+                }
+                "channel_rename" | "group_rename" => {
+                    let channel = event.get("channel");
+                    if let (Some(id), Some(name)) = (
+                        channel.and_then(|c| c.get("id")).and_then(|v| v.as_str()),
+                        channel.and_then(|c| c.get("name")).and_then(|v| v.as_str()),
+                    ) {
+                        pending_updates.lock().await.push(SlackUpdate::ChannelRenamed {
+                            channel_id: id.to_string(),
+                            name: name.to_string(),
+                        });
+                    }
+                }
+                "channel_archive" | "group_archive" => {
+                    if let Some(id) = event.get("channel").and_then(|v| v.as_str()) {
+                        pending_updates.lock().await.push(SlackUpdate::ChannelRemoved {
+                            channel_id: id.to_string(),
+                        });
+                    }
+                }
+                "member_joined_channel" | "member_left_channel" => {
+                    let my_id = user_id.lock().await.clone().unwrap_or_default();
+                    let (Some(member), Some(channel_id)) = (
+                        event.get("user").and_then(|v| v.as_str()),
+                        event.get("channel").and_then(|v| v.as_str()),
+                    ) else {
+                        return;
+                    };
+                    if my_id.is_empty() {
+                        return;
+                    }
+                    if member != my_id {
+                        pending_updates.lock().await.push(SlackUpdate::MemberChanged {
+                            channel_id: channel_id.to_string(),
+                            user_id: member.to_string(),
+                            joined: event_type == "member_joined_channel",
+                        });
+                        return;
+                    }
+                    if event_type == "member_left_channel" {
+                        pending_updates.lock().await.push(SlackUpdate::ChannelRemoved {
+                            channel_id: channel_id.to_string(),
+                        });
+                        return;
+                    }
+                    match Self::fetch_channel_info(http, token, stats, channel_id).await {
+                        Ok(chat) => pending_updates.lock().await.push(SlackUpdate::ChannelAdded(chat)),
+                        Err(e) => warn!("conversations.info for joined channel {} failed: {}", channel_id, e),
+                    }
+                }
+                "im_created" => {
+                    let (Some(other_user), Some(channel_id)) = (
+                        event.get("user").and_then(|v| v.as_str()),
+                        event
+                            .get("channel")
+                            .and_then(|c| c.get("id"))
+                            .and_then(|v| v.as_str()),
+                    ) else {
+                        return;
+                    };
+                    let name = Self::fetch_user_info(http, token, stats, other_user)
+                        .await
+                        .unwrap_or_else(|_| other_user.to_string());
+                    pending_updates.lock().await.push(SlackUpdate::ChannelAdded(ChatInfo {
+                        id: channel_id.to_string(),
+                        name,
+                        username: Some(other_user.to_string()),
+                        unread: 0,
+                        mentions: 0,
+                        posting_restricted: false,
+                        sharing: Sharing::Internal,
+                        section: ChatSection::DirectMessage,
+                    }));
+                }
+                "pin_added" | "pin_removed" => {
+                    // Only pinned messages; pinned files have no place in a pane
+                    let item = event.get("item");
+                    let message = item.and_then(|i| i.get("message"));
+                    let (Some(channel_id), Some(ts)) = (
+                        event.get("channel_id").and_then(|v| v.as_str()),
+                        message.and_then(|m| m.get("ts")).and_then(|v| v.as_str()),
+                    ) else {
+                        return;
+                    };
+                    let user_name = match event.get("user").and_then(|v| v.as_str()) {
+                        Some(user) => Self::fetch_user_info(http, token, stats, user)
+                            .await
+                            .unwrap_or_else(|_| user.to_string()),
+                        None => "Someone".to_string(),
+                    };
+                    pending_updates.lock().await.push(SlackUpdate::MessagePinned {
+                        channel_id: channel_id.to_string(),
+                        user_name,
+                        ts: ts.to_string(),
+                        text: message
+                            .and_then(|m| m.get("text"))
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_default()
+                            .to_string(),
+                        event_ts: event
+                            .get("event_ts")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or(ts)
+                            .to_string(),
+                        pinned: event_type == "pin_added",
+                    });
+                }
+                "star_added" | "star_removed" => {
+                    // Starred conversations; starred messages and files aren't shown anywhere
+                    let item = event.get("item");
+                    let kind = item.and_then(|i| i.get("type")).and_then(|v| v.as_str());
+                    if let (Some("channel" | "group" | "im"), Some(channel_id)) =
+                        (kind, item.and_then(|i| i.get("channel")).and_then(|v| v.as_str()))
+                    {
+                        pending_updates.lock().await.push(SlackUpdate::ChannelStarred {
+                            channel_id: channel_id.to_string(),
+                            starred: event_type == "star_added",
                         });
                     }
                 }
@@ -663,7 +1982,7 @@ impl SlackClient {
                         event.get("user").and_then(|v| v.as_str()),
                     ) {
                         let user_name = if let Ok(user_info) =
-                            Self::fetch_user_info(http, token, user_id).await
+                            Self::fetch_user_info(http, token, stats, user_id).await
                         {
                             user_info
                         } else {
@@ -690,7 +2009,7 @@ impl SlackClient {
             }
         }
         // Fetch and cache
-        let name = Self::fetch_user_info(&self.http, &self.token, user_id)
+        let name = Self::fetch_user_info(&self.http, &self.token(), &self.stats, user_id)
             .await
             .unwrap_or_else(|_| user_id.to_string());
         self.user_name_cache
@@ -700,49 +2019,192 @@ impl SlackClient {
         name
     }
 
+    /// Fill the name cache (and bot/deleted flags) for the whole workspace with a
+    /// paged users.list. Returns the number of users loaded.
+    pub async fn prefetch_users(&self) -> Result<usize> {
+        let mut names = std::collections::HashMap::new();
+        let mut flags = std::collections::HashMap::new();
+        let mut profiles = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            // Cursors are base64 and may contain '+', '/' and '='
+            let mut request = self.http.get("https://slack.com/api/users.list").query(&[("limit", "500")]);
+            if let Some(ref c) = cursor {
+                request = request.query(&[("cursor", c)]);
+            }
+
+            let response: UsersListResponse = self.api_call(request).await?;
+            for user in response.members {
+                flags.insert(
+                    user.id.clone(),
+                    UserFlags {
+                        is_bot: user.is_bot,
+                        deleted: user.deleted,
+                    },
+                );
+                if !user.deleted {
+                    profiles.push(UserSummary::from_user(&user));
+                }
+                let id = user.id.clone();
+                names.insert(id, user_display_name(user));
+            }
+
+            cursor = response
+                .response_metadata
+                .map(|m| m.next_cursor)
+                .filter(|c| !c.trim().is_empty());
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        let count = flags.len();
+        self.user_name_cache.lock().await.extend(names);
+        self.user_flags.lock().await.extend(flags);
+        *self.user_profiles.lock().await = profiles;
+        Ok(count)
+    }
+
+    /// Users whose names, title or email contain `query`. Searches the users.list
+    /// prefetch (loading it if it failed at startup) and falls back to
+    /// users.lookupByEmail for an email address that isn't cached.
+    pub async fn search_users(&self, query: &str) -> Result<Vec<UserSummary>> {
+        if self.user_profiles.lock().await.is_empty() {
+            self.prefetch_users().await?;
+        }
+        let matches: Vec<UserSummary> = self
+            .user_profiles
+            .lock()
+            .await
+            .iter()
+            .filter(|u| u.matches(query))
+            .cloned()
+            .collect();
+        if !matches.is_empty() || !query.contains('@') {
+            return Ok(matches);
+        }
+
+        let url = format!("https://slack.com/api/users.lookupByEmail?email={}", query);
+        match self.api_call::<UserLookupResponse>(self.http.get(&url)).await {
+            Ok(response) => Ok(vec![UserSummary::from_user(&response.user)]),
+            Err(e) if e.downcast_ref::<SlackError>().map(|e| e.code()) == Some("users_not_found") => {
+                Ok(Vec::new())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Active (not deleted) members from the users.list prefetch; empty if it failed.
+    pub async fn user_profiles(&self) -> Vec<UserSummary> {
+        self.user_profiles.lock().await.clone()
+    }
+
+    /// "active" or "away" (users.getPresence).
+    pub async fn get_presence(&self, user_id: &str) -> Result<String> {
+        let url = format!("https://slack.com/api/users.getPresence?user={}", user_id);
+        let response: PresenceResponse = self.api_call(self.http.get(&url)).await?;
+        Ok(response.presence)
+    }
+
+    /// Seed the name cache with names remembered from the last session; fresh names win.
+    pub async fn add_user_names(&self, names: std::collections::HashMap<String, String>) {
+        let mut cache = self.user_name_cache.lock().await;
+        for (id, name) in names {
+            cache.entry(id).or_insert(name);
+        }
+    }
+
     /// Get a snapshot of the user name cache for synchronous lookups.
     pub async fn get_user_name_cache(&self) -> std::collections::HashMap<String, String> {
         self.user_name_cache.lock().await.clone()
     }
 
-    async fn fetch_user_info(http: &HttpClient, token: &str, user_id: &str) -> Result<String> {
-        let response: UserInfoResponse = http
-            .get(&format!(
+    async fn fetch_user_info(http: &HttpClient, token: &str, stats: &ApiStats, user_id: &str) -> Result<String> {
+        let started = std::time::Instant::now();
+        let response = http
+            .get(format!(
                 "https://slack.com/api/users.info?user={}",
                 user_id
             ))
             .bearer_auth(token)
             .send()
-            .await?
-            .json()
             .await?;
+        let status = response.status();
+        let response: UserInfoResponse = response.json().await?;
+        stats.record_response("users.info", started, status, response.ok);
 
         if response.ok {
-            // Prefer display_name > name (username)
-            let display_name = response
-                .user
-                .profile
-                .and_then(|p| p.display_name)
-                .filter(|n| !n.is_empty());
-            Ok(display_name.unwrap_or(response.user.name))
+            Ok(user_display_name(response.user))
         } else {
             Ok(user_id.to_string())
         }
     }
 
+    /// Sidebar entry for a public/private channel or group DM, from conversations.info.
+    async fn fetch_channel_info(
+        http: &HttpClient,
+        token: &str,
+        stats: &ApiStats,
+        channel_id: &str,
+    ) -> Result<ChatInfo> {
+        let started = std::time::Instant::now();
+        let response = http
+            .get(format!(
+                "https://slack.com/api/conversations.info?channel={}",
+                channel_id
+            ))
+            .bearer_auth(token)
+            .send()
+            .await?;
+        let status = response.status();
+        let response: ConversationInfoResponse = response.json().await?;
+        stats.record_response("conversations.info", started, status, response.ok);
+
+        let ch = match response.channel {
+            Some(ch) if response.ok => ch,
+            _ => return Err(anyhow!("conversations.info failed for {}", channel_id)),
+        };
+        let section = if ch.is_mpim {
+            ChatSection::Group
+        } else if ch.is_private || ch.is_group {
+            ChatSection::Private
+        } else {
+            ChatSection::Public
+        };
+        let sharing = ch.sharing();
+        Ok(ChatInfo {
+            name: ch.name.unwrap_or_else(|| ch.id.clone()),
+            username: ch.user.or(Some(ch.id.clone())),
+            id: ch.id,
+            unread: 0,
+            mentions: 0,
+            posting_restricted: ch.is_read_only || ch.is_thread_only,
+            sharing,
+            section,
+        })
+    }
+
     pub async fn is_user_bot(&self, user_id: &str) -> bool {
+        if let Some(flags) = self.user_flags.lock().await.get(user_id) {
+            return flags.is_bot;
+        }
+
+        let started = std::time::Instant::now();
         let resp = self
             .http
-            .get(&format!(
+            .get(format!(
                 "https://slack.com/api/users.info?user={}",
                 user_id
             ))
-            .bearer_auth(&self.token)
+            .bearer_auth(self.token())
             .send()
             .await;
 
         if let Ok(resp) = resp {
+            let status = resp.status();
             if let Ok(info) = resp.json::<UserInfoResponse>().await {
+                self.stats.record_response("users.info", started, status, info.ok);
                 if info.ok {
                     return info.user.is_bot;
                 }
@@ -752,18 +2214,25 @@ impl SlackClient {
     }
 
     pub async fn is_user_deleted(&self, user_id: &str) -> bool {
+        if let Some(flags) = self.user_flags.lock().await.get(user_id) {
+            return flags.deleted;
+        }
+
+        let started = std::time::Instant::now();
         let resp = self
             .http
-            .get(&format!(
+            .get(format!(
                 "https://slack.com/api/users.info?user={}",
                 user_id
             ))
-            .bearer_auth(&self.token)
+            .bearer_auth(self.token())
             .send()
             .await;
 
         if let Ok(resp) = resp {
+            let status = resp.status();
             if let Ok(info) = resp.json::<UserInfoResponse>().await {
+                self.stats.record_response("users.info", started, status, info.ok);
                 if info.ok {
                     return info.user.deleted;
                 }
@@ -782,19 +2251,23 @@ impl SlackClient {
         }
         
         // Fetch bot info
+        let started = std::time::Instant::now();
         let resp = self
             .http
-            .get(&format!(
+            .get(format!(
                 "https://slack.com/api/bots.info?bot={}",
                 bot_id
             ))
-            .bearer_auth(&self.token)
+            .bearer_auth(self.token())
             .send()
             .await;
 
         if let Ok(resp) = resp {
+            let status = resp.status();
             if let Ok(json) = resp.json::<serde_json::Value>().await {
-                if json.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+                let ok = json.get("ok").and_then(|v| v.as_bool()).unwrap_or(false);
+                self.stats.record_response("bots.info", started, status, ok);
+                if ok {
                     if let Some(name) = json.get("bot")
                         .and_then(|b| b.get("name"))
                         .and_then(|n| n.as_str()) {
@@ -813,42 +2286,77 @@ impl SlackClient {
         bot_id.to_string()
     }
 
+    /// Name of a group DM: its members' first names ("Ana, Bo"), without ours. Uses the
+    /// cached members unless `refresh`; empty if only we are left.
+    pub async fn group_dm_name(&self, channel_id: &str, refresh: bool) -> Result<String> {
+        let cached = self.group_members.lock().unwrap().get(channel_id).cloned();
+        let members = match cached {
+            Some(members) if !refresh => members,
+            _ => {
+                let members = self.get_conversation_members(channel_id).await?;
+                self.group_members
+                    .lock()
+                    .unwrap()
+                    .insert(channel_id.to_string(), members.clone());
+                members
+            }
+        };
+        Ok(self.first_names(&members).await)
+    }
+
+    /// Someone joined or left a group DM: its new name, if its members are cached.
+    pub async fn group_member_changed(&self, channel_id: &str, user_id: &str, joined: bool) -> Option<String> {
+        let members = {
+            let mut groups = self.group_members.lock().unwrap();
+            let members = groups.get_mut(channel_id)?;
+            members.retain(|m| m != user_id);
+            if joined {
+                members.push(user_id.to_string());
+            }
+            members.clone()
+        };
+        Some(self.first_names(&members).await)
+    }
+
+    async fn first_names(&self, members: &[String]) -> String {
+        let my_user_id = self.get_my_user_id().await.unwrap_or_default();
+        let mut names = Vec::new();
+        for member in members.iter().filter(|m| **m != my_user_id) {
+            let name = self.resolve_user_name(member).await;
+            names.push(name.split_whitespace().next().unwrap_or(&name).to_string());
+        }
+        names.join(", ")
+    }
+
+    /// Seed the group DM members remembered from the last session.
+    pub fn add_group_members(&self, groups: std::collections::HashMap<String, Vec<String>>) {
+        let mut cached = self.group_members.lock().unwrap();
+        for (id, members) in groups {
+            cached.entry(id).or_insert(members);
+        }
+    }
+
+    /// Snapshot of the group DM members, for the session cache.
+    pub fn group_members(&self) -> std::collections::HashMap<String, Vec<String>> {
+        self.group_members.lock().unwrap().clone()
+    }
+
     pub async fn get_conversation_members(&self, channel_id: &str) -> Result<Vec<String>> {
         let response: ConversationMembersResponse = self
-            .http
-            .get(&format!(
+            .api_call(self.http.get(format!(
                 "https://slack.com/api/conversations.members?channel={}&limit=100",
                 channel_id
-            ))
-            .bearer_auth(&self.token)
-            .send()
-            .await?
-            .json()
+            )))
             .await?;
 
-        if !response.ok {
-            return Err(anyhow!("Failed to fetch conversation members"));
-        }
-
         Ok(response.members)
     }
 
     pub async fn get_conversations(&self) -> Result<Vec<ChatInfo>> {
         let response: ConversationsListResponse = self
-            .http
-            .get("https://slack.com/api/conversations.list?types=public_channel,private_channel,mpim,im&limit=200")
-            .bearer_auth(&self.token)
-            .send()
-            .await?
-            .json()
+            .api_call(self.http.get("https://slack.com/api/conversations.list?types=public_channel,private_channel,mpim,im&limit=200"))
             .await?;
 
-        if !response.ok {
-            return Err(anyhow!("Failed to fetch conversations"));
-        }
-
-        let my_user_id = self.get_my_user_id().await.unwrap_or_default();
-
         let mut chats = Vec::new();
         for ch in response.channels {
             if ch.is_archived {
@@ -890,30 +2398,12 @@ impl SlackClient {
                 ChatSection::Public
             };
 
+            let sharing = ch.sharing();
             let name = match section {
-                ChatSection::Group => {
-                    // Fetch members and build "Name1, Name2" excluding self
-                    match self.get_conversation_members(&ch.id).await {
-                        Ok(members) => {
-                            let mut names = Vec::new();
-                            for mid in &members {
-                                if mid != &my_user_id {
-                                    let n = self.resolve_user_name(mid).await;
-                                    // Use first name only
-                                    let first =
-                                        n.split_whitespace().next().unwrap_or(&n).to_string();
-                                    names.push(first);
-                                }
-                            }
-                            if names.is_empty() {
-                                ch.name.unwrap_or_else(|| ch.id.clone())
-                            } else {
-                                names.join(", ")
-                            }
-                        }
-                        Err(_) => ch.name.unwrap_or_else(|| ch.id.clone()),
-                    }
-                }
+                ChatSection::Group => match self.group_dm_name(&ch.id, false).await {
+                    Ok(name) if !name.is_empty() => name,
+                    _ => ch.name.unwrap_or_else(|| ch.id.clone()),
+                },
                 ChatSection::DirectMessage | ChatSection::Bot => {
                     if let Some(ref user_id) = ch.user {
                         self.resolve_user_name(user_id).await
@@ -929,6 +2419,9 @@ impl SlackClient {
                 name,
                 username: ch.user.or(Some(ch.id)),
                 unread: ch.unread_count.unwrap_or(0),
+                mentions: 0,
+                posting_restricted: ch.is_read_only || ch.is_thread_only,
+                sharing,
                 section,
             });
         }
@@ -943,7 +2436,7 @@ impl SlackClient {
     ) -> Result<Vec<SlackMessage>> {
         let mut all_messages: Vec<SlackMessage> = Vec::new();
         let mut cursor: Option<String> = None;
-        let page_limit = limit.min(200).max(1);
+        let page_limit = limit.clamp(1, 200);
 
         loop {
             let mut url = format!(
@@ -955,18 +2448,9 @@ impl SlackClient {
             }
 
             let response: ConversationHistoryResponse = self
-                .http
-                .get(&url)
-                .bearer_auth(&self.token)
-                .send()
-                .await?
-                .json()
+                .api_call(self.http.get(&url))
                 .await?;
 
-            if !response.ok {
-                return Err(anyhow!("Failed to fetch conversation history"));
-            }
-
             all_messages.extend(response.messages);
             if all_messages.len() >= limit {
                 all_messages.truncate(limit);
@@ -1000,7 +2484,7 @@ impl SlackClient {
     ) -> Result<Vec<SlackMessage>> {
         let mut all_messages: Vec<SlackMessage> = Vec::new();
         let mut cursor: Option<String> = None;
-        let page_limit = limit.min(200).max(1);
+        let page_limit = limit.clamp(1, 200);
 
         loop {
             let mut url = format!(
@@ -1012,18 +2496,9 @@ impl SlackClient {
             }
 
             let response: ConversationHistoryResponse = self
-                .http
-                .get(&url)
-                .bearer_auth(&self.token)
-                .send()
-                .await?
-                .json()
+                .api_call(self.http.get(&url))
                 .await?;
 
-            if !response.ok {
-                return Err(anyhow!("Failed to fetch thread replies"));
-            }
-
             all_messages.extend(response.messages);
             if all_messages.len() >= limit {
                 all_messages.truncate(limit);
@@ -1049,38 +2524,77 @@ impl SlackClient {
         Ok(all_messages)
     }
 
+    /// Post a message and return its `ts`. With `broadcast`, a thread reply is also
+    /// shown in the channel (`reply_broadcast`).
     pub async fn send_message(
         &self,
         channel_id: &str,
         text: &str,
         thread_ts: Option<&str>,
-    ) -> Result<()> {
+        broadcast: bool,
+    ) -> Result<String> {
         let mut payload = serde_json::json!({
             "channel": channel_id,
             "text": text,
         });
         if let Some(ts) = thread_ts {
             payload["thread_ts"] = serde_json::Value::String(ts.to_string());
+            if broadcast {
+                payload["reply_broadcast"] = serde_json::Value::Bool(true);
+            }
         }
 
-        let response: serde_json::Value = self
+        let response: PostMessageResponse = self
+            .api_call(
+                self.http
+                    .post("https://slack.com/api/chat.postMessage")
+                    .json(&payload),
+            )
+            .await?;
+
+        Ok(response.ts)
+    }
+
+    /// Share `content` as a plain-text snippet (files.getUploadURLExternal, upload,
+    /// files.completeUploadExternal). Needs files:write.
+    pub async fn upload_snippet(
+        &self,
+        channel_id: &str,
+        content: &str,
+        filename: &str,
+        thread_ts: Option<&str>,
+    ) -> Result<()> {
+        let length = content.len().to_string();
+        let upload: UploadUrlResponse = self
+            .api_call(self.http.post("https://slack.com/api/files.getUploadURLExternal").query(&[
+                ("filename", filename),
+                ("length", length.as_str()),
+                ("snippet_type", "text"),
+            ]))
+            .await?;
+
+        let response = self
             .http
-            .post("https://slack.com/api/chat.postMessage")
-            .bearer_auth(&self.token)
-            .json(&payload)
+            .post(&upload.upload_url)
+            .body(content.to_string())
             .send()
-            .await?
-            .json()
             .await?;
-
-        if !response
-            .get("ok")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false)
-        {
-            return Err(anyhow!("Failed to send message"));
+        if !response.status().is_success() {
+            return Err(anyhow!("upload failed: {}", response.status()));
         }
 
+        let files = serde_json::json!([{ "id": upload.file_id, "title": filename }]).to_string();
+        let mut params = vec![("files", files.as_str()), ("channel_id", channel_id)];
+        if let Some(ts) = thread_ts {
+            params.push(("thread_ts", ts));
+        }
+        let _: serde_json::Value = self
+            .api_call(
+                self.http
+                    .post("https://slack.com/api/files.completeUploadExternal")
+                    .query(&params),
+            )
+            .await?;
         Ok(())
     }
 
@@ -1091,23 +2605,12 @@ impl SlackClient {
             "name": emoji,
         });
 
-        let response: serde_json::Value = self
-            .http
-            .post("https://slack.com/api/reactions.add")
-            .bearer_auth(&self.token)
-            .json(&payload)
-            .send()
-            .await?
-            .json()
-            .await?;
-
-        if !response
-            .get("ok")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false)
-        {
-            return Err(anyhow!("Failed to add reaction"));
-        }
+        self.api_call::<serde_json::Value>(
+            self.http
+                .post("https://slack.com/api/reactions.add")
+                .json(&payload),
+        )
+        .await?;
 
         Ok(())
     }
@@ -1117,150 +2620,266 @@ impl SlackClient {
             "channel": channel_id,
         });
 
-        let response: serde_json::Value = self
-            .http
-            .post("https://slack.com/api/conversations.leave")
-            .bearer_auth(&self.token)
-            .json(&payload)
-            .send()
-            .await?
-            .json()
+        self.api_call::<serde_json::Value>(
+            self.http
+                .post("https://slack.com/api/conversations.leave")
+                .json(&payload),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_permalink(&self, channel_id: &str, ts: &str) -> Result<String> {
+        let response: PermalinkResponse = self
+            .api_call(
+                self.http
+                    .get("https://slack.com/api/chat.getPermalink")
+                    .query(&[("channel", channel_id), ("message_ts", ts)]),
+            )
             .await?;
+        Ok(response.permalink)
+    }
 
-        if !response
-            .get("ok")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false)
-        {
-            return Err(anyhow!("Failed to leave conversation"));
-        }
+    /// Star (or unstar) a conversation on Slack (stars.add / stars.remove, needs stars:write).
+    pub async fn set_channel_starred(&self, channel_id: &str, starred: bool) -> Result<()> {
+        let method = if starred { "stars.add" } else { "stars.remove" };
+        self.api_call::<serde_json::Value>(
+            self.http
+                .post(format!("https://slack.com/api/{}", method))
+                .query(&[("channel", channel_id)]),
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_channel_details(&self, channel_id: &str) -> Result<ChannelDetails> {
+        let url = format!(
+            "https://slack.com/api/conversations.info?channel={}&include_num_members=true",
+            channel_id
+        );
+        let response: ChannelDetailsResponse = self.api_call(self.http.get(&url)).await?;
+        Ok(response.channel)
+    }
+
+    /// Messages matching `query`, newest first, and the total number of hits
+    /// (search.messages). Slack operators like `from:@name` or `in:#channel` are passed
+    /// through as typed. Needs a user token with search:read.
+    pub async fn search_messages(&self, query: &str, count: usize) -> Result<(Vec<SearchMatch>, u32)> {
+        let count = count.to_string();
+        let response: SearchResponse = self
+            .api_call(self.http.get("https://slack.com/api/search.messages").query(&[
+                ("query", query),
+                ("count", count.as_str()),
+                ("sort", "timestamp"),
+                ("sort_dir", "desc"),
+            ]))
+            .await?;
+        Ok((response.messages.matches, response.messages.total))
+    }
+
+    /// Files shared in a conversation, newest first (files.list, needs files:read).
+    pub async fn list_channel_files(&self, channel_id: &str, count: usize) -> Result<Vec<SharedFile>> {
+        let url = format!(
+            "https://slack.com/api/files.list?channel={}&count={}",
+            channel_id, count
+        );
+        let response: FilesListResponse = self.api_call(self.http.get(&url)).await?;
+        Ok(response.files)
+    }
+
+    /// Move the read cursor of a conversation to `ts` (conversations.mark).
+    pub async fn mark_conversation(&self, channel_id: &str, ts: &str) -> Result<()> {
+        let payload = serde_json::json!({
+            "channel": channel_id,
+            "ts": ts,
+        });
+
+        self.api_call::<serde_json::Value>(
+            self.http
+                .post("https://slack.com/api/conversations.mark")
+                .json(&payload),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Open (or fetch the existing) direct message with a user and return its channel ID.
+    pub async fn open_dm(&self, user_id: &str) -> Result<String> {
+        let payload = serde_json::json!({
+            "users": user_id,
+        });
+
+        let response: ConversationOpenResponse = self
+            .api_call(
+                self.http
+                    .post("https://slack.com/api/conversations.open")
+                    .json(&payload),
+            )
+            .await?;
+
+        Ok(response.channel.id)
+    }
+
+    pub async fn invite_to_conversation(&self, channel_id: &str, user_id: &str) -> Result<()> {
+        let payload = serde_json::json!({
+            "channel": channel_id,
+            "users": user_id,
+        });
+
+        self.api_call::<serde_json::Value>(
+            self.http
+                .post("https://slack.com/api/conversations.invite")
+                .json(&payload),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn kick_from_conversation(&self, channel_id: &str, user_id: &str) -> Result<()> {
+        let payload = serde_json::json!({
+            "channel": channel_id,
+            "user": user_id,
+        });
+
+        self.api_call::<serde_json::Value>(
+            self.http
+                .post("https://slack.com/api/conversations.kick")
+                .json(&payload),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Set the user's presence: `away = true` for away, `false` for auto (active).
+    pub async fn set_presence(&self, away: bool) -> Result<()> {
+        let payload = serde_json::json!({
+            "presence": if away { "away" } else { "auto" },
+        });
+
+        self.api_call::<serde_json::Value>(
+            self.http
+                .post("https://slack.com/api/users.setPresence")
+                .json(&payload),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Current Do Not Disturb snooze (dnd.info, needs dnd:read).
+    pub async fn get_dnd_info(&self) -> Result<DndInfo> {
+        self.api_call(self.http.get("https://slack.com/api/dnd.info")).await
+    }
+
+    /// Snooze notifications for `minutes` (dnd.setSnooze, needs dnd:write).
+    pub async fn set_snooze(&self, minutes: u64) -> Result<DndInfo> {
+        let minutes = minutes.to_string();
+        self.api_call(
+            self.http
+                .post("https://slack.com/api/dnd.setSnooze")
+                .query(&[("num_minutes", minutes.as_str())]),
+        )
+        .await
+    }
 
+    pub async fn end_snooze(&self) -> Result<()> {
+        self.api_call::<serde_json::Value>(self.http.post("https://slack.com/api/dnd.endSnooze"))
+            .await?;
         Ok(())
     }
 
+    /// Resolves once updates have been queued since the last wait (see `get_pending_updates`).
+    pub async fn updates_ready(&self) {
+        self.updates_ready.notified().await;
+    }
+
     pub async fn get_pending_updates(&self) -> Vec<SlackUpdate> {
         let mut updates = self.pending_updates.lock().await;
         std::mem::take(&mut *updates)
     }
 
     #[allow(dead_code)]
-    pub async fn download_file(&self, file_id: &str, _channel_id: &str) -> Result<std::path::PathBuf> {
-        use std::fs::OpenOptions;
+    pub async fn download_file(&self, file_id: &str, _channel_id: &str, dir: &std::path::Path) -> Result<std::path::PathBuf> {
         use std::io::Write;
         
-        let log_to_file = |msg: &str| {
-            if let Ok(mut file) = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open("/tmp/slack_rust_debug.log")
-            {
-                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                let _ = writeln!(file, "[{}] {}", timestamp, msg);
-            }
-        };
-        
-        log_to_file(&format!("=== DOWNLOAD FILE DEBUG ==="));
-        log_to_file(&format!("file_id: {}", file_id));
+        debug!("=== DOWNLOAD FILE DEBUG ===");
+        debug!("file_id: {}", file_id);
         
         // First, get file info to get the download URL
         let file_info_url = format!("https://slack.com/api/files.info?file={}", file_id);
-        log_to_file(&format!("Requesting file info from: {}", file_info_url));
+        debug!("Requesting file info from: {}", file_info_url);
         
-        let file_info_response: serde_json::Value = self
-            .http
-            .get(&file_info_url)
-            .bearer_auth(&self.token)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let file_info_response: serde_json::Value = self.api_call(self.http.get(&file_info_url)).await?;
 
-        log_to_file(&format!("File info response: {}", serde_json::to_string_pretty(&file_info_response).unwrap_or_default()));
-
-        if !file_info_response.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
-            let error = file_info_response.get("error").and_then(|v| v.as_str()).unwrap_or("unknown");
-            log_to_file(&format!("Failed to get file info: {}", error));
-            return Err(anyhow!("Failed to get file info: {}", error));
-        }
+        trace!("File info response: {}", serde_json::to_string_pretty(&file_info_response).unwrap_or_default());
 
         let file = file_info_response.get("file").ok_or_else(|| {
-            log_to_file("No file data in response");
+            debug!("No file data in response");
             anyhow!("No file data")
         })?;
         
-        log_to_file(&format!("File data: {}", serde_json::to_string_pretty(file).unwrap_or_default()));
+        trace!("File data: {}", serde_json::to_string_pretty(file).unwrap_or_default());
         
         let url_private = file.get("url_private")
             .and_then(|v| v.as_str())
             .ok_or_else(|| {
-                log_to_file("No url_private in file data");
+                debug!("No url_private in file data");
                 anyhow!("No download URL")
             })?;
         
-        log_to_file(&format!("Download URL: {}", url_private));
+        debug!("Download URL: {}", url_private);
         
         let file_name = file.get("name")
             .and_then(|v| v.as_str())
             .unwrap_or("file");
         
-        log_to_file(&format!("File name: {}", file_name));
+        debug!("File name: {}", file_name);
         
-        // Create store directory if it doesn't exist
-        let store_dir = std::path::Path::new("store");
-        log_to_file(&format!("Creating store directory: {:?}", store_dir));
-        std::fs::create_dir_all(store_dir)?;
+        debug!("Creating download directory: {:?}", dir);
+        std::fs::create_dir_all(dir)?;
         
         // Download the file
-        log_to_file("Starting file download...");
+        debug!("Starting file download...");
         let response = self
             .http
             .get(url_private)
-            .bearer_auth(&self.token)
+            .bearer_auth(self.token())
+            .timeout(DOWNLOAD_TIMEOUT)
             .send()
             .await?;
         
-        log_to_file(&format!("Download response status: {}", response.status()));
+        debug!("Download response status: {}", response.status());
         
         if !response.status().is_success() {
-            log_to_file(&format!("Download failed with status: {}", response.status()));
+            warn!("Download failed with status: {}", response.status());
             return Err(anyhow!("Failed to download file: {}", response.status()));
         }
         
-        let file_path = store_dir.join(file_name);
-        log_to_file(&format!("Saving file to: {:?}", file_path));
+        let file_path = crate::utils::unique_path(dir, file_name);
+        debug!("Saving file to: {:?}", file_path);
         
         let mut file = std::fs::File::create(&file_path)?;
         let bytes = response.bytes().await?;
-        log_to_file(&format!("Received {} bytes", bytes.len()));
+        debug!("Received {} bytes", bytes.len());
         
         file.write_all(&bytes)?;
-        log_to_file(&format!("File saved successfully to: {:?}", file_path));
+        debug!("File saved successfully to: {:?}", file_path);
         
         Ok(file_path)
     }
 
     /// Extract redirect URL from HTML response (handles meta refresh, window.location, etc.)
     fn extract_redirect_from_html(html: &str) -> Option<String> {
-        use std::fs::OpenOptions;
-        use std::io::Write;
-        
-        let log_to_file = |msg: &str| {
-            if let Ok(mut file) = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open("/tmp/slack_rust_debug.log")
-            {
-                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                let _ = writeln!(file, "[{}] {}", timestamp, msg);
-            }
-        };
-        
-        log_to_file("=== EXTRACT REDIRECT FROM HTML ===");
+        debug!("=== EXTRACT REDIRECT FROM HTML ===");
         
         // First, try to find URL in JSON data (data-props, entryPoint, etc.)
         // Look for "entryPoint":"https:\/\/files.slack.com...
         if let Some(entry_start) = html.find("\"entryPoint\"") {
-            log_to_file("Found entryPoint in JSON data");
+            debug!("Found entryPoint in JSON data");
             let after_entry = &html[entry_start..];
             // Look for the URL after entryPoint
             if let Some(url_start_pos) = after_entry.find("https:\\/\\/files.slack.com") {
@@ -1284,7 +2903,7 @@ impl SlackClient {
                 let mut url = escaped_url.replace("\\/", "/").replace("\\\"", "\"").replace("\\'", "'");
                 // Remove any trailing HTML entities or quotes
                 url = url.trim_end_matches("&quot;").trim_end_matches("&amp;").trim_end_matches('"').trim_end_matches('\'').to_string();
-                log_to_file(&format!("Found URL in entryPoint: {}", url));
+                debug!("Found URL in entryPoint: {}", url);
                 if url.starts_with("https://files.slack.com") && !url.contains("/beacon/") && !url.contains("/tracking/") {
                     return Some(url);
                 }
@@ -1293,7 +2912,7 @@ impl SlackClient {
         
         // Also look for escaped https://files.slack.com directly
         if let Some(start) = html.find("https:\\/\\/files.slack.com") {
-            log_to_file("Found escaped https://files.slack.com");
+            debug!("Found escaped https://files.slack.com");
             let url_part = &html[start..];
             let mut url_end = url_part.len();
             for (i, c) in url_part.char_indices() {
@@ -1316,7 +2935,7 @@ impl SlackClient {
             let mut url = escaped_url.replace("\\/", "/").replace("\\\"", "\"").replace("\\'", "'");
             // Remove any trailing HTML entities or quotes
             url = url.trim_end_matches("&quot;").trim_end_matches("&amp;").trim_end_matches('"').trim_end_matches('\'').to_string();
-            log_to_file(&format!("Found escaped URL: {}", url));
+            debug!("Found escaped URL: {}", url);
             if url.starts_with("https://files.slack.com") && !url.contains("/beacon/") && !url.contains("/tracking/") {
                 return Some(url);
             }
@@ -1347,7 +2966,7 @@ impl SlackClient {
             }
             
             if found_protocol {
-                log_to_file(&format!("Found protocol at position {}", url_start));
+                debug!("Found protocol at position {}", url_start);
                 // Find the end of the URL (until quote, space, or other delimiter)
                 let url_part = &html[url_start..];
                 let mut url_end = url_part.len();
@@ -1359,26 +2978,26 @@ impl SlackClient {
                     }
                 }
                 let url = url_part[..url_end].to_string();
-                log_to_file(&format!("Found potential URL: {}", url));
+                debug!("Found potential URL: {}", url);
                 
                 // Filter out tracking URLs - accept any files.slack.com URL that's not tracking
                 if !url.contains("/beacon/") && !url.contains("/tracking/") && 
                    !url.contains("/analytics/") && !url.contains("/api/") {
                     // Unescape the URL if needed
                     let unescaped_url = url.replace("\\/", "/").replace("\\\"", "\"").replace("\\'", "'");
-                    log_to_file(&format!("Unescaped URL: {}", unescaped_url));
+                    debug!("Unescaped URL: {}", unescaped_url);
                     // Make sure it's a valid URL
                     if unescaped_url.starts_with("http://") || unescaped_url.starts_with("https://") {
-                        log_to_file(&format!("Returning valid URL: {}", unescaped_url));
+                        debug!("Returning valid URL: {}", unescaped_url);
                         return Some(unescaped_url);
                     } else {
-                        log_to_file(&format!("URL doesn't start with http:// or https://"));
+                        debug!("URL doesn't start with http:// or https://");
                     }
                 } else {
-                    log_to_file(&format!("URL filtered out (contains tracking/beacon/analytics/api)"));
+                    debug!("URL filtered out (contains tracking/beacon/analytics/api)");
                 }
             } else {
-                log_to_file(&format!("Could not find protocol before files.slack.com at position {}", absolute_start));
+                debug!("Could not find protocol before files.slack.com at position {}", absolute_start);
             }
             
             // Move search forward
@@ -1460,31 +3079,21 @@ impl SlackClient {
             }
         }
         
-        log_to_file("No valid files.slack.com URL found in HTML");
+        debug!("No valid files.slack.com URL found in HTML");
         None
     }
 
-    pub async fn download_file_from_url(&self, url: &str, file_name: &str) -> Result<std::path::PathBuf> {
+    pub async fn download_file_from_url(&self, url: &str, file_name: &str, dir: &std::path::Path,
+        on_progress: &DownloadProgress,
+    ) -> Result<std::path::PathBuf> {
         use std::collections::HashSet;
         
         let mut redirect_count = 0;
         let mut current_url = url.to_string();
         let mut tried_urls = HashSet::new();
         
-        use std::fs::OpenOptions;
         use std::io::Write;
         
-        let log_to_file = |msg: &str| {
-            if let Ok(mut file) = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open("/tmp/slack_rust_debug.log")
-            {
-                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                let _ = writeln!(file, "[{}] {}", timestamp, msg);
-            }
-        };
-        
         loop {
             if redirect_count > 5 {
                 return Err(anyhow!("Too many redirects (max 5)"));
@@ -1492,45 +3101,44 @@ impl SlackClient {
             
             // Check if we've already tried this URL (avoid infinite loops)
             if tried_urls.contains(&current_url) {
-                log_to_file(&format!("URL redirect loop detected: already tried {}", current_url));
+                debug!("URL redirect loop detected: already tried {}", current_url);
                 return Err(anyhow!("URL redirect loop detected. The file URL requires authentication that we cannot provide. Try adding 'files:write:user' scope to your Slack app for direct file downloads."));
             }
             tried_urls.insert(current_url.clone());
             
-            log_to_file(&format!("=== DOWNLOAD FILE FROM URL DEBUG (redirect {}) ===", redirect_count));
-            log_to_file(&format!("URL: {}", current_url));
-            log_to_file(&format!("File name: {}", file_name));
+            debug!("=== DOWNLOAD FILE FROM URL DEBUG (redirect {}) ===", redirect_count);
+            debug!("URL: {}", current_url);
+            debug!("File name: {}", file_name);
             
-            // Create store directory if it doesn't exist
-            let store_dir = std::path::Path::new("store");
             if redirect_count == 0 {
-                log_to_file(&format!("Creating store directory: {:?}", store_dir));
-                std::fs::create_dir_all(store_dir)?;
+                debug!("Creating download directory: {:?}", dir);
+                std::fs::create_dir_all(dir)?;
             }
             
             // Download the file directly from URL
-            log_to_file("Starting file download from URL...");
+            debug!("Starting file download from URL...");
             let request = self
                 .http
                 .get(&current_url)
-                .bearer_auth(&self.token)
+                .bearer_auth(self.token())
                 .header("Accept", "*/*")
-                .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36");
+                .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36")
+                .timeout(DOWNLOAD_TIMEOUT);
             
             // If this is a redirect, try to preserve cookies from previous request
             // (reqwest Client should handle this automatically, but we can be explicit)
             let response = request.send().await?;
         
-        log_to_file(&format!("Download response status: {}", response.status()));
+        debug!("Download response status: {}", response.status());
         
         // Log response headers
         let headers = response.headers();
-        log_to_file("Response headers:");
+        debug!("Response headers:");
         for (name, value) in headers.iter() {
             if let Ok(value_str) = value.to_str() {
-                log_to_file(&format!("  {}: {}", name, value_str));
+                debug!("  {}: {}", name, value_str);
             } else {
-                log_to_file(&format!("  {}: <binary>", name));
+                debug!("  {}: <binary>", name);
             }
         }
         
@@ -1538,15 +3146,15 @@ impl SlackClient {
         let content_type = headers.get("content-type")
             .and_then(|v| v.to_str().ok())
             .unwrap_or("");
-        log_to_file(&format!("Content-Type: {}", content_type));
+        debug!("Content-Type: {}", content_type);
         
             if content_type.contains("text/html") {
-                log_to_file("WARNING: Received HTML instead of file. Attempting to extract redirect URL from HTML...");
+                debug!("WARNING: Received HTML instead of file. Attempting to extract redirect URL from HTML...");
                 
                 // Read the HTML response
                 let html_bytes = response.bytes().await?;
                 let html = String::from_utf8_lossy(&html_bytes);
-                log_to_file(&format!("HTML response (first 1000 chars): {}", &html.chars().take(1000).collect::<String>()));
+                debug!("HTML response (first 1000 chars): {}", &html.chars().take(1000).collect::<String>());
                 
                 // Also log if we can find any files.slack.com URLs in the HTML
                 let mut search_pos = 0;
@@ -1557,38 +3165,38 @@ impl SlackClient {
                     let start = absolute_pos.saturating_sub(100);
                     let end = (absolute_pos + 200).min(html.len());
                     let context = &html[start..end];
-                    log_to_file(&format!("Context around files.slack.com #{}: ...{}...", occurrence_count, context));
+                    trace!("Context around files.slack.com #{}: ...{}...", occurrence_count, context);
                     search_pos = absolute_pos + 1;
                     if search_pos >= html.len() {
                         break;
                     }
                 }
-                log_to_file(&format!("Found {} mentions of 'files.slack.com' in HTML", occurrence_count));
+                debug!("Found {} mentions of 'files.slack.com' in HTML", occurrence_count);
                 
                 // Also try to find the URL in a different way - look for the file ID pattern
                 if let Some(file_id_pos) = html.find("F0ACD4WMTV2") {
                     let start = file_id_pos.saturating_sub(50);
                     let end = (file_id_pos + 150).min(html.len());
                     let context = &html[start..end];
-                    log_to_file(&format!("Context around file ID: ...{}...", context));
+                    trace!("Context around file ID: ...{}...", context);
                 }
                 
                 // Try to find a redirect URL in the HTML (common patterns)
                 // Look for meta refresh, window.location, or direct download links
                 if let Some(redirect_url) = Self::extract_redirect_from_html(&html) {
-                    log_to_file(&format!("Found redirect URL in HTML: {}", redirect_url));
+                    debug!("Found redirect URL in HTML: {}", redirect_url);
                     // Update URL and continue loop
                     current_url = redirect_url;
                     redirect_count += 1;
                     continue;
                 }
                 
-                log_to_file("ERROR: Could not extract redirect URL from HTML.");
+                warn!("ERROR: Could not extract redirect URL from HTML.");
                 return Err(anyhow!("Received HTML response instead of file, and could not find redirect URL."));
             }
             
             if !response.status().is_success() {
-                log_to_file(&format!("Download failed with status: {}", response.status()));
+                warn!("Download failed with status: {}", response.status());
                 return Err(anyhow!("Failed to download file: {}", response.status()));
             }
             
@@ -1598,80 +3206,66 @@ impl SlackClient {
                 .map(|c| if c.is_control() || c == '/' || c == '\\' { '_' } else { c })
                 .collect::<String>();
             
-            let file_path = store_dir.join(&sanitized_name);
-            log_to_file(&format!("Saving file to: {:?} (sanitized from: {})", file_path, file_name));
+            let file_path = crate::utils::unique_path(dir, &sanitized_name);
+            debug!("Saving file to: {:?} (sanitized from: {})", file_path, file_name);
             
-            // Read all bytes and write to file
-            let bytes = response.bytes().await?;
-            log_to_file(&format!("Received {} bytes", bytes.len()));
-            
-            // Check first few bytes to verify it's valid
-            if bytes.len() >= 8 {
-                let header = &bytes[0..8.min(bytes.len())];
-                log_to_file(&format!("File header (first {} bytes): {:?}", header.len(), header));
-                
-                // Verify it's not HTML
-                if header.starts_with(b"<!DOCTYPE") || header.starts_with(b"<html") {
-                    log_to_file("ERROR: File appears to be HTML, not a binary file!");
-                    return Err(anyhow!("Downloaded file appears to be HTML, not the actual file."));
+            let total = response.content_length();
+            let mut response = response;
+            let mut file = std::fs::File::create(&file_path)?;
+            let mut received: u64 = 0;
+            let mut last_reported: u64 = 0;
+            on_progress(0, total);
+
+            // Stream to disk so large files never sit in memory
+            let streamed: Result<()> = async {
+                while let Some(chunk) = response.chunk().await? {
+                    // The first bytes tell whether Slack sent a login page instead of the file
+                    if received == 0
+                        && (chunk.starts_with(b"<!DOCTYPE") || chunk.starts_with(b"<html"))
+                    {
+                        warn!("ERROR: File appears to be HTML, not a binary file!");
+                        return Err(anyhow!("Downloaded file appears to be HTML, not the actual file."));
+                    }
+                    file.write_all(&chunk)?;
+                    received += chunk.len() as u64;
+                    if received - last_reported >= PROGRESS_STEP_BYTES {
+                        last_reported = received;
+                        on_progress(received, total);
+                    }
                 }
+                file.sync_all()?; // Ensure all data is written to disk
+                Ok(())
             }
-            
-            let mut file = std::fs::File::create(&file_path)?;
-            file.write_all(&bytes)?;
-            file.sync_all()?; // Ensure all data is written to disk
-            log_to_file(&format!("File saved successfully to: {:?}", file_path));
+            .await;
+            if let Err(e) = streamed {
+                let _ = std::fs::remove_file(&file_path);
+                return Err(e);
+            }
+            on_progress(received, total);
+            debug!("Received {} bytes", received);
+            debug!("File saved successfully to: {:?}", file_path);
             
             return Ok(file_path);
         }
     }
 
-    pub async fn get_shared_public_url(&self, file_id: &str, file_name: &str) -> Result<std::path::PathBuf> {
-        use std::fs::OpenOptions;
-        use std::io::Write;
-        
-        let log_to_file = |msg: &str| {
-            if let Ok(mut file) = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open("/tmp/slack_rust_debug.log")
-            {
-                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                let _ = writeln!(file, "[{}] {}", timestamp, msg);
-            }
-        };
-        
-        log_to_file(&format!("=== GET SHARED PUBLIC URL DEBUG ==="));
-        log_to_file(&format!("file_id: {}, file_name: {}", file_id, file_name));
+    pub async fn get_shared_public_url(&self, file_id: &str, file_name: &str, dir: &std::path::Path,
+        on_progress: &DownloadProgress,
+    ) -> Result<std::path::PathBuf> {
+        debug!("=== GET SHARED PUBLIC URL DEBUG ===");
+        debug!("file_id: {}, file_name: {}", file_id, file_name);
         
         // Use files.sharedPublicURL API to get a direct download URL
         let share_url = format!("https://slack.com/api/files.sharedPublicURL?file={}", file_id);
-        log_to_file(&format!("Requesting shared public URL from: {}", share_url));
+        debug!("Requesting shared public URL from: {}", share_url);
         
-        let share_response: serde_json::Value = self
-            .http
-            .get(&share_url)
-            .bearer_auth(&self.token)
-            .send()
-            .await?
-            .json()
-            .await?;
-
-        log_to_file(&format!("Share response: {}", serde_json::to_string_pretty(&share_response).unwrap_or_default()));
+        let share_response: serde_json::Value = self.api_call(self.http.get(&share_url)).await?;
 
-        if !share_response.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
-            let error = share_response.get("error").and_then(|v| v.as_str()).unwrap_or("unknown");
-            let needed = share_response.get("needed").and_then(|v| v.as_str()).unwrap_or("");
-            log_to_file(&format!("Failed to get shared public URL: {} (needed: {})", error, needed));
-            if error == "missing_scope" {
-                return Err(anyhow!("Missing scope '{}'. Please add this scope to your Slack app's OAuth scopes and reinstall the app.", needed));
-            }
-            return Err(anyhow!("Failed to get shared public URL: {}", error));
-        }
+        trace!("Share response: {}", serde_json::to_string_pretty(&share_response).unwrap_or_default());
 
         // Get the download URL from the share response
         let file = share_response.get("file").ok_or_else(|| {
-            log_to_file("No file data in share response");
+            debug!("No file data in share response");
             anyhow!("No file data in share response")
         })?;
         
@@ -1681,58 +3275,33 @@ impl SlackClient {
             .or_else(|| file.get("url_private"))
             .and_then(|v| v.as_str())
             .ok_or_else(|| {
-                log_to_file("No download URL in share response");
+                debug!("No download URL in share response");
                 anyhow!("No download URL in share response")
             })?;
         
-        log_to_file(&format!("Got download URL from share: {}", download_url));
+        debug!("Got download URL from share: {}", download_url);
         
         // Now download the file
-        self.download_file_from_url(download_url, file_name).await
+        self.download_file_from_url(download_url, file_name, dir, on_progress).await
     }
 
     #[allow(dead_code)]
-    pub async fn download_file_by_id(&self, file_id: &str, file_name: &str) -> Result<std::path::PathBuf> {
-        use std::fs::OpenOptions;
-        use std::io::Write;
-        
-        let log_to_file = |msg: &str| {
-            if let Ok(mut file) = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open("/tmp/slack_rust_debug.log")
-            {
-                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                let _ = writeln!(file, "[{}] {}", timestamp, msg);
-            }
-        };
-        
-        log_to_file(&format!("=== DOWNLOAD FILE BY ID DEBUG ==="));
-        log_to_file(&format!("file_id: {}, file_name: {}", file_id, file_name));
+    pub async fn download_file_by_id(&self, file_id: &str, file_name: &str, dir: &std::path::Path,
+        on_progress: &DownloadProgress,
+    ) -> Result<std::path::PathBuf> {
+        debug!("=== DOWNLOAD FILE BY ID DEBUG ===");
+        debug!("file_id: {}, file_name: {}", file_id, file_name);
         
         // Get file info to get url_private_download
         let file_info_url = format!("https://slack.com/api/files.info?file={}", file_id);
-        log_to_file(&format!("Requesting file info from: {}", file_info_url));
+        debug!("Requesting file info from: {}", file_info_url);
         
-        let file_info_response: serde_json::Value = self
-            .http
-            .get(&file_info_url)
-            .bearer_auth(&self.token)
-            .send()
-            .await?
-            .json()
-            .await?;
+        let file_info_response: serde_json::Value = self.api_call(self.http.get(&file_info_url)).await?;
 
-        log_to_file(&format!("File info response: {}", serde_json::to_string_pretty(&file_info_response).unwrap_or_default()));
-
-        if !file_info_response.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
-            let error = file_info_response.get("error").and_then(|v| v.as_str()).unwrap_or("unknown");
-            log_to_file(&format!("Failed to get file info: {}", error));
-            return Err(anyhow!("Failed to get file info: {}", error));
-        }
+        trace!("File info response: {}", serde_json::to_string_pretty(&file_info_response).unwrap_or_default());
 
         let file = file_info_response.get("file").ok_or_else(|| {
-            log_to_file("No file data in response");
+            debug!("No file data in response");
             anyhow!("No file data")
         })?;
         
@@ -1741,54 +3310,196 @@ impl SlackClient {
             .or_else(|| file.get("url_private"))
             .and_then(|v| v.as_str())
             .ok_or_else(|| {
-                log_to_file("No download URL in file data");
+                debug!("No download URL in file data");
                 anyhow!("No download URL")
             })?;
         
-        log_to_file(&format!("Got download URL: {}", download_url));
+        debug!("Got download URL: {}", download_url);
         
         // Now download the file
-        self.download_file_from_url(download_url, file_name).await
+        self.download_file_from_url(download_url, file_name, dir, on_progress).await
     }
 
     /// Gracefully shutdown the background WebSocket task.
     pub async fn shutdown(&self) {
-        let _ = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("/tmp/slack_rust_debug.log")
-            .and_then(|mut f| {
-                use std::io::Write;
-                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                writeln!(f, "[{}] shutdown() called", timestamp)
-            });
+        debug!("shutdown() called");
         
         // Send shutdown signal to gracefully close WebSocket
         if let Some(tx) = self.ws_shutdown.lock().await.take() {
             let _ = tx.send(());
-            let _ = std::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open("/tmp/slack_rust_debug.log")
-                .and_then(|mut f| {
-                    use std::io::Write;
-                    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                    writeln!(f, "[{}] Shutdown signal sent", timestamp)
-                });
+            debug!("Shutdown signal sent");
         }
         
         // Wait for the task to finish (with timeout)
         if let Some(handle) = self.ws_handle.lock().await.take() {
             let _ = tokio::time::timeout(std::time::Duration::from_secs(2), handle).await;
-            let _ = std::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open("/tmp/slack_rust_debug.log")
-                .and_then(|mut f| {
-                    use std::io::Write;
-                    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                    writeln!(f, "[{}] WebSocket task finished", timestamp)
-                });
+            debug!("WebSocket task finished");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_mentions_me() {
+        let groups = vec!["S111".to_string()];
+        assert!(text_mentions_me("hi <@U1>", "U1", &groups));
+        assert!(text_mentions_me("hi <@U1|me>", "U1", &groups));
+        assert!(text_mentions_me("<!here> deploy", "U1", &groups));
+        assert!(text_mentions_me("<!channel|channel> ping", "U1", &groups));
+        assert!(text_mentions_me("<!everyone>", "U1", &groups));
+        assert!(text_mentions_me("<!subteam^S111|@oncall> help", "U1", &groups));
+        assert!(!text_mentions_me("<!subteam^S222|@other> help", "U1", &groups));
+        assert!(!text_mentions_me("hi <@U2>", "U1", &groups));
+        assert!(!text_mentions_me("<!here>", "", &groups));
+    }
+
+    #[test]
+    fn test_is_system_subtype() {
+        assert!(is_system_subtype("channel_join"));
+        assert!(is_system_subtype("group_topic"));
+        assert!(!is_system_subtype("bot_message"));
+        assert!(!is_system_subtype("thread_broadcast"));
+    }
+
+    #[test]
+    fn test_slack_error_from_response() {
+        let body = serde_json::json!({"ok": false, "error": "missing_scope", "needed": "chat:write"});
+        let err = SlackError::from_response(&body, None);
+        assert_eq!(err, SlackError::MissingScope { needed: "chat:write".to_string() });
+        assert_eq!(err.code(), "missing_scope");
+
+        let body = serde_json::json!({"ok": false, "error": "ratelimited"});
+        let err = SlackError::from_response(&body, Some(30));
+        assert_eq!(err.to_string(), "rate limited by Slack, retry in 30s");
+
+        let body = serde_json::json!({"ok": false, "error": "restricted_action"});
+        let err = SlackError::from_response(&body, None);
+        assert_eq!(err, SlackError::NotAllowed("restricted_action".to_string()));
+        assert_eq!(err.code(), "restricted_action");
+
+        let body = serde_json::json!({"ok": false, "error": "is_archived"});
+        assert_eq!(SlackError::from_response(&body, None), SlackError::Api("is_archived".to_string()));
+    }
+
+    #[test]
+    fn test_rotated_token() {
+        let bot = serde_json::json!({
+            "ok": true, "token_type": "bot", "access_token": "xoxe.xoxb-1-a",
+            "refresh_token": "xoxe-1-b", "expires_in": 43200
+        });
+        assert_eq!(
+            RotatedToken::from_response(&bot, 1000).unwrap(),
+            RotatedToken {
+                access_token: "xoxe.xoxb-1-a".to_string(),
+                refresh_token: "xoxe-1-b".to_string(),
+                expires_at: 44200,
+            }
+        );
+
+        let user = serde_json::json!({
+            "ok": true, "authed_user": {
+                "access_token": "xoxe.xoxp-1-c", "refresh_token": "xoxe-1-d", "expires_in": 60
+            }
+        });
+        let rotated = RotatedToken::from_response(&user, 0).unwrap();
+        assert_eq!((rotated.access_token.as_str(), rotated.expires_at), ("xoxe.xoxp-1-c", 60));
+
+        let revoked = serde_json::json!({"ok": false, "error": "invalid_refresh_token"});
+        assert!(RotatedToken::from_response(&revoked, 0).is_err());
+        assert!(RotatedToken::from_response(&serde_json::json!({"ok": true}), 0).is_err());
+    }
+
+    #[test]
+    fn test_retry_delay_bounds() {
+        for attempt in 1..10 {
+            let step = (RETRY_BASE_DELAY_MS << (attempt - 1)).min(RETRY_MAX_DELAY_MS);
+            let delay = retry_delay(attempt as u32).as_millis() as u64;
+            assert!(delay >= step / 2 && delay <= step, "attempt {attempt}: {delay}ms");
         }
     }
+
+    #[test]
+    fn test_is_read_method() {
+        for method in ["conversations.history", "users.info", "auth.test", "search.messages", "chat.getPermalink"] {
+            assert!(is_read_method(method), "{method}");
+        }
+        for method in ["chat.postMessage", "files.completeUploadExternal", "reactions.add", "conversations.mark"] {
+            assert!(!is_read_method(method), "{method}");
+        }
+    }
+
+    #[test]
+    fn test_scope_key() {
+        let http = reqwest::Client::new();
+        let history = http
+            .get("https://slack.com/api/conversations.history?channel=G123&limit=50")
+            .build()
+            .unwrap();
+        assert_eq!(request_channel(&history).as_deref(), Some("G123"));
+        let mark = http
+            .post("https://slack.com/api/conversations.mark")
+            .json(&serde_json::json!({"channel": "C1", "ts": "1.2"}))
+            .build()
+            .unwrap();
+        assert_eq!(request_channel(&mark).as_deref(), Some("C1"));
+        let files = http.get("https://slack.com/api/files.list").build().unwrap();
+        assert_eq!(request_channel(&files), None);
+        assert_eq!(scope_key("files.list", None), "files.list");
+        assert_eq!(scope_key("conversations.history", Some("G123")), "conversations.history in G123");
+    }
+
+    #[test]
+    fn test_reconnect_delay_bounds() {
+        for attempt in 1..20u32 {
+            let step = (RECONNECT_BASE_DELAY_MS << (attempt - 1).min(16)).min(RECONNECT_MAX_DELAY_MS);
+            let delay = reconnect_delay(attempt).as_millis() as u64;
+            assert!(delay >= step / 2 && delay <= step, "attempt {attempt}: {delay}ms");
+        }
+    }
+
+    #[test]
+    fn test_user_summary_matches() {
+        let user = UserSummary {
+            id: "U1".to_string(),
+            name: "ada".to_string(),
+            handle: "ada.l".to_string(),
+            real_name: "Ada Lovelace".to_string(),
+            title: "Analytical Engineer".to_string(),
+            email: "ada@example.com".to_string(),
+        };
+        assert!(user.matches("LOVE"));
+        assert!(user.matches("engineer"));
+        assert!(user.matches("@example"));
+        assert!(!user.matches("babbage"));
+    }
+
+    #[test]
+    fn test_api_stats() {
+        let stats = ApiStats::default();
+        let started = std::time::Instant::now();
+        stats.record("users.info", started, CallOutcome::Ok);
+        stats.record_response("users.info", started, reqwest::StatusCode::TOO_MANY_REQUESTS, false);
+        stats.record_response("auth.test", started, reqwest::StatusCode::OK, false);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot[0].0, "users.info");
+        let (calls, failures, limited) = (snapshot[0].1.calls, snapshot[0].1.failures, snapshot[0].1.rate_limited);
+        assert_eq!((calls, failures, limited), (2, 1, 1));
+        assert_eq!((snapshot[1].1.failures, snapshot[1].1.rate_limited), (1, 0));
+    }
+
+    #[test]
+    fn test_huddle_room() {
+        let msg: SlackMessage = serde_json::from_value(serde_json::json!({
+            "ts": "1.2", "subtype": "huddle_thread",
+            "room": {"id": "R1", "has_ended": false, "participants": ["U1", "U2"]}
+        }))
+        .unwrap();
+        assert_eq!(msg.room.as_ref().and_then(HuddleRoom::live_participants), Some(2));
+        let ended: HuddleRoom = serde_json::from_value(serde_json::json!({"has_ended": true})).unwrap();
+        assert_eq!(ended.live_participants(), None);
+    }
 }
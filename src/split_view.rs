@@ -17,7 +17,7 @@ pub enum PaneNode {
     Single(usize), // Index into App.panes
     Split {
         direction: SplitDirection,
-        children: Vec<Box<PaneNode>>,
+        children: Vec<PaneNode>,
         #[serde(default)]
         ratios: Vec<u16>, // Per-child percentage ratios (empty = equal)
     },
@@ -32,7 +32,7 @@ impl PaneNode {
         // If already a split in the same direction, append to it for equal sizing
         if let PaneNode::Split { direction: d, children, ratios } = self {
             if *d == direction {
-                children.push(Box::new(PaneNode::Single(new_pane_idx)));
+                children.push(PaneNode::Single(new_pane_idx));
                 ratios.clear(); // Reset to equal sizing
                 return;
             }
@@ -40,7 +40,7 @@ impl PaneNode {
         let old_node = std::mem::replace(self, PaneNode::Single(0));
         *self = PaneNode::Split {
             direction,
-            children: vec![Box::new(old_node), Box::new(PaneNode::Single(new_pane_idx))],
+            children: vec![old_node, PaneNode::Single(new_pane_idx)],
             ratios: vec![],
         };
     }
@@ -56,8 +56,8 @@ impl PaneNode {
             PaneNode::Split { direction: d, children, ratios } => {
                 // If a direct child is the target and our direction matches, add sibling here
                 if *d == direction {
-                    if let Some(_pos) = children.iter().position(|c| matches!(**c, PaneNode::Single(idx) if idx == target_pane_idx)) {
-                        children.push(Box::new(PaneNode::Single(new_pane_idx)));
+                    if let Some(_pos) = children.iter().position(|c| matches!(*c, PaneNode::Single(idx) if idx == target_pane_idx)) {
+                        children.push(PaneNode::Single(new_pane_idx));
                         ratios.clear();
                         return true;
                     }
@@ -80,7 +80,7 @@ impl PaneNode {
                 let old_percent = 100u16.saturating_sub(new_pane_percent);
                 *self = PaneNode::Split {
                     direction,
-                    children: vec![Box::new(old_node), Box::new(PaneNode::Single(new_pane_idx))],
+                    children: vec![old_node, PaneNode::Single(new_pane_idx)],
                     ratios: vec![old_percent, new_pane_percent],
                 };
                 true
@@ -89,16 +89,16 @@ impl PaneNode {
             PaneNode::Split { direction: d, children, ratios } => {
                 // If a direct child is the target and our direction matches, add sibling here
                 if *d == direction {
-                    if let Some(pos) = children.iter().position(|c| matches!(**c, PaneNode::Single(idx) if idx == target_pane_idx)) {
+                    if let Some(pos) = children.iter().position(|c| matches!(*c, PaneNode::Single(idx) if idx == target_pane_idx)) {
                         // Insert new pane after the target
-                        children.insert(pos + 1, Box::new(PaneNode::Single(new_pane_idx)));
+                        children.insert(pos + 1, PaneNode::Single(new_pane_idx));
                         // Recalculate ratios: keep existing ratios proportional, add new one
                         let total_ratio: u16 = ratios.iter().sum();
                         let old_percent = total_ratio.saturating_sub(new_pane_percent);
                         // Scale existing ratios to fit the remaining space
-                        if total_ratio > 0 {
+                        if let Some(total) = std::num::NonZeroU16::new(total_ratio) {
                             for ratio in ratios.iter_mut() {
-                                *ratio = (*ratio * old_percent) / total_ratio;
+                                *ratio = (*ratio * old_percent) / total;
                             }
                         } else {
                             // If no ratios set, distribute equally
@@ -134,7 +134,7 @@ impl PaneNode {
         let old_percent = 100u16.saturating_sub(new_pane_percent);
         *self = PaneNode::Split {
             direction,
-            children: vec![Box::new(old_node), Box::new(PaneNode::Single(new_pane_idx))],
+            children: vec![old_node, PaneNode::Single(new_pane_idx)],
             ratios: vec![old_percent, new_pane_percent],
         };
     }
@@ -166,7 +166,7 @@ impl PaneNode {
                 // Check if any child IS the pane we want to remove
                 if let Some(pos) = children
                     .iter()
-                    .position(|child| matches!(**child, PaneNode::Single(idx) if idx == pane_idx))
+                    .position(|child| matches!(*child, PaneNode::Single(idx) if idx == pane_idx))
                 {
                     // Remove this direct child
                     children.remove(pos);
@@ -174,7 +174,7 @@ impl PaneNode {
                     // If only one child remains, collapse the split
                     if children.len() == 1 {
                         let child = children.remove(0);
-                        *self = *child;
+                        *self = child;
                     }
                     return true;
                 }
@@ -217,23 +217,7 @@ impl PaneNode {
                     return;
                 }
 
-                let constraints: Vec<Constraint> = if ratios.len() == children.len() {
-                    ratios.iter().map(|&r| Constraint::Percentage(r)).collect()
-                } else {
-                    let n = children.len() as u32;
-                    (0..n).map(|_| Constraint::Ratio(1, n)).collect()
-                };
-
-                let layout_direction = match direction {
-                    SplitDirection::Horizontal => Direction::Vertical,
-                    SplitDirection::Vertical => Direction::Horizontal,
-                };
-
-                let chunks = Layout::default()
-                    .direction(layout_direction)
-                    .constraints(constraints)
-                    .split(area);
-
+                let chunks = child_areas(*direction, children.len(), ratios, area);
                 for (i, child) in children.iter().enumerate() {
                     if let Some(&chunk) = chunks.get(i) {
                         child.render(f, chunk, panes, focused_idx, render_fn, pane_areas);
@@ -278,7 +262,7 @@ impl PaneNode {
             // Find and remove the pane
             if let Some(pos) = children
                 .iter()
-                .position(|child| matches!(**child, PaneNode::Single(idx) if idx == pane_idx))
+                .position(|child| matches!(*child, PaneNode::Single(idx) if idx == pane_idx))
             {
                 children.remove(pos);
                 if pos < ratios.len() {
@@ -294,12 +278,154 @@ impl PaneNode {
             // If only one child left, collapse to single
             if children.len() == 1 {
                 if let Some(child) = children.first() {
-                    *self = (**child).clone();
+                    *self = child.clone();
                 }
             }
         }
     }
     
+    /// Swap a pane with its previous/next sibling in the enclosing split.
+    /// Ratios stay with their positions, so only the contents move.
+    pub fn swap_with_neighbor(&mut self, pane_idx: usize, forward: bool) -> bool {
+        match self {
+            PaneNode::Single(_) => false,
+            PaneNode::Split { children, .. } => {
+                if let Some(pos) = children
+                    .iter()
+                    .position(|child| matches!(*child, PaneNode::Single(idx) if idx == pane_idx))
+                {
+                    let other = if forward {
+                        pos + 1
+                    } else {
+                        match pos.checked_sub(1) {
+                            Some(p) => p,
+                            None => return false,
+                        }
+                    };
+                    if other >= children.len() {
+                        return false;
+                    }
+                    children.swap(pos, other);
+                    return true;
+                }
+                children
+                    .iter_mut()
+                    .any(|child| child.swap_with_neighbor(pane_idx, forward))
+            }
+        }
+    }
+
+    /// Move a pane to the opposite end of the enclosing split: the first
+    /// child goes last, anything else goes first.
+    pub fn move_to_other_end(&mut self, pane_idx: usize) -> bool {
+        match self {
+            PaneNode::Single(_) => false,
+            PaneNode::Split { children, .. } => {
+                if let Some(pos) = children
+                    .iter()
+                    .position(|child| matches!(*child, PaneNode::Single(idx) if idx == pane_idx))
+                {
+                    let node = children.remove(pos);
+                    if pos == 0 {
+                        children.push(node);
+                    } else {
+                        children.insert(0, node);
+                    }
+                    return true;
+                }
+                children
+                    .iter_mut()
+                    .any(|child| child.move_to_other_end(pane_idx))
+            }
+        }
+    }
+
+    /// The divider under (col, row), as the path of child indices down to its
+    /// split and the index of the child before it. A divider is the touching
+    /// edge columns (or rows) of two neighbouring children.
+    pub fn divider_at(&self, area: Rect, col: u16, row: u16) -> Option<(Vec<usize>, usize)> {
+        let PaneNode::Split { direction, children, ratios } = self else {
+            return None;
+        };
+        let chunks = child_areas(*direction, children.len(), ratios, area);
+        for (i, pair) in chunks.windows(2).enumerate() {
+            let (before, after) = (pair[0], pair[1]);
+            let hit = match direction {
+                SplitDirection::Vertical => {
+                    (col + 1 == after.x || col == after.x)
+                        && row >= area.y
+                        && row < area.y + area.height
+                }
+                SplitDirection::Horizontal => {
+                    (row + 1 == after.y || row == after.y)
+                        && col >= area.x
+                        && col < area.x + area.width
+                }
+            };
+            if hit && before.width > 0 && before.height > 0 {
+                return Some((Vec::new(), i));
+            }
+        }
+        for (i, (child, chunk)) in children.iter().zip(chunks.iter()).enumerate() {
+            let inside = col >= chunk.x
+                && col < chunk.x + chunk.width
+                && row >= chunk.y
+                && row < chunk.y + chunk.height;
+            if inside {
+                return child.divider_at(*chunk, col, row).map(|(mut path, divider)| {
+                    path.insert(0, i);
+                    (path, divider)
+                });
+            }
+        }
+        None
+    }
+
+    /// Move a divider found with `divider_at` so the child before it ends at
+    /// (col, row). Both neighbours keep at least `MIN_PANE_PERCENT`.
+    pub fn resize_divider(&mut self, area: Rect, path: &[usize], divider: usize, col: u16, row: u16) -> bool {
+        let PaneNode::Split { direction, children, ratios } = self else {
+            return false;
+        };
+        if let Some((&first, rest)) = path.split_first() {
+            let chunks = child_areas(*direction, children.len(), ratios, area);
+            return match (children.get_mut(first), chunks.get(first)) {
+                (Some(child), Some(&chunk)) => child.resize_divider(chunk, rest, divider, col, row),
+                _ => false,
+            };
+        }
+        if divider + 1 >= children.len() {
+            return false;
+        }
+
+        // Equal sizing is implicit; make it explicit before moving one divider
+        if ratios.len() != children.len() {
+            let n = children.len() as u16;
+            *ratios = vec![100 / n; children.len()];
+            ratios[children.len() - 1] += 100 % n;
+        }
+
+        let (start, len, pos) = match direction {
+            SplitDirection::Vertical => (area.x, area.width, col),
+            SplitDirection::Horizontal => (area.y, area.height, row),
+        };
+        if len == 0 {
+            return false;
+        }
+        let pos_percent = ((pos.saturating_sub(start) as u32 + 1) * 100 / len as u32) as u16;
+        let before: u16 = ratios[..divider].iter().sum();
+        let combined = ratios[divider] + ratios[divider + 1];
+        if combined < 2 * MIN_PANE_PERCENT {
+            return false;
+        }
+        let new_size = pos_percent
+            .saturating_sub(before)
+            .clamp(MIN_PANE_PERCENT, combined - MIN_PANE_PERCENT);
+        ratios[divider] = new_size;
+        ratios[divider + 1] = combined - new_size;
+        true
+    }
+
     /// Reindex all pane indices after a pane is removed
     /// All indices > removed_idx need to be decremented by 1
     pub fn reindex_after_removal(&mut self, removed_idx: usize) {
@@ -318,6 +444,30 @@ impl PaneNode {
     }
 }
 
+/// Smallest share of a split a pane can be dragged to
+const MIN_PANE_PERCENT: u16 = 10;
+
+/// Areas of a split's children: `ratios` as percentages, or equal parts when
+/// they don't cover every child.
+fn child_areas(direction: SplitDirection, count: usize, ratios: &[u16], area: Rect) -> std::rc::Rc<[Rect]> {
+    let constraints: Vec<Constraint> = if ratios.len() == count {
+        ratios.iter().map(|&r| Constraint::Percentage(r)).collect()
+    } else {
+        let n = count as u32;
+        (0..n).map(|_| Constraint::Ratio(1, n)).collect()
+    };
+
+    let layout_direction = match direction {
+        SplitDirection::Horizontal => Direction::Vertical,
+        SplitDirection::Vertical => Direction::Horizontal,
+    };
+
+    Layout::default()
+        .direction(layout_direction)
+        .constraints(constraints)
+        .split(area)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -359,4 +509,49 @@ mod tests {
         let next = node.get_next_pane_idx(2);
         assert_eq!(next, Some(0)); // Wraps around
     }
+
+    #[test]
+    fn test_swap_and_move_panes() {
+        let mut node = PaneNode::new_single(0);
+        node.split(SplitDirection::Vertical, 1);
+        node.split(SplitDirection::Vertical, 2);
+        assert!(node.split_pane(1, SplitDirection::Horizontal, 3));
+        // Layout: [0, (1 / 3), 2]
+
+        assert!(node.swap_with_neighbor(0, true));
+        assert_eq!(node.get_pane_indices(), vec![1, 3, 0, 2]);
+        assert!(!node.swap_with_neighbor(2, true)); // Already last
+        assert!(node.swap_with_neighbor(3, false)); // Within the nested split
+        assert_eq!(node.get_pane_indices(), vec![3, 1, 0, 2]);
+
+        assert!(node.move_to_other_end(2));
+        assert_eq!(node.get_pane_indices(), vec![2, 3, 1, 0]);
+        assert!(node.move_to_other_end(2));
+        assert_eq!(node.get_pane_indices(), vec![3, 1, 0, 2]);
+        assert!(!node.move_to_other_end(9));
+    }
+
+    #[test]
+    fn test_drag_divider() {
+        let mut node = PaneNode::new_single(0);
+        node.split(SplitDirection::Vertical, 1);
+        let area = Rect::new(0, 0, 100, 40);
+
+        // Equal halves: the divider is at columns 49/50
+        assert_eq!(node.divider_at(area, 50, 10), Some((vec![], 0)));
+        assert_eq!(node.divider_at(area, 20, 10), None);
+
+        assert!(node.resize_divider(area, &[], 0, 29, 10));
+        match &node {
+            PaneNode::Split { ratios, .. } => assert_eq!(ratios, &vec![30, 70]),
+            _ => panic!("Expected a split"),
+        }
+
+        // Clamped so the right pane keeps its minimum
+        assert!(node.resize_divider(area, &[], 0, 99, 10));
+        match &node {
+            PaneNode::Split { ratios, .. } => assert_eq!(ratios, &vec![90, 10]),
+            _ => panic!("Expected a split"),
+        }
+    }
 }
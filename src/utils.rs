@@ -24,3 +24,476 @@ pub fn send_desktop_notification(title: &str, message: &str) {
             .output();
     }
 }
+
+/// Put `text` on the system clipboard: pbcopy (macOS), wl-copy, xclip or xsel
+/// (Linux), and otherwise the terminal via an OSC 52 escape. Returns what was used.
+pub fn copy_to_clipboard(text: &str) -> &'static str {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let tools: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("pbcopy", &[])]
+    } else {
+        &[
+            ("wl-copy", &[]),
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ]
+    };
+    for (tool, args) in tools {
+        let Ok(mut child) = Command::new(tool)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        let written = child
+            .stdin
+            .take()
+            .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+        if written && child.wait().is_ok_and(|status| status.success()) {
+            return tool;
+        }
+    }
+
+    // Works over SSH and in most terminal emulators (and tmux with set-clipboard on)
+    let mut stdout = std::io::stdout();
+    let _ = write!(stdout, "\x1b]52;c;{}\x07", base64_encode(text.as_bytes()));
+    let _ = stdout.flush();
+    "terminal"
+}
+
+/// Set the terminal window title (OSC 2); tmux shows it as `#{pane_title}`.
+pub fn set_terminal_title(title: &str) {
+    use std::io::Write;
+    let mut stdout = std::io::stdout();
+    let _ = write!(stdout, "\x1b]2;{}\x07", strip_control_chars(title));
+    let _ = stdout.flush();
+}
+
+/// Push the current terminal title on the terminal's title stack (xterm CSI 22 t)...
+pub fn save_terminal_title() {
+    use std::io::Write;
+    let mut stdout = std::io::stdout();
+    let _ = write!(stdout, "\x1b[22;0t");
+    let _ = stdout.flush();
+}
+
+/// ...and pop it again (CSI 23 t), so the shell's title comes back on exit.
+pub fn restore_terminal_title() {
+    use std::io::Write;
+    let mut stdout = std::io::stdout();
+    let _ = write!(stdout, "\x1b[23;0t");
+    let _ = stdout.flush();
+}
+
+/// Undo what the TUI set up (raw mode, alternate screen, mouse capture, bracketed
+/// paste, focus events, hidden cursor, title). Best effort: also called from the
+/// panic hook, where the terminal may be half set up.
+pub fn restore_terminal() {
+    use crossterm::{cursor, event, terminal};
+    let _ = terminal::disable_raw_mode();
+    let _ = crossterm::execute!(
+        std::io::stdout(),
+        terminal::LeaveAlternateScreen,
+        event::DisableMouseCapture,
+        event::DisableBracketedPaste,
+        event::DisableFocusChange,
+        cursor::Show
+    );
+    restore_terminal_title();
+}
+
+/// Post a notification through the terminal itself (OSC 9: iTerm2, WezTerm,
+/// kitty, foot, Windows Terminal...), which also works over SSH.
+pub fn send_terminal_notification(message: &str) {
+    use std::io::Write;
+    let mut stdout = std::io::stdout();
+    let osc = format!("\x1b]9;{}\x07", strip_control_chars(message));
+    if std::env::var_os("TMUX").is_some() {
+        // tmux only forwards escapes wrapped in a passthrough DCS (needs allow-passthrough)
+        let _ = write!(stdout, "\x1bPtmux;{}\x1b\\", osc.replace('\x1b', "\x1b\x1b"));
+    } else {
+        let _ = write!(stdout, "{}", osc);
+    }
+    let _ = stdout.flush();
+}
+
+/// Escape sequences can't carry newlines or other control characters
+fn strip_control_chars(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Whether `now` falls in a "HH:MM-HH:MM" range; ranges may wrap past midnight.
+/// An empty or malformed spec never matches.
+pub fn in_quiet_hours(spec: &str, now: chrono::NaiveTime) -> bool {
+    let Some((start, end)) = spec.split_once('-') else {
+        return false;
+    };
+    let parse = |t: &str| chrono::NaiveTime::parse_from_str(t.trim(), "%H:%M").ok();
+    match (parse(start), parse(end)) {
+        (Some(start), Some(end)) if start <= end => now >= start && now < end,
+        (Some(start), Some(end)) => now >= start || now < end,
+        _ => false,
+    }
+}
+
+/// Minutes in a duration like "45m", "2h", "1h30m" or a bare number of minutes.
+pub fn parse_duration_minutes(text: &str) -> Option<u64> {
+    let text = text.trim().to_lowercase();
+    if let Ok(minutes) = text.parse::<u64>() {
+        return Some(minutes);
+    }
+    let mut total = 0;
+    let mut number = String::new();
+    for c in text.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' if !number.is_empty() => {
+                let n: u64 = number.parse().ok()?;
+                total += if c == 'h' { n * 60 } else { n };
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+    (number.is_empty() && total > 0).then_some(total)
+}
+
+/// Whether a key press matches a spec like "alt+b", "ctrl+shift+c" or "ctrl+`".
+/// An empty or malformed spec never matches.
+pub fn key_matches(spec: &str, key: &crossterm::event::KeyEvent) -> bool {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let KeyCode::Char(pressed) = key.code else {
+        return false;
+    };
+    let spec = spec.trim().to_lowercase();
+    let (mods, name) = spec.rsplit_once('+').unwrap_or(("", spec.as_str()));
+    let mut chars = name.chars();
+    let (Some(wanted), None) = (chars.next(), chars.next()) else {
+        return false;
+    };
+    let mut modifiers = KeyModifiers::NONE;
+    for part in mods.split('+').filter(|p| !p.is_empty()) {
+        modifiers |= match part {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" | "meta" | "option" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return false,
+        };
+    }
+    // Terminals report Shift+letter as an uppercase letter, with or without SHIFT
+    let mut pressed_mods =
+        key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+    if pressed.is_uppercase() {
+        pressed_mods |= KeyModifiers::SHIFT;
+    }
+    pressed.to_lowercase().eq(std::iter::once(wanted)) && pressed_mods == modifiers
+}
+
+/// Open a file with the system's default application (macOS and Linux).
+pub fn open_with_default_app(path: &std::path::Path) {
+    #[cfg(target_os = "macos")]
+    {
+        let _ = std::process::Command::new("open").arg(path).output();
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let _ = std::process::Command::new("xdg-open").arg(path).output();
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        let _ = path;
+    }
+}
+
+/// Open a downloaded file with `opener` (a command line; `{}` is replaced by the
+/// path, otherwise the path is appended), or the system default without one.
+pub fn open_file(path: &std::path::Path, opener: Option<&str>) {
+    use std::process::{Command, Stdio};
+
+    let Some(opener) = opener else {
+        open_with_default_app(path);
+        return;
+    };
+    let path_str = path.to_string_lossy();
+    let mut parts: Vec<String> = opener.split_whitespace().map(str::to_string).collect();
+    if parts.is_empty() {
+        open_with_default_app(path);
+        return;
+    }
+    if parts.iter().any(|p| p.contains("{}")) {
+        for part in &mut parts {
+            *part = part.replace("{}", &path_str);
+        }
+    } else {
+        parts.push(path_str.into_owned());
+    }
+
+    // Detached so a viewer doesn't block the UI or draw over it
+    let result = Command::new(&parts[0])
+        .args(&parts[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Err(e) = result {
+        tracing::warn!("Failed to run opener '{}': {}", opener, e);
+        open_with_default_app(path);
+    }
+}
+
+/// Run a hook command through the shell without waiting for it. `payload` (a JSON
+/// object) goes to its stdin, and its string fields to `SLACK_<FIELD>` variables.
+pub fn run_hook(command: &str, payload: &serde_json::Value) {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    if let Some(fields) = payload.as_object() {
+        for (key, value) in fields {
+            if let Some(value) = value.as_str() {
+                cmd.env(format!("SLACK_{}", key.to_uppercase()), value);
+            }
+        }
+    }
+    let child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::warn!("Failed to run hook '{}': {}", command, e);
+            return;
+        }
+    };
+
+    // Feed stdin and reap the process off the UI thread
+    let input = payload.to_string();
+    let command = command.to_string();
+    std::thread::spawn(move || {
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(input.as_bytes());
+        }
+        match child.wait() {
+            Ok(status) if !status.success() => tracing::warn!("Hook '{}' exited with {}", command, status),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Hook '{}' failed: {}", command, e),
+        }
+    });
+}
+
+/// Mimetype for common file extensions (lowercase, without the dot).
+pub fn guess_mimetype(ext: &str) -> Option<&'static str> {
+    let mimetype = match ext {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "heic" => "image/heic",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "mkv" => "video/x-matroska",
+        "avi" => "video/x-msvideo",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "json" => "application/json",
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        _ => return None,
+    };
+    Some(mimetype)
+}
+
+/// `dir/file_name`, or `dir/stem (N).ext` with the first free N if that file exists.
+pub fn unique_path(dir: &std::path::Path, file_name: &str) -> std::path::PathBuf {
+    let candidate = dir.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let name = std::path::Path::new(file_name);
+    let stem = name
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| file_name.to_string());
+    let ext = name.extension().map(|e| e.to_string_lossy().into_owned());
+    (1..)
+        .map(|n| match &ext {
+            Some(ext) => dir.join(format!("{} ({}).{}", stem, n, ext)),
+            None => dir.join(format!("{} ({})", stem, n)),
+        })
+        .find(|path| !path.exists())
+        .expect("unbounded range yields a free name")
+}
+
+/// Append one message to a tee file, creating parent directories as needed.
+pub fn append_tee_line(
+    rule: &crate::config::TeeRule,
+    channel_name: &str,
+    ts: &str,
+    author: &str,
+    text: &str,
+) -> std::io::Result<()> {
+    use crate::config::TeeFormat;
+    use chrono::TimeZone;
+    use std::io::Write;
+
+    let time = ts
+        .split('.')
+        .next()
+        .and_then(|secs| secs.parse::<i64>().ok())
+        .and_then(|secs| chrono::Local.timestamp_opt(secs, 0).single())
+        .unwrap_or_else(chrono::Local::now)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+
+    let line = match rule.format {
+        TeeFormat::Text => format!("[{}] {}: {}", time, author, text.replace('\n', "\n  ")),
+        TeeFormat::Jsonl => serde_json::json!({
+            "ts": ts,
+            "time": time,
+            "channel": channel_name,
+            "author": author,
+            "text": text,
+        })
+        .to_string(),
+    };
+
+    if let Some(parent) = rule.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&rule.path)?;
+    writeln!(file, "{}", line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use chrono::NaiveTime;
+
+    #[test]
+    fn test_key_matches() {
+        let key = |c: char, modifiers: KeyModifiers| KeyEvent::new(KeyCode::Char(c), modifiers);
+        assert!(key_matches("ctrl+b", &key('b', KeyModifiers::CONTROL)));
+        assert!(key_matches(" Alt+E ", &key('e', KeyModifiers::ALT)));
+        assert!(!key_matches("ctrl+b", &key('b', KeyModifiers::NONE)));
+        assert!(!key_matches("ctrl+b", &key('b', KeyModifiers::CONTROL | KeyModifiers::ALT)));
+        // Shift may come as an uppercase letter, the SHIFT flag or both
+        assert!(key_matches("ctrl+shift+c", &key('C', KeyModifiers::CONTROL)));
+        assert!(key_matches("ctrl+shift+c", &key('c', KeyModifiers::CONTROL | KeyModifiers::SHIFT)));
+        assert!(!key_matches("ctrl+c", &key('C', KeyModifiers::CONTROL)));
+        assert!(key_matches("ctrl+`", &key('`', KeyModifiers::CONTROL)));
+        assert!(!key_matches("", &key('b', KeyModifiers::NONE)));
+        assert!(!key_matches("hyper+b", &key('b', KeyModifiers::NONE)));
+        assert!(!key_matches("ctrl+bb", &key('b', KeyModifiers::CONTROL)));
+        assert!(!key_matches("ctrl+i", &KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn test_in_quiet_hours() {
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert!(in_quiet_hours("09:00-17:00", at(12, 0)));
+        assert!(!in_quiet_hours("09:00-17:00", at(17, 0)));
+        // Wrapping past midnight
+        assert!(in_quiet_hours("22:00-07:00", at(23, 30)));
+        assert!(in_quiet_hours("22:00-07:00", at(3, 0)));
+        assert!(!in_quiet_hours("22:00-07:00", at(7, 0)));
+        assert!(!in_quiet_hours("22:00-07:00", at(12, 0)));
+        assert!(!in_quiet_hours("", at(12, 0)));
+        assert!(!in_quiet_hours("22:00", at(23, 0)));
+        assert!(!in_quiet_hours("late-07:00", at(3, 0)));
+    }
+
+    #[test]
+    fn test_parse_duration_minutes() {
+        assert_eq!(parse_duration_minutes("45"), Some(45));
+        assert_eq!(parse_duration_minutes("45m"), Some(45));
+        assert_eq!(parse_duration_minutes(" 2H "), Some(120));
+        assert_eq!(parse_duration_minutes("1h30m"), Some(90));
+        assert_eq!(parse_duration_minutes(""), None);
+        assert_eq!(parse_duration_minutes("h"), None);
+        assert_eq!(parse_duration_minutes("0m"), None);
+        assert_eq!(parse_duration_minutes("1h30"), None);
+        assert_eq!(parse_duration_minutes("1.5h"), None);
+        assert_eq!(parse_duration_minutes("-5"), None);
+        assert_eq!(parse_duration_minutes("soon"), None);
+    }
+
+    #[test]
+    fn test_unique_path() {
+        let dir = std::env::temp_dir().join(format!("slack_rust_unique_path_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(unique_path(&dir, "a.txt"), dir.join("a.txt"));
+        std::fs::write(dir.join("a.txt"), "").unwrap();
+        std::fs::write(dir.join("a (1).txt"), "").unwrap();
+        assert_eq!(unique_path(&dir, "a.txt"), dir.join("a (2).txt"));
+        std::fs::write(dir.join("notes"), "").unwrap();
+        assert_eq!(unique_path(&dir, "notes"), dir.join("notes (1)"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_guess_mimetype() {
+        assert_eq!(guess_mimetype("png"), Some("image/png"));
+        assert_eq!(guess_mimetype("jpeg"), Some("image/jpeg"));
+        assert_eq!(guess_mimetype("PNG"), None);
+        assert_eq!(guess_mimetype("xyz"), None);
+    }
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+}
@@ -1,14 +1,42 @@
 use ratatui::text::Line;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum FilterType {
     Sender,
     Media,
     Link,
+    Regex,
+    Since, // Messages on or after a date (YYYY-MM-DD)
+    Until, // Messages on or before a date (YYYY-MM-DD)
+}
+
+/// When a pane follows new messages from others (`auto_scroll` setting).
+/// Your own messages always scroll into view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoScroll {
+    Always,
+    /// Only while the last message is on screen
+    #[default]
+    AtBottom,
+    Never,
+}
+
+impl AutoScroll {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "always" | "on" => Some(AutoScroll::Always),
+            "bottom" | "at_bottom" => Some(AutoScroll::AtBottom),
+            "never" | "off" => Some(AutoScroll::Never),
+            _ => None,
+        }
+    }
 }
 
 /// Represents a single message with all its metadata for display
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MessageData {
     pub sender_name: String,
     pub text: String,
@@ -18,13 +46,157 @@ pub struct MessageData {
     pub reply_count: u32,
     pub forwarded_text: Option<String>,
     pub mentions_me: bool, // True if this message mentions the current user
+    #[serde(default)]
+    pub matches_keyword: bool, // Contains a highlight keyword: shown like a mention, not counted as one
     pub local_echo_id: Option<u64>, // Unique ID for local echo deduplication
     pub is_edited: bool, // True if message was edited
     pub is_deleted: bool, // True if message was deleted
+    pub is_system: bool, // Channel notice (join/leave/topic...), rendered dimmed
+    #[serde(default)]
+    pub is_bot: bool, // Posted by a bot or app (bot_id set), hidden by `/filter bots`
     pub media_type: Option<String>, // "image" or "video" if message contains media
     pub file_ids: Vec<String>, // List of file IDs for media download (deprecated, use file_urls)
     pub file_urls: Vec<String>, // List of file download URLs (url_private or url_private_download)
     pub file_names: Vec<String>, // List of file names for download
+    pub files: Vec<FileAttachment>, // Shown as one line per file under the message
+}
+
+/// File attached to a message, as rendered in the message list
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FileAttachment {
+    pub name: String,
+    pub kind: String, // pretty_type ("PDF"), or the filetype
+    pub size: Option<u64>,
+}
+
+/// Incremental search over a pane's loaded messages (started with '/')
+#[derive(Clone, Debug, Default)]
+pub struct PaneSearch {
+    pub query: String,
+    pub editing: bool,        // Keys go to the query until Enter/Esc
+    pub matches: Vec<usize>,  // Indices into msg_data, oldest first
+    pub current: usize,       // Index into `matches`
+}
+
+impl PaneSearch {
+    pub fn current_msg(&self) -> Option<usize> {
+        self.matches.get(self.current).copied()
+    }
+}
+
+/// Modal text box drawn over the panes until dismissed with Esc/Enter
+#[derive(Clone, Debug)]
+pub struct InfoPopup {
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+/// One row of the /files browser
+pub struct FileEntry {
+    pub name: String,
+    pub kind: String,
+    pub size: u64,
+    pub uploader: String,
+    pub date: String,
+    pub url: Option<String>, // url_private_download, or url_private
+}
+
+/// Selectable list of files shared in a channel (/files)
+pub struct FileBrowser {
+    pub title: String,
+    pub files: Vec<FileEntry>,
+    pub selected: usize,
+}
+
+/// A user found with /finduser
+pub struct UserEntry {
+    pub id: String,
+    pub name: String,
+    pub real_name: String,
+    pub title: String,
+    pub email: String,
+    pub presence: Option<String>, // "active"/"away", looked up for the first matches
+}
+
+/// Selectable list of /finduser matches; Enter opens a DM
+pub struct UserPicker {
+    pub query: String,
+    pub users: Vec<UserEntry>,
+    pub selected: usize,
+}
+
+/// What a `/workspace add`, `rename` or `reauth` prompt is asking for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorkspaceStep {
+    Name,
+    Token,
+    AppToken,
+    Rename(usize), // New name for this workspace index
+    Reauth(usize), // New token for this workspace index, after Slack rejected the old one
+}
+
+/// One-line input box drawn over the panes; token answers are masked
+pub struct WorkspacePrompt {
+    pub step: WorkspaceStep,
+    pub input: String,
+    pub name: String,  // Answers so far (/workspace add)
+    pub token: String,
+    pub error: Option<String>, // Why the last answer was rejected
+}
+
+impl WorkspacePrompt {
+    pub fn new(step: WorkspaceStep, input: String) -> Self {
+        Self {
+            step,
+            input,
+            name: String::new(),
+            token: String::new(),
+            error: None,
+        }
+    }
+
+    /// Why the current answer can't be used; `names` are the configured workspaces.
+    pub fn invalid_answer(&self, names: &[&str]) -> Option<String> {
+        let input = self.input.trim();
+        let name_taken = |except: Option<usize>| {
+            names
+                .iter()
+                .enumerate()
+                .any(|(i, name)| Some(i) != except && name.eq_ignore_ascii_case(input))
+        };
+        match self.step {
+            WorkspaceStep::Name | WorkspaceStep::Rename(_) if input.is_empty() => {
+                Some("The name can't be empty".to_string())
+            }
+            // Also used in file names (layout_<name>.json)
+            WorkspaceStep::Name | WorkspaceStep::Rename(_) if input.contains(['/', '\\']) => {
+                Some("The name can't contain / or \\".to_string())
+            }
+            WorkspaceStep::Name if name_taken(None) => {
+                Some(format!("There already is a workspace named {}", input))
+            }
+            WorkspaceStep::Rename(idx) if name_taken(Some(idx)) => {
+                Some(format!("There already is a workspace named {}", input))
+            }
+            // Rotating tokens look like xoxe.xoxp-1-...
+            WorkspaceStep::Token | WorkspaceStep::Reauth(_)
+                if !["xoxp-", "xoxb-"].iter().any(|p| input.trim_start_matches("xoxe.").starts_with(p)) =>
+            {
+                Some("Expected a user (xoxp-) or bot (xoxb-) token".to_string())
+            }
+            WorkspaceStep::AppToken if !input.is_empty() && !input.starts_with("xapp-") => {
+                Some("Expected an app-level token (xapp-), or nothing".to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Members matching the `@word` being typed in the composer
+pub struct MentionPicker {
+    pub start: usize, // Byte offset of the '@' in the focused pane's input
+    pub users: Vec<UserEntry>,
+    pub selected: usize,
 }
 
 pub struct ChatPane {
@@ -35,26 +207,82 @@ pub struct ChatPane {
     pub messages: Vec<String>,      // Formatted display lines
     pub msg_data: Vec<MessageData>, // Raw message data for formatting
     pub scroll_offset: usize,
-    pub reply_to_message: Option<i32>, // Message ID to reply to
+    pub reply_to_message: Option<String>, // ts of the message whose thread the next send goes to
     pub reply_preview: Option<String>, // Text shown in reply preview bar
     pub thread_ts: Option<String>,     // If set, this pane shows a thread
     pub filter_type: Option<FilterType>,
     pub filter_value: Option<String>,
+    pub hide_bots: bool, // `/filter bots`: skip bot and app messages, independent of the filter
     pub typing_indicator: Option<String>, // "Name is typing..."
     pub typing_expire: Option<std::time::Instant>,
     pub online_status: String,
     pub pinned_message: Option<String>,
     pub dirty: bool,
-    pub cached_lines: Option<Vec<Line<'static>>>,
-    pub cached_line_count: Option<(u16, usize)>, // (width, wrapped_count)
+    pub cached_lines: std::cell::RefCell<std::collections::HashMap<String, CachedMessage>>, // By ts, filled by draw
+    pub cache_key: std::cell::Cell<Option<u64>>, // Width and display settings the cached lines were wrapped for
     pub input_buffer: String,                    // Per-pane input buffer
     pub input_cursor: usize,                     // Byte index cursor into input_buffer
     pub tab_complete_state: Option<TabCompleteState>,
+    pub search: Option<PaneSearch>,
+    pub scroll_to_msg: Option<usize>, // Bring msg_data[i] into view on the next draw
+    pub resolved_scroll: std::cell::Cell<Option<usize>>, // Line offset for scroll_to_msg, set by draw
+    pub first_unread_ts: Option<String>, // Oldest message not seen yet (unread divider)
+    pub click_targets: std::cell::RefCell<Vec<ClickTarget>>, // Clickable spans, set by draw
+    pub rendered_lines: std::cell::RefCell<Vec<String>>, // Plain text of the drawn message lines (copy mode)
+    pub rendered_view: std::cell::Cell<(usize, usize)>,  // (first visible line, visible lines), set by draw
+    pub rendered_msgs: std::cell::RefCell<Vec<Option<usize>>>, // msg_data index of each drawn line
+    pub at_bottom: std::cell::Cell<bool>, // Last line was visible in the last draw
+    pub new_below: usize,                 // Messages that arrived while scrolled up
+    pub loading: bool,                    // History is being fetched in the background
+}
+
+/// Wrapped lines of one message as drawn at `idx` in its pane
+#[derive(Clone, Debug)]
+pub struct CachedMessage {
+    pub idx: usize,
+    pub lines: Vec<Line<'static>>,
+    pub urls: Option<Vec<(String, String)>>, // (shown text, URL) of links; None for system notices
+}
+
+/// Copy mode cursor and selection over the focused pane's drawn lines,
+/// as (line, column in characters)
+#[derive(Clone, Debug, Default)]
+pub struct CopyMode {
+    pub cursor: (usize, usize),
+    pub anchor: Option<(usize, usize)>, // Selection start, set with 'v'
+}
+
+impl CopyMode {
+    /// Selected range, ordered, inclusive of the end character; just the cursor without a selection.
+    pub fn range(&self) -> ((usize, usize), (usize, usize)) {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        if anchor <= self.cursor {
+            (anchor, self.cursor)
+        } else {
+            (self.cursor, anchor)
+        }
+    }
+}
+
+/// Clickable part of a drawn message (screen row and column range)
+#[derive(Clone, Debug)]
+pub struct ClickTarget {
+    pub row: u16,
+    pub cols: std::ops::Range<u16>,
+    pub action: ClickAction,
+}
+
+#[derive(Clone, Debug)]
+pub enum ClickAction {
+    OpenThread(usize), // Index into msg_data
+    OpenUrl(String),
+    JumpToLatest, // The "new messages" badge
 }
 
 #[derive(Clone, Debug)]
 pub struct TabCompleteState {
-    pub before: String,          // Text before @prefix
+    pub before: String,          // Text before @prefix / #prefix
+    pub sigil: char,             // '/', '@', '#', ':' or ' ' (a command argument)
     pub after: String,           // Text after cursor when tab completion started
     pub candidates: Vec<String>, // Matching names
     pub index: usize,            // Current candidate index
@@ -75,6 +303,7 @@ impl ChatPane {
             thread_ts: None,
             filter_type: None,
             filter_value: None,
+            hide_bots: false,
             typing_indicator: None,
             typing_expire: None,
             online_status: String::new(),
@@ -82,9 +311,20 @@ impl ChatPane {
             input_buffer: String::new(),
             input_cursor: 0,
             dirty: true,
-            cached_lines: None,
-            cached_line_count: None,
+            cached_lines: std::cell::RefCell::new(std::collections::HashMap::new()),
+            cache_key: std::cell::Cell::new(None),
             tab_complete_state: None,
+            search: None,
+            scroll_to_msg: None,
+            resolved_scroll: std::cell::Cell::new(None),
+            first_unread_ts: None,
+            click_targets: std::cell::RefCell::new(Vec::new()),
+            rendered_lines: std::cell::RefCell::new(Vec::new()),
+            rendered_view: std::cell::Cell::new((0, 0)),
+            rendered_msgs: std::cell::RefCell::new(Vec::new()),
+            at_bottom: std::cell::Cell::new(true),
+            new_below: 0,
+            loading: false,
         }
     }
 
@@ -94,16 +334,23 @@ impl ChatPane {
         self.scroll_offset = 0;
         self.input_buffer.clear();
         self.input_cursor = 0;
+        self.search = None;
+        self.scroll_to_msg = None;
+        self.first_unread_ts = None;
         self.invalidate_cache();
     }
 
     pub fn invalidate_cache(&mut self) {
         self.dirty = true;
-        self.cached_lines = None;
-        self.cached_line_count = None;
+        self.cached_lines.get_mut().clear();
     }
 
     pub fn scroll_up(&mut self) {
+        // scroll_offset may still be usize::MAX ("follow the bottom"); start from what is drawn
+        let (first, height) = self.rendered_view.get();
+        if height > 0 {
+            self.scroll_offset = self.scroll_offset.min(first);
+        }
         self.scroll_offset = self.scroll_offset.saturating_sub(1);
     }
 
@@ -111,6 +358,31 @@ impl ChatPane {
         self.scroll_offset = self.scroll_offset.saturating_add(1);
     }
 
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll_offset = usize::MAX;
+        self.new_below = 0;
+    }
+
+    /// A message was appended: scroll to it if `mode` says so, otherwise leave
+    /// the view alone and count it for the "new messages" badge.
+    pub fn follow_new_message(&mut self, mode: AutoScroll) {
+        let follow = match mode {
+            AutoScroll::Always => true,
+            AutoScroll::AtBottom => self.scroll_offset == usize::MAX || self.at_bottom.get(),
+            AutoScroll::Never => false,
+        };
+        if follow {
+            self.scroll_to_bottom();
+            return;
+        }
+        // Pin a view that was following the bottom to the lines on screen
+        let (first, height) = self.rendered_view.get();
+        if self.scroll_offset == usize::MAX && height > 0 {
+            self.scroll_offset = first;
+        }
+        self.new_below += 1;
+    }
+
     pub fn show_typing_indicator(&mut self, name: &str) {
         self.typing_indicator = Some(format!("{} is typing...", name));
         self.typing_expire = Some(std::time::Instant::now() + std::time::Duration::from_secs(5));
@@ -129,14 +401,53 @@ impl ChatPane {
         }
     }
 
+    /// Give a locally echoed message the `ts` Slack assigned to it, so the copy
+    /// arriving through events is recognised as a duplicate. If that copy is
+    /// already in the pane the echo is dropped instead.
+    pub fn confirm_local_echo(&mut self, local_echo_id: u64, ts: &str, text: &str) {
+        let Some(pos) = self
+            .msg_data
+            .iter()
+            .position(|m| m.local_echo_id == Some(local_echo_id))
+        else {
+            return;
+        };
+        if self.msg_data.iter().any(|m| m.ts == ts) {
+            self.msg_data.remove(pos);
+        } else {
+            let msg = &mut self.msg_data[pos];
+            msg.ts = ts.to_string();
+            msg.text = text.to_string();
+            msg.local_echo_id = None;
+        }
+        self.invalidate_cache();
+    }
+
     pub fn hide_reply_preview(&mut self) {
         self.reply_preview = None;
     }
 
+    /// Send the next message into the thread of the message `ts`, with `preview` shown above the input.
+    pub fn set_reply(&mut self, ts: &str, preview: String) {
+        self.reply_to_message = Some(ts.to_string());
+        self.reply_preview = Some(preview);
+    }
+
+    /// The parent message of a thread pane (conversations.replies lists it first).
+    pub fn thread_root(&self) -> Option<&MessageData> {
+        let thread_ts = self.thread_ts.as_deref()?;
+        self.msg_data.first().filter(|m| m.ts == thread_ts)
+    }
+
     /// Build the header text including online status, username, pinned message, typing indicator
     pub fn header_text(&self) -> String {
         let mut header = self.chat_name.clone();
 
+        if let Some(root) = self.thread_root() {
+            let replies = root.reply_count;
+            header.push_str(&format!(" ({} repl{})", replies, if replies == 1 { "y" } else { "ies" }));
+        }
+
         if !self.online_status.is_empty() {
             header.push_str(&format!(" [{}]", self.online_status));
         }
@@ -147,6 +458,17 @@ impl ChatPane {
             }
         }
 
+        if let Some(filter) = self.filter_type {
+            match self.filter_value {
+                Some(ref value) => header.push_str(&format!(" [filter {:?}: {}]", filter, value)),
+                None => header.push_str(&format!(" [filter {:?}]", filter)),
+            }
+        }
+
+        if self.hide_bots {
+            header.push_str(" [no bots]");
+        }
+
         if let Some(ref pinned) = self.pinned_message {
             header.push_str(&format!(" | Pinned: {}", pinned));
         }